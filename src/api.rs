@@ -0,0 +1,138 @@
+//! Programmatic canvas construction: build a `.glyph` file in code without
+//! running the app.
+//!
+//! [`GlyphCanvas`] wraps a [`CanvasSnapshot`], the same structure `:w` saves,
+//! so anything written here opens in Glyph like a hand-drawn canvas.
+//!
+//! ```
+//! use glyph::api::GlyphCanvas;
+//!
+//! let mut canvas = GlyphCanvas::new();
+//! let main = canvas.add_node("main", 0.0, 0.0);
+//! let parse = canvas.add_node("parse", -150.0, -120.0);
+//! let run = canvas.add_node("run", 150.0, -120.0);
+//! canvas.connect(main, parse, None);
+//! canvas.connect(main, run, Some("then"));
+//! canvas.set_camera(0.0, -60.0, 1.0);
+//!
+//! let path = std::env::temp_dir().join("glyph_api_doctest.glyph");
+//! canvas.to_glyph_file(&path).unwrap();
+//!
+//! let read = GlyphCanvas::from_glyph_file(&path).unwrap();
+//! let snapshot = read.snapshot();
+//! assert_eq!(snapshot.nodes.len(), 3);
+//! assert_eq!(snapshot.edges.len(), 2);
+//! assert_eq!(snapshot.edges[1].label.as_deref(), Some("then"));
+//! assert_eq!(read.find_node("run"), Some(run));
+//! # std::fs::remove_file(&path).ok();
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::io::file_io::{
+    default_color, read_snapshot, write_snapshot, CanvasSnapshot, SerializableEdge,
+    SerializableNode, SerializedCameraPrefs,
+};
+
+/// Id of a node in a [`GlyphCanvas`]; the `id` it is saved under.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(pub u64);
+
+/// A canvas under construction. Nodes get the default color and shape;
+/// edges get the default stroke and curve.
+#[derive(Clone, Debug)]
+pub struct GlyphCanvas {
+    snapshot: CanvasSnapshot,
+    next_id: u64,
+}
+
+impl Default for GlyphCanvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GlyphCanvas {
+    /// An empty canvas.
+    pub fn new() -> Self {
+        Self::from_snapshot(CanvasSnapshot {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            camera: None,
+            marks: BTreeMap::new(),
+        })
+    }
+
+    /// Wrap an existing snapshot; new nodes are numbered after its highest id.
+    pub fn from_snapshot(snapshot: CanvasSnapshot) -> Self {
+        let next_id = snapshot.nodes.iter().map(|n| n.id + 1).max().unwrap_or(0);
+        Self { snapshot, next_id }
+    }
+
+    /// Add a node with `text` centered at (`x`, `y`) in world units.
+    pub fn add_node(&mut self, text: impl Into<String>, x: f32, y: f32) -> NodeId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.snapshot.nodes.push(SerializableNode {
+            id,
+            x,
+            y,
+            text: text.into(),
+            color: default_color(),
+            source: None,
+            decision: false,
+            shape: Default::default(),
+            size: None,
+            pinned: false,
+        });
+        NodeId(id)
+    }
+
+    /// Add an edge from `a` to `b`, optionally labeled.
+    pub fn connect(&mut self, a: NodeId, b: NodeId, label: Option<&str>) -> &mut Self {
+        self.snapshot.edges.push(SerializableEdge {
+            source_id: a.0,
+            target_id: b.0,
+            label: label.map(str::to_string),
+            stroke: Default::default(),
+            curve: None,
+            reversed: false,
+        });
+        self
+    }
+
+    /// Where the camera opens: centered on (`x`, `y`) at zoom `scale`
+    /// (world units per pixel).
+    pub fn set_camera(&mut self, x: f32, y: f32, scale: f32) -> &mut Self {
+        self.snapshot.camera = Some(SerializedCameraPrefs { x, y, scale });
+        self
+    }
+
+    /// First node whose text is `text`.
+    pub fn find_node(&self, text: &str) -> Option<NodeId> {
+        self.snapshot
+            .nodes
+            .iter()
+            .find(|n| n.text == text)
+            .map(|n| NodeId(n.id))
+    }
+
+    pub fn snapshot(&self) -> &CanvasSnapshot {
+        &self.snapshot
+    }
+
+    pub fn into_snapshot(self) -> CanvasSnapshot {
+        self.snapshot
+    }
+
+    /// Write the canvas as a `.glyph` file, creating missing parent directories.
+    pub fn to_glyph_file(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        write_snapshot(path.as_ref(), &self.snapshot)
+    }
+
+    /// Read a `.glyph` file to add to or inspect.
+    pub fn from_glyph_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        read_snapshot(path.as_ref()).map(Self::from_snapshot)
+    }
+}
@@ -1,6 +1,7 @@
 //! ECS components for the whiteboard.
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Component)]
 pub struct CanvasNode;
@@ -18,9 +19,9 @@ pub struct TextData {
 #[derive(Component)]
 pub struct Selected;
 
-/// Marker on the temporary floating tag labels during easymotion.
+/// Temporary floating tag label during easymotion, holding its tag (`a`, `zb`).
 #[derive(Component)]
-pub struct JumpTag;
+pub struct JumpTag(pub String);
 
 /// Marker on the Text2d child entity that lives inside every CanvasNode.
 #[derive(Component)]
@@ -30,10 +31,77 @@ pub struct TextLabel;
 #[derive(Component)]
 pub struct NodeMainSprite;
 
+/// Marker on the drop-shadow sprite child of a CanvasNode.
+#[derive(Component)]
+pub struct NodeShadow;
+
+/// World-space size of a CanvasNode's box. `base` is the node's own width and
+/// minimum height: loaded from the file, or `node_width` × `node_height` from
+/// the config on the first text sync. `current` is `base` grown to fit the
+/// wrapped label. Kept in sync by `sync_text_system`.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct NodeSize {
+    pub base: Option<Vec2>,
+    pub current: Vec2,
+}
+
+impl NodeSize {
+    pub fn with_base(base: Vec2) -> Self {
+        Self {
+            base: Some(base),
+            current: base,
+        }
+    }
+}
+
+impl Default for NodeSize {
+    fn default() -> Self {
+        Self {
+            base: None,
+            current: crate::core::helpers::NODE_SIZE,
+        }
+    }
+}
+
 /// Stores the node's fill color for serialization. Synced with NodeMainSprite child.
 #[derive(Component, Clone, Copy)]
 pub struct NodeColor(pub Color);
 
+/// Outline of a CanvasNode. Absent means `Rect`; hit-testing always uses the
+/// bounding box.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeShape {
+    #[default]
+    Rect,
+    Ellipse,
+    Diamond,
+}
+
+impl NodeShape {
+    /// Parse the name used by `:shape` and in `.glyph` files.
+    pub fn from_name(name: &str) -> Option<NodeShape> {
+        match name {
+            "rect" => Some(NodeShape::Rect),
+            "ellipse" => Some(NodeShape::Ellipse),
+            "diamond" => Some(NodeShape::Diamond),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NodeShape::Rect => "rect",
+            NodeShape::Ellipse => "ellipse",
+            NodeShape::Diamond => "diamond",
+        }
+    }
+
+    pub fn is_rect(&self) -> bool {
+        *self == NodeShape::Rect
+    }
+}
+
 /// Directed edge between two CanvasNode entities.
 #[derive(Component, Clone)]
 pub struct Edge {
@@ -43,6 +111,37 @@ pub struct Edge {
     pub label: Option<String>,
 }
 
+/// Per-edge stroke set by `:estyle`. `width` is in multiples of the gizmo
+/// line width and is drawn as parallel offset curves. Absent on most edges,
+/// which draw as the default: one solid line. (`config::EdgeStyle` is the
+/// canvas-wide curve/stub setting.)
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EdgeStroke {
+    pub width: f32,
+    pub dashed: bool,
+}
+
+impl Default for EdgeStroke {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            dashed: false,
+        }
+    }
+}
+
+impl EdgeStroke {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Hand-set bend of an edge: its Bezier control point relative to the chord
+/// midpoint. Set by dragging the edge's midpoint handle and cleared by
+/// `:straighten`; absent on most edges, which bend automatically.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct CurveOffset(pub Vec2);
+
 /// Marker on the Text2d child of an Edge entity for label rendering.
 #[derive(Component)]
 pub struct EdgeLabel;
@@ -53,10 +152,13 @@ pub struct MainCamera;
 
 /// Attached to a CanvasNode while it is being mouse-dragged.
 /// `offset` is (cursor_world – node_center) at the moment the drag began,
-/// so the node does not "snap" to the cursor centre.
+/// so the node does not "snap" to the cursor centre. `start` is the node
+/// centre at that moment. An Alt+drag puts one on every node of the
+/// connected component.
 #[derive(Component)]
 pub struct Dragging {
     pub offset: Vec2,
+    pub start: Vec2,
 }
 
 /// Source file location for a crawled node.
@@ -73,6 +175,67 @@ pub struct SourceLocation {
 #[derive(Component)]
 pub struct FileLabel;
 
+/// Marker on the "NN lines" Text2d rendered at the top of crawled function
+/// nodes when `show_complexity` is on.
+#[derive(Component)]
+pub struct LineCountLabel;
+
+/// Marker for crawled control-flow decision nodes (if/for/while/match).
+#[derive(Component)]
+pub struct DecisionNode;
+
+/// Marker for a node whose exclusive callee subtree is folded away (`zc`).
+/// Display-only: not saved and not recorded in undo history.
+#[derive(Component)]
+pub struct Collapsed;
+
+/// Marker for a node force layout must not move (`zp` / `:pin`). It still
+/// pushes and pulls its neighbours. Saved in the `.glyph` file.
+#[derive(Component)]
+pub struct Pinned;
+
+/// Marker for nodes and edges hidden from display: under a `Collapsed` node,
+/// or tagged `FlowHidden` or `InactiveCanvas`. Owned by
+/// `render::collapse::sync_collapsed_system`.
+#[derive(Component)]
+pub struct CollapseHidden;
+
+/// Namespaced crawler id of a crawled node (`relative/path.rs::name`; decision
+/// nodes also carry `DECISION_SEP`). Not saved.
+#[derive(Component, Clone)]
+pub struct FlowNodeId(pub String);
+
+/// Marker for decision nodes and their edges hidden by `:flow off`.
+#[derive(Component)]
+pub struct FlowHidden;
+
+/// Marker for the caller → callee edges `:flow off` draws in place of hidden
+/// decision nodes. Never saved or exported.
+#[derive(Component)]
+pub struct FlowBypass;
+
+/// Marker for a crawled edge that `:callers` turned around (callee → caller);
+/// `:callees` turns these back.
+#[derive(Component)]
+pub struct ReversedCall;
+
+/// Tab (`core::tabs::Canvases` index) a node or edge belongs to. Not saved.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CanvasId(pub usize);
+
+/// Marker for nodes and edges of every tab but the active one.
+#[derive(Component)]
+pub struct InactiveCanvas;
+
+/// Marker for nodes and edges faded out by `:grep`.
+#[derive(Component)]
+pub struct GrepDimmed;
+
 /// Marker for nodes and edges that are part of a traced data flow path.
 #[derive(Component)]
 pub struct TracedPath;
+
+/// Marker for the edges of the path found by `:path`, removed after a few
+/// seconds by `render::call_path::expire_path_highlight_system`.
+#[derive(Component)]
+pub struct PathHighlight;
@@ -1,9 +1,12 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::core::state::InputMode;
+
 /// Application configuration loaded from `~/.glyphrc`.
 #[derive(Debug, Clone, Serialize, Deserialize, Resource)]
 pub struct GlyphConfig {
@@ -25,8 +28,231 @@ pub struct GlyphConfig {
     pub status_message_duration: f32,
     #[serde(default = "default_undo_history_cap")]
     pub undo_history_cap: usize,
+    /// How many files File → Open Recent remembers.
+    #[serde(default = "default_max_recent")]
+    pub max_recent: usize,
     #[serde(default = "default_curve_segments")]
     pub curve_segments: usize,
+    /// Call names to keep during `:crawl` even though they appear in a
+    /// language's builtins list (e.g. `["unwrap", "println"]`).
+    #[serde(default)]
+    pub crawl_include_calls: Vec<String>,
+    /// How edges are drawn: `"curve"` (full Bezier) or `"stub"` (short
+    /// segment + arrowhead at the target, for association diagrams).
+    #[serde(default)]
+    pub edge_style: EdgeStyle,
+    /// Draw parallel edges between the same pair of nodes as one thicker curve
+    /// with a `×N` badge instead of fanning them out. Toggle with `:bundle`.
+    #[serde(default)]
+    pub bundle_edges: bool,
+    /// Bend curves that would cross an unrelated node around it. Costs a
+    /// spatial lookup per edge per frame, so it is off by default.
+    #[serde(default)]
+    pub edge_avoid_nodes: bool,
+    /// Draw edges as triangle meshes (`render::edge_mesh`) instead of gizmo
+    /// lines: widths in world units that scale with zoom, round joins, MSAA.
+    #[serde(default)]
+    pub edge_meshes: bool,
+    /// Draw a soft drop shadow under rect nodes so overlapping nodes read as
+    /// stacked cards.
+    #[serde(default = "default_node_shadows")]
+    pub node_shadows: bool,
+    /// Show the minimap overlay in the bottom-right corner. Toggle with `:minimap`.
+    #[serde(default)]
+    pub minimap: bool,
+    /// Ease the camera to mark, `gg`, search and finder jumps over ~200 ms
+    /// instead of cutting straight there.
+    #[serde(default = "default_smooth_camera")]
+    pub smooth_camera: bool,
+    /// Tint crawled function nodes by their source file, matching the halo
+    /// and Modules legend colors. Decision nodes keep the decision fill.
+    #[serde(default = "default_color_by_module")]
+    pub color_by_module: bool,
+    /// Label crawled function nodes with their length ("42 lines") to spot
+    /// large functions.
+    #[serde(default)]
+    pub show_complexity: bool,
+    /// Length of each arrowhead wing at the target end of an edge (world units).
+    #[serde(default = "default_edge_arrow_size")]
+    pub edge_arrow_size: f32,
+    /// Easymotion jump tag text color in hex format.
+    #[serde(default = "default_jump_tag_color")]
+    pub jump_tag_color: String,
+    #[serde(default = "default_jump_tag_font_size")]
+    pub jump_tag_font_size: f32,
+    /// Optional hex fill for a pill drawn behind each jump tag; unset draws plain text.
+    #[serde(default)]
+    pub jump_tag_background: Option<String>,
+    /// Grid size in world units that dropped and hjkl-moved nodes snap to; unset = off.
+    #[serde(default)]
+    pub snap_grid: Option<f32>,
+    /// Reference grid drawn behind the canvas, at the snap grid size or
+    /// `render::grid::DEFAULT_SPACING`; unset draws lines only while snapping.
+    #[serde(default)]
+    pub background_grid: Option<GridStyle>,
+    /// Width of a new node's box in world units; labels wrap to fit inside it.
+    #[serde(default = "default_node_width", alias = "node_max_width")]
+    pub node_width: f32,
+    /// Height of a new node's box; long labels grow it.
+    #[serde(default = "default_node_height")]
+    pub node_height: f32,
+    /// Height of one wrapped label line; nodes grow by this per extra line.
+    #[serde(default = "default_line_height")]
+    pub line_height: f32,
+    /// Command `gd` runs to open a crawled node's source, e.g.
+    /// `"nvim +{line} {file}"`; `{file}`, `{line}` and `{col}` are filled in.
+    /// Unset guesses from `$EDITOR`.
+    #[serde(default)]
+    pub editor_cmd: Option<String>,
+    /// Color scheme: `"dark"` (default) or `"light"`. Switch at runtime with `:theme`.
+    /// `background_color` and `node_color` only apply to the dark theme.
+    #[serde(default)]
+    pub theme: ThemeName,
+    /// Normal-mode key overrides, action name → key name (e.g. `move_left = "a"`).
+    /// Actions left out keep their default key.
+    #[serde(default)]
+    pub keymap: HashMap<String, String>,
+}
+
+/// Rendering style for edges, selected via `edge_style` in `~/.glyphrc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeStyle {
+    /// Full quadratic Bezier from source to target.
+    #[default]
+    Curve,
+    /// Only the last stretch of the curve plus an arrowhead at the target.
+    Stub,
+}
+
+/// Background grid drawing, selected via `background_grid` in `~/.glyphrc`
+/// or `:grid`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GridStyle {
+    /// A faint dot at every grid crossing.
+    Dots,
+    /// Faint horizontal and vertical lines.
+    Lines,
+}
+
+impl GridStyle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dots" => Some(GridStyle::Dots),
+            "lines" => Some(GridStyle::Lines),
+            _ => None,
+        }
+    }
+}
+
+/// Built-in color scheme, selected via `theme` in `~/.glyphrc` or `:theme`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl ThemeName {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(ThemeName::Dark),
+            "light" => Some(ThemeName::Light),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ThemeName::Dark => "dark",
+            ThemeName::Light => "light",
+        }
+    }
+}
+
+/// Resolved colors for the active theme. Rendering systems read this resource
+/// instead of baked-in constants; `:theme` replaces it at runtime.
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct Theme {
+    pub name: ThemeName,
+    pub background: Color,
+    /// Fill for nodes created by hand (`n`, `a`, `o`, click).
+    pub node: Color,
+    /// Fill for crawled function nodes.
+    pub crawl_node: Color,
+    /// Fill for crawled decision (branch) nodes.
+    pub decision: Color,
+    pub edge: Color,
+    /// The edge under the mouse cursor.
+    pub edge_hover: Color,
+    /// Traced call paths: edges and node outlines.
+    pub traced: Color,
+    pub text: Color,
+    pub select_normal: Color,
+    pub select_insert: Color,
+    pub select_easymotion: Color,
+    pub select_standard: Color,
+    /// Outline for VimVisual members.
+    pub select_visual: Color,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            name: ThemeName::Dark,
+            background: Color::srgb_u8(0x1e, 0x1e, 0x2e), // Catppuccin Mocha Base
+            node: Color::srgb_u8(0x31, 0x32, 0x44),       // Catppuccin Mocha Surface0
+            crawl_node: Color::srgb(0.35, 0.55, 0.45),
+            decision: Color::srgb(0.85, 0.65, 0.15), // gold/amber
+            edge: Color::srgb(0.22, 0.32, 0.48),     // muted blue
+            edge_hover: Color::srgb(0.45, 0.65, 0.95),
+            traced: Color::srgb(1.0, 0.2, 0.2),
+            text: Color::srgb(0.95, 0.96, 0.98),
+            select_normal: Color::srgb(0.3, 0.6, 1.0),
+            select_insert: Color::srgb(0.2, 0.85, 0.4),
+            select_easymotion: Color::srgb(1.0, 0.6, 0.1),
+            select_standard: Color::srgb(0.85, 0.4, 0.9),
+            select_visual: Color::srgb(1.0, 0.45, 0.75),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            name: ThemeName::Light,
+            background: Color::srgb_u8(0xef, 0xf1, 0xf5), // Catppuccin Latte Base
+            node: Color::srgb_u8(0xcc, 0xd0, 0xda),       // Catppuccin Latte Surface0
+            crawl_node: Color::srgb(0.62, 0.82, 0.68),
+            decision: Color::srgb(0.98, 0.80, 0.42),
+            edge: Color::srgb(0.36, 0.42, 0.56),
+            edge_hover: Color::srgb(0.15, 0.35, 0.8),
+            traced: Color::srgb(0.85, 0.1, 0.15),
+            text: Color::srgb_u8(0x4c, 0x4f, 0x69), // Catppuccin Latte Text
+            select_normal: Color::srgb(0.12, 0.4, 0.9),
+            select_insert: Color::srgb(0.1, 0.6, 0.25),
+            select_easymotion: Color::srgb(0.9, 0.45, 0.0),
+            select_standard: Color::srgb(0.6, 0.2, 0.75),
+            select_visual: Color::srgb(0.9, 0.2, 0.55),
+        }
+    }
+
+    /// Selection outline color for the current input mode.
+    pub fn selection(&self, mode: &InputMode) -> Color {
+        match mode {
+            InputMode::VimInsert => self.select_insert,
+            InputMode::VimEasymotion => self.select_easymotion,
+            InputMode::Standard => self.select_standard,
+            InputMode::VimNormal | InputMode::VimCommand => self.select_normal,
+            InputMode::VimVisual => self.select_visual,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
 }
 
 fn default_hjkl_base_speed() -> f32 { 10.0 }
@@ -36,7 +262,17 @@ fn default_flow_row_height() -> f32 { 380.0 }
 fn default_flow_node_spacing() -> f32 { 320.0 }
 fn default_status_message_duration() -> f32 { 4.0 }
 fn default_undo_history_cap() -> usize { 100 }
+fn default_max_recent() -> usize { 10 }
 fn default_curve_segments() -> usize { 24 }
+fn default_edge_arrow_size() -> f32 { 14.0 }
+fn default_smooth_camera() -> bool { true }
+fn default_color_by_module() -> bool { true }
+fn default_node_shadows() -> bool { true }
+fn default_jump_tag_color() -> String { "#ffd91a".to_string() }
+fn default_jump_tag_font_size() -> f32 { 28.0 }
+fn default_node_width() -> f32 { 160.0 }
+fn default_node_height() -> f32 { 120.0 }
+fn default_line_height() -> f32 { 20.0 }
 
 impl Default for GlyphConfig {
     fn default() -> Self {
@@ -50,25 +286,90 @@ impl Default for GlyphConfig {
             flow_node_spacing: default_flow_node_spacing(),
             status_message_duration: default_status_message_duration(),
             undo_history_cap: default_undo_history_cap(),
+            max_recent: default_max_recent(),
             curve_segments: default_curve_segments(),
+            crawl_include_calls: Vec::new(),
+            edge_style: EdgeStyle::default(),
+            bundle_edges: false,
+            edge_avoid_nodes: false,
+            edge_meshes: false,
+            node_shadows: default_node_shadows(),
+            minimap: false,
+            smooth_camera: default_smooth_camera(),
+            color_by_module: default_color_by_module(),
+            show_complexity: false,
+            edge_arrow_size: default_edge_arrow_size(),
+            jump_tag_color: default_jump_tag_color(),
+            jump_tag_font_size: default_jump_tag_font_size(),
+            jump_tag_background: None,
+            snap_grid: None,
+            background_grid: None,
+            node_width: default_node_width(),
+            node_height: default_node_height(),
+            line_height: default_line_height(),
+            editor_cmd: None,
+            theme: ThemeName::default(),
+            keymap: HashMap::new(),
         }
     }
 }
 
 impl GlyphConfig {
-    /// Parse the background hex string into a Bevy Color.
+    /// Background of the active theme; the dark theme uses the `background_color` hex.
     pub fn bg_color(&self) -> Color {
-        bevy::color::Srgba::hex(&self.background_color)
-            .unwrap_or(bevy::color::Srgba::new(0.12, 0.12, 0.18, 1.0))
-            .into()
+        match self.theme {
+            ThemeName::Dark => bevy::color::Srgba::hex(&self.background_color)
+                .unwrap_or(bevy::color::Srgba::new(0.12, 0.12, 0.18, 1.0))
+                .into(),
+            ThemeName::Light => Theme::light().background,
+        }
     }
 
-    /// Parse the node hex string into a Bevy Color.
+    /// Default node fill of the active theme; the dark theme uses the `node_color` hex.
     pub fn node_color(&self) -> Color {
-        bevy::color::Srgba::hex(&self.node_color)
-            .unwrap_or(bevy::color::Srgba::new(0.38, 0.44, 0.52, 1.0))
+        match self.theme {
+            ThemeName::Dark => bevy::color::Srgba::hex(&self.node_color)
+                .unwrap_or(bevy::color::Srgba::new(0.38, 0.44, 0.52, 1.0))
+                .into(),
+            ThemeName::Light => Theme::light().node,
+        }
+    }
+
+    /// Resolve the `Theme` resource for the configured theme name.
+    pub fn theme(&self) -> Theme {
+        let base = match self.theme {
+            ThemeName::Dark => Theme::dark(),
+            ThemeName::Light => Theme::light(),
+        };
+        Theme {
+            background: self.bg_color(),
+            node: self.node_color(),
+            ..base
+        }
+    }
+
+    /// Parse the jump tag hex string into a Bevy Color.
+    pub fn jump_tag_color(&self) -> Color {
+        bevy::color::Srgba::hex(&self.jump_tag_color)
+            .unwrap_or(bevy::color::Srgba::new(1.0, 0.85, 0.1, 1.0))
             .into()
     }
+
+    /// Box size for new nodes, `node_width` × `node_height`.
+    pub fn node_size(&self) -> Vec2 {
+        Vec2::new(self.node_width, self.node_height)
+    }
+
+    /// Active snap grid size, treating zero or negative sizes as off.
+    pub fn snap_grid(&self) -> Option<f32> {
+        self.snap_grid.filter(|g| *g > 0.0)
+    }
+
+    /// Parse the jump tag pill color, if one is configured and valid.
+    pub fn jump_tag_background(&self) -> Option<Color> {
+        let hex = self.jump_tag_background.as_deref()?;
+        bevy::color::Srgba::hex(hex).ok().map(Into::into)
+    }
 }
 
 /// Attempts to load the configuration from `~/.glyphrc`.
@@ -164,13 +465,57 @@ mod tests {
             flow_node_spacing: 350.0,
             status_message_duration: 5.0,
             undo_history_cap: 200,
+            max_recent: 5,
             curve_segments: 32,
+            crawl_include_calls: vec!["unwrap".to_string()],
+            edge_style: EdgeStyle::Stub,
+            bundle_edges: true,
+            edge_avoid_nodes: true,
+            edge_meshes: true,
+            node_shadows: false,
+            minimap: true,
+            smooth_camera: false,
+            color_by_module: false,
+            show_complexity: true,
+            edge_arrow_size: 18.0,
+            jump_tag_color: "#000000".to_string(),
+            jump_tag_font_size: 20.0,
+            jump_tag_background: Some("#ffffff".to_string()),
+            snap_grid: Some(20.0),
+            background_grid: Some(GridStyle::Dots),
+            node_width: 200.0,
+            node_height: 90.0,
+            line_height: 18.0,
+            editor_cmd: Some("nvim +{line} {file}".to_string()),
+            theme: ThemeName::Light,
+            keymap: HashMap::from([("move_left".to_string(), "a".to_string())]),
         };
         let toml_str = toml::to_string(&config).unwrap();
         let parsed: GlyphConfig = toml::from_str(&toml_str).unwrap();
         assert_eq!(parsed.hjkl_base_speed, 15.0);
         assert_eq!(parsed.curve_segments, 32);
         assert_eq!(parsed.undo_history_cap, 200);
+        assert_eq!(parsed.max_recent, 5);
+        assert_eq!(parsed.crawl_include_calls, vec!["unwrap".to_string()]);
+        assert_eq!(parsed.edge_style, EdgeStyle::Stub);
+        assert_eq!(parsed.edge_arrow_size, 18.0);
+        assert!(parsed.bundle_edges);
+        assert!(parsed.edge_avoid_nodes);
+        assert!(parsed.edge_meshes);
+        assert!(!parsed.node_shadows);
+        assert!(parsed.minimap);
+        assert!(!parsed.smooth_camera);
+        assert!(!parsed.color_by_module);
+        assert!(parsed.show_complexity);
+        assert_eq!(parsed.jump_tag_font_size, 20.0);
+        assert_eq!(parsed.jump_tag_background.as_deref(), Some("#ffffff"));
+        assert_eq!(parsed.snap_grid, Some(20.0));
+        assert_eq!(parsed.background_grid, Some(GridStyle::Dots));
+        assert_eq!(parsed.node_size(), Vec2::new(200.0, 90.0));
+        assert_eq!(parsed.line_height, 18.0);
+        assert_eq!(parsed.editor_cmd.as_deref(), Some("nvim +{line} {file}"));
+        assert_eq!(parsed.theme, ThemeName::Light);
+        assert_eq!(parsed.keymap.get("move_left").map(String::as_str), Some("a"));
 
         // Minimal TOML (only colors) should use defaults for new fields
         let minimal = r##"
@@ -181,6 +526,61 @@ node_color = "#313244"
         assert_eq!(parsed.hjkl_base_speed, 10.0);
         assert_eq!(parsed.curve_segments, 24);
         assert_eq!(parsed.undo_history_cap, 100);
+        assert_eq!(parsed.max_recent, 10);
+        assert!(parsed.crawl_include_calls.is_empty());
+        assert_eq!(parsed.edge_style, EdgeStyle::Curve);
+        assert_eq!(parsed.edge_arrow_size, 14.0);
+        assert!(!parsed.bundle_edges);
+        assert!(!parsed.edge_avoid_nodes);
+        assert!(!parsed.edge_meshes);
+        assert!(parsed.node_shadows);
+        assert!(!parsed.minimap);
+        assert!(parsed.smooth_camera);
+        assert!(parsed.color_by_module);
+        assert!(!parsed.show_complexity);
+        assert_eq!(parsed.jump_tag_font_size, 28.0);
+        assert!(parsed.jump_tag_background.is_none());
+        assert!(parsed.snap_grid.is_none());
+        assert!(parsed.background_grid.is_none());
+        assert!(parsed.editor_cmd.is_none());
+        assert_eq!(parsed.node_size(), crate::core::helpers::NODE_SIZE);
+
+        // The old key name still sets the width.
+        let old_key: GlyphConfig =
+            toml::from_str(&format!("{}node_max_width = 180.0\n", minimal)).unwrap();
+        assert_eq!(old_key.node_width, 180.0);
+        assert_eq!(parsed.line_height, 20.0);
+        assert_eq!(parsed.theme, ThemeName::Dark);
+        assert!(parsed.keymap.is_empty());
+    }
+
+    #[test]
+    fn theme_resolves_from_config() {
+        let mut config = GlyphConfig {
+            background_color: "#ff0000".to_string(),
+            ..Default::default()
+        };
+        let dark = config.theme();
+        assert_eq!(dark.name, ThemeName::Dark);
+        assert_eq!(dark.background, config.bg_color());
+        assert_eq!(dark.edge, Theme::dark().edge);
+
+        // The hex overrides belong to the dark theme; light uses its own palette.
+        config.theme = ThemeName::Light;
+        assert_eq!(config.theme(), Theme::light());
+        assert_eq!(config.node_color(), Theme::light().node);
+        assert_eq!(ThemeName::from_name("light"), Some(ThemeName::Light));
+        assert_eq!(ThemeName::from_name("solarized"), None);
+    }
+
+    #[test]
+    fn jump_tag_background_parses_or_disables() {
+        let mut config = GlyphConfig::default();
+        assert!(config.jump_tag_background().is_none());
+        config.jump_tag_background = Some("#202020".to_string());
+        assert!(config.jump_tag_background().is_some());
+        config.jump_tag_background = Some("nope".to_string());
+        assert!(config.jump_tag_background().is_none());
     }
 
     #[test]
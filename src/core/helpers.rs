@@ -12,6 +12,11 @@ pub fn ctrl_pressed(keys: &bevy::prelude::ButtonInput<bevy::prelude::KeyCode>) -
     keys.pressed(KeyCode::ControlLeft) || keys.pressed(KeyCode::ControlRight)
 }
 
+pub fn alt_pressed(keys: &bevy::prelude::ButtonInput<bevy::prelude::KeyCode>) -> bool {
+    use bevy::prelude::KeyCode;
+    keys.pressed(KeyCode::AltLeft) || keys.pressed(KeyCode::AltRight)
+}
+
 /// Returns true if Ctrl or Cmd (Super) is held — the platform save modifier.
 pub fn ctrl_or_cmd_pressed(keys: &bevy::prelude::ButtonInput<bevy::prelude::KeyCode>) -> bool {
     use bevy::prelude::KeyCode;
@@ -25,10 +30,16 @@ use bevy::prelude::*;
 use bevy::text::{Justify, LineBreak, TextBounds};
 
 use crate::core::components::{
-    CanvasNode, Edge, NodeColor, NodeMainSprite, Selected, TextData, TextLabel,
+    CanvasNode, Edge, FileLabel, LineCountLabel, NodeColor, NodeMainSprite, NodeShadow, NodeSize,
+    Selected, TextData, TextLabel,
 };
 
-/// Node size and shadow offset.
+/// Round `pos` to the nearest multiple of `grid` on both axes.
+pub fn snap_to_grid(pos: Vec2, grid: f32) -> Vec2 {
+    (pos / grid).round() * grid
+}
+
+/// Default node size (`node_width` × `node_height`) and shadow offset.
 pub const NODE_SIZE: Vec2 = Vec2::new(160.0, 120.0);
 const SHADOW_OFFSET: Vec2 = Vec2::new(-4.0, -4.0);
 const SHADOW_SIZE: Vec2 = Vec2::new(168.0, 128.0);
@@ -36,8 +47,13 @@ const SHADOW_SIZE: Vec2 = Vec2::new(168.0, 128.0);
 /// Text area inside node (padding from edges). Enables multi-line wrapping.
 const TEXT_BOUNDS: Vec2 = Vec2::new(150.0, 110.0);
 
-/// Drop shadow color.
-const SHADOW_COLOR: Color = Color::srgb(0.12, 0.14, 0.18);
+/// Gap between the node edge and its label on each side.
+pub const LABEL_PADDING: f32 = 5.0;
+/// Advance of one glyph of the 15px default (monospace) label font.
+const LABEL_CHAR_WIDTH: f32 = 9.0;
+
+/// Drop shadow color: dark and translucent so it darkens whatever is below.
+const SHADOW_COLOR: Color = Color::srgba(0.0, 0.01, 0.03, 0.45);
 
 /// Node text color (light gray on dark nodes).
 const TEXT_COLOR: Color = Color::srgb(0.95, 0.96, 0.98);
@@ -59,6 +75,7 @@ pub fn spawn_canvas_node(
             content: content.clone(),
         },
         NodeColor(color),
+        NodeSize::default(),
     ));
     if selected {
         entity_cmd.insert(Selected);
@@ -68,6 +85,7 @@ pub fn spawn_canvas_node(
             parent.spawn((
                 Sprite::from_color(SHADOW_COLOR, SHADOW_SIZE),
                 Transform::from_xyz(SHADOW_OFFSET.x, SHADOW_OFFSET.y, -0.1),
+                NodeShadow,
             ));
             parent.spawn((
                 Sprite::from_color(color, NODE_SIZE),
@@ -90,6 +108,58 @@ pub fn spawn_canvas_node(
         .id()
 }
 
+/// `text` with a caret bar before its `pos`-th char, for labels in insert mode.
+pub fn with_caret(text: &str, pos: usize) -> String {
+    let mut out: String = text.chars().take(pos).collect();
+    out.push('|');
+    out.extend(text.chars().skip(pos));
+    out
+}
+
+/// Break `text` into lines of at most `max_chars` characters: words wrap at
+/// whitespace, longer tokens are split hard. Explicit newlines are kept.
+pub fn wrap_label(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut len = 0;
+        for word in paragraph.split_whitespace() {
+            let mut chars: Vec<char> = word.chars().collect();
+            let fits = len + usize::from(len > 0) + chars.len() <= max_chars;
+            if !fits && len > 0 {
+                lines.push(std::mem::take(&mut line));
+                len = 0;
+            }
+            while chars.len() > max_chars {
+                let rest = chars.split_off(max_chars);
+                lines.push(chars.into_iter().collect());
+                chars = rest;
+            }
+            if len > 0 {
+                line.push(' ');
+                len += 1;
+            }
+            line.extend(&chars);
+            len += chars.len();
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Characters that fit on one label line of a node `node_width` wide.
+pub fn label_chars_per_line(node_width: f32) -> usize {
+    ((node_width - 2.0 * LABEL_PADDING) / LABEL_CHAR_WIDTH).floor().max(1.0) as usize
+}
+
+/// Box size for a label of `lines` lines: `base.x` wide, and at least
+/// `base.y` tall, growing by `line_height` per line beyond that.
+pub fn wrapped_node_size(lines: usize, base: Vec2, line_height: f32) -> Vec2 {
+    let text_height = lines as f32 * line_height + 2.0 * LABEL_PADDING;
+    Vec2::new(base.x, text_height.max(base.y))
+}
+
 /// Convenience wrapper for spawning an unselected node at (x, y).
 pub fn spawn_node_with_color(
     commands: &mut Commands,
@@ -101,6 +171,46 @@ pub fn spawn_node_with_color(
     spawn_canvas_node(commands, Vec2::new(x, y), text, color, false)
 }
 
+/// Attach the small filename label shown at the bottom of crawled nodes.
+/// `path` may be relative or absolute; only its basename is displayed.
+pub fn spawn_file_label(commands: &mut Commands, node: Entity, path: &str) {
+    let basename = std::path::Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path)
+        .to_string();
+    commands.entity(node).with_children(|parent| {
+        parent.spawn((
+            Text2d::new(basename),
+            TextFont {
+                font_size: 9.0,
+                ..default()
+            },
+            TextColor(Color::srgba(0.65, 0.70, 0.75, 0.65)),
+            Transform::from_xyz(0.0, -48.0, 1.0),
+            FileLabel,
+        ));
+    });
+}
+
+/// Attach the small "NN lines" label shown at the top of crawled function
+/// nodes under `show_complexity`.
+pub fn spawn_line_count_label(commands: &mut Commands, node: Entity, lines: u32) {
+    let text = if lines == 1 { "1 line".to_string() } else { format!("{} lines", lines) };
+    commands.entity(node).with_children(|parent| {
+        parent.spawn((
+            Text2d::new(text),
+            TextFont {
+                font_size: 9.0,
+                ..default()
+            },
+            TextColor(Color::srgba(0.65, 0.70, 0.75, 0.65)),
+            Transform::from_xyz(0.0, 48.0, 1.0),
+            LineCountLabel,
+        ));
+    });
+}
+
 /// Delete a node and all edges connected to it.
 pub fn delete_node(
     commands: &mut Commands,
@@ -143,6 +253,16 @@ pub fn keycode_to_char(key: &KeyCode) -> Option<char> {
         KeyCode::KeyX => Some('x'),
         KeyCode::KeyY => Some('y'),
         KeyCode::KeyZ => Some('z'),
+        KeyCode::Digit0 => Some('0'),
+        KeyCode::Digit1 => Some('1'),
+        KeyCode::Digit2 => Some('2'),
+        KeyCode::Digit3 => Some('3'),
+        KeyCode::Digit4 => Some('4'),
+        KeyCode::Digit5 => Some('5'),
+        KeyCode::Digit6 => Some('6'),
+        KeyCode::Digit7 => Some('7'),
+        KeyCode::Digit8 => Some('8'),
+        KeyCode::Digit9 => Some('9'),
         _ => None,
     }
 }
@@ -152,10 +272,45 @@ mod tests {
     use super::*;
 
     #[test]
-    fn keycode_to_char_letters() {
+    fn snap_to_grid_rounds_to_nearest_cell() {
+        assert_eq!(snap_to_grid(Vec2::new(29.0, -31.0), 20.0), Vec2::new(20.0, -40.0));
+        assert_eq!(snap_to_grid(Vec2::new(40.0, 0.0), 20.0), Vec2::new(40.0, 0.0));
+    }
+
+    #[test]
+    fn with_caret_marks_the_char_position() {
+        assert_eq!(with_caret("añb", 2), "añ|b");
+        assert_eq!(with_caret("ab", 9), "ab|");
+        assert_eq!(with_caret("", 0), "|");
+    }
+
+    #[test]
+    fn wrap_label_soft_wraps_and_hard_breaks() {
+        assert_eq!(wrap_label("short", 10), vec!["short"]);
+        assert_eq!(wrap_label("one two three four", 9), vec!["one two", "three", "four"]);
+        assert_eq!(
+            wrap_label("handle_crawl_requests now", 8),
+            vec!["handle_c", "rawl_req", "uests", "now"]
+        );
+        assert_eq!(wrap_label("a\nb", 10), vec!["a", "b"]);
+        assert_eq!(wrap_label("", 10), vec![""]);
+    }
+
+    #[test]
+    fn wrapped_node_grows_past_default_height() {
+        assert_eq!(wrapped_node_size(1, NODE_SIZE, 20.0), NODE_SIZE);
+        assert_eq!(wrapped_node_size(8, NODE_SIZE, 20.0), Vec2::new(160.0, 170.0));
+        assert_eq!(wrapped_node_size(2, Vec2::new(240.0, 60.0), 20.0), Vec2::new(240.0, 60.0));
+        assert_eq!(label_chars_per_line(160.0), 16);
+    }
+
+    #[test]
+    fn keycode_to_char_letters_and_digits() {
         assert_eq!(keycode_to_char(&KeyCode::KeyA), Some('a'));
         assert_eq!(keycode_to_char(&KeyCode::KeyZ), Some('z'));
         assert_eq!(keycode_to_char(&KeyCode::KeyM), Some('m'));
+        assert_eq!(keycode_to_char(&KeyCode::Digit1), Some('1'));
+        assert_eq!(keycode_to_char(&KeyCode::Digit0), Some('0'));
     }
 
     #[test]
@@ -163,6 +318,5 @@ mod tests {
         assert_eq!(keycode_to_char(&KeyCode::Space), None);
         assert_eq!(keycode_to_char(&KeyCode::Enter), None);
         assert_eq!(keycode_to_char(&KeyCode::Escape), None);
-        assert_eq!(keycode_to_char(&KeyCode::Digit1), None);
     }
 }
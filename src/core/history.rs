@@ -1,5 +1,9 @@
-use crate::core::components::{Edge, MainCamera, NodeColor, Selected, SourceLocation, TextData};
-use crate::core::helpers::spawn_canvas_node;
+use crate::core::components::{
+    CurveOffset, Edge, EdgeStroke, MainCamera, NodeColor, NodeShape, Selected, SourceLocation,
+    TextData,
+};
+use crate::core::helpers::{delete_node, spawn_canvas_node};
+use crate::core::snapshot::{CanvasSnapshot, PendingCanvasRestore};
 use bevy::prelude::*;
 
 /// Represents a reversible action in the whiteboard.
@@ -11,7 +15,10 @@ pub enum Action {
         text: String,
         color: Color,
     },
+    /// `entity` is the node as it was deleted; undo respawns it as a new
+    /// entity and writes that id back, together with its `edges`.
     DeleteNode {
+        entity: Entity,
         pos: Vec2,
         text: String,
         color: Color,
@@ -39,13 +46,60 @@ pub enum Action {
         target: Entity,
         label: Option<String>,
     },
+    /// Edge flipped in place by `gr` / `:reverse`; `source`/`target` are the
+    /// orientation before the flip.
+    ReverseEdge {
+        entity: Entity,
+        source: Entity,
+        target: Entity,
+    },
+    /// Node fill changed by `:color` or a palette swatch.
+    RecolorNode {
+        entity: Entity,
+        old: Color,
+        new: Color,
+    },
+    /// Node outline changed by `:shape`.
+    ReshapeNode {
+        entity: Entity,
+        old: NodeShape,
+        new: NodeShape,
+    },
+    /// Edge width or dash changed by `:estyle`.
+    RestyleEdge {
+        entity: Entity,
+        old: EdgeStroke,
+        new: EdgeStroke,
+    },
+    /// Edge bent by dragging its midpoint or straightened by `:straighten`;
+    /// `None` is the automatic curve.
+    CurveEdge {
+        entity: Entity,
+        old: Option<Vec2>,
+        new: Option<Vec2>,
+    },
+    /// Wholesale canvas change (e.g. `:simplify`): undo restores `before`,
+    /// redo restores `after`.
+    ReplaceCanvas {
+        before: CanvasSnapshot,
+        after: CanvasSnapshot,
+    },
+    /// Several actions undone/redone as one step (e.g. `:layout`).
+    Batch(Vec<Action>),
 }
 
+/// A `MoveNode` pushed within this long of the previous push, continuing the
+/// same node's move, is merged into it: a burst of `hjkl` taps undoes as one.
+pub const MOVE_COALESCE_WINDOW: std::time::Duration = std::time::Duration::from_millis(600);
+
 #[derive(Resource)]
 pub struct UndoHistory {
     pub undo_stack: Vec<Action>,
     pub redo_stack: Vec<Action>,
     pub cap: usize,
+    /// When the top of `undo_stack` was pushed; cleared by undo and redo so
+    /// a move after them starts a new step.
+    pub last_push: Option<std::time::Instant>,
 }
 
 impl Default for UndoHistory {
@@ -54,75 +108,294 @@ impl Default for UndoHistory {
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
             cap: 100,
+            last_push: None,
         }
     }
 }
 
 impl UndoHistory {
     pub fn push(&mut self, action: Action) {
-        self.undo_stack.push(action);
+        self.push_at(action, std::time::Instant::now());
+    }
+
+    /// [`Self::push`] at time `now`: a `MoveNode` that picks up where the
+    /// top `MoveNode` of the same node left off, within
+    /// [`MOVE_COALESCE_WINDOW`], extends it instead of adding a step.
+    pub fn push_at(&mut self, action: Action, now: std::time::Instant) {
+        let recent = self
+            .last_push
+            .is_some_and(|t| now.saturating_duration_since(t) <= MOVE_COALESCE_WINDOW);
+        self.last_push = Some(now);
         self.redo_stack.clear();
+        if let (
+            true,
+            Some(Action::MoveNode { entity: top, to, .. }),
+            Action::MoveNode { entity, from, to: new_to },
+        ) = (recent, self.undo_stack.last_mut(), &action)
+        {
+            if *top == *entity && to.distance(*from) < 0.1 {
+                *to = *new_to;
+                return;
+            }
+        }
+        self.undo_stack.push(action);
         if self.undo_stack.len() > self.cap {
             self.undo_stack.remove(0);
         }
     }
 
     pub fn pop_undo(&mut self) -> Option<Action> {
+        self.last_push = None;
         self.undo_stack.pop()
     }
 
     pub fn pop_redo(&mut self) -> Option<Action> {
+        self.last_push = None;
         self.redo_stack.pop()
     }
 
     pub fn push_redo(&mut self, action: Action) {
         self.redo_stack.push(action);
     }
+
+    /// The undo stack oldest first, one numbered line per action; the parts
+    /// of a `Batch` follow it, indented. Shown by `:history`.
+    pub fn log_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (i, action) in self.undo_stack.iter().enumerate() {
+            lines.push(format!("{:>3}  {}", i + 1, action));
+            if let Action::Batch(actions) = action {
+                lines.extend(actions.iter().map(|a| format!("       {}", a)));
+            }
+        }
+        lines
+    }
+}
+
+/// `e12`: an entity's index, enough to tell nodes apart in the log.
+struct EntityTag(Entity);
+
+impl std::fmt::Display for EntityTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "e{}", self.0.index_u32())
+    }
+}
+
+/// `(x,y)` rounded to whole world units (never `-0`).
+struct Point(Vec2);
+
+impl std::fmt::Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let p = self.0.round() + Vec2::ZERO;
+        write!(f, "({},{})", p.x, p.y)
+    }
+}
+
+fn stroke_name(stroke: &EdgeStroke) -> String {
+    let dash = if stroke.dashed { "dashed" } else { "solid" };
+    format!("{} w{}", dash, stroke.width)
+}
+
+fn curve_name(curve: &Option<Vec2>) -> String {
+    curve.map_or_else(|| "auto".to_string(), |offset| Point(offset).to_string())
 }
 
+fn label_suffix(label: &Option<String>) -> String {
+    label.as_ref().map(|l| format!(" {:?}", l)).unwrap_or_default()
+}
+
+/// One-line summary for the `:history` log, e.g. `MoveNode e12 (0,0)->(50,0)`.
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hex = |c: &Color| c.to_srgba().to_hex();
+        match self {
+            Action::CreateNode { entity, pos, text, .. } => {
+                write!(f, "CreateNode {} {:?} at {}", EntityTag(*entity), text, Point(*pos))
+            }
+            Action::DeleteNode { pos, text, edges, .. } => write!(
+                f,
+                "DeleteNode {:?} at {} with {} edges",
+                text,
+                Point(*pos),
+                edges.len()
+            ),
+            Action::MoveNode { entity, from, to } => {
+                write!(f, "MoveNode {} {}->{}", EntityTag(*entity), Point(*from), Point(*to))
+            }
+            Action::EditText { entity, old, new } => {
+                write!(f, "EditText {} {:?}->{:?}", EntityTag(*entity), old, new)
+            }
+            Action::CreateEdge { entity, source, target, label } => write!(
+                f,
+                "CreateEdge {} {}->{}{}",
+                EntityTag(*entity),
+                EntityTag(*source),
+                EntityTag(*target),
+                label_suffix(label)
+            ),
+            Action::DeleteEdge { source, target, label } => write!(
+                f,
+                "DeleteEdge {}->{}{}",
+                EntityTag(*source),
+                EntityTag(*target),
+                label_suffix(label)
+            ),
+            Action::ReverseEdge { entity, source, target } => write!(
+                f,
+                "ReverseEdge {} {}->{} now {}->{}",
+                EntityTag(*entity),
+                EntityTag(*source),
+                EntityTag(*target),
+                EntityTag(*target),
+                EntityTag(*source)
+            ),
+            Action::RecolorNode { entity, old, new } => {
+                write!(f, "RecolorNode {} {}->{}", EntityTag(*entity), hex(old), hex(new))
+            }
+            Action::ReshapeNode { entity, old, new } => {
+                write!(f, "ReshapeNode {} {}->{}", EntityTag(*entity), old.name(), new.name())
+            }
+            Action::RestyleEdge { entity, old, new } => write!(
+                f,
+                "RestyleEdge {} {}->{}",
+                EntityTag(*entity),
+                stroke_name(old),
+                stroke_name(new)
+            ),
+            Action::CurveEdge { entity, old, new } => write!(
+                f,
+                "CurveEdge {} {}->{}",
+                EntityTag(*entity),
+                curve_name(old),
+                curve_name(new)
+            ),
+            Action::ReplaceCanvas { before, after } => write!(
+                f,
+                "ReplaceCanvas {} nodes, {} edges -> {} nodes, {} edges",
+                before.nodes.len(),
+                before.edges.len(),
+                after.nodes.len(),
+                after.edges.len()
+            ),
+            Action::Batch(actions) => write!(f, "Batch of {}", actions.len()),
+        }
+    }
+}
+
+/// Node fields `apply_action` reads and writes when replaying history.
+pub type UndoNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Transform,
+        &'static mut TextData,
+        &'static mut NodeColor,
+        Option<&'static SourceLocation>,
+    ),
+    (With<Selected>, Without<MainCamera>),
+>;
+
+/// Make `entity` the only selected node.
+fn select_only(commands: &mut Commands, query: &UndoNodeQuery, entity: Entity) {
+    let Ok(mut e_cmd) = commands.get_entity(entity) else {
+        return;
+    };
+    e_cmd.insert(Selected);
+    for (prev, ..) in query.iter() {
+        if prev != entity {
+            commands.entity(prev).remove::<Selected>();
+        }
+    }
+}
+
+/// Undo (`revert`) or redo `action`. Like the cursor in Vim, the selection
+/// follows the change: the node it moved, edited, recolored or respawned ends
+/// up the only selected node. Batches (`:layout`, visual-mode edits) leave the
+/// selection alone. A respawned node's new id is written back into `action`,
+/// so push it onto the other stack after replaying it.
 pub fn apply_action(
-    action: &Action,
+    action: &mut Action,
+    revert: bool,
+    commands: &mut Commands,
+    query: &mut UndoNodeQuery,
+    edge_query: &Query<(Entity, &Edge)>,
+) {
+    replay(action, revert, commands, query, edge_query, true);
+}
+
+fn replay(
+    action: &mut Action,
     revert: bool,
     commands: &mut Commands,
-    query: &mut Query<
-        (
-            Entity,
-            &mut Transform,
-            &mut TextData,
-            &mut NodeColor,
-            Option<&SourceLocation>,
-        ),
-        (With<Selected>, Without<MainCamera>),
-    >,
+    query: &mut UndoNodeQuery,
     edge_query: &Query<(Entity, &Edge)>,
+    follow: bool,
 ) {
     match action {
         Action::CreateNode {
-            pos, text, color, ..
+            entity,
+            pos,
+            text,
+            color,
         } => {
             if revert {
-                if let Some((e, ..)) = query
+                // The recorded entity may be unselected (`:template insert`,
+                // `Y`). If it was deleted and undeleted since, the respawned
+                // node is found by position instead.
+                if let Ok(mut e_cmd) = commands.get_entity(*entity) {
+                    e_cmd.despawn();
+                } else if let Some((e, ..)) = query
                     .iter()
                     .find(|(_, t, ..)| (t.translation.truncate() - *pos).length() < 0.1)
                 {
                     commands.entity(e).despawn();
                 }
             } else {
-                spawn_canvas_node(commands, *pos, text, *color, false);
+                *entity = spawn_canvas_node(commands, *pos, text.as_str(), *color, false);
+                if follow {
+                    select_only(commands, query, *entity);
+                }
             }
         }
         Action::DeleteNode {
-            pos, text, color, ..
+            entity,
+            pos,
+            text,
+            color,
+            edges,
         } => {
             if revert {
-                spawn_canvas_node(commands, *pos, text, *color, false);
-            } else {
-                if let Some((e, ..)) = query
-                    .iter()
-                    .find(|(_, t, ..)| (t.translation.truncate() - *pos).length() < 0.1)
-                {
-                    commands.entity(e).despawn();
+                let old = *entity;
+                *entity = spawn_canvas_node(commands, *pos, text.as_str(), *color, false);
+                // An edge to a node deleted in the same batch comes back with
+                // whichever of the two is respawned last.
+                for (source, target, label) in edges.iter_mut() {
+                    for end in [&mut *source, &mut *target] {
+                        if *end == old {
+                            *end = *entity;
+                        }
+                    }
+                    if commands.get_entity(*source).is_ok()
+                        && commands.get_entity(*target).is_ok()
+                    {
+                        commands.spawn(Edge {
+                            source: *source,
+                            target: *target,
+                            label: label.clone(),
+                        });
+                    }
+                }
+                if follow {
+                    select_only(commands, query, *entity);
                 }
+            } else if commands.get_entity(*entity).is_ok() {
+                delete_node(commands, *entity, edge_query);
+            } else if let Some((e, ..)) = query
+                .iter()
+                .find(|(_, t, ..)| (t.translation.truncate() - *pos).length() < 0.1)
+            {
+                commands.entity(e).despawn();
             }
         }
         Action::MoveNode { entity, from, to } => {
@@ -130,12 +403,29 @@ pub fn apply_action(
             if let Ok((_, mut transform, ..)) = query.get_mut(*entity) {
                 transform.translation.x = target_pos.x;
                 transform.translation.y = target_pos.y;
+            } else if let Ok(mut e_cmd) = commands.get_entity(*entity) {
+                // Not the selected node (e.g. part of a `:layout` batch).
+                e_cmd.entry::<Transform>().and_modify(move |mut transform| {
+                    transform.translation.x = target_pos.x;
+                    transform.translation.y = target_pos.y;
+                });
+            }
+            if follow {
+                select_only(commands, query, *entity);
             }
         }
         Action::EditText { entity, old, new } => {
             let target_text = if revert { old } else { new };
             if let Ok((_, _, mut text_data, ..)) = query.get_mut(*entity) {
                 text_data.content = target_text.clone();
+            } else if let Ok(mut e_cmd) = commands.get_entity(*entity) {
+                let content = target_text.clone();
+                e_cmd
+                    .entry::<TextData>()
+                    .and_modify(move |mut text_data| text_data.content = content);
+            }
+            if follow {
+                select_only(commands, query, *entity);
             }
         }
         Action::CreateEdge {
@@ -151,11 +441,13 @@ pub fn apply_action(
             } else if commands.get_entity(*source).is_ok()
                 && commands.get_entity(*target).is_ok()
             {
-                commands.spawn(Edge {
-                    source: *source,
-                    target: *target,
-                    label: label.clone(),
-                });
+                *entity = commands
+                    .spawn(Edge {
+                        source: *source,
+                        target: *target,
+                        label: label.clone(),
+                    })
+                    .id();
             } else {
                 warn!("[UNDO] Skipping edge redo: source or target entity missing");
             }
@@ -186,6 +478,105 @@ pub fn apply_action(
                 }
             }
         }
+        Action::ReverseEdge {
+            entity,
+            source,
+            target,
+        } => {
+            let (source, target) = if revert { (*source, *target) } else { (*target, *source) };
+            if let Ok(mut e_cmd) = commands.get_entity(*entity) {
+                e_cmd.entry::<Edge>().and_modify(move |mut edge| {
+                    edge.source = source;
+                    edge.target = target;
+                });
+            }
+        }
+        Action::RecolorNode { entity, old, new } => {
+            let fill = if revert { *old } else { *new };
+            if let Ok(mut e_cmd) = commands.get_entity(*entity) {
+                e_cmd.entry::<NodeColor>().and_modify(move |mut color| color.0 = fill);
+            }
+            if follow {
+                select_only(commands, query, *entity);
+            }
+        }
+        Action::ReshapeNode { entity, old, new } => {
+            let shape = if revert { *old } else { *new };
+            if let Ok(mut e_cmd) = commands.get_entity(*entity) {
+                e_cmd.insert(shape);
+            }
+            if follow {
+                select_only(commands, query, *entity);
+            }
+        }
+        Action::RestyleEdge { entity, old, new } => {
+            let stroke = if revert { *old } else { *new };
+            if let Ok(mut e_cmd) = commands.get_entity(*entity) {
+                e_cmd.insert(stroke);
+            }
+        }
+        Action::CurveEdge { entity, old, new } => {
+            let curve = if revert { *old } else { *new };
+            if let Ok(mut e_cmd) = commands.get_entity(*entity) {
+                match curve {
+                    Some(offset) => e_cmd.insert(CurveOffset(offset)),
+                    None => e_cmd.remove::<CurveOffset>(),
+                };
+            }
+        }
+        Action::ReplaceCanvas { before, after } => {
+            let snapshot = if revert { before } else { after };
+            commands.insert_resource(PendingCanvasRestore(Some(snapshot.clone())));
+        }
+        Action::Batch(actions) => {
+            if revert {
+                for i in (0..actions.len()).rev() {
+                    let deleted = match &actions[i] {
+                        Action::DeleteNode { entity, .. } => Some(*entity),
+                        _ => None,
+                    };
+                    replay(&mut actions[i], revert, commands, query, edge_query, false);
+                    if let (Some(old), Action::DeleteNode { entity, .. }) = (deleted, &actions[i])
+                    {
+                        let new = *entity;
+                        retarget_edges(actions, old, new);
+                    }
+                }
+            } else {
+                for i in 0..actions.len() {
+                    let created = match &actions[i] {
+                        Action::CreateNode { entity, .. } => Some(*entity),
+                        _ => None,
+                    };
+                    replay(&mut actions[i], revert, commands, query, edge_query, false);
+                    if let (Some(old), Action::CreateNode { entity, .. }) = (created, &actions[i])
+                    {
+                        let new = *entity;
+                        retarget_edges(actions, old, new);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Point the recorded edges of every `DeleteNode` and `CreateEdge` in a batch
+/// at `new`, the node respawned in place of `old`.
+fn retarget_edges(actions: &mut [Action], old: Entity, new: Entity) {
+    for action in actions {
+        let ends: Vec<&mut Entity> = match action {
+            Action::DeleteNode { edges, .. } => edges
+                .iter_mut()
+                .flat_map(|(source, target, _)| [source, target])
+                .collect(),
+            Action::CreateEdge { source, target, .. } => vec![source, target],
+            _ => continue,
+        };
+        for end in ends {
+            if *end == old {
+                *end = new;
+            }
+        }
     }
 }
 
@@ -198,6 +589,70 @@ mod tests {
         world.spawn_empty().id()
     }
 
+    #[test]
+    fn quick_moves_of_one_node_coalesce_into_one_step() {
+        let mut world = World::new();
+        let (a, b) = (test_entity(&mut world), test_entity(&mut world));
+        let step = |entity, x: f32| Action::MoveNode {
+            entity,
+            from: Vec2::new(x, 0.0),
+            to: Vec2::new(x + 10.0, 0.0),
+        };
+        let t0 = std::time::Instant::now();
+        let ms = |n| t0 + std::time::Duration::from_millis(n);
+        let mut history = UndoHistory::default();
+        history.push_at(step(a, 0.0), ms(0));
+        history.push_at(step(a, 10.0), ms(100));
+        history.push_at(step(a, 20.0), ms(200));
+        assert_eq!(history.undo_stack.len(), 1);
+        assert!(matches!(
+            history.undo_stack[0],
+            Action::MoveNode { from, to, .. } if from.x == 0.0 && to.x == 30.0
+        ));
+
+        // Another node, a pause, or an undo in between each start a new step.
+        history.push_at(step(b, 0.0), ms(300));
+        history.push_at(step(b, 10.0), ms(300) + MOVE_COALESCE_WINDOW * 2);
+        assert_eq!(history.undo_stack.len(), 3);
+        history.pop_undo();
+        history.push_at(step(b, 10.0), ms(2000));
+        assert_eq!(history.undo_stack.len(), 3);
+    }
+
+    #[test]
+    fn log_lines_describe_each_action_oldest_first() {
+        let mut world = World::new();
+        let (a, b) = (test_entity(&mut world), test_entity(&mut world));
+        let mut history = UndoHistory::default();
+        history.push(Action::CreateNode {
+            entity: a,
+            pos: Vec2::ZERO,
+            text: "foo".to_string(),
+            color: Color::WHITE,
+        });
+        history.push(Action::Batch(vec![Action::MoveNode {
+            entity: a,
+            from: Vec2::ZERO,
+            to: Vec2::new(50.0, -0.4),
+        }]));
+        history.push(Action::DeleteEdge {
+            source: a,
+            target: b,
+            label: Some("calls".to_string()),
+        });
+
+        let (ea, eb) = (a.index_u32(), b.index_u32());
+        assert_eq!(
+            history.log_lines(),
+            [
+                format!("  1  CreateNode e{} \"foo\" at (0,0)", ea),
+                "  2  Batch of 1".to_string(),
+                format!("       MoveNode e{} (0,0)->(50,0)", ea),
+                format!("  3  DeleteEdge e{}->e{} \"calls\"", ea, eb),
+            ]
+        );
+    }
+
     fn make_move_action(entity: Entity, id: f32) -> Action {
         Action::MoveNode {
             entity,
@@ -367,6 +822,7 @@ mod tests {
         let e3 = test_entity(&mut world);
         let e4 = test_entity(&mut world);
         let action = Action::DeleteNode {
+            entity: e1,
             pos: Vec2::new(5.0, 5.0),
             text: "node".to_string(),
             color: Color::WHITE,
@@ -421,4 +877,273 @@ mod tests {
         let _ = create.clone();
         let _ = delete.clone();
     }
+
+    #[test]
+    fn batch_is_one_undo_step() {
+        let mut world = World::new();
+        let e = test_entity(&mut world);
+        let mut h = UndoHistory::default();
+        h.push(Action::Batch(vec![
+            make_move_action(e, 1.0),
+            make_move_action(e, 2.0),
+        ]));
+        assert_eq!(h.undo_stack.len(), 1);
+        match h.pop_undo().unwrap() {
+            Action::Batch(actions) => assert_eq!(actions.len(), 2),
+            _ => panic!("expected Batch"),
+        }
+    }
+
+    /// Replays `action` and returns it as it goes onto the other stack.
+    fn undo(world: &mut World, mut action: Action) -> Action {
+        use bevy::ecs::system::RunSystemOnce;
+        world
+            .run_system_once(
+                move |mut commands: Commands,
+                      mut query: UndoNodeQuery,
+                      edges: Query<(Entity, &Edge)>| {
+                    apply_action(&mut action, true, &mut commands, &mut query, &edges);
+                    action.clone()
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn undo_selects_the_node_it_changed() {
+        let mut world = World::new();
+        let node = |world: &mut World, x: f32, selected: bool| {
+            let mut entity = world.spawn((
+                Transform::from_xyz(x, 0.0, 0.0),
+                TextData {
+                    content: "n".into(),
+                },
+                NodeColor(Color::WHITE),
+            ));
+            if selected {
+                entity.insert(Selected);
+            }
+            entity.id()
+        };
+        let cursor = node(&mut world, 0.0, true);
+        let moved = node(&mut world, 50.0, false);
+
+        undo(
+            &mut world,
+            Action::MoveNode {
+                entity: moved,
+                from: Vec2::new(10.0, 20.0),
+                to: Vec2::new(50.0, 0.0),
+            },
+        );
+        assert!(world.get::<Selected>(moved).is_some());
+        assert!(world.get::<Selected>(cursor).is_none());
+        assert_eq!(
+            world.get::<Transform>(moved).unwrap().translation.truncate(),
+            Vec2::new(10.0, 20.0)
+        );
+
+        undo(
+            &mut world,
+            Action::EditText {
+                entity: cursor,
+                old: "before".into(),
+                new: "n".into(),
+            },
+        );
+        assert!(world.get::<Selected>(cursor).is_some());
+        assert!(world.get::<Selected>(moved).is_none());
+        assert_eq!(world.get::<TextData>(cursor).unwrap().content, "before");
+
+        // Undoing a delete respawns the node, selected.
+        undo(
+            &mut world,
+            Action::DeleteNode {
+                entity: Entity::PLACEHOLDER,
+                pos: Vec2::new(300.0, 0.0),
+                text: "gone".into(),
+                color: Color::WHITE,
+                edges: vec![],
+            },
+        );
+        let selected: Vec<String> = world
+            .query_filtered::<&TextData, With<Selected>>()
+            .iter(&world)
+            .map(|t| t.content.clone())
+            .collect();
+        assert_eq!(selected, ["gone"]);
+    }
+
+    /// Replays `action` and returns it as it goes onto the other stack.
+    fn redo(world: &mut World, mut action: Action) -> Action {
+        use bevy::ecs::system::RunSystemOnce;
+        world
+            .run_system_once(
+                move |mut commands: Commands,
+                      mut query: UndoNodeQuery,
+                      edges: Query<(Entity, &Edge)>| {
+                    apply_action(&mut action, false, &mut commands, &mut query, &edges);
+                    action.clone()
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn batch_delete_undo_restores_edges_and_redo_deletes_again() {
+        let mut world = World::new();
+        let mut node = |name: &str| {
+            world
+                .spawn((
+                    Transform::default(),
+                    TextData {
+                        content: name.into(),
+                    },
+                    NodeColor(Color::WHITE),
+                ))
+                .id()
+        };
+        let (a, b, kept) = (node("a"), node("b"), node("kept"));
+        // Visual `d` on a and b: both edges go with them.
+        let batch = Action::Batch(vec![
+            Action::DeleteNode {
+                entity: a,
+                pos: Vec2::ZERO,
+                text: "a".into(),
+                color: Color::WHITE,
+                edges: vec![(a, b, Some("calls".into()))],
+            },
+            Action::DeleteNode {
+                entity: b,
+                pos: Vec2::ZERO,
+                text: "b".into(),
+                color: Color::WHITE,
+                edges: vec![(a, b, Some("calls".into())), (b, kept, None)],
+            },
+        ]);
+        world.despawn(a);
+        world.despawn(b);
+
+        let batch = undo(&mut world, batch);
+        let texts = |world: &mut World| {
+            let mut texts: Vec<String> = world
+                .query::<&TextData>()
+                .iter(world)
+                .map(|t| t.content.clone())
+                .collect();
+            texts.sort();
+            texts
+        };
+        assert_eq!(texts(&mut world), ["a", "b", "kept"]);
+        let edges: Vec<Edge> = world.query::<&Edge>().iter(&world).cloned().collect();
+        assert_eq!(edges.len(), 2, "the a->b edge comes back once");
+        for edge in &edges {
+            assert!(world.get::<TextData>(edge.source).is_some());
+            assert!(world.get::<TextData>(edge.target).is_some());
+        }
+        assert!(edges.iter().any(|e| e.target == kept && e.label.is_none()));
+
+        redo(&mut world, batch);
+        assert_eq!(texts(&mut world), ["kept"]);
+        assert_eq!(world.query::<&Edge>().iter(&world).count(), 0);
+    }
+
+    #[test]
+    fn duplicate_undo_redo_undo_leaves_no_clone() {
+        let mut world = World::new();
+        let mut node = |name: &str, x: f32| {
+            world
+                .spawn((
+                    Transform::from_xyz(x, 0.0, 0.0),
+                    TextData {
+                        content: name.into(),
+                    },
+                    NodeColor(Color::WHITE),
+                ))
+                .id()
+        };
+        let (original, kept, clone) = (node("a", 0.0), node("kept", 200.0), node("a", 60.0));
+        let label = Some("calls".to_string());
+        let edge = world
+            .spawn(Edge {
+                source: clone,
+                target: kept,
+                label: label.clone(),
+            })
+            .id();
+        // What `Y` pushes; the clone is not selected.
+        let batch = Action::Batch(vec![
+            Action::CreateNode {
+                entity: clone,
+                pos: Vec2::new(60.0, 0.0),
+                text: "a".into(),
+                color: Color::WHITE,
+            },
+            Action::CreateEdge {
+                entity: edge,
+                source: clone,
+                target: kept,
+                label,
+            },
+        ]);
+        let count = |world: &mut World| {
+            (
+                world.query::<&TextData>().iter(world).count(),
+                world.query::<&Edge>().iter(world).count(),
+            )
+        };
+
+        let batch = undo(&mut world, batch);
+        assert_eq!(count(&mut world), (2, 0));
+        let batch = redo(&mut world, batch);
+        assert_eq!(count(&mut world), (3, 1));
+        let redone = world.query::<&Edge>().single(&world).unwrap().clone();
+        assert!(redone.source != clone && redone.target == kept);
+        assert!(world.get::<TextData>(redone.source).is_some());
+
+        undo(&mut world, batch);
+        assert_eq!(count(&mut world), (2, 0));
+        assert!(world.get_entity(original).is_ok());
+    }
+
+    #[test]
+    fn recolor_and_reshape_undo_and_redo() {
+        let mut world = World::new();
+        let (red, blue) = (Color::srgb(1.0, 0.0, 0.0), Color::srgb(0.0, 0.0, 1.0));
+        let node = world
+            .spawn((
+                Transform::default(),
+                TextData {
+                    content: "n".into(),
+                },
+                NodeColor(blue),
+                NodeShape::Diamond,
+            ))
+            .id();
+        let recolor = Action::RecolorNode {
+            entity: node,
+            old: red,
+            new: blue,
+        };
+        let reshape = Action::ReshapeNode {
+            entity: node,
+            old: NodeShape::Rect,
+            new: NodeShape::Diamond,
+        };
+        assert_eq!(
+            reshape.to_string(),
+            format!("ReshapeNode e{} rect->diamond", node.index_u32())
+        );
+
+        undo(&mut world, reshape.clone());
+        undo(&mut world, recolor.clone());
+        assert_eq!(world.get::<NodeColor>(node).unwrap().0, red);
+        assert_eq!(world.get::<NodeShape>(node), Some(&NodeShape::Rect));
+        assert!(world.get::<Selected>(node).is_some());
+
+        redo(&mut world, recolor);
+        redo(&mut world, reshape);
+        assert_eq!(world.get::<NodeColor>(node).unwrap().0, blue);
+        assert_eq!(world.get::<NodeShape>(node), Some(&NodeShape::Diamond));
+    }
 }
@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+
+/// Most positions kept; the oldest is dropped first.
+pub const JUMP_LIST_CAP: usize = 50;
+
+/// Where the view was before a jump: camera centre and the selected node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JumpEntry {
+    pub camera: Vec2,
+    pub selected: Option<Entity>,
+}
+
+/// Vim-style jump list walked with Ctrl+O (back) and Ctrl+I (forward).
+/// `index == entries.len()` means "at the newest position, not walking".
+#[derive(Resource, Default)]
+pub struct JumpList {
+    entries: Vec<JumpEntry>,
+    index: usize,
+}
+
+impl JumpList {
+    /// Record the position before a jump. Forward history past the current
+    /// point is discarded, as in Vim.
+    pub fn record(&mut self, entry: JumpEntry) {
+        self.entries.truncate(self.index);
+        if self.entries.last() != Some(&entry) {
+            self.entries.push(entry);
+        }
+        if self.entries.len() > JUMP_LIST_CAP {
+            self.entries.remove(0);
+        }
+        self.index = self.entries.len();
+    }
+
+    /// Step back. `current` is stored on the first step so Ctrl+I can return to it.
+    pub fn back(&mut self, current: JumpEntry) -> Option<JumpEntry> {
+        if self.index == 0 {
+            return None;
+        }
+        if self.index == self.entries.len() {
+            self.entries.push(current);
+        }
+        self.index -= 1;
+        Some(self.entries[self.index])
+    }
+
+    /// Step forward again after `back`.
+    pub fn forward(&mut self) -> Option<JumpEntry> {
+        if self.index + 1 >= self.entries.len() {
+            return None;
+        }
+        self.index += 1;
+        Some(self.entries[self.index])
+    }
+
+    /// Drop entries whose selected node no longer exists.
+    pub fn prune(&mut self, alive: impl Fn(Entity) -> bool) {
+        let mut kept = Vec::with_capacity(self.entries.len());
+        let mut index = self.index;
+        for (i, entry) in self.entries.drain(..).enumerate() {
+            if entry.selected.is_none_or(&alive) {
+                kept.push(entry);
+            } else if i < self.index {
+                index -= 1;
+            }
+        }
+        self.entries = kept;
+        self.index = index.min(self.entries.len());
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32) -> JumpEntry {
+        JumpEntry {
+            camera: Vec2::new(x, 0.0),
+            selected: None,
+        }
+    }
+
+    #[test]
+    fn back_and_forward_walk_the_list() {
+        let mut list = JumpList::default();
+        list.record(at(1.0));
+        list.record(at(2.0));
+        assert_eq!(list.back(at(3.0)), Some(at(2.0)));
+        assert_eq!(list.back(at(2.0)), Some(at(1.0)));
+        assert_eq!(list.back(at(1.0)), None);
+        assert_eq!(list.forward(), Some(at(2.0)));
+        assert_eq!(list.forward(), Some(at(3.0)), "returns to where Ctrl+O started");
+        assert_eq!(list.forward(), None);
+    }
+
+    #[test]
+    fn new_jump_discards_forward_history() {
+        let mut list = JumpList::default();
+        list.record(at(1.0));
+        list.record(at(2.0));
+        list.back(at(3.0));
+        list.back(at(2.0));
+        list.record(at(9.0));
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.back(at(10.0)), Some(at(9.0)));
+    }
+
+    #[test]
+    fn record_is_capped() {
+        let mut list = JumpList::default();
+        for i in 0..(JUMP_LIST_CAP + 10) {
+            list.record(at(i as f32));
+        }
+        assert_eq!(list.len(), JUMP_LIST_CAP);
+        let mut oldest = None;
+        while let Some(e) = list.back(at(-1.0)) {
+            oldest = Some(e);
+        }
+        assert_eq!(oldest, Some(at(10.0)));
+    }
+
+    #[test]
+    fn prune_drops_despawned_nodes_and_keeps_position() {
+        let mut world = World::new();
+        let gone = world.spawn_empty().id();
+        let kept = world.spawn_empty().id();
+        let mut list = JumpList::default();
+        list.record(JumpEntry { camera: Vec2::ZERO, selected: Some(kept) });
+        list.record(JumpEntry { camera: Vec2::ONE, selected: Some(gone) });
+        list.record(at(2.0));
+        world.despawn(gone);
+
+        list.prune(|e| world.get_entity(e).is_ok());
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.back(at(3.0)), Some(at(2.0)));
+        assert_eq!(list.back(at(2.0)).and_then(|e| e.selected), Some(kept));
+    }
+}
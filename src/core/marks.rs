@@ -1,7 +1,8 @@
 use bevy::prelude::*;
 use std::collections::HashMap;
 
-/// Resource storing saved marks mapped from a character ('a'..='z') to a world position
+/// Resource storing saved marks mapped from a character ('a'..='z', '0'..='9') to a world
+/// position. A crawl fills '1'..='9' with its roots (`set_root_marks`).
 #[derive(Resource, Default)]
 pub struct Marks {
     pub locations: HashMap<char, Vec2>,
@@ -16,3 +17,34 @@ pub fn set_mark(marks: &mut Marks, key: char, pos: Vec2) {
 pub fn get_mark(marks: &Marks, key: char) -> Option<Vec2> {
     marks.locations.get(&key).copied()
 }
+
+/// Replace marks `1`–`9` with `roots`, one per root in order; a crawl calls
+/// this so `'1`…`'9` hop between its entry points. Letter marks (and `0`) are
+/// left alone, and digits past the last root are cleared.
+pub fn set_root_marks(marks: &mut Marks, roots: impl IntoIterator<Item = Vec2>) {
+    marks.locations.retain(|key, _| !('1'..='9').contains(key));
+    for (key, pos) in ('1'..='9').zip(roots) {
+        marks.locations.insert(key, pos);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_marks_refresh_digits_and_keep_letters() {
+        let mut marks = Marks::default();
+        set_mark(&mut marks, 'a', Vec2::new(5.0, 5.0));
+        set_root_marks(&mut marks, (0..12).map(|i| Vec2::new(i as f32, 0.0)));
+        assert_eq!(get_mark(&marks, '1'), Some(Vec2::ZERO));
+        assert_eq!(get_mark(&marks, '9'), Some(Vec2::new(8.0, 0.0)));
+        assert_eq!(marks.locations.len(), 10, "nine roots plus 'a'");
+
+        // A re-crawl with fewer roots drops the stale digits.
+        set_root_marks(&mut marks, [Vec2::ONE]);
+        assert_eq!(get_mark(&marks, '1'), Some(Vec2::ONE));
+        assert_eq!(get_mark(&marks, '2'), None);
+        assert_eq!(get_mark(&marks, 'a'), Some(Vec2::new(5.0, 5.0)));
+    }
+}
@@ -4,7 +4,11 @@ pub mod components;
 pub mod config;
 pub mod helpers;
 pub mod history;
+pub mod jumplist;
 pub mod marks;
 pub mod resources;
+pub mod settings;
+pub mod snapshot;
 pub mod spatial;
 pub mod state;
+pub mod tabs;
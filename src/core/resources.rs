@@ -1,16 +1,39 @@
 //! Resources for the whiteboard.
 
 use bevy::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// World units per spatial grid cell. Tune for your typical node density.
 pub const CELL_SIZE: f32 = 1000.0;
 
-/// Maps single-character jump tags ('a', 'b', …) to their CanvasNode entity.
+/// Maps jump tags ("a", "b", …, or two letters such as "za" when more targets
+/// are visible) to their entity, plus the tag prefix typed so far.
 /// Populated on VimEasymotion entry; cleared on exit.
 #[derive(Resource, Default)]
 pub struct JumpMap {
-    pub char_to_entity: HashMap<char, Entity>,
+    pub tags: HashMap<String, Entity>,
+    pub typed: String,
+}
+
+impl JumpMap {
+    /// Extend the typed prefix with `c`. Returns the target once the prefix
+    /// fits exactly one tag; a key that fits no tag is ignored.
+    pub fn type_char(&mut self, c: char) -> Option<Entity> {
+        let mut prefix = self.typed.clone();
+        prefix.push(c);
+        let mut matches = self.tags.iter().filter(|(tag, _)| tag.starts_with(&prefix));
+        let (_, &first) = matches.next()?;
+        if matches.next().is_none() {
+            return Some(first);
+        }
+        self.typed = prefix;
+        None
+    }
+
+    pub fn clear(&mut self) {
+        self.tags.clear();
+        self.typed.clear();
+    }
 }
 
 /// Spatial hash grid for O(1) viewport-culled lookups. Keys are (cell_x, cell_y).
@@ -24,6 +47,137 @@ pub struct SpatialIndex {
 #[derive(Resource, Default)]
 pub struct SelectedEdge(pub Option<Entity>);
 
+/// The edge under the mouse cursor, drawn in the theme's `edge_hover` color.
+#[derive(Resource, Default)]
+pub struct HoveredEdge(pub Option<Entity>);
+
+/// Text cursor of VimInsert: a char index into the label of `entity` (the
+/// selected node or edge). `entity` is `None` outside insert mode.
+#[derive(Resource, Default)]
+pub struct InsertCaret {
+    pub entity: Option<Entity>,
+    pub pos: usize,
+}
+
+impl InsertCaret {
+    /// Caret position if `entity` is the label being edited.
+    pub fn at(&self, entity: Entity) -> Option<usize> {
+        (self.entity == Some(entity)).then_some(self.pos)
+    }
+
+    /// Insert `s` before the caret and step past it.
+    pub fn insert(&mut self, text: &mut String, s: &str) {
+        text.insert_str(byte_offset(text, self.pos), s);
+        self.pos += s.chars().count();
+    }
+
+    /// Delete the char before the caret (Backspace). Returns false at the start.
+    pub fn backspace(&mut self, text: &mut String) -> bool {
+        if self.pos == 0 {
+            return false;
+        }
+        self.pos -= 1;
+        text.remove(byte_offset(text, self.pos));
+        true
+    }
+
+    /// Delete the char under the caret (Delete). Returns false at the end.
+    pub fn delete(&self, text: &mut String) -> bool {
+        if self.pos >= text.chars().count() {
+            return false;
+        }
+        text.remove(byte_offset(text, self.pos));
+        true
+    }
+}
+
+/// Byte offset of the `pos`-th char of `text`, or its length past the end.
+fn byte_offset(text: &str, pos: usize) -> usize {
+    text.char_indices().nth(pos).map_or(text.len(), |(i, _)| i)
+}
+
+/// Pattern set by `:grep`. Nodes whose text does not match are dimmed; `None` shows all.
+#[derive(Resource, Default)]
+pub struct GrepFilter(pub Option<String>);
+
+/// Nodes matched by the last `:grep` or `/` search, in reading order (top to
+/// bottom, then left to right). While non-empty, `n` / `N` step through them
+/// instead of creating a node. `idx == ordered.len()` means no match visited yet.
+#[derive(Resource, Default)]
+pub struct SearchMatches {
+    pub query: String,
+    pub ordered: Vec<Entity>,
+    pub idx: usize,
+}
+
+impl SearchMatches {
+    /// Matches for `query` at their world positions, sorted into reading order.
+    pub fn new(query: impl Into<String>, mut matches: Vec<(Entity, Vec2)>) -> Self {
+        matches.sort_by(|(_, a), (_, b)| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+        let ordered: Vec<Entity> = matches.into_iter().map(|(e, _)| e).collect();
+        Self {
+            query: query.into(),
+            idx: ordered.len(),
+            ordered,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.ordered.is_empty()
+    }
+
+    /// Make `entity` the current match, if it is one.
+    pub fn set_current(&mut self, entity: Entity) {
+        if let Some(i) = self.ordered.iter().position(|e| *e == entity) {
+            self.idx = i;
+        }
+    }
+
+    /// Advance (`forward`) or retreat to the next match, wrapping around.
+    /// The first step goes to the first (or, backwards, the last) match.
+    pub fn step(&mut self, forward: bool) -> Option<Entity> {
+        let n = self.ordered.len();
+        if n == 0 {
+            return None;
+        }
+        self.idx = match (self.idx >= n, forward) {
+            (true, true) => 0,
+            (true, false) => n - 1,
+            (false, true) => (self.idx + 1) % n,
+            (false, false) => (self.idx + n - 1) % n,
+        };
+        Some(self.ordered[self.idx])
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Nodes marked in VimVisual mode. Separate from `Selected`, which stays single.
+#[derive(Resource, Default)]
+pub struct VisualSelection(pub HashSet<Entity>);
+
+impl VisualSelection {
+    /// Add `entity` if absent, remove it if present. Returns true if now a member.
+    pub fn toggle(&mut self, entity: Entity) -> bool {
+        if self.0.remove(&entity) {
+            false
+        } else {
+            self.0.insert(entity);
+            true
+        }
+    }
+}
+
+/// Vim unnamed register: the last node yanked (`yy`) or deleted (`dd`).
+/// Absent until the first yank, so `p` on an empty register is a no-op.
+#[derive(Resource, Clone)]
+pub struct Register {
+    pub text: String,
+    pub color: Color,
+}
+
 /// Transient status / error message displayed in the bottom bar.
 /// `timer` counts down in seconds; the message is visible while `timer > 0`.
 #[derive(Resource, Default)]
@@ -100,6 +254,73 @@ mod tests {
         Entity::from_bits(i as u64)
     }
 
+    #[test]
+    fn jump_map_narrows_two_letter_tags() {
+        let mut map = JumpMap::default();
+        for (i, tag) in ["a", "za", "zb", "ya"].into_iter().enumerate() {
+            map.tags.insert(tag.to_string(), entity(i as u32 + 1));
+        }
+        assert_eq!(map.type_char('a'), Some(entity(1)));
+        assert_eq!(map.type_char('q'), None, "no tag starts with q");
+        assert!(map.typed.is_empty());
+        assert_eq!(map.type_char('y'), Some(entity(4)), "the only y tag is picked at once");
+        assert_eq!(map.type_char('z'), None);
+        assert_eq!(map.typed, "z");
+        assert_eq!(map.type_char('b'), Some(entity(3)));
+    }
+
+    #[test]
+    fn insert_caret_edits_mid_string() {
+        let mut text = "héllo".to_string();
+        let mut caret = InsertCaret {
+            entity: Some(entity(1)),
+            pos: 2,
+        };
+        caret.insert(&mut text, "XY");
+        assert_eq!((text.as_str(), caret.pos), ("héXYllo", 4));
+        assert!(caret.backspace(&mut text));
+        assert!(caret.delete(&mut text));
+        assert_eq!((text.as_str(), caret.pos), ("héXlo", 3));
+        caret.pos = 0;
+        assert!(!caret.backspace(&mut text));
+        caret.pos = 5;
+        assert!(!caret.delete(&mut text));
+        assert_eq!(caret.at(entity(1)), Some(5));
+        assert_eq!(caret.at(entity(2)), None);
+    }
+
+    #[test]
+    fn visual_selection_toggle() {
+        let mut sel = VisualSelection::default();
+        assert!(sel.toggle(entity(1)));
+        assert!(sel.toggle(entity(2)));
+        assert!(!sel.toggle(entity(1)));
+        assert_eq!(sel.0.len(), 1);
+        assert!(sel.0.contains(&entity(2)));
+    }
+
+    #[test]
+    fn search_matches_read_in_order_and_wrap() {
+        let mut search = SearchMatches::new(
+            "x",
+            vec![
+                (entity(1), Vec2::new(100.0, 0.0)),
+                (entity(2), Vec2::new(-100.0, 0.0)),
+                (entity(3), Vec2::new(500.0, 300.0)),
+            ],
+        );
+        assert_eq!(search.ordered, vec![entity(3), entity(2), entity(1)]);
+        assert_eq!(search.step(false), Some(entity(1)));
+        assert_eq!(search.step(true), Some(entity(3)));
+        assert_eq!(search.step(true), Some(entity(2)));
+        search.set_current(entity(1));
+        assert_eq!(search.step(true), Some(entity(3)));
+
+        search.clear();
+        assert!(!search.is_active());
+        assert_eq!(search.step(true), None);
+    }
+
     #[test]
     fn world_to_cell() {
         assert_eq!(SpatialIndex::world_to_cell(Vec3::ZERO), (0, 0));
@@ -0,0 +1,201 @@
+//! `:set key value` / `:set key?`: the `GlyphConfig` fields that can be
+//! changed at runtime. `SETTINGS` is the whole whitelist; making another field
+//! settable is one `setting!` line, provided its type implements
+//! `SettingValue` or the line names a parser (colors use
+//! [`parse_hex_color`]) and, when `to_string` will not do, a formatter (the
+//! grid shows `off`). Only fields something reads after startup belong here.
+
+use crate::core::config::GlyphConfig;
+
+/// How a config field's type is read from and shown on the command line.
+pub trait SettingValue: Sized {
+    fn parse_setting(value: &str) -> Result<Self, String>;
+    fn show_setting(&self) -> String;
+}
+
+impl SettingValue for bool {
+    fn parse_setting(value: &str) -> Result<Self, String> {
+        match value {
+            "on" | "true" | "yes" | "1" => Ok(true),
+            "off" | "false" | "no" | "0" => Ok(false),
+            _ => Err(format!("expected on or off, got '{}'", value)),
+        }
+    }
+
+    fn show_setting(&self) -> String {
+        if *self { "on" } else { "off" }.to_string()
+    }
+}
+
+impl SettingValue for f32 {
+    fn parse_setting(value: &str) -> Result<Self, String> {
+        match value.parse::<f32>() {
+            Ok(n) if n.is_finite() && n >= 0.0 => Ok(n),
+            _ => Err(format!("expected a non-negative number, got '{}'", value)),
+        }
+    }
+
+    fn show_setting(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl SettingValue for usize {
+    fn parse_setting(value: &str) -> Result<Self, String> {
+        value
+            .parse()
+            .map_err(|_| format!("expected a whole number, got '{}'", value))
+    }
+
+    fn show_setting(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Parse the value of `:set grid <size>`. `off` or `0` disables snapping.
+pub fn parse_grid_setting(value: &str) -> Result<Option<f32>, String> {
+    if value == "off" {
+        return Ok(None);
+    }
+    match value.parse::<f32>() {
+        Ok(size) if size.is_finite() && size >= 0.0 => Ok((size > 0.0).then_some(size)),
+        _ => Err(format!("invalid grid size: {}", value)),
+    }
+}
+
+/// Show the snap grid as `:set grid?` prints it: the size, or `off`.
+pub fn show_grid_setting(size: &Option<f32>) -> String {
+    size.map_or("off".to_string(), |n| n.to_string())
+}
+
+/// Parse a `:set` color: `#rrggbb`, `#` optional, stored lowercase with `#`.
+pub fn parse_hex_color(value: &str) -> Result<String, String> {
+    let hex = value.trim_start_matches('#');
+    match bevy::color::Srgba::hex(hex) {
+        Ok(_) if hex.len() == 6 => Ok(format!("#{}", hex.to_lowercase())),
+        _ => Err(format!("expected #rrggbb, got '{}'", value)),
+    }
+}
+
+/// One key of `:set`, bound to a `GlyphConfig` field.
+pub struct Setting {
+    pub name: &'static str,
+    get: fn(&GlyphConfig) -> String,
+    set: fn(&mut GlyphConfig, &str) -> Result<(), String>,
+}
+
+impl Setting {
+    /// Current value as `:set key?` shows it.
+    pub fn get(&self, config: &GlyphConfig) -> String {
+        (self.get)(config)
+    }
+
+    /// Parse `value` into the field; the field is unchanged on error.
+    pub fn set(&self, config: &mut GlyphConfig, value: &str) -> Result<(), String> {
+        (self.set)(config, value)
+    }
+}
+
+macro_rules! setting {
+    ($name:literal => $field:ident) => {
+        Setting {
+            name: $name,
+            get: |config| SettingValue::show_setting(&config.$field),
+            set: |config, value| {
+                config.$field = SettingValue::parse_setting(value)?;
+                Ok(())
+            },
+        }
+    };
+    ($name:literal => $field:ident, $parse:path) => {
+        Setting {
+            name: $name,
+            get: |config| config.$field.to_string(),
+            set: |config, value| {
+                config.$field = $parse(value)?;
+                Ok(())
+            },
+        }
+    };
+    ($name:literal => $field:ident, $parse:path, $show:path) => {
+        Setting {
+            name: $name,
+            get: |config| $show(&config.$field),
+            set: |config, value| {
+                config.$field = $parse(value)?;
+                Ok(())
+            },
+        }
+    };
+}
+
+/// Every key `:set` accepts.
+pub const SETTINGS: &[Setting] = &[
+    setting!("grid" => snap_grid, parse_grid_setting, show_grid_setting),
+    setting!("smooth_camera" => smooth_camera),
+    setting!("color_by_module" => color_by_module),
+    setting!("show_complexity" => show_complexity),
+    setting!("bundle_edges" => bundle_edges),
+    setting!("edge_avoid_nodes" => edge_avoid_nodes),
+    setting!("edge_meshes" => edge_meshes),
+    setting!("node_shadows" => node_shadows),
+    setting!("minimap" => minimap),
+    setting!("hjkl_base_speed" => hjkl_base_speed),
+    setting!("hjkl_accel_threshold" => hjkl_accel_threshold),
+    setting!("hjkl_accel_mult" => hjkl_accel_mult),
+    setting!("flow_row_height" => flow_row_height),
+    setting!("flow_node_spacing" => flow_node_spacing),
+    setting!("edge_arrow_size" => edge_arrow_size),
+    setting!("node_color" => node_color, parse_hex_color),
+    setting!("jump_tag_color" => jump_tag_color, parse_hex_color),
+];
+
+/// The `:set` key called `name`.
+pub fn find_setting(name: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|s| s.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_parse_by_field_type() {
+        let mut config = GlyphConfig::default();
+        let set = |config: &mut GlyphConfig, name: &str, value: &str| {
+            find_setting(name).unwrap().set(config, value)
+        };
+
+        set(&mut config, "smooth_camera", "off").unwrap();
+        assert!(!config.smooth_camera);
+        set(&mut config, "hjkl_base_speed", "14.5").unwrap();
+        assert_eq!(config.hjkl_base_speed, 14.5);
+        set(&mut config, "grid", "25").unwrap();
+        assert_eq!(config.snap_grid, Some(25.0));
+        set(&mut config, "node_color", "FF8800").unwrap();
+        assert_eq!(config.node_color, "#ff8800");
+        assert_eq!(find_setting("grid").unwrap().get(&config), "25");
+        set(&mut config, "grid", "off").unwrap();
+        assert_eq!(find_setting("grid").unwrap().get(&config), "off");
+        assert_eq!(find_setting("smooth_camera").unwrap().get(&config), "off");
+
+        // Bad values leave the field alone.
+        assert!(set(&mut config, "smooth_camera", "maybe").is_err());
+        assert!(set(&mut config, "hjkl_base_speed", "-1").is_err());
+        assert!(set(&mut config, "node_color", "#abc").is_err());
+        assert_eq!(config.hjkl_base_speed, 14.5);
+        assert_eq!(config.node_color, "#ff8800");
+        assert!(find_setting("theme").is_none());
+        // Nothing reads it, so `:set` would be a silent no-op.
+        assert!(find_setting("curve_segments").is_none());
+    }
+
+    #[test]
+    fn parse_grid_setting_accepts_off_and_zero() {
+        assert_eq!(parse_grid_setting("20"), Ok(Some(20.0)));
+        assert_eq!(parse_grid_setting("0"), Ok(None));
+        assert_eq!(parse_grid_setting("off"), Ok(None));
+        assert!(parse_grid_setting("-5").is_err());
+        assert!(parse_grid_setting("big").is_err());
+    }
+}
@@ -0,0 +1,113 @@
+//! The saved form of a canvas: what `:w` writes, undo keeps for
+//! `Action::ReplaceCanvas`, and `io::file_io` reads back and spawns.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::core::components::{EdgeStroke, NodeShape};
+
+/// Default node color when loading files without color (backwards compat).
+const DEFAULT_NODE_COLOR: [f32; 3] = [0.70, 0.85, 0.95];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SerializedColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl SerializedColor {
+    pub fn from_bevy(color: &Color) -> Self {
+        let srgba = color.to_srgba();
+        Self {
+            r: srgba.red,
+            g: srgba.green,
+            b: srgba.blue,
+        }
+    }
+    pub fn to_bevy(&self) -> Color {
+        Color::srgb(self.r, self.g, self.b)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SerializableNode {
+    pub id: u64,
+    pub x: f32,
+    pub y: f32,
+    pub text: String,
+    #[serde(default = "default_color")]
+    pub color: SerializedColor,
+    /// Source location of a crawled function node (powers `gd`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SerializedSource>,
+    /// True for crawled control-flow decision nodes.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub decision: bool,
+    #[serde(default, skip_serializing_if = "NodeShape::is_rect")]
+    pub shape: NodeShape,
+    /// Box width and minimum height as `[w, h]`; absent in older files, which
+    /// get `node_width` × `node_height` from the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<[f32; 2]>,
+    /// Kept in place by force layout (`zp` / `:pin`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SerializedSource {
+    pub file: String,
+    pub line: u32,
+}
+
+pub(crate) fn default_color() -> SerializedColor {
+    SerializedColor {
+        r: DEFAULT_NODE_COLOR[0],
+        g: DEFAULT_NODE_COLOR[1],
+        b: DEFAULT_NODE_COLOR[2],
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SerializableEdge {
+    pub source_id: u64,
+    pub target_id: u64,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// `:estyle` width and dash; absent for a plain solid edge.
+    #[serde(default, skip_serializing_if = "EdgeStroke::is_default")]
+    pub stroke: EdgeStroke,
+    /// Hand-set control point offset (`CurveOffset`); absent for an
+    /// automatic curve.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curve: Option<[f32; 2]>,
+    /// Shown turned around by `:callers`; `source_id` → `target_id` is still
+    /// the call direction.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reversed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SerializedCameraPrefs {
+    pub x: f32,
+    pub y: f32,
+    pub scale: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CanvasSnapshot {
+    pub nodes: Vec<SerializableNode>,
+    pub edges: Vec<SerializableEdge>,
+    #[serde(default)]
+    pub camera: Option<SerializedCameraPrefs>,
+    /// Named positions set with `m{char}`, as `[x, y]`.
+    #[serde(default)]
+    pub marks: BTreeMap<char, [f32; 2]>,
+}
+
+/// Whole-canvas replacement queued by undo/redo of `Action::ReplaceCanvas`.
+/// Processed in Update because `apply_action` only has `Commands` access.
+#[derive(Resource, Default)]
+pub struct PendingCanvasRestore(pub Option<CanvasSnapshot>);
@@ -2,16 +2,21 @@
 
 use bevy::prelude::*;
 
-use crate::core::components::{CanvasNode, GridCell};
+use crate::core::components::{CanvasNode, GridCell, InactiveCanvas};
 use crate::core::resources::SpatialIndex;
 
 /// Keeps SpatialIndex in sync with CanvasNode transforms. Runs in PostUpdate.
+/// Other tabs' nodes stay out of the index (see `core::tabs`).
 pub fn update_spatial_index_system(
     mut spatial_index: ResMut<SpatialIndex>,
     mut commands: Commands,
     moved_nodes: Query<
         (Entity, &Transform, Option<&GridCell>),
-        (With<CanvasNode>, Or<(Added<CanvasNode>, Changed<Transform>)>),
+        (
+            With<CanvasNode>,
+            Without<InactiveCanvas>,
+            Or<(Added<CanvasNode>, Changed<Transform>)>,
+        ),
     >,
 ) {
     for (entity, transform, grid_cell) in &moved_nodes {
@@ -11,4 +11,6 @@ pub enum InputMode {
     VimEasymotion,
     /// Vim `:` command-line mode. Active while user types a command.
     VimCommand,
+    /// Vim `v` visual mode. Builds a multi-node `VisualSelection` for bulk move/delete.
+    VimVisual,
 }
@@ -0,0 +1,314 @@
+//! Multiple in-memory canvases (tabs): `:tabnew`, `:tabnext` / `gt`,
+//! `:tabprev` / `gT` and `:tabclose`.
+//!
+//! All tabs live in the one World: every node and edge carries the `CanvasId`
+//! of the tab it was created in, and the other tabs' entities are tagged
+//! `InactiveCanvas`. `sync_collapsed_system` hides those, and canvas-wide
+//! systems (save, load, crawl, layout, search, picking) skip them. Each tab
+//! keeps its own `CurrentFile`, undo history, marks, search and crawl state
+//! (see [`TabState`]); a background crawl lands in the tab that started it.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::core::components::{CanvasId, CanvasNode, Edge, InactiveCanvas, Selected};
+use crate::core::history::UndoHistory;
+use crate::core::marks::Marks;
+use crate::core::resources::{GrepFilter, SearchMatches, SelectedEdge, SpatialIndex, StatusMessage};
+use crate::crawler::flow::CrawledFlow;
+use crate::crawler::{CrawlJob, WatchState};
+use crate::io::file_io::CurrentFile;
+
+/// Message sent by the tab commands.
+#[derive(Message, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TabRequest {
+    New,
+    Next,
+    Prev,
+    Close,
+}
+
+/// Everything that belongs to one tab besides its entities: the resources
+/// of the active tab, or a tab set aside while another is active. Keeping
+/// the crawl state (`WatchState`, `CrawledFlow`) per tab means a re-crawl,
+/// `:flow` or `:callers` only ever touches the tab that crawled.
+#[derive(Default)]
+pub struct TabState {
+    pub file: CurrentFile,
+    pub history: UndoHistory,
+    pub marks: Marks,
+    pub watch: WatchState,
+    pub flow: CrawledFlow,
+    pub grep: GrepFilter,
+    pub search: SearchMatches,
+}
+
+/// Open tabs. `parked[active]` is an empty placeholder; the active tab's
+/// state lives in its resources until the tab is left.
+#[derive(Resource)]
+pub struct Canvases {
+    pub active: usize,
+    parked: Vec<TabState>,
+}
+
+impl Default for Canvases {
+    fn default() -> Self {
+        Self {
+            active: 0,
+            parked: vec![TabState::default()],
+        }
+    }
+}
+
+impl Canvases {
+    pub fn len(&self) -> usize {
+        self.parked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parked.is_empty()
+    }
+
+    /// Park the active tab's state (`live`) and make `index` active.
+    pub fn switch(&mut self, index: usize, live: &mut TabState) {
+        let cap = live.history.cap;
+        self.parked[self.active] = std::mem::take(live);
+        self.active = index;
+        self.restore(live, cap);
+    }
+
+    /// Append an empty tab and make it active.
+    pub fn open(&mut self, live: &mut TabState) {
+        self.parked.push(TabState::default());
+        self.switch(self.len() - 1, live);
+    }
+
+    /// Drop the active tab (its entities are the caller's job) and activate
+    /// the tab that takes its place. Tabs after it shift down by one.
+    pub fn close(&mut self, live: &mut TabState) {
+        let cap = live.history.cap;
+        self.parked.remove(self.active);
+        self.active = self.active.min(self.len() - 1);
+        self.restore(live, cap);
+    }
+
+    fn restore(&mut self, live: &mut TabState, cap: usize) {
+        *live = std::mem::take(&mut self.parked[self.active]);
+        live.history.cap = cap;
+    }
+
+    /// `Tab 2/3: name.glyph` for the status line.
+    pub fn describe(&self, current_file: &CurrentFile) -> String {
+        let name = current_file
+            .0
+            .as_deref()
+            .and_then(|p| p.file_name())
+            .map_or("[No Name]".into(), |n| n.to_string_lossy());
+        format!("Tab {}/{}: {}", self.active + 1, self.len(), name)
+    }
+}
+
+/// Active-tab state swapped when switching tabs.
+#[derive(SystemParam)]
+pub struct ActiveTabState<'w> {
+    current_file: ResMut<'w, CurrentFile>,
+    history: ResMut<'w, UndoHistory>,
+    marks: ResMut<'w, Marks>,
+    watch: ResMut<'w, WatchState>,
+    flow: ResMut<'w, CrawledFlow>,
+    grep: ResMut<'w, GrepFilter>,
+    search: ResMut<'w, SearchMatches>,
+    selected_edge: ResMut<'w, SelectedEdge>,
+    crawl_job: Option<ResMut<'w, CrawlJob>>,
+}
+
+impl ActiveTabState<'_> {
+    /// Move the active tab's resources out, leaving defaults behind.
+    fn take(&mut self) -> TabState {
+        TabState {
+            file: std::mem::take(&mut *self.current_file),
+            history: std::mem::take(&mut *self.history),
+            marks: std::mem::take(&mut *self.marks),
+            watch: std::mem::take(&mut *self.watch),
+            flow: std::mem::take(&mut *self.flow),
+            grep: std::mem::take(&mut *self.grep),
+            search: std::mem::take(&mut *self.search),
+        }
+    }
+
+    fn put(&mut self, tab: TabState) {
+        *self.current_file = tab.file;
+        *self.history = tab.history;
+        *self.marks = tab.marks;
+        *self.watch = tab.watch;
+        *self.flow = tab.flow;
+        *self.grep = tab.grep;
+        *self.search = tab.search;
+    }
+}
+
+/// Apply tab requests: switch, open or close tabs and drop the selection of
+/// the tab being left. Closing despawns the tab's nodes and edges.
+pub fn handle_tab_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<TabRequest>,
+    mut canvases: ResMut<Canvases>,
+    mut tab: ActiveTabState,
+    mut status: ResMut<StatusMessage>,
+    entities: Query<(Entity, &CanvasId, Has<Selected>)>,
+) {
+    for request in requests.read() {
+        let (len, active) = (canvases.len(), canvases.active);
+        let mut live = tab.take();
+        let switched = match request {
+            TabRequest::New => {
+                canvases.open(&mut live);
+                true
+            }
+            TabRequest::Next | TabRequest::Prev if len == 1 => {
+                status.set("Only one tab open");
+                false
+            }
+            TabRequest::Next => {
+                canvases.switch((active + 1) % len, &mut live);
+                true
+            }
+            TabRequest::Prev => {
+                canvases.switch((active + len - 1) % len, &mut live);
+                true
+            }
+            TabRequest::Close if len == 1 => {
+                status.set("error: cannot close the last tab");
+                false
+            }
+            TabRequest::Close => {
+                for (entity, id, _) in &entities {
+                    if id.0 == active {
+                        commands.entity(entity).try_despawn();
+                    } else if id.0 > active {
+                        commands.entity(entity).try_insert(CanvasId(id.0 - 1));
+                    }
+                }
+                // A crawl started in a later tab follows it down; one started
+                // here has nowhere left to go.
+                match tab.crawl_job.as_deref_mut() {
+                    Some(job) if job.canvas == active => {
+                        commands.remove_resource::<CrawlJob>();
+                    }
+                    Some(job) if job.canvas > active => job.canvas -= 1,
+                    _ => {}
+                }
+                canvases.close(&mut live);
+                true
+            }
+        };
+        tab.put(live);
+        if !switched {
+            continue;
+        }
+        for (entity, ..) in entities.iter().filter(|(_, _, selected)| *selected) {
+            commands.entity(entity).try_remove::<Selected>();
+        }
+        tab.selected_edge.0 = None;
+        status.set(canvases.describe(&tab.current_file));
+        info!("[TAB] {:?} → {}", request, canvases.describe(&tab.current_file));
+    }
+}
+
+type UntaggedQuery<'w, 's> =
+    Query<'w, 's, Entity, (Or<(With<CanvasNode>, With<Edge>)>, Without<CanvasId>)>;
+
+type TaggedQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static CanvasId, Has<InactiveCanvas>, Option<&'static Transform>),
+>;
+
+/// Tag new nodes and edges with the active tab and, when the active tab
+/// changes, keep `InactiveCanvas` on exactly the other tabs' entities. Only
+/// the active tab's nodes are kept in the `SpatialIndex`.
+pub fn sync_canvas_tabs_system(
+    mut commands: Commands,
+    canvases: Res<Canvases>,
+    mut spatial_index: ResMut<SpatialIndex>,
+    untagged: UntaggedQuery,
+    tagged: TaggedQuery,
+) {
+    for entity in &untagged {
+        commands.entity(entity).try_insert(CanvasId(canvases.active));
+    }
+    if !canvases.is_changed() {
+        return;
+    }
+    for (entity, id, inactive, transform) in &tagged {
+        match (id.0 != canvases.active, inactive) {
+            (true, false) => {
+                commands.entity(entity).try_insert(InactiveCanvas);
+                spatial_index.remove(entity);
+            }
+            (false, true) => {
+                commands.entity(entity).try_remove::<InactiveCanvas>();
+                if let Some(transform) = transform {
+                    let cell = SpatialIndex::world_to_cell(transform.translation);
+                    spatial_index.insert(entity, cell);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::history::Action;
+
+    fn move_action(world: &mut World) -> Action {
+        Action::MoveNode {
+            entity: world.spawn_empty().id(),
+            from: Vec2::ZERO,
+            to: Vec2::ONE,
+        }
+    }
+
+    #[test]
+    fn tabs_keep_their_own_file_and_history() {
+        let mut world = World::new();
+        let mut canvases = Canvases::default();
+        let mut live = TabState {
+            file: CurrentFile(Some(PathBuf::from("a.glyph")), None),
+            history: UndoHistory {
+                cap: 7,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        live.history.push(move_action(&mut world));
+        live.marks.locations.insert('a', Vec2::ONE);
+        live.flow.callers = true;
+
+        canvases.open(&mut live);
+        assert_eq!((canvases.active, canvases.len()), (1, 2));
+        assert!(live.file.0.is_none() && live.history.undo_stack.is_empty());
+        assert!(live.marks.locations.is_empty() && !live.flow.callers);
+        assert_eq!(live.history.cap, 7);
+        live.file.0 = Some(PathBuf::from("b.glyph"));
+        live.watch.watch_paths = vec!["src".into()];
+
+        canvases.switch(0, &mut live);
+        assert_eq!(live.file.0.as_deref(), Some(std::path::Path::new("a.glyph")));
+        assert_eq!(live.history.undo_stack.len(), 1);
+        assert_eq!(live.marks.locations.get(&'a'), Some(&Vec2::ONE));
+        assert!(live.flow.callers && live.watch.watch_paths.is_empty());
+        assert_eq!(canvases.describe(&live.file), "Tab 1/2: a.glyph");
+
+        // Closing tab 1 brings tab 2 (now the only one) back with its state.
+        canvases.close(&mut live);
+        assert_eq!((canvases.active, canvases.len()), (0, 1));
+        assert_eq!(live.file.0.as_deref(), Some(std::path::Path::new("b.glyph")));
+        assert_eq!(live.watch.watch_paths, ["src"]);
+        assert!(live.history.undo_stack.is_empty());
+    }
+}
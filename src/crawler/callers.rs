@@ -0,0 +1,243 @@
+//! `:callers` / `:callees`: turn the crawled edges of the last crawl around
+//! without re-crawling, so arrows point from a function to its callers, and
+//! lay the nodes out again top-down on the reversed graph. `:callees` puts the
+//! call direction and layout back.
+//!
+//! Turned edges carry `ReversedCall` and are saved in their call direction
+//! with a `reversed` flag, so a reloaded file still knows which way the calls
+//! go and `:callees` can turn them back. The relayout is one undo step.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use super::flow::CrawledFlow;
+use super::{flow_layout, FlowEdge, FlowMap};
+use crate::core::components::{Edge, FlowBypass, FlowNodeId, InactiveCanvas, ReversedCall};
+use crate::core::config::GlyphConfig;
+use crate::core::history::{Action, UndoHistory};
+use crate::core::resources::StatusMessage;
+
+/// Message sent by `:callers` (`true`) and `:callees` (`false`).
+#[derive(Message)]
+pub struct CallersRequest(pub bool);
+
+/// `graph` with every edge turned around: callee → caller. Every function of
+/// `graph` is a key, so leaves of the call graph become roots.
+pub fn transpose(graph: &FlowMap) -> FlowMap {
+    let mut out: FlowMap = graph.keys().map(|id| (id.clone(), Vec::new())).collect();
+    for (caller, edges) in graph {
+        for edge in edges {
+            out.entry(edge.target.clone()).or_default().push(FlowEdge {
+                target: caller.clone(),
+                label: edge.label.clone(),
+            });
+        }
+    }
+    out
+}
+
+type FlowNodeQuery<'w, 's> =
+    Query<'w, 's, (Entity, &'static FlowNodeId, &'static mut Transform), Without<InactiveCanvas>>;
+
+type CallEdgeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static mut Edge, Has<ReversedCall>),
+    (Without<FlowBypass>, Without<InactiveCanvas>),
+>;
+
+/// Handles `CallersRequest`: reverses every edge of the crawl (hand-drawn
+/// edges stay) and moves the crawled nodes to the hierarchy layout of the
+/// shown direction, recorded as a `Batch` of `MoveNode`s.
+/// `sync_flow_system` turns the `:flow off` bypasses along. Going back only
+/// needs the `ReversedCall` markers, so it works on a reloaded file too.
+pub fn handle_callers_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<CallersRequest>,
+    mut crawled: ResMut<CrawledFlow>,
+    mut nodes: FlowNodeQuery,
+    mut edges: CallEdgeQuery,
+    config: Res<GlyphConfig>,
+    mut history: ResMut<UndoHistory>,
+    mut status: ResMut<StatusMessage>,
+) {
+    for CallersRequest(callers) in requests.read() {
+        let view = if *callers { "callers" } else { "callees" };
+        let ids: HashMap<Entity, String> =
+            nodes.iter().map(|(e, id, _)| (e, id.0.clone())).collect();
+        let showing_callers = crawled.callers || edges.iter().any(|(.., turned)| turned);
+        if showing_callers == *callers {
+            status.set(format!("Already showing {}", view));
+            continue;
+        }
+        if *callers && (crawled.graph.is_empty() || ids.is_empty()) {
+            status.set(format!("{}: no crawled nodes to show", view));
+            continue;
+        }
+
+        // Going to callers, turn every edge a → b where a calls b; going
+        // back, exactly the edges turned then, so hand-drawn ones stay put.
+        let mut reversed = 0;
+        for (entity, mut edge, was_reversed) in &mut edges {
+            let turn = if *callers {
+                let (Some(from), Some(to)) = (ids.get(&edge.source), ids.get(&edge.target)) else {
+                    continue;
+                };
+                crawled
+                    .graph
+                    .get(from)
+                    .is_some_and(|out| out.iter().any(|e| &e.target == to))
+            } else {
+                was_reversed
+            };
+            if !turn {
+                continue;
+            }
+            let edge = &mut *edge;
+            std::mem::swap(&mut edge.source, &mut edge.target);
+            if *callers {
+                commands.entity(entity).insert(ReversedCall);
+            } else {
+                commands.entity(entity).remove::<ReversedCall>();
+            }
+            reversed += 1;
+        }
+
+        // Without the crawl (a reloaded file) the nodes keep their places.
+        let mut names: Vec<String> = ids.values().cloned().collect();
+        names.sort();
+        names.dedup();
+        let shown = if *callers {
+            transpose(&crawled.graph)
+        } else {
+            crawled.graph.clone()
+        };
+        let positions: HashMap<String, Vec2> = if shown.is_empty() {
+            HashMap::new()
+        } else {
+            flow_layout(&shown, &names, &config).into_iter().collect()
+        };
+        let mut moves = Vec::new();
+        for (entity, id, mut transform) in &mut nodes {
+            let Some(&to) = positions.get(&id.0) else {
+                continue;
+            };
+            let from = transform.translation.truncate();
+            if from != to {
+                transform.translation.x = to.x;
+                transform.translation.y = to.y;
+                moves.push(Action::MoveNode { entity, from, to });
+            }
+        }
+        if !moves.is_empty() {
+            history.push(Action::Batch(moves));
+        }
+
+        crawled.callers = *callers;
+        info!("[CALLERS] {} view: reversed {} edges", view, reversed);
+        status.set(if *callers {
+            "Showing callers (:callees to switch back)"
+        } else {
+            "Showing callees"
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn call(target: &str) -> FlowEdge {
+        FlowEdge {
+            target: target.to_string(),
+            label: None,
+        }
+    }
+
+    #[test]
+    fn transpose_turns_leaves_into_roots() {
+        let graph: FlowMap = [
+            ("main".to_string(), vec![call("run"), call("log")]),
+            ("run".to_string(), vec![call("log")]),
+            ("log".to_string(), vec![]),
+        ]
+        .into_iter()
+        .collect();
+        let t = transpose(&graph);
+        let targets = |id: &str| {
+            let mut out: Vec<&str> = t[id].iter().map(|e| e.target.as_str()).collect();
+            out.sort();
+            out
+        };
+        assert_eq!(targets("log"), vec!["main", "run"]);
+        assert_eq!(targets("run"), vec!["main"]);
+        assert!(targets("main").is_empty());
+    }
+
+    #[test]
+    fn callers_view_reverses_crawled_edges_and_back() {
+        let mut world = World::new();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<GlyphConfig>();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<Messages<CallersRequest>>();
+        let graph: FlowMap = [
+            ("main".to_string(), vec![call("run")]),
+            ("run".to_string(), vec![]),
+        ]
+        .into_iter()
+        .collect();
+        world.insert_resource(CrawledFlow { graph, ..default() });
+        let mut node = |id: &str| {
+            world
+                .spawn((FlowNodeId(id.to_string()), Transform::default()))
+                .id()
+        };
+        let (main, run) = (node("main"), node("run"));
+        let crawled_edge = world
+            .spawn(Edge {
+                source: main,
+                target: run,
+                label: None,
+            })
+            .id();
+        let drawn = world
+            .spawn(Edge {
+                source: run,
+                target: main,
+                label: None,
+            })
+            .id();
+        let y = |world: &World, e: Entity| world.get::<Transform>(e).unwrap().translation.y;
+        let ends = |world: &World, e: Entity| {
+            let edge = world.get::<Edge>(e).unwrap();
+            (edge.source, edge.target)
+        };
+
+        world.write_message(CallersRequest(true));
+        world.run_system_once(handle_callers_requests).unwrap();
+        assert_eq!(ends(&world, crawled_edge), (run, main));
+        assert_eq!(
+            ends(&world, drawn),
+            (run, main),
+            "hand-drawn edge untouched"
+        );
+        assert!(y(&world, run) > y(&world, main), "the callee is on top");
+        assert!(matches!(
+            world.resource::<UndoHistory>().undo_stack.last(),
+            Some(Action::Batch(moves)) if !moves.is_empty()
+        ));
+
+        // A reloaded file has no crawl, only the markers: `:callees` still
+        // turns the edge back.
+        world.insert_resource(CrawledFlow::default());
+
+        world.write_message(CallersRequest(false));
+        world.run_system_once(handle_callers_requests).unwrap();
+        assert_eq!(ends(&world, crawled_edge), (main, run));
+        assert_eq!(ends(&world, drawn), (run, main));
+        assert!(world.get::<ReversedCall>(crawled_edge).is_none());
+        assert!(!world.resource::<CrawledFlow>().callers);
+    }
+}
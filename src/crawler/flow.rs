@@ -0,0 +1,198 @@
+//! `:flow on|off`: show or hide the decision nodes of the last crawl without
+//! re-crawling. Hidden decision nodes are bypassed by `FlowBypass` edges
+//! (caller → decision → callee becomes caller → callee).
+//!
+//! Display-only, like folding: nothing is saved or pushed to the undo history.
+
+use bevy::prelude::*;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use super::parsers::walker::DECISION_SEP;
+use super::FlowMap;
+use crate::core::components::{Edge, FlowBypass, FlowHidden, FlowNodeId, InactiveCanvas};
+use crate::core::resources::StatusMessage;
+
+/// Message sent by `:flow`: `Some(true)` shows decision nodes, `Some(false)`
+/// hides them, `None` toggles.
+#[derive(Message)]
+pub struct FlowRequest(pub Option<bool>);
+
+/// Flow map of the last crawl, whether its decision nodes are hidden and
+/// whether `:callers` shows its edges reversed (see `crawler::callers`).
+#[derive(Resource, Default)]
+pub struct CrawledFlow {
+    pub graph: FlowMap,
+    pub hide_decisions: bool,
+    pub callers: bool,
+}
+
+fn is_decision(id: &str) -> bool {
+    id.contains(DECISION_SEP)
+}
+
+/// Caller → callee pairs that only exist through decision nodes: every
+/// function reached from a function by walking through one or more decision
+/// nodes, skipping pairs already linked directly. Sorted.
+pub fn bypass_edges(graph: &FlowMap) -> Vec<(String, String)> {
+    let mut out = BTreeSet::new();
+    for (caller, edges) in graph.iter().filter(|(id, _)| !is_decision(id)) {
+        let direct: HashSet<&String> = edges
+            .iter()
+            .filter(|e| !is_decision(&e.target))
+            .map(|e| &e.target)
+            .collect();
+        let mut seen: HashSet<&String> = HashSet::new();
+        let mut stack: Vec<&String> = edges
+            .iter()
+            .map(|e| &e.target)
+            .filter(|t| is_decision(t))
+            .collect();
+        while let Some(decision) = stack.pop() {
+            if !seen.insert(decision) {
+                continue;
+            }
+            for edge in graph.get(decision).into_iter().flatten() {
+                if is_decision(&edge.target) {
+                    stack.push(&edge.target);
+                } else if graph.contains_key(&edge.target) && !direct.contains(&edge.target) {
+                    out.insert((caller.clone(), edge.target.clone()));
+                }
+            }
+        }
+    }
+    out.into_iter().collect()
+}
+
+/// Apply `:flow` requests to `CrawledFlow`.
+pub fn handle_flow_requests(
+    mut requests: MessageReader<FlowRequest>,
+    mut crawled: ResMut<CrawledFlow>,
+    mut status: ResMut<StatusMessage>,
+) {
+    for FlowRequest(show) in requests.read() {
+        if !crawled.graph.keys().any(|id| is_decision(id)) {
+            status.set("flow: the current crawl has no decision nodes");
+            continue;
+        }
+        crawled.hide_decisions = !show.unwrap_or(crawled.hide_decisions);
+        status.set(if crawled.hide_decisions {
+            "Decision nodes hidden"
+        } else {
+            "Decision nodes shown"
+        });
+    }
+}
+
+/// Keep `FlowHidden` and the `FlowBypass` edges in line with `CrawledFlow`
+/// whenever it changes or crawled nodes appear. `sync_collapsed_system` does
+/// the actual hiding.
+pub fn sync_flow_system(
+    mut commands: Commands,
+    crawled: Res<CrawledFlow>,
+    nodes: Query<(Entity, Ref<FlowNodeId>), Without<InactiveCanvas>>,
+    edges: Query<(Entity, &Edge), Without<FlowBypass>>,
+    hidden: Query<Entity, With<FlowHidden>>,
+    bypasses: Query<Entity, With<FlowBypass>>,
+) {
+    if !crawled.is_changed() && !nodes.iter().any(|(_, id)| id.is_added()) {
+        return;
+    }
+    for entity in &bypasses {
+        commands.entity(entity).try_despawn();
+    }
+
+    let mut want: HashSet<Entity> = HashSet::new();
+    if crawled.hide_decisions {
+        let by_id: HashMap<&str, Entity> =
+            nodes.iter().map(|(e, id)| (id.into_inner().0.as_str(), e)).collect();
+        want.extend(nodes.iter().filter(|(_, id)| is_decision(&id.0)).map(|(e, _)| e));
+        let hidden_edges: Vec<Entity> = edges
+            .iter()
+            .filter(|(_, e)| want.contains(&e.source) || want.contains(&e.target))
+            .map(|(entity, _)| entity)
+            .collect();
+        want.extend(hidden_edges);
+        for (caller, callee) in bypass_edges(&crawled.graph) {
+            let (Some(&source), Some(&target)) =
+                (by_id.get(caller.as_str()), by_id.get(callee.as_str()))
+            else {
+                continue;
+            };
+            let (source, target) = if crawled.callers {
+                (target, source)
+            } else {
+                (source, target)
+            };
+            commands.spawn((
+                Edge {
+                    source,
+                    target,
+                    label: None,
+                },
+                FlowBypass,
+            ));
+        }
+    }
+
+    let current: HashSet<Entity> = hidden.iter().collect();
+    for &entity in want.difference(&current) {
+        commands.entity(entity).try_insert(FlowHidden);
+    }
+    for &entity in current.difference(&want) {
+        commands.entity(entity).try_remove::<FlowHidden>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crawler::FlowEdge;
+
+    fn graph(edges: &[(&str, &[&str])]) -> FlowMap {
+        edges
+            .iter()
+            .map(|(from, to)| {
+                let out = to
+                    .iter()
+                    .map(|t| FlowEdge {
+                        target: t.to_string(),
+                        label: None,
+                    })
+                    .collect();
+                (from.to_string(), out)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bypass_skips_through_decision_chains() {
+        let (d1, d2) = ("a.rs::_decision_0\u{1f}if", "a.rs::_decision_1\u{1f}for");
+        // main → if → {run, for → stop}; main also calls log directly.
+        let g = graph(&[
+            ("a.rs::main", &[d1, "a.rs::log"]),
+            (d1, &["a.rs::run", d2]),
+            (d2, &["a.rs::stop", "unwrap"]),
+            ("a.rs::run", &[]),
+            ("a.rs::stop", &[]),
+            ("a.rs::log", &[]),
+        ]);
+        assert_eq!(
+            bypass_edges(&g),
+            vec![
+                ("a.rs::main".to_string(), "a.rs::run".to_string()),
+                ("a.rs::main".to_string(), "a.rs::stop".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn bypass_omits_pairs_already_linked_and_handles_cycles() {
+        let d = "a.rs::_decision_0\u{1f}while";
+        let g = graph(&[
+            ("a.rs::main", &[d, "a.rs::run"]),
+            (d, &[d, "a.rs::run"]),
+            ("a.rs::run", &[]),
+        ]);
+        assert!(bypass_edges(&g).is_empty());
+    }
+}
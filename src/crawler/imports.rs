@@ -0,0 +1,467 @@
+//! Module-dependency graph for `:crawl --imports`: one node per source file,
+//! an edge from each file to every crawled file it imports.
+//!
+//! A light regex pass per language instead of the tree-sitter parsers; only
+//! imports that resolve to a file inside the crawl become edges, so standard
+//! library and third-party imports drop out.
+//!
+//! - Rust: `mod x;` and `use crate::` / `super::` / `self::` paths.
+//! - Python: `import a.b` and `from .a import b`.
+//! - TypeScript: relative `import` / `export … from` / `require(…)` specifiers.
+//! - Go: import paths, matched by suffix to the crawled package directories.
+//! - C: `#include "x.h"`, from the including file's directory, then the root.
+
+use regex::Regex;
+use std::collections::{BTreeSet, HashSet};
+use std::sync::LazyLock;
+
+use super::{CallGraph, FlowEdge, SourceMap};
+
+static RUST_MOD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+([A-Za-z_][A-Za-z0-9_]*)\s*;").unwrap()
+});
+static RUST_USE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+([^;]+);").unwrap());
+static PY_IMPORT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*import\s+([\w.]+(?:\s+as\s+\w+)?(?:\s*,\s*[\w.]+(?:\s+as\s+\w+)?)*)")
+        .unwrap()
+});
+static PY_FROM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*from\s+(\.*)([\w.]*)\s+import\s+\(?\s*([\w \t,]+)").unwrap()
+});
+static TS_IMPORT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?:\bfrom\s*|\bimport\s*\(?\s*|\brequire\s*\(\s*)['"](\.\.?/[^'"]+)['"]"#)
+        .unwrap()
+});
+static GO_IMPORT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?ms)^\s*import\s*(?:\((.*?)\)|(?:[\w.]+\s+)?"([^"]+)")"#).unwrap()
+});
+static QUOTED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#""([^"]+)""#).unwrap());
+static C_INCLUDE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?m)^\s*#\s*include\s*"([^"]+)""#).unwrap());
+
+/// Directory part of a `/`-separated relative path (`""` at the root).
+fn parent(path: &str) -> &str {
+    path.rfind('/').map_or("", |i| &path[..i])
+}
+
+/// `dir/rest` with `.` and `..` segments folded; `None` if it climbs above the root.
+fn join(dir: &str, rest: &str) -> Option<String> {
+    let mut parts: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+    for seg in rest.split('/') {
+        match seg {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            _ => parts.push(seg),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// The first of `candidates` that is a crawled file.
+fn first_file(
+    files: &HashSet<String>,
+    candidates: impl IntoIterator<Item = String>,
+) -> Option<String> {
+    candidates.into_iter().find(|c| files.contains(c))
+}
+
+// ── Rust ────────────────────────────────────────────────────────────────────
+
+/// Directory holding the child modules of the module in `file`.
+fn rust_module_dir(file: &str) -> String {
+    let name = &file[parent(file).len()..].trim_start_matches('/');
+    match *name {
+        "mod.rs" | "lib.rs" | "main.rs" => parent(file).to_string(),
+        _ => file.trim_end_matches(".rs").to_string(),
+    }
+}
+
+/// Directory of the crate root (`lib.rs` / `main.rs`) above `file`.
+fn rust_crate_dir(file: &str, files: &HashSet<String>) -> String {
+    let mut dir = parent(file);
+    loop {
+        let has_root = ["lib.rs", "main.rs"]
+            .iter()
+            .any(|root| files.contains(&join(dir, root).unwrap_or_default()));
+        if has_root || dir.is_empty() {
+            return dir.to_string();
+        }
+        dir = parent(dir);
+    }
+}
+
+/// File of the module whose children live in `dir`.
+fn rust_module_file(dir: &str, crate_dir: &str, files: &HashSet<String>) -> Option<String> {
+    if dir == crate_dir {
+        return first_file(
+            files,
+            ["lib.rs", "main.rs"].map(|f| join(dir, f).unwrap_or_default()),
+        );
+    }
+    first_file(files, [format!("{}.rs", dir), format!("{}/mod.rs", dir)])
+}
+
+/// `tree` without whitespace or `as` renames: `a::{b as c, d}` → `a::{b,d}`.
+fn strip_use_renames(tree: &str) -> String {
+    let mut out = String::new();
+    let mut words = tree.split_whitespace();
+    while let Some(word) = words.next() {
+        if word == "as" {
+            // Keep whatever follows the alias in the same word, e.g. `c,`.
+            let alias = words.next().unwrap_or_default();
+            out.push_str(alias.trim_start_matches(|c: char| c.is_alphanumeric() || c == '_'));
+        } else {
+            out.push_str(word);
+        }
+    }
+    out
+}
+
+/// The leading paths of a `use` tree: `a::b::{c, d::e}` → `a::b::c`, `a::b::d::e`.
+fn rust_use_paths(tree: &str) -> Vec<String> {
+    let tree = strip_use_renames(tree);
+    let Some(open) = tree.find('{') else {
+        return vec![tree];
+    };
+    let prefix = &tree[..open];
+    let inner = tree[open + 1..].trim_end_matches('}');
+    let mut paths = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, ch) in inner.char_indices().chain([(inner.len(), ',')]) {
+        match ch {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                let item = &inner[start..i];
+                if !item.is_empty() {
+                    paths.push(format!(
+                        "{}{}",
+                        prefix,
+                        item.split('{').next().unwrap_or(item)
+                    ));
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    paths
+}
+
+fn rust_imports(file: &str, code: &str, files: &HashSet<String>) -> Vec<String> {
+    let dir = rust_module_dir(file);
+    let crate_dir = rust_crate_dir(file, files);
+    let mut out = Vec::new();
+    for cap in RUST_MOD.captures_iter(code) {
+        let name = &cap[1];
+        let candidates = [format!("{}.rs", name), format!("{}/mod.rs", name)];
+        out.extend(first_file(
+            files,
+            candidates.map(|c| join(&dir, &c).unwrap_or_default()),
+        ));
+    }
+    for cap in RUST_USE.captures_iter(code) {
+        for path in rust_use_paths(&cap[1]) {
+            let mut segs: Vec<&str> = path.split("::").filter(|s| !s.is_empty()).collect();
+            let mut base = match segs.first() {
+                Some(&"crate") => crate_dir.clone(),
+                Some(&"self") => dir.clone(),
+                Some(&"super") => parent(&dir).to_string(),
+                _ => continue,
+            };
+            segs.remove(0);
+            while segs.first() == Some(&"super") {
+                base = parent(&base).to_string();
+                segs.remove(0);
+            }
+            // The longest module prefix that is a file; the rest are items.
+            let target = (1..=segs.len())
+                .rev()
+                .find_map(|k| {
+                    let module = join(&base, &segs[..k].join("/"))?;
+                    rust_module_file(&module, &crate_dir, files)
+                })
+                .or_else(|| rust_module_file(&base, &crate_dir, files));
+            out.extend(target);
+        }
+    }
+    out
+}
+
+// ── Python ──────────────────────────────────────────────────────────────────
+
+/// `base/a/b.py` or `base/a/b/__init__.py`, trying shorter prefixes of the
+/// dotted path (`a.b.func`) until one is a file.
+fn python_module(base: &str, dotted: &str, files: &HashSet<String>) -> Option<String> {
+    let segs: Vec<&str> = dotted.split('.').filter(|s| !s.is_empty()).collect();
+    (1..=segs.len()).rev().find_map(|k| {
+        let module = join(base, &segs[..k].join("/"))?;
+        first_file(
+            files,
+            [format!("{}.py", module), format!("{}/__init__.py", module)],
+        )
+    })
+}
+
+fn python_imports(file: &str, code: &str, files: &HashSet<String>) -> Vec<String> {
+    let dir = parent(file);
+    let mut out = Vec::new();
+    for cap in PY_IMPORT.captures_iter(code) {
+        for item in cap[1].split(',') {
+            let module = item.split_whitespace().next().unwrap_or("");
+            out.extend(
+                python_module("", module, files).or_else(|| python_module(dir, module, files)),
+            );
+        }
+    }
+    for cap in PY_FROM.captures_iter(code) {
+        let (dots, module) = (cap[1].len(), &cap[2]);
+        let base = if dots == 0 {
+            String::new()
+        } else {
+            let Some(base) = join(dir, &vec![".."; dots - 1].join("/")) else {
+                continue;
+            };
+            base
+        };
+        // `from pkg import sub` may name submodules; otherwise it's the module.
+        let mut found = false;
+        for name in cap[3]
+            .split(',')
+            .filter_map(|n| n.split_whitespace().next())
+        {
+            let dotted = format!("{}.{}", module, name);
+            let sub = python_module(&base, &dotted, files).filter(|f| {
+                f.ends_with(&format!("{}.py", name))
+                    || f.ends_with(&format!("{}/__init__.py", name))
+            });
+            found |= sub.is_some();
+            out.extend(sub);
+        }
+        if !found && !module.is_empty() {
+            out.extend(python_module(&base, module, files));
+        }
+    }
+    out
+}
+
+// ── TypeScript, Go, C ───────────────────────────────────────────────────────
+
+fn typescript_imports(file: &str, code: &str, files: &HashSet<String>) -> Vec<String> {
+    TS_IMPORT
+        .captures_iter(code)
+        .filter_map(|cap| {
+            let target = join(parent(file), &cap[1])?;
+            let candidates = ["", ".ts", ".tsx", "/index.ts", "/index.tsx"];
+            first_file(files, candidates.map(|ext| format!("{}{}", target, ext)))
+        })
+        .collect()
+}
+
+fn go_imports(file: &str, code: &str, files: &HashSet<String>) -> Vec<String> {
+    let mut paths = Vec::new();
+    for cap in GO_IMPORT.captures_iter(code) {
+        match (cap.get(1), cap.get(2)) {
+            (Some(block), _) => paths.extend(
+                QUOTED
+                    .captures_iter(block.as_str())
+                    .map(|q| q[1].to_string()),
+            ),
+            (None, Some(path)) => paths.push(path.as_str().to_string()),
+            _ => {}
+        }
+    }
+    let go_dirs: BTreeSet<&str> = files
+        .iter()
+        .filter(|f| f.ends_with(".go"))
+        .map(|f| parent(f))
+        .filter(|d| !d.is_empty())
+        .collect();
+    let mut out = Vec::new();
+    for path in paths {
+        // The deepest crawled directory the import path ends with is the package.
+        let Some(pkg) = go_dirs
+            .iter()
+            .filter(|d| path == **d || path.ends_with(&format!("/{}", d)))
+            .max_by_key(|d| d.len())
+        else {
+            continue;
+        };
+        if *pkg == parent(file) {
+            continue;
+        }
+        out.extend(
+            files
+                .iter()
+                .filter(|f| f.ends_with(".go") && parent(f) == *pkg)
+                .cloned(),
+        );
+    }
+    out
+}
+
+fn c_imports(file: &str, code: &str, files: &HashSet<String>) -> Vec<String> {
+    C_INCLUDE
+        .captures_iter(code)
+        .filter_map(|cap| {
+            let include = &cap[1];
+            let candidates = [join(parent(file), include), join("", include)];
+            first_file(files, candidates.into_iter().flatten()).or_else(|| {
+                let suffix = format!("/{}", include);
+                let mut matches = files.iter().filter(|f| f.ends_with(&suffix));
+                let only = matches.next()?;
+                matches.next().is_none().then(|| only.clone())
+            })
+        })
+        .collect()
+}
+
+/// Crawled files `file` imports, by its extension. `files` holds every
+/// crawled file as a `/`-separated path relative to the root.
+pub fn file_imports(file: &str, code: &str, files: &HashSet<String>) -> Vec<String> {
+    let ext = file.rsplit('.').next().unwrap_or("").to_lowercase();
+    let mut imports = match ext.as_str() {
+        "rs" => rust_imports(file, code, files),
+        "py" => python_imports(file, code, files),
+        "ts" | "tsx" => typescript_imports(file, code, files),
+        "go" => go_imports(file, code, files),
+        "c" | "h" => c_imports(file, code, files),
+        _ => Vec::new(),
+    };
+    imports.retain(|target| target != file);
+    imports.sort();
+    imports.dedup();
+    imports
+}
+
+/// Import graph of `sources` (`(relative path, absolute path, contents)`).
+/// Node IDs are `rel/path::rel/path` so the spawn pipeline shows the relative
+/// path as the label; every file is a node, imported or not.
+pub fn import_graph(sources: &[(String, String, String)]) -> (CallGraph, SourceMap) {
+    let files: HashSet<String> = sources.iter().map(|(rel, ..)| rel.clone()).collect();
+    let id = |rel: &str| format!("{}::{}", rel, rel);
+    let mut graph = CallGraph::new();
+    let mut source_map = SourceMap::new();
+    for (rel, abs, code) in sources {
+        let edges = file_imports(rel, code, &files)
+            .into_iter()
+            .map(|target| FlowEdge {
+                target: id(&target),
+                label: None,
+            })
+            .collect();
+        graph.insert(id(rel), edges);
+        source_map.insert(id(rel), (abs.clone(), 1, code.lines().count().max(1) as u32));
+    }
+    (graph, source_map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(paths: &[&str]) -> HashSet<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn rust_use_paths_drop_renames_inside_and_outside_braces() {
+        assert_eq!(rust_use_paths("crate::core::config as cfg"), ["crate::core::config"]);
+        assert_eq!(
+            rust_use_paths("crate::core::{config as cfg, helpers::{wrap as w, x},resources}"),
+            [
+                "crate::core::config",
+                "crate::core::helpers::",
+                "crate::core::resources"
+            ]
+        );
+        assert_eq!(
+            rust_use_paths("super::{a as b,c as d}"),
+            ["super::a", "super::c"]
+        );
+    }
+
+    #[test]
+    fn rust_imports_resolve_mod_and_crate_paths() {
+        let files = files(&[
+            "src/lib.rs",
+            "src/core/mod.rs",
+            "src/core/config.rs",
+            "src/core/helpers.rs",
+            "src/render/edges.rs",
+        ]);
+        let code = "pub mod core;\nmod missing;\nuse crate::core::config::{GlyphConfig, Theme};\n\
+                    use crate::core::{helpers::wrap, config};\nuse bevy::prelude::*;\n";
+        assert_eq!(
+            file_imports("src/lib.rs", code, &files),
+            [
+                "src/core/config.rs",
+                "src/core/helpers.rs",
+                "src/core/mod.rs"
+            ]
+        );
+        // `super` of a `mod.rs` is the crate root; of `helpers.rs`, `core`.
+        let code = "use super::config::GlyphConfig;\nuse self::x::Y;\n";
+        assert_eq!(
+            file_imports("src/core/mod.rs", code, &files),
+            ["src/lib.rs"]
+        );
+        let code = "use super::config::GlyphConfig;\nuse super::{helpers, Core};\n";
+        assert_eq!(
+            file_imports("src/core/helpers.rs", code, &files),
+            ["src/core/config.rs", "src/core/mod.rs"]
+        );
+        let code = "use super::super::core::config::Theme;\n";
+        assert_eq!(
+            file_imports("src/render/edges.rs", code, &files),
+            ["src/core/config.rs"]
+        );
+    }
+
+    #[test]
+    fn python_typescript_go_and_c_imports_resolve_to_crawled_files() {
+        let files = files(&[
+            "app/__init__.py",
+            "app/models.py",
+            "app/views/home.py",
+            "web/api.ts",
+            "web/ui/index.tsx",
+            "web/main.ts",
+            "cmd/main.go",
+            "pkg/store/db.go",
+            "pkg/store/cache.go",
+            "lib/util.h",
+            "lib/util.c",
+        ]);
+        let py =
+            "import os\nimport app.models as m\nfrom .. import models\nfrom app import views\n";
+        assert_eq!(
+            file_imports("app/views/home.py", py, &files),
+            ["app/__init__.py", "app/models.py"]
+        );
+
+        let ts =
+            "import { get } from './api';\nimport Ui from \"./ui\";\nconst x = require('react');\n";
+        assert_eq!(
+            file_imports("web/main.ts", ts, &files),
+            ["web/api.ts", "web/ui/index.tsx"]
+        );
+
+        let go = "package main\n\nimport (\n\t\"fmt\"\n\tst \"example.com/app/pkg/store\"\n)\n";
+        assert_eq!(
+            file_imports("cmd/main.go", go, &files),
+            ["pkg/store/cache.go", "pkg/store/db.go"]
+        );
+
+        let c = "#include <stdio.h>\n#include \"util.h\"\n";
+        assert_eq!(file_imports("lib/util.c", c, &files), ["lib/util.h"]);
+        assert_eq!(
+            file_imports("main.c", c, &files),
+            ["lib/util.h"],
+            "unique suffix match"
+        );
+    }
+}
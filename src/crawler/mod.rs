@@ -2,18 +2,25 @@
 //!
 //! LanguageParser trait + CrawlerRouter for extension-based dispatch. Tree-sitter Query for Rust MVP.
 
+pub mod callers;
+pub mod flow;
+mod imports;
 pub mod parsers;
 mod router;
+pub mod simplify;
 pub mod tracing;
 
-use crate::core::components::{CanvasNode, Edge, FileLabel, SourceLocation};
-use crate::core::helpers::spawn_node_with_color;
+use crate::core::components::{
+    DecisionNode, Edge, FlowNodeId, NodeShape, SourceLocation,
+};
+use crate::core::helpers::{spawn_file_label, spawn_line_count_label, spawn_node_with_color};
 use crate::render::layout::ForceLayoutActive;
 use crate::core::resources::SpatialIndex;
 use bevy::prelude::*;
 use parsers::walker::DECISION_SEP;
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 
 pub use router::CrawlerRouter;
 pub use tracing::TraceRequest;
@@ -30,10 +37,20 @@ pub struct WatchState {
     _watcher: std::sync::Mutex<Option<notify::RecommendedWatcher>>,
     /// Receives raw file-system events.
     rx: std::sync::Mutex<Option<std::sync::mpsc::Receiver<notify::Result<notify::Event>>>>,
-    /// Path currently being watched (passed to re-crawl).
-    pub watch_path: Option<String>,
+    /// Roots currently being watched (passed to re-crawl).
+    pub watch_paths: Vec<String>,
     /// Preserve the `no_flow` setting used for the last crawl.
     pub no_flow: bool,
+    /// Preserve the `--depth` limit used for the last crawl.
+    pub max_depth: Option<usize>,
+    /// Preserve the `--exclude` patterns used for the last crawl.
+    pub exclude: Vec<String>,
+    /// Preserve the `--follow-links` setting used for the last crawl.
+    pub follow_links: bool,
+    /// Preserve the `--respect-gitignore` setting used for the last crawl.
+    pub respect_gitignore: bool,
+    /// Preserve the graph kind (`--imports`) used for the last crawl.
+    pub mode: CrawlMode,
     /// Time of the most recent relevant file-change event (for debouncing).
     last_event: Option<std::time::Instant>,
 }
@@ -43,13 +60,74 @@ impl Default for WatchState {
         Self {
             _watcher: std::sync::Mutex::new(None),
             rx: std::sync::Mutex::new(None),
-            watch_path: None,
+            watch_paths: Vec::new(),
             no_flow: false,
+            max_depth: None,
+            exclude: Vec::new(),
+            follow_links: false,
+            respect_gitignore: false,
+            mode: CrawlMode::Calls,
             last_event: None,
         }
     }
 }
 
+/// Provenance of the most recent crawl, shown in the Modules legend.
+#[derive(Resource, Default)]
+pub struct CrawlInfo {
+    /// Names of the crawl roots (e.g. `src`, `main.go`), each with whether it
+    /// is a directory.
+    pub roots: Vec<(String, bool)>,
+    /// Number of source files that contributed at least one function.
+    pub file_count: usize,
+    /// When the crawl finished; used to show how fresh the graph is.
+    pub crawled_at: Option<std::time::Instant>,
+}
+
+impl CrawlInfo {
+    /// One-line summary such as `Crawled src/ · 34 files · 2m ago`, or
+    /// `Crawled frontend/ + backend/ · …` for several roots. Single-file
+    /// roots get no trailing `/`.
+    /// Returns `None` before the first crawl.
+    pub fn summary(&self) -> Option<String> {
+        if self.roots.is_empty() {
+            return None;
+        }
+        let root = self
+            .roots
+            .iter()
+            .map(|(name, is_dir)| if *is_dir { format!("{}/", name) } else { name.clone() })
+            .collect::<Vec<_>>()
+            .join(" + ");
+        let at = self.crawled_at?;
+        let files = if self.file_count == 1 { "file" } else { "files" };
+        Some(format!(
+            "Crawled {} · {} {} · {}",
+            root,
+            self.file_count,
+            files,
+            format_age(at.elapsed().as_secs())
+        ))
+    }
+}
+
+/// Human-readable age for the crawl summary: `just now`, `45s ago`, `2m ago`, `3h ago`.
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..=4 => "just now".to_string(),
+        5..=59 => format!("{}s ago", secs),
+        60..=3599 => format!("{}m ago", secs / 60),
+        _ => format!("{}h ago", secs / 3600),
+    }
+}
+
+/// True if a change to `path` re-crawls: a source file the crawler parses,
+/// under one of the watched `roots`. A crawled file's whole directory is
+/// watched; its neighbours do not count.
+fn triggers_recrawl(path: &std::path::Path, roots: &[String]) -> bool {
+    router::is_supported_source(path) && roots.iter().any(|root| path.starts_with(root))
+}
+
 /// Checks the watcher channel for source-file changes and fires a re-crawl
 /// after a 500 ms debounce window.
 pub fn watch_trigger_system(
@@ -63,12 +141,7 @@ pub fn watch_trigger_system(
             loop {
                 match rx.try_recv() {
                     Ok(Ok(ev)) => {
-                        let is_source = ev.paths.iter().any(|p| {
-                            p.extension()
-                                .and_then(|e| e.to_str())
-                                .map_or(false, |e| matches!(e, "rs" | "py" | "ts" | "tsx"))
-                        });
-                        if is_source {
+                        if ev.paths.iter().any(|p| triggers_recrawl(p, &watch.watch_paths)) {
                             found = true;
                         }
                     }
@@ -92,24 +165,140 @@ pub fn watch_trigger_system(
     if let Some(last) = watch.last_event {
         if last.elapsed() >= std::time::Duration::from_millis(500) {
             watch.last_event = None;
-            if let Some(path) = watch.watch_path.clone() {
-                info!("[WATCH] Re-crawling {} (file changed)", path);
+            if !watch.watch_paths.is_empty() {
+                let paths = watch.watch_paths.clone();
+                info!("[WATCH] Re-crawling {} (file changed)", paths.join(", "));
                 crawl_events.write(CrawlRequest {
-                    path,
+                    paths,
                     no_flow: watch.no_flow,
+                    max_depth: watch.max_depth,
+                    exclude: watch.exclude.clone(),
+                    follow_links: watch.follow_links,
+                    respect_gitignore: watch.respect_gitignore,
+                    mode: watch.mode,
                 });
             }
         }
     }
 }
 
+/// What a crawl turns into nodes and edges.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CrawlMode {
+    /// Functions (and decision nodes) linked by calls.
+    #[default]
+    Calls,
+    /// Source files linked by the imports between them. Pass `--imports`.
+    Imports,
+}
+
 /// Message sent when user requests a crawl (e.g. from Command Palette).
-#[derive(Message)]
+#[derive(Message, Clone)]
 pub struct CrawlRequest {
-    pub path: String,
+    /// Directories to crawl into one graph. With more than one, node IDs start
+    /// with each root's directory name.
+    pub paths: Vec<String>,
     /// When `true`, decision nodes (if/for/while/match) are suppressed and the
     /// resulting graph contains only function nodes. Pass `--no-flow` to `:crawl`.
     pub no_flow: bool,
+    /// Keep only nodes within this many hops of a root function. Pass
+    /// `--depth N` to `:crawl`; `--depth 0` keeps only the roots.
+    pub max_depth: Option<usize>,
+    /// Glob patterns for paths to skip, relative to each crawl root. Pass
+    /// `--exclude target,node_modules` to `:crawl`.
+    pub exclude: Vec<String>,
+    /// Descend into symlinked directories. Pass `--follow-links` to `:crawl`.
+    pub follow_links: bool,
+    /// Skip paths excluded by `.gitignore` / `.ignore` files under the root.
+    /// Pass `--respect-gitignore` to `:crawl`.
+    pub respect_gitignore: bool,
+    /// Call graph, or file import graph with `--imports`.
+    pub mode: CrawlMode,
+}
+
+/// Arguments of `:crawl` / palette `crawl`: one or more paths plus optional `--no-flow`,
+/// `--depth N`, `--exclude a,b`, `--follow-links`, `--respect-gitignore` and
+/// `--imports` flags in any position.
+#[derive(Debug, PartialEq)]
+pub struct CrawlArgs<'a> {
+    pub paths: Vec<&'a str>,
+    pub no_flow: bool,
+    pub max_depth: Option<usize>,
+    pub exclude: Vec<String>,
+    pub follow_links: bool,
+    pub respect_gitignore: bool,
+    pub mode: CrawlMode,
+}
+
+/// Whitespace-separated words of `arg`; a `"double-quoted"` word may contain
+/// spaces and is returned without its quotes.
+fn crawl_words(arg: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut rest = arg.trim_start();
+    while !rest.is_empty() {
+        let (word, tail) = match rest.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => (quoted, ""),
+            },
+            None => rest.split_at(rest.find(char::is_whitespace).unwrap_or(rest.len())),
+        };
+        if !word.is_empty() {
+            words.push(word);
+        }
+        rest = tail.trim_start();
+    }
+    words
+}
+
+/// Parse crawl arguments. Every word that is not a flag is a path; quote a
+/// path that contains spaces. Errors on a missing path or a bad `--depth` value.
+pub fn parse_crawl_args(arg: &str) -> Result<CrawlArgs<'_>, String> {
+    let mut no_flow = false;
+    let mut max_depth = None;
+    let mut exclude = Vec::new();
+    let mut follow_links = false;
+    let mut respect_gitignore = false;
+    let mut mode = CrawlMode::Calls;
+    let mut paths = Vec::new();
+    let mut tokens = crawl_words(arg).into_iter();
+    while let Some(tok) = tokens.next() {
+        match tok {
+            "--no-flow" => no_flow = true,
+            "--follow-links" => follow_links = true,
+            "--respect-gitignore" => respect_gitignore = true,
+            "--imports" => mode = CrawlMode::Imports,
+            "--depth" => {
+                let n = tokens.next().ok_or("--depth requires a number")?;
+                let n = n
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid --depth: {}", n))?;
+                max_depth = Some(n);
+            }
+            "--exclude" => {
+                let list = tokens.next().ok_or("--exclude requires a pattern list")?;
+                exclude.extend(
+                    list.split(',')
+                        .map(str::trim)
+                        .filter(|p| !p.is_empty())
+                        .map(str::to_string),
+                );
+            }
+            _ => paths.push(tok),
+        }
+    }
+    if paths.is_empty() {
+        return Err("missing path".to_string());
+    }
+    Ok(CrawlArgs {
+        paths,
+        no_flow,
+        max_depth,
+        exclude,
+        follow_links,
+        respect_gitignore,
+        mode,
+    })
 }
 
 /// FlowEdge: labeled edge in the flow map.
@@ -125,33 +314,34 @@ pub type FlowMap = HashMap<String, Vec<FlowEdge>>;
 /// CallGraph is now an alias for FlowMap for compatibility.
 pub type CallGraph = FlowMap;
 
-/// Maps a namespaced node ID → (absolute file path, 1-indexed line number).
+/// Maps a namespaced node ID → (absolute file path, 1-indexed start line, end line).
 /// Built by the router from tree-sitter line information; used to power `gd`.
-pub type SourceMap = HashMap<String, (String, u32)>;
+pub type SourceMap = HashMap<String, (String, u32, u32)>;
 
 /// Trait for language-specific AST parsing. Returns caller -> callees map.
 pub trait LanguageParser: Send + Sync {
     /// Parse source code and extract call graph. Returns empty map on parse failure (no panic).
     fn parse(&self, code: &str) -> CallGraph;
 
-    /// Like `parse` but also returns a bare-name → 1-indexed line-number map for each
-    /// defined function.  Default delegates to `parse` with an empty line map; override
+    /// Like `parse` but also returns a bare-name → 1-indexed (start, end) line map for
+    /// each defined function.  Default delegates to `parse` with an empty line map; override
     /// for accuracy.
     ///
-    /// `no_flow` suppresses control-flow decision nodes; see [`walk_tree`].
-    fn parse_with_lines(&self, code: &str, no_flow: bool) -> (CallGraph, HashMap<String, u32>) {
-        let _ = no_flow;
+    /// `no_flow` suppresses control-flow decision nodes; `include_calls` names
+    /// bypass the builtins filter. See [`walk_tree`].
+    fn parse_with_lines(
+        &self,
+        code: &str,
+        no_flow: bool,
+        include_calls: &HashSet<String>,
+    ) -> (CallGraph, HashMap<String, (u32, u32)>) {
+        let _ = (no_flow, include_calls);
         (self.parse(code), HashMap::new())
     }
 }
 
-/// Color for crawled function nodes.
-const CRAWL_NODE_COLOR: Color = Color::srgb(0.35, 0.55, 0.45);
-/// Color for decision (branch) nodes.
-const DECISION_NODE_COLOR: Color = Color::srgb(0.85, 0.65, 0.15); // gold/amber
-
 /// Compute hierarchy levels: roots (never callees) = 0, callees = 1 + max(caller level).
-fn hierarchy_levels(graph: &CallGraph, all_fns: &[String]) -> HashMap<String, usize> {
+pub(crate) fn hierarchy_levels(graph: &CallGraph, all_fns: &[String]) -> HashMap<String, usize> {
     let mut callee_to_callers: HashMap<String, Vec<String>> = HashMap::new();
     for (caller, edges) in graph {
         for edge in edges {
@@ -226,188 +416,411 @@ fn hierarchy_levels(graph: &CallGraph, all_fns: &[String]) -> HashMap<String, us
     level
 }
 
-/// Ingestion system: listen for CrawlRequest, use CrawlerRouter, spawn nodes and edges.
+/// World position of every name in `names` for the top-down flow layout:
+/// one row per `hierarchy_levels` level, each row sorted and centered on x = 0.
+pub(crate) fn flow_layout(
+    graph: &CallGraph,
+    names: &[String],
+    config: &crate::core::config::GlyphConfig,
+) -> Vec<(String, Vec2)> {
+    let levels = hierarchy_levels(graph, names);
+    let mut by_level: HashMap<usize, Vec<String>> = HashMap::new();
+    for name in names {
+        let lvl = levels.get(name).copied().unwrap_or(0);
+        by_level.entry(lvl).or_default().push(name.clone());
+    }
+    let mut level_order: Vec<_> = by_level.keys().copied().collect();
+    level_order.sort();
+
+    let mut out = Vec::with_capacity(names.len());
+    for lvl in level_order {
+        let mut row = by_level.remove(&lvl).unwrap_or_default();
+        row.sort();
+        let row_len = row.len();
+        let y = -(lvl as f32) * config.flow_row_height;
+        for (i, name) in row.into_iter().enumerate() {
+            let x = (i as f32 - row_len as f32 * 0.5) * config.flow_node_spacing;
+            out.push((name, Vec2::new(x, y)));
+        }
+    }
+    out
+}
+
+/// Nodes reachable from `roots` in at most `max_depth` hops (decision nodes
+/// count as a hop). `max_depth == 0` keeps only the roots.
+fn nodes_within_depth(graph: &CallGraph, roots: &[String], max_depth: usize) -> HashSet<String> {
+    let mut kept: HashSet<String> = roots.iter().cloned().collect();
+    let mut frontier: Vec<String> = roots.to_vec();
+    for _ in 0..max_depth {
+        let mut next = Vec::new();
+        for name in &frontier {
+            for edge in graph.get(name).into_iter().flatten() {
+                if kept.insert(edge.target.clone()) {
+                    next.push(edge.target.clone());
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+    kept
+}
+
+/// A crawl running on a background thread. A newer `:crawl` replaces it and
+/// Esc drops it; either way the thread's result is then discarded.
+#[derive(Resource)]
+pub struct CrawlJob {
+    /// Tab (`core::tabs::Canvases` index) the crawl was started in; the result
+    /// waits until that tab is active.
+    pub canvas: usize,
+    request: CrawlRequest,
+    abs_roots: Vec<std::path::PathBuf>,
+    /// Source files read so far, for the `Crawling… N files` indicator.
+    files_read: Arc<AtomicUsize>,
+    /// Wrapped in Mutex because Receiver is Send but not Sync.
+    rx: Mutex<mpsc::Receiver<(CallGraph, SourceMap)>>,
+}
+
+impl CrawlJob {
+    /// Bottom-bar text while the crawl runs.
+    pub fn progress_label(&self) -> String {
+        let n = self.files_read.load(Ordering::Relaxed);
+        format!("Crawling… {} {}", n, if n == 1 { "file" } else { "files" })
+    }
+}
+
+/// Listen for CrawlRequest and start a [`CrawlJob`] that parses the roots on
+/// a background thread; [`finish_crawl_system`] spawns the result.
 pub fn handle_crawl_requests(
     mut commands: Commands,
-    mut spatial_index: ResMut<SpatialIndex>,
-    mut force_layout: ResMut<ForceLayoutActive>,
     mut crawl_events: MessageReader<CrawlRequest>,
-    mut watch_state: ResMut<WatchState>,
     mut status: ResMut<crate::core::resources::StatusMessage>,
     config: Res<crate::core::config::GlyphConfig>,
-    node_query: Query<Entity, With<CanvasNode>>,
-    edge_entity_query: Query<Entity, With<Edge>>,
+    canvases: Res<crate::core::tabs::Canvases>,
 ) {
     for ev in crawl_events.read() {
-        let path = ev.path.trim();
-        if path.is_empty() {
+        let paths: Vec<&str> = ev
+            .paths
+            .iter()
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .collect();
+        if paths.is_empty() {
             continue;
         }
 
         // Resolve relative paths (../.. , ./src, etc.) to absolute so that
         // SourceMap entries are always absolute and `gd` works correctly.
-        let abs_root = std::path::Path::new(path)
-            .canonicalize()
-            .unwrap_or_else(|_| std::path::PathBuf::from(path));
-
-        if !abs_root.exists() || !abs_root.is_dir() {
-            let msg = format!("crawl: path not found: {}", path);
+        let abs_roots: Vec<std::path::PathBuf> = paths
+            .iter()
+            .map(|path| {
+                std::path::Path::new(path)
+                    .canonicalize()
+                    .unwrap_or_else(|_| std::path::PathBuf::from(path))
+            })
+            .collect();
+        // A root is a directory or a single source file.
+        if let Some(missing) = paths.iter().zip(&abs_roots).find(|(_, abs)| !abs.exists()) {
+            let msg = format!("crawl: no such file or directory: {}", missing.0);
             warn!("[CRAWL] {}", msg);
             status.set(msg);
             continue;
         }
 
-        let abs_root_str = abs_root.to_string_lossy().into_owned();
-        let (graph, source_map) = CrawlerRouter::crawl(&abs_root_str, ev.no_flow);
-        if graph.is_empty() {
-            let msg = format!("crawl: no functions found in {}", path);
-            warn!("[CRAWL] No functions found in {}", path);
-            status.set(msg);
-            continue;
-        }
+        let mut request = ev.clone();
+        request.paths = paths.iter().map(|p| p.to_string()).collect();
+        let roots: Vec<String> = abs_roots
+            .iter()
+            .map(|root| root.to_string_lossy().into_owned())
+            .collect();
+        let include_calls: HashSet<String> = config.crawl_include_calls.iter().cloned().collect();
+        let files_read = Arc::new(AtomicUsize::new(0));
+        let (tx, rx) = mpsc::channel();
+        let ev = request.clone();
+        let counter = files_read.clone();
+        std::thread::spawn(move || {
+            let roots: Vec<&str> = roots.iter().map(String::as_str).collect();
+            let result = match ev.mode {
+                CrawlMode::Calls => CrawlerRouter::crawl_counting(
+                    &roots,
+                    ev.no_flow,
+                    &include_calls,
+                    &ev.exclude,
+                    ev.follow_links,
+                    ev.respect_gitignore,
+                    &counter,
+                ),
+                CrawlMode::Imports => CrawlerRouter::crawl_imports_counting(
+                    &roots,
+                    &ev.exclude,
+                    ev.follow_links,
+                    ev.respect_gitignore,
+                    &counter,
+                ),
+            };
+            // The job may have been cancelled; nobody is listening then.
+            let _ = tx.send(result);
+        });
+        commands.insert_resource(CrawlJob {
+            canvas: canvases.active,
+            request,
+            abs_roots,
+            files_read,
+            rx: Mutex::new(rx),
+        });
+    }
+}
 
-        // Only include functions defined in the codebase (graph.keys()). Filter out std/method
-        // calls like as_mut, unwrap, iter, etc. that the parser picks up.
-        let defined: std::collections::HashSet<String> = graph.keys().cloned().collect();
+/// Esc in Normal mode cancels a running crawl.
+pub fn cancel_crawl_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    job: Option<Res<CrawlJob>>,
+    mut status: ResMut<crate::core::resources::StatusMessage>,
+) {
+    if job.is_some() && keys.just_pressed(KeyCode::Escape) {
+        commands.remove_resource::<CrawlJob>();
+        status.set("Crawl cancelled");
+    }
+}
 
-        // Despawn existing nodes and edges.
-        for entity in node_query.iter().collect::<Vec<_>>() {
-            commands.entity(entity).despawn();
-        }
-        for entity in edge_entity_query.iter().collect::<Vec<_>>() {
-            commands.entity(entity).despawn();
-        }
-        spatial_index.clear();
-
-        // Sort for deterministic layout.
-        let mut sorted: Vec<_> = defined.iter().cloned().collect();
-        sorted.sort();
-
-        // Hierarchical flow layout: roots at top, callees below.
-        let levels = hierarchy_levels(&graph, &sorted);
-        let mut by_level: HashMap<usize, Vec<String>> = HashMap::new();
-        for name in &sorted {
-            let lvl = levels.get(name).copied().unwrap_or(0);
-            by_level.entry(lvl).or_default().push(name.clone());
+/// Once the [`CrawlJob`] thread is done and its tab is active, replace the
+/// canvas with its graph.
+/// The canvas it replaces is kept as a `ReplaceCanvas` undo checkpoint.
+/// Marks `1`–`9` move to the first nine root functions, left to right.
+pub fn finish_crawl_system(
+    mut commands: Commands,
+    job: Option<Res<CrawlJob>>,
+    mut spatial_index: ResMut<SpatialIndex>,
+    mut force_layout: ResMut<ForceLayoutActive>,
+    mut watch_state: ResMut<WatchState>,
+    mut crawl_info: ResMut<CrawlInfo>,
+    mut crawled_flow: ResMut<flow::CrawledFlow>,
+    mut marks: ResMut<crate::core::marks::Marks>,
+    mut status: ResMut<crate::core::resources::StatusMessage>,
+    config: Res<crate::core::config::GlyphConfig>,
+    theme: Res<crate::core::config::Theme>,
+    canvas_query: crate::io::file_io::CanvasEntityQuery,
+    mut checkpoint: crate::io::file_io::CanvasCheckpoint,
+    canvases: Res<crate::core::tabs::Canvases>,
+) {
+    let Some(job) = job.filter(|job| job.canvas == canvases.active) else {
+        return;
+    };
+    let received = match job.rx.lock() {
+        Ok(rx) => rx.try_recv(),
+        Err(_) => Err(mpsc::TryRecvError::Disconnected),
+    };
+    let (graph, source_map) = match received {
+        Ok(result) => result,
+        Err(mpsc::TryRecvError::Empty) => return,
+        Err(mpsc::TryRecvError::Disconnected) => {
+            commands.remove_resource::<CrawlJob>();
+            status.set("crawl: the crawl thread stopped unexpectedly");
+            return;
         }
-        let mut level_order: Vec<_> = by_level.keys().copied().collect();
-        level_order.sort();
-
-        let mut name_to_entity: HashMap<String, Entity> = HashMap::new();
-        for lvl in level_order {
-            let mut names = by_level.get(&lvl).cloned().unwrap_or_default();
-            names.sort();
-            let row_len = names.len();
-            let y = -(lvl as f32) * config.flow_row_height;
-            for (i, name) in names.iter().enumerate() {
-                let x = (i as f32 - row_len as f32 * 0.5) * config.flow_node_spacing;
-                // Node IDs are namespaced: `relative/path.rs::function_name`
-                // Decision nodes: `relative/path.rs::_decision_N\x1FDISPLAY_TEXT`
-                // Detect by DECISION_SEP presence (only decision nodes contain it).
-                let is_decision = name.contains(DECISION_SEP);
-                let color = if is_decision {
-                    DECISION_NODE_COLOR
-                } else {
-                    CRAWL_NODE_COLOR
-                };
-                // Strip the namespace prefix (split at first "::"), then strip the
-                // decision-node ID prefix (split at DECISION_SEP) to get display text.
-                let after_ns = name.splitn(2, "::").nth(1).unwrap_or(name.as_str());
-                let display_name = after_ns.splitn(2, DECISION_SEP).nth(1).unwrap_or(after_ns);
-                let entity = spawn_node_with_color(&mut commands, x, y, display_name, color);
-                name_to_entity.insert(name.clone(), entity);
-
-                // Attach source location (for gd) and file label only on function nodes.
-                if !is_decision {
-                    if let Some((abs_file, line)) = source_map.get(name) {
-                        commands.entity(entity).insert(SourceLocation {
-                            file: abs_file.clone(),
-                            line: *line,
-                        });
-                    }
-                    // Small filename label at the bottom of the node.
-                    let rel_path = name.splitn(2, "::").next().unwrap_or("");
-                    let basename = Path::new(rel_path)
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or(rel_path);
-                    let basename = basename.to_string();
-                    commands.entity(entity).with_children(|parent| {
-                        parent.spawn((
-                            Text2d::new(basename),
-                            TextFont {
-                                font_size: 9.0,
-                                ..default()
-                            },
-                            TextColor(Color::srgba(0.65, 0.70, 0.75, 0.65)),
-                            Transform::from_xyz(0.0, -48.0, 1.0),
-                            FileLabel,
-                        ));
-                    });
+    };
+    commands.remove_resource::<CrawlJob>();
+    let ev = &job.request;
+    let abs_roots = &job.abs_roots;
+    let abs_root_strs: Vec<String> = abs_roots
+        .iter()
+        .map(|root| root.to_string_lossy().into_owned())
+        .collect();
+    let path = ev.paths.join(" ");
+    if graph.is_empty() {
+        let found = match ev.mode {
+            CrawlMode::Calls => "functions",
+            CrawlMode::Imports => "source files",
+        };
+        warn!("[CRAWL] No {} found in {}", found, path);
+        status.set(format!("crawl: no {} found in {}", found, path));
+        return;
+    }
+
+    // Only include functions defined in the codebase (graph.keys()). Filter out std/method
+    // calls like as_mut, unwrap, iter, etc. that the parser picks up.
+    let mut defined: HashSet<String> = graph.keys().cloned().collect();
+
+    // `--depth N`: keep only nodes within N hops of a root (level-0) function.
+    if let Some(max_depth) = ev.max_depth {
+        let all: Vec<String> = defined.iter().cloned().collect();
+        let mut roots: Vec<String> = hierarchy_levels(&graph, &all)
+            .into_iter()
+            .filter(|(_, lvl)| *lvl == 0)
+            .map(|(name, _)| name)
+            .collect();
+        roots.sort();
+        let kept = nodes_within_depth(&graph, &roots, max_depth);
+        defined.retain(|name| kept.contains(name));
+    }
+
+    // Despawn the active tab's nodes and edges; one `u` brings them back.
+    checkpoint.record_before();
+    for entity in canvas_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    spatial_index.clear();
+
+    // Sort for deterministic layout.
+    let mut sorted: Vec<_> = defined.iter().cloned().collect();
+    sorted.sort();
+
+    // Hierarchical flow layout: roots at top, callees below.
+    let layout = flow_layout(&graph, &sorted, &config);
+    let levels = hierarchy_levels(&graph, &sorted);
+    let roots = layout
+        .iter()
+        .filter(|(name, _)| levels.get(name) == Some(&0) && !name.contains(DECISION_SEP))
+        .map(|(_, pos)| *pos);
+    crate::core::marks::set_root_marks(&mut marks, roots);
+    let mut name_to_entity: HashMap<String, Entity> = HashMap::new();
+    for (name, pos) in &layout {
+        let (x, y) = (pos.x, pos.y);
+        // Node IDs are namespaced: `relative/path.rs::function_name`
+        // Decision nodes: `relative/path.rs::_decision_N\x1FDISPLAY_TEXT`
+        // Detect by DECISION_SEP presence (only decision nodes contain it).
+        let is_decision = name.contains(DECISION_SEP);
+        let color = match source_map.get(name) {
+            _ if is_decision => theme.decision,
+            Some((abs_file, ..)) if config.color_by_module => {
+                crate::render::cluster::module_fill(abs_file, theme.crawl_node)
+            }
+            _ => theme.crawl_node,
+        };
+        // Strip the namespace prefix (split at first "::"), then strip the
+        // decision-node ID prefix (split at DECISION_SEP) to get display text.
+        let after_ns = name.splitn(2, "::").nth(1).unwrap_or(name.as_str());
+        let display_name = after_ns.splitn(2, DECISION_SEP).nth(1).unwrap_or(after_ns);
+        let entity = spawn_node_with_color(&mut commands, x, y, display_name, color);
+        commands.entity(entity).insert(FlowNodeId(name.clone()));
+        name_to_entity.insert(name.clone(), entity);
+
+        // Attach source location (for gd) and file label only on function nodes.
+        if !is_decision {
+            if let Some((abs_file, line, end)) = source_map.get(name) {
+                commands.entity(entity).insert(SourceLocation {
+                    file: abs_file.clone(),
+                    line: *line,
+                });
+                // `show_complexity`: function length, to spot the big ones.
+                if config.show_complexity && ev.mode == CrawlMode::Calls {
+                    spawn_line_count_label(&mut commands, entity, end.saturating_sub(*line) + 1);
                 }
             }
+            // Small filename label at the bottom of the node; file nodes
+            // of an import graph already show their path.
+            if ev.mode == CrawlMode::Calls {
+                let rel_path = name.splitn(2, "::").next().unwrap_or("");
+                spawn_file_label(&mut commands, entity, rel_path);
+            }
+        } else {
+            commands.entity(entity).insert((DecisionNode, NodeShape::Diamond));
         }
+    }
 
-        // Spawn edges with labels (for flow map). Only link to defined or decision nodes.
-        let mut edge_count = 0;
-        for (caller, edges) in &graph {
-            let Some(&source) = name_to_entity.get(caller) else {
-                continue;
-            };
-            for edge in edges {
-                let target_name = &edge.target;
-                if let Some(&target) = name_to_entity.get(target_name) {
-                    if source != target {
-                        commands.spawn(Edge {
-                            source,
-                            target,
-                            label: edge.label.clone(),
-                        });
-                        edge_count += 1;
-                    }
-                }
+    // Spawn edges with labels (for flow map). Only link to defined or decision nodes.
+    let mut edge_count = 0;
+    for (caller, edges) in &graph {
+        let Some(&source) = name_to_entity.get(caller) else {
+            continue;
+        };
+        for edge in edges {
+            let target_name = &edge.target;
+            // Self-edges (recursion) are kept and drawn as loops.
+            if let Some(&target) = name_to_entity.get(target_name) {
+                commands.spawn(Edge {
+                    source,
+                    target,
+                    label: edge.label.clone(),
+                });
+                edge_count += 1;
             }
         }
+    }
 
-        force_layout.active = false; // hierarchy layout — no force collapse
-
-        let node_count = sorted.len();
-        info!(
-            "[CRAWL] Spawned {} nodes, {} edges from {}",
-            node_count, edge_count, abs_root_str
-        );
-        status.set(format!(
+    force_layout.active = false; // hierarchy layout — no force collapse
+
+    let node_count = sorted.len();
+    info!(
+        "[CRAWL] Spawned {} nodes, {} edges from {}",
+        node_count,
+        edge_count,
+        abs_root_strs.join(", ")
+    );
+    match ev.max_depth {
+        Some(depth) => status.set(format!(
+            "Crawled: {} nodes, {} edges (depth {})",
+            node_count, edge_count, depth
+        )),
+        None => status.set(format!(
             "Crawled: {} nodes, {} edges",
             node_count, edge_count
-        ));
-
-        // ── Start/restart the file-system watcher ────────────────────────────
-        watch_state.no_flow = ev.no_flow;
-        watch_state.watch_path = Some(abs_root_str.clone());
-        watch_state.last_event = None;
-
-        use notify::{RecommendedWatcher, RecursiveMode, Watcher};
-        let abs = abs_root.clone();
-        let (tx, rx) = std::sync::mpsc::channel();
-        match RecommendedWatcher::new(
-            move |res| {
-                let _ = tx.send(res);
-            },
-            notify::Config::default(),
-        ) {
-            Ok(mut watcher) => {
-                if watcher.watch(&abs, RecursiveMode::Recursive).is_ok() {
-                    if let (Ok(mut w), Ok(mut r)) =
-                        (watch_state._watcher.lock(), watch_state.rx.lock())
-                    {
-                        *w = Some(watcher);
-                        *r = Some(rx);
-                    }
+        )),
+    }
+
+    let files: HashSet<&String> = source_map.values().map(|(file, ..)| file).collect();
+    crawl_info.roots = abs_roots
+        .iter()
+        .zip(&abs_root_strs)
+        .map(|(root, root_str)| {
+            let name = root
+                .file_name()
+                .map_or_else(|| root_str.clone(), |n| n.to_string_lossy().into_owned());
+            (name, root.is_dir())
+        })
+        .collect();
+    crawl_info.file_count = files.len();
+    crawl_info.crawled_at = Some(std::time::Instant::now());
+    // Kept so `:flow off` and `:callers` can redraw later without a re-crawl.
+    crawled_flow.graph = graph;
+    crawled_flow.callers = false;
+
+    // ── Start/restart the file-system watcher ────────────────────────────
+    watch_state.no_flow = ev.no_flow;
+    watch_state.max_depth = ev.max_depth;
+    watch_state.exclude = ev.exclude.clone();
+    watch_state.follow_links = ev.follow_links;
+    watch_state.respect_gitignore = ev.respect_gitignore;
+    watch_state.mode = ev.mode;
+    watch_state.watch_paths = abs_root_strs.clone();
+    watch_state.last_event = None;
+
+    use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+    let (tx, rx) = std::sync::mpsc::channel();
+    match RecommendedWatcher::new(
+        move |res| {
+            let _ = tx.send(res);
+        },
+        notify::Config::default(),
+    ) {
+        Ok(mut watcher) => {
+            // A single-file root is watched through its directory, since
+            // editors often replace the file instead of writing to it.
+            let watched: Vec<&std::path::Path> = abs_roots
+                .iter()
+                .map(|abs| match abs.parent() {
+                    Some(dir) if abs.is_file() => (dir, RecursiveMode::NonRecursive),
+                    _ => (abs.as_path(), RecursiveMode::Recursive),
+                })
+                .filter(|(path, mode)| watcher.watch(path, *mode).is_ok())
+                .map(|(path, _)| path)
+                .collect();
+            if !watched.is_empty() {
+                if let (Ok(mut w), Ok(mut r)) =
+                    (watch_state._watcher.lock(), watch_state.rx.lock())
+                {
+                    *w = Some(watcher);
+                    *r = Some(rx);
+                }
+                for abs in watched {
                     info!("[WATCH] Watching {} for changes", abs.display());
                 }
             }
-            Err(e) => warn!("[WATCH] Could not create watcher: {}", e),
         }
+        Err(e) => warn!("[WATCH] Could not create watcher: {}", e),
     }
 }
 
@@ -467,4 +880,123 @@ mod tests {
         assert!(levels["a"] < usize::MAX);
         assert!(levels["b"] < usize::MAX);
     }
+
+    #[test]
+    fn depth_limit_keeps_nodes_within_hops() {
+        // main -> if (decision) -> a -> b
+        let mut graph = CallGraph::new();
+        graph.insert("main".into(), vec![FlowEdge { target: "if".into(), label: None }]);
+        graph.insert("if".into(), vec![FlowEdge { target: "a".into(), label: Some("true".into()) }]);
+        graph.insert("a".into(), vec![FlowEdge { target: "b".into(), label: None }]);
+        graph.insert("b".into(), vec![]);
+        let roots = vec!["main".to_string()];
+
+        let only_roots = nodes_within_depth(&graph, &roots, 0);
+        assert_eq!(only_roots, HashSet::from(["main".to_string()]));
+
+        let two = nodes_within_depth(&graph, &roots, 2);
+        assert!(two.contains("if") && two.contains("a"));
+        assert!(!two.contains("b"));
+    }
+
+    #[test]
+    fn parse_crawl_args_flags_and_depth() {
+        assert_eq!(
+            parse_crawl_args("./src --depth 2 --no-flow"),
+            Ok(CrawlArgs {
+                paths: vec!["./src"],
+                no_flow: true,
+                max_depth: Some(2),
+                exclude: vec![],
+                follow_links: false,
+                respect_gitignore: false,
+                mode: CrawlMode::Calls,
+            })
+        );
+        assert_eq!(
+            parse_crawl_args("--depth 0 \"./my src\" ./lib"),
+            Ok(CrawlArgs {
+                paths: vec!["./my src", "./lib"],
+                no_flow: false,
+                max_depth: Some(0),
+                exclude: vec![],
+                follow_links: false,
+                respect_gitignore: false,
+                mode: CrawlMode::Calls,
+            })
+        );
+        assert_eq!(
+            parse_crawl_args("./ --exclude target,node_modules").map(|a| a.exclude),
+            Ok(vec!["target".to_string(), "node_modules".to_string()])
+        );
+        assert_eq!(
+            parse_crawl_args("--follow-links ./pkgs").map(|a| (a.paths[0], a.follow_links)),
+            Ok(("./pkgs", true))
+        );
+        assert_eq!(
+            parse_crawl_args("./ --respect-gitignore").map(|a| (a.paths[0], a.respect_gitignore)),
+            Ok(("./", true))
+        );
+        assert_eq!(
+            parse_crawl_args("./ --imports").map(|a| (a.paths[0], a.mode)),
+            Ok(("./", CrawlMode::Imports))
+        );
+        assert!(parse_crawl_args("./ --exclude").is_err());
+        assert!(parse_crawl_args("./src --depth").is_err());
+        assert!(parse_crawl_args("./src --depth x").is_err());
+        assert!(parse_crawl_args("--no-flow").is_err());
+        assert_eq!(
+            parse_crawl_args("./frontend --no-flow ./backend").map(|a| a.paths),
+            Ok(vec!["./frontend", "./backend"])
+        );
+    }
+
+    #[test]
+    fn format_age_buckets() {
+        assert_eq!(format_age(0), "just now");
+        assert_eq!(format_age(45), "45s ago");
+        assert_eq!(format_age(125), "2m ago");
+        assert_eq!(format_age(7300), "2h ago");
+    }
+
+    #[test]
+    fn watcher_recrawls_for_every_crawled_language() {
+        let roots = vec!["/repo/src".to_string()];
+        let triggers = |path: &str| triggers_recrawl(std::path::Path::new(path), &roots);
+        assert!(triggers("/repo/src/main.rs"));
+        assert!(triggers("/repo/src/server/handler.go"));
+        assert!(triggers("/repo/src/vm.c"));
+        assert!(triggers("/repo/src/vm.h"));
+        assert!(!triggers("/repo/src/notes.md"));
+        assert!(!triggers("/repo/other/main.go"), "outside the watched root");
+        let file_root = vec!["/repo/tool.c".to_string()];
+        assert!(triggers_recrawl(std::path::Path::new("/repo/tool.c"), &file_root));
+        assert!(!triggers_recrawl(std::path::Path::new("/repo/other.c"), &file_root));
+    }
+
+    #[test]
+    fn crawl_info_summary() {
+        assert!(CrawlInfo::default().summary().is_none());
+        let info = CrawlInfo {
+            roots: vec![("src".into(), true)],
+            file_count: 34,
+            crawled_at: Some(std::time::Instant::now()),
+        };
+        assert_eq!(info.summary().unwrap(), "Crawled src/ · 34 files · just now");
+        let info = CrawlInfo {
+            roots: vec![("frontend".into(), true), ("backend".into(), true)],
+            file_count: 1,
+            crawled_at: Some(std::time::Instant::now()),
+        };
+        assert_eq!(
+            info.summary().unwrap(),
+            "Crawled frontend/ + backend/ · 1 file · just now"
+        );
+        let info = CrawlInfo {
+            roots: vec![("main.go".into(), false)],
+            file_count: 1,
+            crawled_at: Some(std::time::Instant::now()),
+        };
+        assert_eq!(info.summary().unwrap(), "Crawled main.go · 1 file · just now");
+    }
 }
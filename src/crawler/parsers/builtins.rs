@@ -42,3 +42,21 @@ pub static TYPESCRIPT_BUILTINS: phf::Set<&'static str> = phf_set! {
     "Array", "Object", "String", "Number", "Boolean", "Math", "JSON", "Promise",
     "console", "setTimeout", "setInterval", "clearTimeout", "clearInterval",
 };
+
+/// Go predeclared functions and common fmt helpers.
+pub static GO_BUILTINS: phf::Set<&'static str> = phf_set! {
+    "make", "len", "append", "cap", "new", "delete", "copy", "close",
+    "panic", "recover", "print", "println", "complex", "real", "imag",
+    "min", "max", "clear",
+    "Println", "Printf", "Print", "Sprintf", "Sprint", "Sprintln",
+    "Fprintf", "Fprintln", "Errorf",
+};
+
+/// C standard library functions (stdio, stdlib, string, assert).
+pub static C_BUILTINS: phf::Set<&'static str> = phf_set! {
+    "printf", "fprintf", "sprintf", "snprintf", "puts", "putchar", "fputs", "perror",
+    "scanf", "sscanf", "fopen", "fclose", "fread", "fwrite", "fgets", "fflush",
+    "malloc", "calloc", "realloc", "free", "exit", "abort", "atoi", "atol", "strtol",
+    "memcpy", "memmove", "memset", "memcmp", "strlen", "strcpy", "strncpy", "strcmp",
+    "strncmp", "strcat", "strncat", "strchr", "strrchr", "strstr", "strdup", "assert",
+};
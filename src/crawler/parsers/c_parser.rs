@@ -0,0 +1,231 @@
+//! CParser — tree-sitter recursive walk via GenericWalker, for `.c` and `.h` files.
+
+use tree_sitter::{Language, Node, Parser};
+
+use super::super::{CallGraph, LanguageParser};
+use super::builtins;
+use super::walker::{walk_tree, WalkerConfig};
+use std::collections::{HashMap, HashSet};
+
+const C_CONFIG: WalkerConfig = WalkerConfig {
+    // int foo(void) { ... } — the name is nested under `declarator`
+    // (function_declarator → identifier), so the walker sees the whole
+    // declarator text; `CParser::parse_with_lines` maps it back to the name.
+    function_kinds: &["function_definition"],
+    function_name_field: "declarator",
+
+    anon_function_kinds: &[],
+    anon_parent_kinds: &[],
+    anon_parent_name_field: "name",
+
+    call_kind: "call_expression",
+    call_function_field: "function",
+    // ops->run() / s.step() → field_expression; the name is in `field`.
+    method_receiver_kind: "field_expression",
+    method_name_field: "field",
+
+    path_call_kind: None,
+    path_name_field: None,
+
+    // `else` wraps its statement in an else_clause; `else if` is a nested
+    // if_statement inside it.
+    if_kind: Some("if_statement"),
+    if_condition_field: Some("condition"),
+    if_then_field: Some("consequence"),
+    if_else_field: Some("alternative"),
+    elif_clause_kind: None,
+    elif_condition_field: None,
+    elif_body_field: None,
+    else_clause_kind: None,
+    else_body_field: None,
+
+    for_kinds: &["for_statement"],
+    while_kinds: &["while_statement", "do_statement"],
+    loop_body_field: Some("body"),
+    while_condition_field: Some("condition"),
+
+    // switch uses the match machinery: each `case` arm is labelled with its
+    // value; `default` falls back to `_`.
+    match_kind: Some("switch_statement"),
+    match_value_field: Some("condition"),
+    match_body_field: Some("body"),
+    match_arm_kind: Some("case_statement"),
+    match_pattern_kind: None,
+    match_pattern_field: Some("value"),
+
+    test_mod_kind: None,
+    test_mod_name_field: "",
+    test_mod_names: &[],
+
+    builtins: &builtins::C_BUILTINS,
+
+    // `@flow` handling needs the bare name, so it is done in `collect_names`.
+    comment_kind: Some("comment"),
+};
+
+/// Bare name of a `function_definition`: follow the `declarator` chain
+/// (`pointer_declarator` → `function_declarator` → `identifier`).
+fn function_name<'a>(node: Node, code: &'a str) -> Option<&'a str> {
+    let mut decl = node.child_by_field_name("declarator")?;
+    while decl.kind() != "identifier" {
+        // parenthesized_declarator has no field; its declarator is the first named child.
+        decl = decl
+            .child_by_field_name("declarator")
+            .or_else(|| decl.named_child(0))?;
+    }
+    code.get(decl.start_byte()..decl.end_byte())
+}
+
+/// Map each function's declarator text (the name the walker records) to its
+/// bare name, and collect names whose definition follows a `@flow` comment.
+fn collect_names(
+    node: Node,
+    code: &str,
+    names: &mut HashMap<String, String>,
+    flow: &mut HashSet<String>,
+) {
+    if node.kind() == "function_definition" {
+        let decl_text = node
+            .child_by_field_name("declarator")
+            .and_then(|d| code.get(d.start_byte()..d.end_byte()));
+        if let (Some(decl_text), Some(name)) = (decl_text, function_name(node, code)) {
+            let has_flow = node.prev_named_sibling().is_some_and(|prev| {
+                prev.kind() == "comment"
+                    && code
+                        .get(prev.start_byte()..prev.end_byte())
+                        .is_some_and(|t| t.contains("@flow"))
+            });
+            if has_flow {
+                flow.insert(name.to_string());
+            }
+            names.insert(decl_text.trim().to_string(), name.to_string());
+        }
+    }
+    for i in 0..node.child_count() {
+        collect_names(node.child(i).unwrap(), code, names, flow);
+    }
+}
+
+pub struct CParser {
+    language: Language,
+}
+
+impl CParser {
+    pub fn new() -> Self {
+        Self { language: tree_sitter_c::LANGUAGE.into() }
+    }
+}
+
+impl Default for CParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LanguageParser for CParser {
+    fn parse(&self, code: &str) -> CallGraph {
+        self.parse_with_lines(code, false, &HashSet::new()).0
+    }
+
+    fn parse_with_lines(
+        &self,
+        code: &str,
+        no_flow: bool,
+        include_calls: &HashSet<String>,
+    ) -> (CallGraph, HashMap<String, (u32, u32)>) {
+        let mut parser = Parser::new();
+        if parser.set_language(&self.language).is_err() {
+            return (CallGraph::new(), HashMap::new());
+        }
+        let Some(tree) = parser.parse(code, None) else {
+            return (CallGraph::new(), HashMap::new());
+        };
+        if tree.root_node().has_error() {
+            return (CallGraph::new(), HashMap::new());
+        }
+
+        let mut names = HashMap::new();
+        let mut include = include_calls.clone();
+        collect_names(tree.root_node(), code, &mut names, &mut include);
+        let (graph, lines) = walk_tree(&C_CONFIG, tree.root_node(), code, no_flow, &include);
+
+        let rename = |key: String| names.get(&key).cloned().unwrap_or(key);
+        let mut renamed = CallGraph::new();
+        for (key, edges) in graph {
+            renamed.entry(rename(key)).or_default().extend(edges);
+        }
+        let lines = lines.into_iter().map(|(key, line)| (rename(key), line)).collect();
+        (renamed, lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_function_calling_another() {
+        let code = "static void bar(void) {}\n\nint foo(int x) {\n    bar();\n    return x;\n}\n";
+        let (g, lines) = CParser::new().parse_with_lines(code, false, &HashSet::new());
+        assert!(g.contains_key("bar"));
+        assert_eq!(g["foo"].len(), 1);
+        assert_eq!(g["foo"][0].target, "bar");
+        assert_eq!(lines.get("foo"), Some(&(3, 6)));
+    }
+
+    #[test]
+    fn parse_pointer_return_and_builtins() {
+        let code = r#"
+#include <stdlib.h>
+
+struct node *make(int v) {
+    struct node *n = malloc(sizeof *n);
+    printf("%d\n", v);
+    ops->init(n);
+    return n;
+}
+"#;
+        let g = CParser::new().parse(code);
+        let make = &g["make"];
+        assert_eq!(make.len(), 1, "malloc/printf are builtins: {:?}", make);
+        assert_eq!(make[0].target, "init");
+    }
+
+    #[test]
+    fn parse_switch_labels_case_arms() {
+        let code = r#"
+void run(int op) {
+    switch (op) {
+    case 1:
+        start();
+        break;
+    default:
+        stop();
+    }
+}
+"#;
+        let g = CParser::new().parse(code);
+        let dec_id = g["run"]
+            .iter()
+            .find(|e| e.target.starts_with("_decision_"))
+            .unwrap()
+            .target
+            .clone();
+        let arms = &g[&dec_id];
+        assert!(arms.iter().any(|e| e.target == "start" && e.label.as_deref() == Some("1")));
+        assert!(arms.iter().any(|e| e.target == "stop" && e.label.as_deref() == Some("_")));
+    }
+
+    #[test]
+    fn parse_flow_comment_keeps_builtin_name() {
+        let code = "// @flow\nvoid free(void *p) {}\n\nvoid done(void *p) {\n    free(p);\n}\n";
+        let g = CParser::new().parse(code);
+        assert_eq!(g["done"].len(), 1);
+        assert_eq!(g["done"][0].target, "free");
+    }
+
+    #[test]
+    fn parse_garbage_no_panic() {
+        let _ = CParser::new().parse("int (((");
+    }
+}
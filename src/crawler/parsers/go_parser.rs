@@ -0,0 +1,159 @@
+//! GoParser — tree-sitter recursive walk via GenericWalker.
+
+use tree_sitter::{Language, Parser};
+
+use super::super::{CallGraph, LanguageParser};
+use super::builtins;
+use super::walker::{walk_tree, WalkerConfig};
+use std::collections::{HashMap, HashSet};
+
+const GO_CONFIG: WalkerConfig = WalkerConfig {
+    // `function_declaration` → func foo() {}
+    // `method_declaration`   → func (r *T) foo() {}
+    function_kinds: &["function_declaration", "method_declaration"],
+    function_name_field: "name",
+
+    // Function literals (`func() {}`) have no name of their own; skip for now.
+    anon_function_kinds: &[],
+    anon_parent_kinds: &[],
+    anon_parent_name_field: "name",
+
+    call_kind: "call_expression",
+    call_function_field: "function",
+    // obj.Method() / pkg.Func() → selector_expression; the name is in the `field` field.
+    method_receiver_kind: "selector_expression",
+    method_name_field: "field",
+
+    path_call_kind: None,
+    path_name_field: None,
+
+    // if / else — field-based like Rust and TypeScript; `else if` is a nested
+    // if_statement inside `alternative`.
+    if_kind: Some("if_statement"),
+    if_condition_field: Some("condition"),
+    if_then_field: Some("consequence"),
+    if_else_field: Some("alternative"),
+    elif_clause_kind: None,
+    elif_condition_field: None,
+    elif_body_field: None,
+    else_clause_kind: None,
+    else_body_field: None,
+
+    // Go has no `while`: `for cond {}` and `for {}` are both `for_statement`,
+    // as are counted and range loops, so every loop is a `for` decision node.
+    for_kinds: &["for_statement"],
+    while_kinds: &[],
+    loop_body_field: Some("body"),
+    while_condition_field: None,
+
+    // switch / select are not yet supported — children are walked normally.
+    match_kind: None,
+    match_value_field: None,
+    match_body_field: None,
+    match_arm_kind: None,
+    match_pattern_kind: None,
+    match_pattern_field: None,
+
+    // Go test filtering is file-level (*_test.go).
+    test_mod_kind: None,
+    test_mod_name_field: "",
+    test_mod_names: &[],
+
+    builtins: &builtins::GO_BUILTINS,
+
+    // `// @flow` above a func bypasses the builtins filter for that name.
+    comment_kind: Some("comment"),
+};
+
+pub struct GoParser {
+    language: Language,
+}
+
+impl GoParser {
+    pub fn new() -> Self {
+        Self { language: tree_sitter_go::LANGUAGE.into() }
+    }
+}
+
+impl Default for GoParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LanguageParser for GoParser {
+    fn parse(&self, code: &str) -> CallGraph {
+        self.parse_with_lines(code, false, &HashSet::new()).0
+    }
+
+    fn parse_with_lines(
+        &self,
+        code: &str,
+        no_flow: bool,
+        include_calls: &HashSet<String>,
+    ) -> (CallGraph, HashMap<String, (u32, u32)>) {
+        let mut parser = Parser::new();
+        if parser.set_language(&self.language).is_err() {
+            return (CallGraph::new(), HashMap::new());
+        }
+        let Some(tree) = parser.parse(code, None) else {
+            return (CallGraph::new(), HashMap::new());
+        };
+        if tree.root_node().has_error() {
+            return (CallGraph::new(), HashMap::new());
+        }
+        walk_tree(&GO_CONFIG, tree.root_node(), code, no_flow, include_calls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_function_calling_another() {
+        let code = "package main\n\nfunc bar() {}\n\nfunc foo() {\n\tbar()\n}\n";
+        let g = GoParser::new().parse(code);
+        assert!(g.contains_key("bar"));
+        assert_eq!(g["foo"].len(), 1);
+        assert_eq!(g["foo"][0].target, "bar");
+    }
+
+    #[test]
+    fn parse_method_and_builtins() {
+        let code = r#"
+package main
+
+type S struct{}
+
+func (s *S) run(xs []int) {
+	xs = append(xs, len(xs))
+	s.step()
+}
+
+func (s *S) step() {}
+"#;
+        let g = GoParser::new().parse(code);
+        let run = &g["run"];
+        assert_eq!(run.len(), 1, "append/len are builtins: {:?}", run);
+        assert_eq!(run[0].target, "step");
+    }
+
+    #[test]
+    fn parse_for_creates_loop_decision_node() {
+        let code = "package main\n\nfunc foo(n int) {\n\tfor n > 0 {\n\t\tbar()\n\t}\n}\n\nfunc bar() {}\n";
+        let g = GoParser::new().parse(code);
+        let dec_id = g["foo"]
+            .iter()
+            .find(|e| e.target.starts_with("_decision_"))
+            .unwrap()
+            .target
+            .clone();
+        assert!(g[&dec_id].iter().any(|e| e.target == "bar" && e.label.as_deref() == Some("Loop")));
+    }
+
+    #[test]
+    fn parse_garbage_no_panic() {
+        let _ = GoParser::new().parse("func (((");
+    }
+}
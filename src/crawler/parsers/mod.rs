@@ -1,6 +1,8 @@
 //! Language parser implementations.
 
 pub mod builtins;
+pub mod c_parser;
+pub mod go_parser;
 pub mod python_parser;
 pub mod rust_parser;
 pub mod typescript_parser;
@@ -5,7 +5,7 @@ use tree_sitter::{Language, Parser};
 use super::super::{CallGraph, LanguageParser};
 use super::builtins;
 use super::walker::{walk_tree, WalkerConfig};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 const PYTHON_CONFIG: WalkerConfig = WalkerConfig {
     // def foo() / async def foo()  — both produce `function_definition`
@@ -53,6 +53,7 @@ const PYTHON_CONFIG: WalkerConfig = WalkerConfig {
     match_body_field: None,
     match_arm_kind: None,
     match_pattern_kind: None,
+    match_pattern_field: None,
 
     // Python has no inline test-module syntax; test filtering is file-level.
     test_mod_kind: None,
@@ -83,10 +84,15 @@ impl Default for PythonParser {
 
 impl LanguageParser for PythonParser {
     fn parse(&self, code: &str) -> CallGraph {
-        self.parse_with_lines(code, false).0
+        self.parse_with_lines(code, false, &HashSet::new()).0
     }
 
-    fn parse_with_lines(&self, code: &str, no_flow: bool) -> (CallGraph, HashMap<String, u32>) {
+    fn parse_with_lines(
+        &self,
+        code: &str,
+        no_flow: bool,
+        include_calls: &HashSet<String>,
+    ) -> (CallGraph, HashMap<String, (u32, u32)>) {
         let mut parser = Parser::new();
         if parser.set_language(&self.language).is_err() {
             return (CallGraph::new(), HashMap::new());
@@ -97,7 +103,7 @@ impl LanguageParser for PythonParser {
         if tree.root_node().has_error() {
             return (CallGraph::new(), HashMap::new());
         }
-        walk_tree(&PYTHON_CONFIG, tree.root_node(), code, no_flow)
+        walk_tree(&PYTHON_CONFIG, tree.root_node(), code, no_flow, include_calls)
     }
 }
 
@@ -5,7 +5,7 @@ use tree_sitter::{Language, Parser};
 use super::super::{CallGraph, LanguageParser};
 use super::builtins;
 use super::walker::{walk_tree, WalkerConfig};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 const RUST_CONFIG: WalkerConfig = WalkerConfig {
     // fn foo() / pub fn foo() / async fn foo()
@@ -48,6 +48,7 @@ const RUST_CONFIG: WalkerConfig = WalkerConfig {
     match_body_field: Some("body"),
     match_arm_kind: Some("match_arm"),
     match_pattern_kind: Some("match_pattern"),
+    match_pattern_field: None,
 
     // `#[cfg(test)] mod tests { ... }` — skip the whole subtree.
     test_mod_kind: Some("mod_item"),
@@ -78,10 +79,15 @@ impl Default for RustParser {
 
 impl LanguageParser for RustParser {
     fn parse(&self, code: &str) -> CallGraph {
-        self.parse_with_lines(code, false).0
+        self.parse_with_lines(code, false, &HashSet::new()).0
     }
 
-    fn parse_with_lines(&self, code: &str, no_flow: bool) -> (CallGraph, HashMap<String, u32>) {
+    fn parse_with_lines(
+        &self,
+        code: &str,
+        no_flow: bool,
+        include_calls: &HashSet<String>,
+    ) -> (CallGraph, HashMap<String, (u32, u32)>) {
         let mut parser = Parser::new();
         if parser.set_language(&self.language).is_err() {
             return (CallGraph::new(), HashMap::new());
@@ -92,7 +98,7 @@ impl LanguageParser for RustParser {
         if tree.root_node().has_error() {
             return (CallGraph::new(), HashMap::new());
         }
-        walk_tree(&RUST_CONFIG, tree.root_node(), code, no_flow)
+        walk_tree(&RUST_CONFIG, tree.root_node(), code, no_flow, include_calls)
     }
 }
 
@@ -8,7 +8,7 @@ use tree_sitter::{Language, Parser};
 use super::super::{CallGraph, LanguageParser};
 use super::builtins;
 use super::walker::{walk_tree, WalkerConfig};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 const TYPESCRIPT_CONFIG: WalkerConfig = WalkerConfig {
     // Named function nodes whose name is an inline field.
@@ -60,6 +60,7 @@ const TYPESCRIPT_CONFIG: WalkerConfig = WalkerConfig {
     match_body_field: None,
     match_arm_kind: None,
     match_pattern_kind: None,
+    match_pattern_field: None,
 
     // TypeScript test filtering is file-level (*.test.ts, *.spec.ts).
     test_mod_kind: None,
@@ -90,10 +91,15 @@ impl Default for TypeScriptParser {
 
 impl LanguageParser for TypeScriptParser {
     fn parse(&self, code: &str) -> CallGraph {
-        self.parse_with_lines(code, false).0
+        self.parse_with_lines(code, false, &HashSet::new()).0
     }
 
-    fn parse_with_lines(&self, code: &str, no_flow: bool) -> (CallGraph, HashMap<String, u32>) {
+    fn parse_with_lines(
+        &self,
+        code: &str,
+        no_flow: bool,
+        include_calls: &HashSet<String>,
+    ) -> (CallGraph, HashMap<String, (u32, u32)>) {
         let mut parser = Parser::new();
         if parser.set_language(&self.language).is_err() {
             return (CallGraph::new(), HashMap::new());
@@ -104,7 +110,7 @@ impl LanguageParser for TypeScriptParser {
         if tree.root_node().has_error() {
             return (CallGraph::new(), HashMap::new());
         }
-        walk_tree(&TYPESCRIPT_CONFIG, tree.root_node(), code, no_flow)
+        walk_tree(&TYPESCRIPT_CONFIG, tree.root_node(), code, no_flow, include_calls)
     }
 }
 
@@ -77,6 +77,9 @@ pub struct WalkerConfig {
     pub match_arm_kind: Option<&'static str>,
     /// The node kind whose text is used as the arm label.
     pub match_pattern_kind: Option<&'static str>,
+    /// Alternatively, the field on the arm whose text is the label (C's
+    /// `case_statement` → `value`, where the value can be any expression kind).
+    pub match_pattern_field: Option<&'static str>,
 
     // --- Test scope filtering -----------------------------------------------
     /// Optional: AST node kind that represents a module/namespace container
@@ -188,15 +191,25 @@ fn collect_force_includes(config: &WalkerConfig, root: Node, code: &str) -> Hash
 // ── Walker ────────────────────────────────────────────────────────────────────
 
 /// Walk the AST and return both the call graph and a map of bare function
-/// name → 1-indexed start line (used by the router to build `SourceMap`).
+/// name → 1-indexed start and end line (used by the router to build `SourceMap`).
 ///
 /// When `no_flow` is `true` all control-flow decision nodes (if/for/while/match)
 /// are suppressed: calls inside branches are attributed directly to the enclosing
 /// function, producing a flat function-only call graph.
-pub fn walk_tree(config: &WalkerConfig, root: Node, code: &str, no_flow: bool) -> (CallGraph, HashMap<String, u32>) {
-    let force_include = collect_force_includes(config, root, code);
+///
+/// `include_calls` lists extra names that bypass the builtins filter for the
+/// whole file, the same way a `@flow` annotation does for a single function.
+pub fn walk_tree(
+    config: &WalkerConfig,
+    root: Node,
+    code: &str,
+    no_flow: bool,
+    include_calls: &HashSet<String>,
+) -> (CallGraph, HashMap<String, (u32, u32)>) {
+    let mut force_include = collect_force_includes(config, root, code);
+    force_include.extend(include_calls.iter().cloned());
     let mut flow_map = CallGraph::new();
-    let mut line_map: HashMap<String, u32> = HashMap::new();
+    let mut line_map: HashMap<String, (u32, u32)> = HashMap::new();
     let mut counter: u32 = 0;
 
     #[derive(Clone, Debug)]
@@ -213,6 +226,11 @@ pub fn walk_tree(config: &WalkerConfig, root: Node, code: &str, no_flow: bool) -
             .to_string()
     }
 
+    /// 1-indexed first and last line of `node`.
+    fn line_span(node: Node) -> (u32, u32) {
+        (node.start_position().row as u32 + 1, node.end_position().row as u32 + 1)
+    }
+
     fn walk(
         node: Node,
         code: &str,
@@ -221,7 +239,7 @@ pub fn walk_tree(config: &WalkerConfig, root: Node, code: &str, no_flow: bool) -
         map: &mut CallGraph,
         ctr: &mut u32,
         force_include: &HashSet<String>,
-        lines: &mut HashMap<String, u32>,
+        lines: &mut HashMap<String, (u32, u32)>,
         no_flow: bool,
     ) {
         let kind = node.kind();
@@ -246,7 +264,7 @@ pub fn walk_tree(config: &WalkerConfig, root: Node, code: &str, no_flow: bool) -
                 .child_by_field_name(cfg.function_name_field)
                 .map(|n| get_text(n, code))
                 .unwrap_or_else(|| "<anon_fn>".to_string());
-            lines.insert(name.clone(), node.start_position().row as u32 + 1);
+            lines.insert(name.clone(), line_span(node));
             stack.push(Scope { id: name.clone(), label: None });
             for i in 0..node.child_count() {
                 walk(node.child(i).unwrap(), code, cfg, stack, map, ctr, force_include, lines, no_flow);
@@ -269,7 +287,7 @@ pub fn walk_tree(config: &WalkerConfig, root: Node, code: &str, no_flow: bool) -
                     }
                 })
                 .unwrap_or_else(|| "<anon_fn>".to_string());
-            lines.insert(name.clone(), node.start_position().row as u32 + 1);
+            lines.insert(name.clone(), line_span(node));
             stack.push(Scope { id: name.clone(), label: None });
             for i in 0..node.child_count() {
                 walk(node.child(i).unwrap(), code, cfg, stack, map, ctr, force_include, lines, no_flow);
@@ -464,6 +482,14 @@ pub fn walk_tree(config: &WalkerConfig, root: Node, code: &str, no_flow: bool) -
                     let child = arms_parent.child(i).unwrap();
                     if child.kind() == arm_kind {
                         let mut label: Option<String> = None;
+                        let pattern_node =
+                            cfg.match_pattern_field.and_then(|f| child.child_by_field_name(f));
+                        if let Some(pat) = pattern_node {
+                            let text = truncate(&get_text(pat, code), 40);
+                            if !text.is_empty() {
+                                label = Some(text);
+                            }
+                        }
                         if let Some(pat_kind) = cfg.match_pattern_kind {
                             for k in 0..child.child_count() {
                                 let sub = child.child(k).unwrap();
@@ -481,7 +507,8 @@ pub fn walk_tree(config: &WalkerConfig, root: Node, code: &str, no_flow: bool) -
                             let sub = child.child(k).unwrap();
                             let is_pattern = cfg
                                 .match_pattern_kind
-                                .map_or(false, |pk| sub.kind() == pk);
+                                .map_or(false, |pk| sub.kind() == pk)
+                                || pattern_node.is_some_and(|p| p.id() == sub.id());
                             if !is_pattern {
                                 walk(sub, code, cfg, stack, map, ctr, force_include, lines, no_flow);
                             }
@@ -1,24 +1,206 @@
-//! CrawlerRouter — walkdir-based directory crawler with extension dispatch.
+//! CrawlerRouter — `ignore`-walker-based directory (or single-file) crawler with
+//! extension dispatch.
 
 use bevy::prelude::*;
-use std::path::Path;
-use walkdir::WalkDir;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
+use super::imports::import_graph;
+use super::parsers::c_parser::CParser;
+use super::parsers::go_parser::GoParser;
 use super::parsers::python_parser::PythonParser;
 use super::parsers::rust_parser::RustParser;
 use super::parsers::typescript_parser::TypeScriptParser;
 use super::parsers::walker::DECISION_SEP;
 use super::{CallGraph, FlowEdge, LanguageParser, SourceMap};
 
+/// Build the matcher for `--exclude` patterns. Each pattern matches the path
+/// itself and everything below it, at any depth (`target` skips `a/target/x.rs`).
+/// Invalid patterns are logged and skipped.
+fn build_exclude_set(patterns: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.trim().trim_end_matches('/');
+        if pattern.is_empty() {
+            continue;
+        }
+        for variant in [
+            pattern.to_string(),
+            format!("**/{}", pattern),
+            format!("{}/**", pattern),
+            format!("**/{}/**", pattern),
+        ] {
+            match Glob::new(&variant) {
+                Ok(glob) => {
+                    builder.add(glob);
+                }
+                Err(e) => {
+                    warn!("[CRAWL] Invalid exclude pattern '{}': {}", pattern, e);
+                    break;
+                }
+            }
+        }
+    }
+    builder.build().unwrap_or_else(|e| {
+        warn!("[CRAWL] Could not build exclude patterns: {}", e);
+        GlobSet::empty()
+    })
+}
+
+/// True if `path` has an extension one of the parsers handles. The watcher
+/// uses it too, so saving any crawled language triggers a re-crawl.
+pub(crate) fn is_supported_source(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    matches!(ext.as_str(), "rs" | "py" | "ts" | "tsx" | "go" | "c" | "h")
+}
+
+/// Supported source files under `root_path` as `(path relative to the root,
+/// path)`, minus `exclude` matches, ignored paths (with `respect_gitignore`)
+/// and test files. Shared by the call-graph and import-graph crawls.
+fn walk_sources(
+    root_path: &Path,
+    exclude: &[String],
+    follow_links: bool,
+    respect_gitignore: bool,
+) -> Vec<(String, PathBuf)> {
+    let excluded = build_exclude_set(exclude);
+    let root = root_path.to_path_buf();
+    let visited_dirs: Mutex<HashSet<PathBuf>> = Mutex::new(HashSet::new());
+    let mut sources = Vec::new();
+    // `.gitignore` / `.ignore` files are only read inside the crawled tree,
+    // whether or not it is a git checkout.
+    let walk = WalkBuilder::new(root_path)
+        .standard_filters(false)
+        .git_ignore(respect_gitignore)
+        .ignore(respect_gitignore)
+        .require_git(false)
+        .follow_links(follow_links)
+        .filter_entry(move |e| {
+            let rel = e.path().strip_prefix(&root).unwrap_or(e.path());
+            if !rel.as_os_str().is_empty() && excluded.is_match(rel) {
+                return false;
+            }
+            // Skip a directory already entered through another path.
+            let is_dir = e.file_type().is_some_and(|t| t.is_dir());
+            !(follow_links && is_dir)
+                || e.path().canonicalize().map_or(true, |canonical| {
+                    visited_dirs.lock().unwrap().insert(canonical)
+                })
+        })
+        .build();
+    for entry in walk.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !is_supported_source(path) {
+            continue;
+        }
+        let rel = path
+            .strip_prefix(root_path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned();
+
+        // Skip test directories (tests/, test/) and test-named files
+        // (test_*.py, *_test.rs, *_test.go, *.test.ts, *.spec.ts).
+        let rel_norm = rel.replace('\\', "/");
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+        if rel_norm.starts_with("tests/")
+            || rel_norm.contains("/tests/")
+            || rel_norm.starts_with("test/")
+            || rel_norm.contains("/test/")
+            || stem.starts_with("test_")
+            || stem.ends_with("_test")
+            || rel_norm.contains(".test.")
+            || rel_norm.contains(".spec.")
+        {
+            continue;
+        }
+        sources.push((rel, path.to_path_buf()));
+    }
+    sources
+}
+
+/// Node ID prefix for each of `roots`: none for a single root, so IDs stay
+/// `rel/path.rs::name`; otherwise the root's directory name
+/// (`backend/api.rs::name`), with `~2`, `~3` on repeated names.
+fn root_prefixes(roots: &[&str]) -> Vec<String> {
+    if roots.len() < 2 {
+        return vec![String::new(); roots.len()];
+    }
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    roots
+        .iter()
+        .map(|root| {
+            let path = Path::new(root);
+            let name = path
+                .canonicalize()
+                .unwrap_or_else(|_| path.to_path_buf())
+                .file_name()
+                .map_or_else(|| root.to_string(), |n| n.to_string_lossy().into_owned());
+            let count = seen.entry(name.clone()).or_default();
+            *count += 1;
+            match *count {
+                1 => format!("{}/", name),
+                n => format!("{}~{}/", name, n),
+            }
+        })
+        .collect()
+}
+
+/// [`walk_sources`] over every existing directory in `roots`, with each
+/// relative path behind its root's [`root_prefixes`] entry. A root that is a
+/// supported source file is taken as is (no exclude, ignore or test-file
+/// filtering); its path is the file name, or the prefix with several roots.
+fn walk_roots(
+    roots: &[&str],
+    exclude: &[String],
+    follow_links: bool,
+    respect_gitignore: bool,
+) -> Vec<(String, PathBuf)> {
+    let mut sources = Vec::new();
+    for (root, prefix) in roots.iter().zip(root_prefixes(roots)) {
+        let root_path = Path::new(root);
+        if root_path.is_file() && is_supported_source(root_path) {
+            let rel = match prefix.strip_suffix('/') {
+                Some(name) => name.to_string(),
+                None => root_path
+                    .file_name()
+                    .map_or_else(|| root.to_string(), |n| n.to_string_lossy().into_owned()),
+            };
+            sources.push((rel, root_path.to_path_buf()));
+            continue;
+        }
+        if !root_path.is_dir() {
+            continue;
+        }
+        sources.extend(
+            walk_sources(root_path, exclude, follow_links, respect_gitignore)
+                .into_iter()
+                .map(|(rel, path)| (format!("{}{}", prefix, rel), path)),
+        );
+    }
+    sources
+}
+
 /// Routes files by extension to the appropriate LanguageParser.
-/// Uses walkdir to iterate; parse failures are logged and skipped (no panic).
+/// Uses `ignore::WalkBuilder` to iterate; parse failures are logged and skipped (no panic).
 pub struct CrawlerRouter;
 
 impl CrawlerRouter {
-    /// Crawl a directory and aggregate call graphs from all supported files.
+    /// Crawl one or more directories and aggregate call graphs from all
+    /// supported files into one graph.
     ///
     /// Node IDs are namespaced as `relative/path.rs::function_name` so that
-    /// identically-named functions in different files remain distinct.  Decision
+    /// identically-named functions in different files remain distinct. With
+    /// several `roots` the path starts with the root's directory name
+    /// (`frontend/app.ts::main`), and calls resolve across roots. Decision
     /// nodes follow the same prefix convention:
     /// `relative/path.rs::_decision_N\x1FDISPLAY_TEXT`.
     ///
@@ -26,73 +208,80 @@ impl CrawlerRouter {
     /// defined name and its declaring file(s), then rewrite edge targets to the
     /// namespaced form.  When a name is defined in more than one file an edge is
     /// emitted to each definition so ambiguity is visible in the graph.
-    pub fn crawl(root: &str, no_flow: bool) -> (CallGraph, SourceMap) {
-        let root_path = Path::new(root);
-        if !root_path.exists() || !root_path.is_dir() {
-            return (CallGraph::new(), SourceMap::new());
-        }
+    ///
+    /// `include_calls` names bypass every parser's builtins filter (see
+    /// `GlyphConfig::crawl_include_calls`). Entries whose path relative to
+    /// their root matches an `exclude` glob are skipped, directories included.
+    ///
+    /// With `follow_links` symlinked directories are crawled too; each
+    /// directory is entered once by canonical path, so symlink cycles end.
+    /// With `respect_gitignore` paths excluded by `.gitignore` / `.ignore`
+    /// files under a root are skipped as well. Missing roots are skipped; a
+    /// root that is a source file is crawled on its own.
+    pub fn crawl(
+        roots: &[&str],
+        no_flow: bool,
+        include_calls: &HashSet<String>,
+        exclude: &[String],
+        follow_links: bool,
+        respect_gitignore: bool,
+    ) -> (CallGraph, SourceMap) {
+        Self::crawl_counting(
+            roots,
+            no_flow,
+            include_calls,
+            exclude,
+            follow_links,
+            respect_gitignore,
+            &AtomicUsize::new(0),
+        )
+    }
 
+    /// [`CrawlerRouter::crawl`] that bumps `files_read` after each source
+    /// file, so a background crawl can report progress.
+    pub fn crawl_counting(
+        roots: &[&str],
+        no_flow: bool,
+        include_calls: &HashSet<String>,
+        exclude: &[String],
+        follow_links: bool,
+        respect_gitignore: bool,
+        files_read: &AtomicUsize,
+    ) -> (CallGraph, SourceMap) {
         let rust_parser = RustParser::new();
         let python_parser = PythonParser::new();
         let typescript_parser = TypeScriptParser::new();
+        let go_parser = GoParser::new();
+        let c_parser = CParser::new();
 
         // ── Phase 1: per-file parse ───────────────────────────────────────────
         // Collect (rel_path, abs_path, bare_call_graph, line_numbers).
-        let mut per_file: Vec<(String, String, CallGraph, std::collections::HashMap<String, u32>)> = Vec::new();
-        for entry in WalkDir::new(root_path)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
+        let mut per_file: Vec<(String, String, CallGraph, std::collections::HashMap<String, (u32, u32)>)> = Vec::new();
+        for (rel, path) in walk_roots(roots, exclude, follow_links, respect_gitignore) {
             let ext = path
                 .extension()
                 .and_then(|e| e.to_str())
                 .unwrap_or("")
                 .to_lowercase();
-
-            let parser: Option<&dyn LanguageParser> = match ext.as_str() {
-                "rs" => Some(&rust_parser),
-                "py" => Some(&python_parser),
-                "ts" | "tsx" => Some(&typescript_parser),
-                _ => continue,
+            let p: &dyn LanguageParser = match ext.as_str() {
+                "rs" => &rust_parser,
+                "py" => &python_parser,
+                "ts" | "tsx" => &typescript_parser,
+                "go" => &go_parser,
+                _ => &c_parser,
             };
-
-            if let Some(p) = parser {
-                let content = match std::fs::read_to_string(path) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        warn!("[CRAWL] Could not read {}: {}", path.display(), e);
-                        continue;
-                    }
-                };
-                    let rel = path
-                        .strip_prefix(root_path)
-                        .unwrap_or(path)
-                        .to_string_lossy()
-                        .into_owned();
-
-                    // Skip test directories (tests/, test/) and test-named files
-                    // (test_*.py, *_test.rs, *.test.ts, *.spec.ts).
-                    let rel_norm = rel.replace('\\', "/");
-                    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-                    if rel_norm.starts_with("tests/")
-                        || rel_norm.contains("/tests/")
-                        || rel_norm.starts_with("test/")
-                        || rel_norm.contains("/test/")
-                        || stem.starts_with("test_")
-                        || stem.ends_with("_test")
-                        || rel_norm.contains(".test.")
-                        || rel_norm.contains(".spec.")
-                    {
-                        continue;
-                    }
-
-                    let abs = path.to_string_lossy().into_owned();
-                    let (file_graph, line_numbers) = p.parse_with_lines(&content, no_flow);
-                    if !file_graph.is_empty() {
-                        per_file.push((rel, abs, file_graph, line_numbers));
-                    }
+            files_read.fetch_add(1, Ordering::Relaxed);
+            let content = match std::fs::read_to_string(&path) {
+                Ok(c) => c,
+                Err(e) => {
+                    warn!("[CRAWL] Could not read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let abs = path.to_string_lossy().into_owned();
+            let (file_graph, line_numbers) = p.parse_with_lines(&content, no_flow, include_calls);
+            if !file_graph.is_empty() {
+                per_file.push((rel, abs, file_graph, line_numbers));
             }
         }
 
@@ -113,7 +302,7 @@ impl CrawlerRouter {
         // Decision IDs keep their full form after the `::` separator:
         //   `relative/path.rs::_decision_1\x1Fif x > 0`
         // Call targets are resolved via the index built in Phase 2.
-        // source_map: ns_key → (abs_path, 1-indexed line) for each function node.
+        // source_map: ns_key → (abs_path, start line, end line) for each function node.
         let mut graph = CallGraph::new();
         let mut source_map = SourceMap::new();
         for (path, abs, file_graph, line_numbers) in per_file {
@@ -148,8 +337,8 @@ impl CrawlerRouter {
                     .collect();
                 // Record source location for regular function nodes.
                 if !bare_key.contains(DECISION_SEP) {
-                    if let Some(&line) = line_numbers.get(&bare_key) {
-                        source_map.insert(ns_key.clone(), (abs.clone(), line));
+                    if let Some(&(start, end)) = line_numbers.get(&bare_key) {
+                        source_map.insert(ns_key.clone(), (abs.clone(), start, end));
                     }
                 }
                 graph.insert(ns_key, ns_edges);
@@ -192,6 +381,55 @@ impl CrawlerRouter {
 
         (graph, source_map)
     }
+
+    /// Crawl one or more directories into their import graph: one node per source file,
+    /// an edge for every `use` / `mod` / `import` / `require` / `#include`
+    /// that resolves to another crawled file (see [`super::imports`]).
+    /// Walks the same files as [`CrawlerRouter::crawl`], with the same
+    /// `exclude`, `follow_links` and `respect_gitignore` handling.
+    pub fn crawl_imports(
+        roots: &[&str],
+        exclude: &[String],
+        follow_links: bool,
+        respect_gitignore: bool,
+    ) -> (CallGraph, SourceMap) {
+        Self::crawl_imports_counting(
+            roots,
+            exclude,
+            follow_links,
+            respect_gitignore,
+            &AtomicUsize::new(0),
+        )
+    }
+
+    /// [`CrawlerRouter::crawl_imports`] that bumps `files_read` after each file.
+    pub fn crawl_imports_counting(
+        roots: &[&str],
+        exclude: &[String],
+        follow_links: bool,
+        respect_gitignore: bool,
+        files_read: &AtomicUsize,
+    ) -> (CallGraph, SourceMap) {
+        let sources: Vec<(String, String, String)> =
+            walk_roots(roots, exclude, follow_links, respect_gitignore)
+                .into_iter()
+                .inspect(|_| {
+                    files_read.fetch_add(1, Ordering::Relaxed);
+                })
+                .filter_map(|(rel, path)| match std::fs::read_to_string(&path) {
+                    Ok(code) => Some((
+                        rel.replace('\\', "/"),
+                        path.to_string_lossy().into_owned(),
+                        code,
+                    )),
+                    Err(e) => {
+                        warn!("[CRAWL] Could not read {}: {}", path.display(), e);
+                        None
+                    }
+                })
+                .collect();
+        import_graph(&sources)
+    }
 }
 
 #[cfg(test)]
@@ -201,13 +439,20 @@ mod tests {
 
     #[test]
     fn crawl_nonexistent_returns_empty() {
-        let (g, _) = CrawlerRouter::crawl("/nonexistent/path/12345", false);
+        let (g, _) = CrawlerRouter::crawl(
+            &["/nonexistent/path/12345"],
+            false,
+            &HashSet::new(),
+            &[],
+            false,
+            false,
+        );
         assert!(g.is_empty());
     }
 
     #[test]
     fn crawl_empty_string_returns_empty() {
-        let (g, _) = CrawlerRouter::crawl("", false);
+        let (g, _) = CrawlerRouter::crawl(&[""], false, &HashSet::new(), &[], false, false);
         assert!(g.is_empty());
     }
 
@@ -226,7 +471,14 @@ pub fn public_api() { helper(); }
         .unwrap();
         fs::write(dir_path.join("other.py"), "def foo(): pass").unwrap();
 
-        let (g, src) = CrawlerRouter::crawl(dir_path.to_str().unwrap(), false);
+        let (g, src) = CrawlerRouter::crawl(
+            &[dir_path.to_str().unwrap()],
+            false,
+            &HashSet::new(),
+            &[],
+            false,
+            false,
+        );
         // Keys are now namespaced as `relative_path::function_name`.
         assert!(g.contains_key("mod.rs::public_api"), "expected mod.rs::public_api in {:?}", g.keys().collect::<Vec<_>>());
         assert!(g.contains_key("mod.rs::helper"));
@@ -254,11 +506,249 @@ def foo():
         )
         .unwrap();
 
-        let (g, _src) = CrawlerRouter::crawl(dir_path.to_str().unwrap(), false);
+        let (g, _src) = CrawlerRouter::crawl(
+            &[dir_path.to_str().unwrap()],
+            false,
+            &HashSet::new(),
+            &[],
+            false,
+            false,
+        );
         assert!(g.contains_key("main.py::foo"), "expected main.py::foo in {:?}", g.keys().collect::<Vec<_>>());
         assert!(g.contains_key("main.py::bar"));
         let foo_edges = g.get("main.py::foo").unwrap();
         assert_eq!(foo_edges.len(), 1);
         assert_eq!(foo_edges[0].target, "main.py::bar");
     }
+
+    #[test]
+    fn crawl_directory_with_go_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path();
+
+        fs::write(
+            dir_path.join("main.go"),
+            r#"
+package main
+
+func foo() {}
+
+func main() {
+	foo()
+}
+"#,
+        )
+        .unwrap();
+
+        let (g, src) = CrawlerRouter::crawl(
+            &[dir_path.to_str().unwrap()],
+            false,
+            &HashSet::new(),
+            &[],
+            false,
+            false,
+        );
+        assert!(g.contains_key("main.go::foo"), "expected main.go::foo in {:?}", g.keys().collect::<Vec<_>>());
+        let main_edges = g.get("main.go::main").unwrap();
+        assert_eq!(main_edges.len(), 1);
+        assert_eq!(main_edges[0].target, "main.go::foo");
+        assert!(src.contains_key("main.go::main"), "source_map missing main");
+    }
+
+    #[test]
+    fn crawl_include_calls_bypasses_builtins_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path();
+
+        fs::write(
+            dir_path.join("lib.rs"),
+            r#"
+fn get() {}
+fn run() { get(); }
+"#,
+        )
+        .unwrap();
+        let root = dir_path.to_str().unwrap();
+
+        let (g, _) = CrawlerRouter::crawl(&[root], false, &HashSet::new(), &[], false, false);
+        assert!(
+            g.get("lib.rs::run").map_or(true, |e| e.is_empty()),
+            "`get` is a builtin and should be filtered by default"
+        );
+
+        let include: HashSet<String> = ["get".to_string()].into_iter().collect();
+        let (g, _) = CrawlerRouter::crawl(&[root], false, &include, &[], false, false);
+        let run_edges = g.get("lib.rs::run").expect("run should be in the graph");
+        assert_eq!(run_edges.len(), 1);
+        assert_eq!(run_edges[0].target, "lib.rs::get");
+    }
+
+    #[test]
+    fn crawl_skips_excluded_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path();
+
+        fs::write(dir_path.join("lib.rs"), "fn kept() {}\n").unwrap();
+        fs::create_dir_all(dir_path.join("target/debug")).unwrap();
+        fs::write(dir_path.join("target/debug/gen.rs"), "fn generated() {}\n").unwrap();
+        fs::create_dir_all(dir_path.join("web/node_modules/pkg")).unwrap();
+        fs::write(dir_path.join("web/node_modules/pkg/index.ts"), "function vendored() {}\n").unwrap();
+        let root = dir_path.to_str().unwrap();
+
+        let (g, _) = CrawlerRouter::crawl(&[root], false, &HashSet::new(), &[], false, false);
+        assert!(g.contains_key("target/debug/gen.rs::generated"));
+
+        let exclude = vec!["target".to_string(), "node_modules".to_string()];
+        let (g, _) = CrawlerRouter::crawl(&[root], false, &HashSet::new(), &exclude, false, false);
+        assert!(g.contains_key("lib.rs::kept"));
+        assert!(
+            g.keys().all(|k| !k.contains("generated") && !k.contains("vendored")),
+            "excluded functions leaked into {:?}",
+            g.keys().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn crawl_respects_gitignore_only_when_asked() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path();
+        fs::write(
+            dir_path.join(".gitignore"),
+            "# output\n*.gen.rs\n!keep.gen.rs\nbuild/\n/top.rs\n",
+        )
+        .unwrap();
+        fs::write(dir_path.join("lib.rs"), "fn kept() {}\n").unwrap();
+        fs::write(dir_path.join("a.gen.rs"), "fn generated() {}\n").unwrap();
+        fs::write(dir_path.join("keep.gen.rs"), "fn kept_gen() {}\n").unwrap();
+        fs::write(dir_path.join("top.rs"), "fn top_generated() {}\n").unwrap();
+        fs::create_dir_all(dir_path.join("build")).unwrap();
+        fs::write(dir_path.join("build/out.rs"), "fn built() {}\n").unwrap();
+        fs::create_dir_all(dir_path.join("src/gen")).unwrap();
+        fs::write(dir_path.join("src/top.rs"), "fn nested_top() {}\n").unwrap();
+        fs::write(dir_path.join("src/.ignore"), "gen/\n").unwrap();
+        fs::write(dir_path.join("src/gen/x.rs"), "fn generated_nested() {}\n").unwrap();
+        let root = dir_path.to_str().unwrap();
+
+        let (g, _) = CrawlerRouter::crawl(&[root], false, &HashSet::new(), &[], false, false);
+        assert!(g.contains_key("a.gen.rs::generated"));
+
+        let (g, _) = CrawlerRouter::crawl(&[root], false, &HashSet::new(), &[], false, true);
+        assert!(g.contains_key("lib.rs::kept"));
+        assert!(g.contains_key("keep.gen.rs::kept_gen"), "negated");
+        assert!(g.contains_key("src/top.rs::nested_top"), "/top.rs is anchored");
+        assert!(
+            g.keys().all(|k| !k.contains("generated") && !k.contains("built")),
+            "ignored functions leaked into {:?}",
+            g.keys().collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn crawl_follows_symlinked_directories_only_when_asked() {
+        let shared = tempfile::tempdir().unwrap();
+        fs::write(shared.path().join("util.rs"), "fn shared_helper() {}\n").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path();
+        fs::write(dir_path.join("lib.rs"), "fn kept() {}\n").unwrap();
+        std::os::unix::fs::symlink(shared.path(), dir_path.join("shared")).unwrap();
+        // A link back to the root must not be walked forever.
+        std::os::unix::fs::symlink(dir_path, dir_path.join("again")).unwrap();
+        let root = dir_path.to_str().unwrap();
+
+        let (g, _) = CrawlerRouter::crawl(&[root], false, &HashSet::new(), &[], false, false);
+        assert!(g.contains_key("lib.rs::kept"));
+        assert!(!g.contains_key("shared/util.rs::shared_helper"));
+
+        let (g, _) = CrawlerRouter::crawl(&[root], false, &HashSet::new(), &[], true, false);
+        assert!(g.contains_key("shared/util.rs::shared_helper"));
+        assert!(
+            g.keys().all(|k| !k.starts_with("again/")),
+            "the root was crawled twice: {:?}",
+            g.keys().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn crawl_merges_several_roots_under_their_names() {
+        let base = tempfile::tempdir().unwrap();
+        let (front, back) = (base.path().join("frontend"), base.path().join("backend"));
+        fs::create_dir_all(&front).unwrap();
+        fs::create_dir_all(&back).unwrap();
+        fs::write(front.join("app.py"), "def main():\n    serve()\n").unwrap();
+        fs::write(back.join("app.py"), "def serve():\n    pass\n").unwrap();
+        let roots = [front.to_str().unwrap(), back.to_str().unwrap()];
+
+        let (g, src) = CrawlerRouter::crawl(&roots, false, &HashSet::new(), &[], false, false);
+        assert!(g.contains_key("backend/app.py::serve"), "{:?}", g.keys().collect::<Vec<_>>());
+        let targets: Vec<&str> = g["frontend/app.py::main"]
+            .iter()
+            .map(|e| e.target.as_str())
+            .collect();
+        assert_eq!(targets, ["backend/app.py::serve"], "calls resolve across roots");
+        assert!(src["backend/app.py::serve"].0.ends_with("app.py"));
+        let (_, start, end) = &src["backend/app.py::serve"];
+        assert_eq!((*start, *end), (1, 2), "source map carries the function's line span");
+
+        assert_eq!(root_prefixes(&["a/src", "b/src"]), ["src/", "src~2/"]);
+        assert_eq!(root_prefixes(&["a/src"]), [""]);
+    }
+
+    #[test]
+    fn crawl_single_file_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("main_test.rs");
+        fs::write(&file, "fn main() { helper(); }\nfn helper() {}\n").unwrap();
+        fs::write(dir.path().join("other.rs"), "fn unrelated() {}\n").unwrap();
+
+        let crawl = |path: &Path| {
+            CrawlerRouter::crawl(&[path.to_str().unwrap()], false, &HashSet::new(), &[], false, false)
+        };
+        let (g, src) = crawl(&file);
+        let mut keys: Vec<&String> = g.keys().collect();
+        keys.sort();
+        // Only that file, and not skipped for its test-like name.
+        assert_eq!(keys, ["main_test.rs::helper", "main_test.rs::main"]);
+        assert!(g["main_test.rs::main"]
+            .iter()
+            .any(|e| e.target == "main_test.rs::helper"));
+        assert_eq!(src["main_test.rs::main"].0, file.to_string_lossy());
+
+        let notes = dir.path().join("notes.txt");
+        fs::write(&notes, "fn not_code() {}\n").unwrap();
+        assert!(crawl(&notes).0.is_empty(), "unsupported files are skipped");
+    }
+
+    #[test]
+    fn crawl_imports_links_files_and_honours_exclude() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path();
+        fs::create_dir_all(dir_path.join("core")).unwrap();
+        fs::create_dir_all(dir_path.join("vendor")).unwrap();
+        fs::write(dir_path.join("lib.rs"), "mod core;
+use crate::core::config::Config;
+").unwrap();
+        fs::write(dir_path.join("core/mod.rs"), "pub mod config;
+").unwrap();
+        fs::write(dir_path.join("core/config.rs"), "pub struct Config;
+").unwrap();
+        fs::write(dir_path.join("vendor/dep.rs"), "use crate::core::config;
+").unwrap();
+        let root = dir_path.to_str().unwrap();
+
+        let (g, src) = CrawlerRouter::crawl_imports(&[root], &["vendor".into()], false, false);
+        let targets = |id: &str| {
+            let mut t: Vec<&str> = g[id].iter().map(|e| e.target.as_str()).collect();
+            t.sort();
+            t
+        };
+        assert_eq!(g.len(), 3, "every file is a node: {:?}", g.keys().collect::<Vec<_>>());
+        assert_eq!(
+            targets("lib.rs::lib.rs"),
+            ["core/config.rs::core/config.rs", "core/mod.rs::core/mod.rs"]
+        );
+        assert_eq!(targets("core/mod.rs::core/mod.rs"), ["core/config.rs::core/config.rs"]);
+        assert!(targets("core/config.rs::core/config.rs").is_empty());
+        assert_eq!(src["lib.rs::lib.rs"].1, 1);
+    }
 }
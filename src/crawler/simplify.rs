@@ -0,0 +1,257 @@
+//! `:simplify` — collapse linear chains of decision nodes into one node.
+//!
+//! Works on a `CanvasSnapshot` of the live canvas so the whole operation is a
+//! single `Action::ReplaceCanvas` checkpoint that `u` reverts in one step.
+
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::core::history::{Action, UndoHistory};
+use crate::io::file_io::{
+    snapshot_canvas, CanvasSnapshot, PendingCanvasRestore, SnapshotEdgeQuery, SnapshotNodeQuery,
+};
+
+/// Message sent by `:simplify`.
+#[derive(Message)]
+pub struct SimplifyRequest;
+
+/// Merge every chain `a → b → …` of decision nodes where each link is the
+/// only outgoing edge of its source and the only incoming edge of its target.
+/// The chain head keeps its position and takes the joined conditions as text
+/// (one per line, prefixed with the branch label that led to it); the tail's
+/// outgoing edges are re-linked to the head. Returns the simplified snapshot
+/// and the number of nodes merged away.
+pub fn simplify_snapshot(snapshot: &CanvasSnapshot) -> (CanvasSnapshot, usize) {
+    let decisions: HashSet<u64> = snapshot
+        .nodes
+        .iter()
+        .filter(|n| n.decision)
+        .map(|n| n.id)
+        .collect();
+
+    let mut out_edges: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut in_edges: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (i, e) in snapshot.edges.iter().enumerate() {
+        out_edges.entry(e.source_id).or_default().push(i);
+        in_edges.entry(e.target_id).or_default().push(i);
+    }
+
+    // a → (b, edge index) for every mergeable link.
+    let mut next: HashMap<u64, (u64, usize)> = HashMap::new();
+    for (i, e) in snapshot.edges.iter().enumerate() {
+        let (a, b) = (e.source_id, e.target_id);
+        if a != b
+            && decisions.contains(&a)
+            && decisions.contains(&b)
+            && out_edges.get(&a).map_or(0, Vec::len) == 1
+            && in_edges.get(&b).map_or(0, Vec::len) == 1
+        {
+            next.insert(a, (b, i));
+        }
+    }
+    let has_prev: HashSet<u64> = next.values().map(|(b, _)| *b).collect();
+
+    let mut texts: HashMap<u64, String> = snapshot
+        .nodes
+        .iter()
+        .map(|n| (n.id, n.text.clone()))
+        .collect();
+    let mut removed_nodes: HashSet<u64> = HashSet::new();
+    let mut removed_edges: HashSet<usize> = HashSet::new();
+    // Merged node id → chain head that absorbed it.
+    let mut merged_into: HashMap<u64, u64> = HashMap::new();
+
+    // Chain heads have a mergeable successor but no mergeable predecessor;
+    // pure cycles have no head and are left alone.
+    let mut heads: Vec<u64> = next.keys().copied().filter(|a| !has_prev.contains(a)).collect();
+    heads.sort_unstable();
+    for head in heads {
+        let mut text = texts.get(&head).cloned().unwrap_or_default();
+        let mut cur = head;
+        while let Some(&(b, edge_idx)) = next.get(&cur) {
+            let b_text = texts.remove(&b).unwrap_or_default();
+            match &snapshot.edges[edge_idx].label {
+                Some(label) if !label.is_empty() => {
+                    text = format!("{}\n{}: {}", text, label, b_text)
+                }
+                _ => text = format!("{}\n{}", text, b_text),
+            }
+            removed_edges.insert(edge_idx);
+            removed_nodes.insert(b);
+            merged_into.insert(b, head);
+            cur = b;
+        }
+        texts.insert(head, text);
+    }
+
+    let nodes = snapshot
+        .nodes
+        .iter()
+        .filter(|n| !removed_nodes.contains(&n.id))
+        .map(|n| {
+            let mut n = n.clone();
+            if let Some(t) = texts.get(&n.id) {
+                n.text = t.clone();
+            }
+            n
+        })
+        .collect();
+    let edges = snapshot
+        .edges
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !removed_edges.contains(i))
+        .map(|(_, e)| {
+            let mut e = e.clone();
+            if let Some(&head) = merged_into.get(&e.source_id) {
+                e.source_id = head;
+            }
+            e
+        })
+        .collect();
+
+    let merged = removed_nodes.len();
+    (
+        CanvasSnapshot {
+            nodes,
+            edges,
+            camera: snapshot.camera.clone(),
+            marks: snapshot.marks.clone(),
+        },
+        merged,
+    )
+}
+
+/// Handles `SimplifyRequest`: snapshot the canvas, merge decision chains,
+/// push a `ReplaceCanvas` checkpoint and queue the simplified canvas.
+pub fn handle_simplify_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<SimplifyRequest>,
+    mut history: ResMut<UndoHistory>,
+    mut status: ResMut<crate::core::resources::StatusMessage>,
+    node_query: SnapshotNodeQuery,
+    edge_query: SnapshotEdgeQuery,
+) {
+    if requests.read().count() == 0 {
+        return;
+    }
+
+    let before = snapshot_canvas(node_query.iter(), edge_query.iter(), None);
+    let (after, merged) = simplify_snapshot(&before);
+    if merged == 0 {
+        status.set("simplify: no decision chains to merge");
+        return;
+    }
+
+    info!("[SIMPLIFY] Merged {} decision nodes", merged);
+    status.set(format!("Simplified: merged {} decision nodes", merged));
+    commands.insert_resource(PendingCanvasRestore(Some(after.clone())));
+    history.push(Action::ReplaceCanvas { before, after });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::file_io::{SerializableEdge, SerializableNode, SerializedColor};
+    use std::collections::BTreeMap;
+
+    fn node(id: u64, text: &str, decision: bool) -> SerializableNode {
+        SerializableNode {
+            id,
+            x: 0.0,
+            y: -(id as f32) * 100.0,
+            text: text.to_string(),
+            color: SerializedColor {
+                r: 0.5,
+                g: 0.5,
+                b: 0.5,
+            },
+            source: None,
+            decision,
+            shape: Default::default(),
+            size: None,
+            pinned: false,
+        }
+    }
+
+    fn edge(source_id: u64, target_id: u64, label: Option<&str>) -> SerializableEdge {
+        SerializableEdge {
+            source_id,
+            target_id,
+            label: label.map(str::to_string),
+            stroke: Default::default(),
+            curve: None,
+            reversed: false,
+        }
+    }
+
+    #[test]
+    fn merges_linear_decision_chain() {
+        // f → if a → (true) for x → g
+        let snap = CanvasSnapshot {
+            nodes: vec![
+                node(0, "f", false),
+                node(1, "if a", true),
+                node(2, "for x", true),
+                node(3, "g", false),
+            ],
+            edges: vec![
+                edge(0, 1, None),
+                edge(1, 2, Some("true")),
+                edge(2, 3, None),
+            ],
+            camera: None,
+            marks: BTreeMap::new(),
+        };
+        let (out, merged) = simplify_snapshot(&snap);
+        assert_eq!(merged, 1);
+        assert_eq!(out.nodes.len(), 3);
+        let head = out.nodes.iter().find(|n| n.id == 1).unwrap();
+        assert_eq!(head.text, "if a\ntrue: for x");
+        // for x → g is re-linked to the merged head.
+        assert!(out.edges.iter().any(|e| e.source_id == 1 && e.target_id == 3));
+        assert!(out.edges.iter().any(|e| e.source_id == 0 && e.target_id == 1));
+        assert_eq!(out.edges.len(), 2);
+    }
+
+    #[test]
+    fn branching_decision_is_not_merged() {
+        // if a has two outgoing branches; neither may be absorbed.
+        let snap = CanvasSnapshot {
+            nodes: vec![node(0, "if a", true), node(1, "if b", true), node(2, "if c", true)],
+            edges: vec![edge(0, 1, Some("true")), edge(0, 2, Some("false"))],
+            camera: None,
+            marks: BTreeMap::new(),
+        };
+        let (out, merged) = simplify_snapshot(&snap);
+        assert_eq!(merged, 0);
+        assert_eq!(out.nodes.len(), 3);
+        assert_eq!(out.edges.len(), 2);
+    }
+
+    #[test]
+    fn function_nodes_are_never_merged() {
+        let snap = CanvasSnapshot {
+            nodes: vec![node(0, "f", false), node(1, "g", false)],
+            edges: vec![edge(0, 1, None)],
+            camera: None,
+            marks: BTreeMap::new(),
+        };
+        assert_eq!(simplify_snapshot(&snap).1, 0);
+    }
+
+    #[test]
+    fn long_chain_collapses_into_head() {
+        let snap = CanvasSnapshot {
+            nodes: vec![node(0, "a", true), node(1, "b", true), node(2, "c", true)],
+            edges: vec![edge(0, 1, None), edge(1, 2, None)],
+            camera: None,
+            marks: BTreeMap::new(),
+        };
+        let (out, merged) = simplify_snapshot(&snap);
+        assert_eq!(merged, 2);
+        assert_eq!(out.nodes.len(), 1);
+        assert_eq!(out.nodes[0].text, "a\nb\nc");
+        assert!(out.edges.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::core::components::{CanvasNode, Edge, TracedPath};
+use crate::core::components::{CanvasNode, Edge, InactiveCanvas, TracedPath};
 use bevy::prelude::*;
 use std::collections::{HashMap, HashSet};
 
@@ -74,12 +74,19 @@ pub fn find_traced_paths(
     result_set
 }
 
+type TraceNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static crate::core::components::TextData),
+    (With<CanvasNode>, Without<InactiveCanvas>),
+>;
+
 /// System to handle `:trace flow` commands.
 pub fn handle_trace_requests(
     mut commands: Commands,
     mut trace_events: MessageReader<TraceRequest>,
     mut status: ResMut<crate::core::resources::StatusMessage>,
-    node_query: Query<(Entity, &crate::core::components::TextData), With<CanvasNode>>,
+    node_query: TraceNodeQuery,
     edge_query: Query<(Entity, &Edge)>,
     traced_query: Query<Entity, With<TracedPath>>,
 ) {
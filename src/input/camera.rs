@@ -3,27 +3,60 @@
 use bevy::prelude::*;
 
 use crate::core::components::{MainCamera, Selected};
+use crate::core::config::GlyphConfig;
+use crate::core::helpers::NODE_SIZE;
 use crate::core::state::InputMode;
 
-/// Scroll-wheel zoom: adjusts the orthographic scale of the main camera.
+/// Camera centre after zooming from `old_scale` to `new_scale` so that the
+/// world point `anchor` stays at the same spot on screen.
+pub fn zoom_toward(camera: Vec2, anchor: Vec2, old_scale: f32, new_scale: f32) -> Vec2 {
+    anchor - (anchor - camera) * (new_scale / old_scale)
+}
+
+/// Scroll-wheel zoom: adjusts the orthographic scale of the main camera,
+/// keeping the world point under the mouse cursor in place (centre zoom
+/// when the cursor is outside the window).
 /// Pinch/scroll in  → scale decreases (zoom in, things appear larger).
 /// Pinch/scroll out → scale increases (zoom out, things appear smaller).
 pub fn camera_zoom_system(
     mut mouse_wheel: MessageReader<bevy::input::mouse::MouseWheel>,
-    mut proj_q: Query<&mut Projection, With<MainCamera>>,
+    window_q: Query<&Window, With<bevy::window::PrimaryWindow>>,
+    mut camera_q: Query<
+        (&Camera, &GlobalTransform, &mut Transform, &mut Projection),
+        With<MainCamera>,
+    >,
 ) {
-    let Ok(mut proj) = proj_q.single_mut() else {
-        return;
-    };
-    for event in mouse_wheel.read() {
-        let Projection::Orthographic(ortho) = proj.as_mut() else {
-            continue;
-        };
-        let delta = match event.unit {
+    let deltas: Vec<f32> = mouse_wheel
+        .read()
+        .map(|event| match event.unit {
             bevy::input::mouse::MouseScrollUnit::Line => event.y * 0.10,
             bevy::input::mouse::MouseScrollUnit::Pixel => event.y * 0.001,
-        };
+        })
+        .collect();
+    if deltas.is_empty() {
+        return;
+    }
+    let Ok((camera, cam_global, mut cam_transform, mut proj)) = camera_q.single_mut() else {
+        return;
+    };
+    let Projection::Orthographic(ortho) = proj.as_mut() else {
+        return;
+    };
+    // Every step keeps this same world point fixed.
+    let anchor = window_q
+        .single()
+        .ok()
+        .and_then(|w| w.cursor_position())
+        .and_then(|cursor| camera.viewport_to_world_2d(cam_global, cursor).ok());
+    for delta in deltas {
+        let old_scale = ortho.scale;
         ortho.scale = (ortho.scale * (1.0 - delta)).clamp(0.1, 10.0);
+        if let Some(anchor) = anchor {
+            let centre = cam_transform.translation.truncate();
+            let moved = zoom_toward(centre, anchor, old_scale, ortho.scale);
+            cam_transform.translation.x = moved.x;
+            cam_transform.translation.y = moved.y;
+        }
     }
 }
 
@@ -127,6 +160,75 @@ pub fn camera_pan_keys_system(
     cam_transform.translation.y += dy * delta;
 }
 
+/// How long an eased camera jump takes (seconds).
+const CAMERA_TWEEN_SECS: f32 = 0.2;
+
+/// An eased camera move in flight, started by [`request_camera_move`].
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct CameraTween {
+    pub from: Vec2,
+    pub to: Vec2,
+    pub elapsed: f32,
+    pub dur: f32,
+}
+
+impl CameraTween {
+    /// Camera centre `elapsed` seconds in, eased out: quick start, soft landing.
+    pub fn position(&self) -> Vec2 {
+        let t = (self.elapsed / self.dur.max(f32::EPSILON)).clamp(0.0, 1.0);
+        self.from.lerp(self.to, 1.0 - (1.0 - t).powi(3))
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.elapsed >= self.dur
+    }
+}
+
+/// Centre the camera on `to`. With `smooth_camera` on this starts a
+/// [`CameraTween`] from where the camera is now, so a jump during a tween
+/// simply retargets it; otherwise the camera cuts there at once. Every jump
+/// (marks, `gg` / `G`, `n` / `N`, the jump list, the finder) goes through here.
+pub fn request_camera_move(
+    commands: &mut Commands,
+    config: &GlyphConfig,
+    camera: &mut Transform,
+    to: Vec2,
+) {
+    if config.smooth_camera {
+        commands.insert_resource(CameraTween {
+            from: camera.translation.truncate(),
+            to,
+            elapsed: 0.0,
+            dur: CAMERA_TWEEN_SECS,
+        });
+    } else {
+        commands.remove_resource::<CameraTween>();
+        camera.translation.x = to.x;
+        camera.translation.y = to.y;
+    }
+}
+
+/// Advance the running [`CameraTween`], if any, and drop it once it lands.
+pub fn camera_tween_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    tween: Option<ResMut<CameraTween>>,
+    mut camera_q: Query<&mut Transform, With<MainCamera>>,
+) {
+    let Some(mut tween) = tween else {
+        return;
+    };
+    tween.elapsed += time.delta_secs();
+    if let Ok(mut cam_transform) = camera_q.single_mut() {
+        let pos = tween.position();
+        cam_transform.translation.x = pos.x;
+        cam_transform.translation.y = pos.y;
+    }
+    if tween.is_done() {
+        commands.remove_resource::<CameraTween>();
+    }
+}
+
 /// Computes the world-space AABB of the visible viewport from the main camera.
 pub fn viewport_world_bounds(
     camera: &Camera,
@@ -156,3 +258,109 @@ pub fn viewport_world_bounds(
 
     (min_x, max_x, min_y, max_y)
 }
+
+/// World-space margin kept around the content when fitting the camera.
+const FIT_PADDING: f32 = 80.0;
+
+/// Camera centre and orthographic scale that frame every node centre in
+/// `points` inside a viewport of `viewport_size` logical pixels.
+/// Never zooms in past 1:1. Returns `None` when there is nothing to frame.
+pub fn fit_to_points(points: &[Vec2], viewport_size: Vec2) -> Option<(Vec2, f32)> {
+    let first = *points.first()?;
+    let (min, max) = points
+        .iter()
+        .fold((first, first), |(lo, hi), p| (lo.min(*p), hi.max(*p)));
+    let margin = NODE_SIZE * 0.5 + Vec2::splat(FIT_PADDING);
+    let (min, max) = (min - margin, max + margin);
+    let extent = max - min;
+    let scale = (extent.x / viewport_size.x.max(1.0)).max(extent.y / viewport_size.y.max(1.0));
+    Some(((min + max) * 0.5, scale.clamp(1.0, 10.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jump(world: &mut World, to: Vec2) {
+        use bevy::ecs::system::RunSystemOnce;
+        world
+            .run_system_once(
+                move |mut commands: Commands,
+                      config: Res<GlyphConfig>,
+                      mut camera_q: Query<&mut Transform, With<MainCamera>>| {
+                    let mut cam = camera_q.single_mut().unwrap();
+                    request_camera_move(&mut commands, &config, &mut cam, to);
+                },
+            )
+            .unwrap();
+    }
+
+    fn step(world: &mut World, secs: f32) -> Vec2 {
+        use bevy::ecs::system::RunSystemOnce;
+        world
+            .resource_mut::<Time>()
+            .advance_by(std::time::Duration::from_secs_f32(secs));
+        world.run_system_once(camera_tween_system).unwrap();
+        let mut cams = world.query_filtered::<&Transform, With<MainCamera>>();
+        cams.single(world).unwrap().translation.truncate()
+    }
+
+    #[test]
+    fn jumps_ease_retarget_and_cut_when_smoothing_is_off() {
+        let mut world = World::new();
+        world.init_resource::<GlyphConfig>();
+        world.init_resource::<Time>();
+        world.spawn((MainCamera, Transform::default()));
+
+        jump(&mut world, Vec2::new(100.0, 0.0));
+        let halfway = step(&mut world, CAMERA_TWEEN_SECS * 0.5);
+        assert!(halfway.x > 50.0 && halfway.x < 100.0, "eased out: {:?}", halfway);
+
+        // A second jump mid-tween starts from where the camera is now.
+        jump(&mut world, Vec2::ZERO);
+        assert_eq!(world.resource::<CameraTween>().from, halfway);
+        assert_eq!(step(&mut world, CAMERA_TWEEN_SECS), Vec2::ZERO);
+        assert!(!world.contains_resource::<CameraTween>());
+
+        world.resource_mut::<GlyphConfig>().smooth_camera = false;
+        jump(&mut world, Vec2::new(-40.0, 25.0));
+        assert!(!world.contains_resource::<CameraTween>());
+        let mut cams = world.query_filtered::<&Transform, With<MainCamera>>();
+        let pos = cams.single(&world).unwrap().translation.truncate();
+        assert_eq!(pos, Vec2::new(-40.0, 25.0));
+    }
+
+    #[test]
+    fn zoom_keeps_the_point_under_the_cursor() {
+        let (camera, anchor) = (Vec2::new(100.0, 50.0), Vec2::new(300.0, -50.0));
+        // Zooming in 2×: the camera closes half the distance to the anchor.
+        let moved = zoom_toward(camera, anchor, 1.0, 0.5);
+        assert_eq!(moved, Vec2::new(200.0, 0.0));
+        // The anchor's offset from the centre, in screen units, is unchanged.
+        assert_eq!((anchor - moved) / 0.5, (anchor - camera) / 1.0);
+        // A clamped step (no scale change) does not move the camera.
+        assert_eq!(zoom_toward(camera, anchor, 10.0, 10.0), camera);
+    }
+
+    #[test]
+    fn fit_to_points_empty_is_none() {
+        assert!(fit_to_points(&[], Vec2::new(1280.0, 720.0)).is_none());
+    }
+
+    #[test]
+    fn fit_to_points_centres_on_content() {
+        let points = [Vec2::new(1000.0, 500.0), Vec2::new(1400.0, 700.0)];
+        let (centre, scale) = fit_to_points(&points, Vec2::new(1280.0, 720.0)).unwrap();
+        assert_eq!(centre, Vec2::new(1200.0, 600.0));
+        assert_eq!(scale, 1.0, "small content is not zoomed in past 1:1");
+    }
+
+    #[test]
+    fn fit_to_points_zooms_out_for_wide_content() {
+        let points = [Vec2::new(-3000.0, 0.0), Vec2::new(3000.0, 0.0)];
+        let (_, scale) = fit_to_points(&points, Vec2::new(1280.0, 720.0)).unwrap();
+        // 6000 wide + node width + padding must fit in 1280 px.
+        assert!(scale > 6000.0 / 1280.0);
+        assert!(scale <= 10.0);
+    }
+}
@@ -0,0 +1,218 @@
+//! `:connect`: keyboard-only edge creation for targets easymotion can't
+//! reach. `:connect 'a 'b` links the nodes nearest marks `a` and `b`;
+//! `:connect <label>` links the selected node to the node whose text is
+//! exactly `<label>`. The edge is recorded as an undoable `CreateEdge`.
+
+use bevy::prelude::*;
+
+use crate::core::components::{
+    CanvasNode, CollapseHidden, Edge, InactiveCanvas, Selected, TextData,
+};
+use crate::core::history::{Action, UndoHistory};
+use crate::core::marks::{get_mark, Marks};
+use crate::core::resources::StatusMessage;
+
+/// What `:connect` links.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnectTarget {
+    /// `:connect 'a 'b`: nearest node to mark `a` → nearest node to mark `b`.
+    Marks(char, char),
+    /// `:connect <label>`: selected node → node with exactly this text.
+    Label(String),
+}
+
+impl ConnectTarget {
+    /// Parse the `:connect` argument. Two `'x` words are marks; anything else
+    /// is a label.
+    pub fn parse(arg: &str) -> Option<Self> {
+        let mark = |word: &str| {
+            let mut chars = word.strip_prefix('\'')?.chars();
+            let ch = chars.next()?;
+            chars.next().is_none().then_some(ch)
+        };
+        let words: Vec<&str> = arg.split_whitespace().collect();
+        if let [a, b] = words[..] {
+            if let (Some(a), Some(b)) = (mark(a), mark(b)) {
+                return Some(Self::Marks(a, b));
+            }
+        }
+        (!arg.is_empty()).then(|| Self::Label(arg.to_string()))
+    }
+}
+
+/// Message sent by `:connect`.
+#[derive(Message)]
+pub struct ConnectRequest(pub ConnectTarget);
+
+/// The node closest to `pos`, if any.
+pub fn nearest_node(nodes: impl IntoIterator<Item = (Entity, Vec2)>, pos: Vec2) -> Option<Entity> {
+    nodes
+        .into_iter()
+        .min_by(|(_, a), (_, b)| a.distance_squared(pos).total_cmp(&b.distance_squared(pos)))
+        .map(|(entity, _)| entity)
+}
+
+type ConnectNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Transform, &'static TextData, Has<Selected>),
+    (
+        With<CanvasNode>,
+        Without<InactiveCanvas>,
+        Without<CollapseHidden>,
+    ),
+>;
+
+/// Source and target of `target` on the visible canvas, or the status error.
+fn resolve(
+    target: &ConnectTarget,
+    nodes: &ConnectNodeQuery,
+    marks: &Marks,
+) -> Result<(Entity, Entity), String> {
+    let positions = || nodes.iter().map(|(e, t, ..)| (e, t.translation.truncate()));
+    match target {
+        ConnectTarget::Marks(a, b) => {
+            let at_mark = |ch: char| {
+                let pos = get_mark(marks, ch).ok_or(format!("mark '{}' is not set", ch))?;
+                nearest_node(positions(), pos).ok_or("no nodes on the canvas".to_string())
+            };
+            Ok((at_mark(*a)?, at_mark(*b)?))
+        }
+        ConnectTarget::Label(label) => {
+            let (source, source_tf, ..) = nodes
+                .iter()
+                .find(|(.., selected)| *selected)
+                .ok_or(":connect <label> needs a selected node")?;
+            // Several nodes can share a label; take the one closest to the source.
+            let matches = nodes
+                .iter()
+                .filter(|(e, _, text, _)| *e != source && text.content == *label)
+                .map(|(e, t, ..)| (e, t.translation.truncate()));
+            let target = nearest_node(matches, source_tf.translation.truncate())
+                .ok_or(format!("no node labelled '{}'", label))?;
+            Ok((source, target))
+        }
+    }
+}
+
+/// Handles `ConnectRequest`: spawn the edge and record it for undo.
+pub fn handle_connect_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<ConnectRequest>,
+    nodes: ConnectNodeQuery,
+    marks: Res<Marks>,
+    mut history: ResMut<UndoHistory>,
+    mut status: ResMut<StatusMessage>,
+) {
+    for ConnectRequest(target) in requests.read() {
+        let (source, target) = match resolve(target, &nodes, &marks) {
+            Ok(pair) => pair,
+            Err(e) => {
+                status.set(format!("error: {}", e));
+                continue;
+            }
+        };
+        if source == target {
+            status.set("error: both marks are nearest the same node");
+            continue;
+        }
+        let entity = commands
+            .spawn(Edge {
+                source,
+                target,
+                label: None,
+            })
+            .id();
+        history.push(Action::CreateEdge {
+            entity,
+            source,
+            target,
+            label: None,
+        });
+        let text = |e: Entity| {
+            nodes
+                .get(e)
+                .map(|(_, _, t, _)| t.content.clone())
+                .unwrap_or_default()
+        };
+        status.set(format!("Connected '{}' → '{}'", text(source), text(target)));
+        info!("[CONNECT] {:?} → {:?}", source, target);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn connect_parses_marks_or_a_label() {
+        assert_eq!(
+            ConnectTarget::parse("'a 'b"),
+            Some(ConnectTarget::Marks('a', 'b'))
+        );
+        assert_eq!(
+            ConnectTarget::parse("parse config"),
+            Some(ConnectTarget::Label("parse config".into()))
+        );
+        assert_eq!(
+            ConnectTarget::parse("'ab 'c"),
+            Some(ConnectTarget::Label("'ab 'c".into()))
+        );
+        assert_eq!(ConnectTarget::parse(""), None);
+    }
+
+    #[test]
+    fn connect_links_marked_and_labelled_nodes() {
+        let mut world = World::new();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<Messages<ConnectRequest>>();
+        let mut node = |text: &str, x: f32, selected: bool| {
+            let mut entity = world.spawn((
+                CanvasNode,
+                Transform::from_xyz(x, 0.0, 0.0),
+                TextData {
+                    content: text.to_string(),
+                },
+            ));
+            if selected {
+                entity.insert(Selected);
+            }
+            entity.id()
+        };
+        let main = node("main", 0.0, true);
+        let near = node("run", 100.0, false);
+        let far = node("run", 5000.0, false);
+        let mut marks = Marks::default();
+        crate::core::marks::set_mark(&mut marks, 'a', Vec2::new(4900.0, 30.0));
+        crate::core::marks::set_mark(&mut marks, 'b', Vec2::new(-20.0, 0.0));
+        world.insert_resource(marks);
+
+        world.write_message(ConnectRequest(ConnectTarget::Marks('a', 'b')));
+        world.write_message(ConnectRequest(ConnectTarget::Label("run".into())));
+        world.write_message(ConnectRequest(ConnectTarget::Label("missing".into())));
+        world.write_message(ConnectRequest(ConnectTarget::Marks('a', 'z')));
+        world.run_system_once(handle_connect_requests).unwrap();
+
+        let mut edges: Vec<(Entity, Entity)> = world
+            .query::<&Edge>()
+            .iter(&world)
+            .map(|e| (e.source, e.target))
+            .collect();
+        edges.sort();
+        let mut expected = vec![(far, main), (main, near)];
+        expected.sort();
+        assert_eq!(edges, expected);
+        let history = world.resource::<UndoHistory>();
+        assert_eq!(history.undo_stack.len(), 2);
+        assert!(history
+            .undo_stack
+            .iter()
+            .all(|a| matches!(a, Action::CreateEdge { .. })));
+        assert_eq!(
+            world.resource::<StatusMessage>().text,
+            "error: mark 'z' is not set"
+        );
+    }
+}
@@ -4,28 +4,84 @@ use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 
 use crate::input::camera::viewport_world_bounds;
-use crate::core::components::{CanvasNode, Edge, JumpTag, MainCamera, Selected};
+use crate::core::components::{
+    CanvasNode, Edge, InactiveCanvas, JumpTag, MainCamera, NodeSize, Selected,
+};
+use crate::core::config::GlyphConfig;
 use crate::core::helpers::keycode_to_char;
+use crate::core::jumplist::{JumpEntry, JumpList};
 use crate::input::vim::EasymotionConnectSource;
-use crate::render::edges::edge_label_world_pos;
-use crate::core::resources::{JumpMap, SelectedEdge, SpatialIndex};
+use crate::render::edges::EdgeLabelPlacer;
+use crate::core::resources::{JumpMap, SelectedEdge, SpatialIndex, VisualSelection};
 use crate::core::state::InputMode;
 
-/// What easymotion is targeting: nodes (f, ce) or edges for label edit (ge).
+/// What easymotion is targeting: nodes (f, ce), edges for label edit (ge),
+/// or nodes to toggle in the visual selection (f in VimVisual).
 #[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
 pub enum EasymotionTarget {
     #[default]
     Node,
     EdgeLabel,
+    VisualToggle,
 }
 
 const TAG_CHARS: &str = "abcdefghijklmnopqrstuvwxyz";
-/// Yellow highlight color for easymotion jump labels.
-const TAG_LABEL_COLOR: Color = Color::srgb(1.0, 0.85, 0.1);
+/// Distance from a node's top edge to its jump tag.
+const JUMP_TAG_GAP: f32 = 10.0;
+
+/// Tags for `n` targets in assignment order. Single letters while they
+/// suffice; beyond that the last letters become prefixes of two-letter tags
+/// (`za`, `zb`, …), keeping as many single letters as still leaves room for
+/// all `n`. At most 26 × 26 tags.
+fn jump_tags(n: usize) -> Vec<String> {
+    let letters: Vec<char> = TAG_CHARS.chars().collect();
+    let count = letters.len();
+    let singles = if n <= count {
+        n
+    } else {
+        (count * count).saturating_sub(n) / (count - 1)
+    };
+    let mut tags: Vec<String> = letters[..singles].iter().map(char::to_string).collect();
+    for first in &letters[singles..] {
+        for second in &letters {
+            if tags.len() == n {
+                return tags;
+            }
+            tags.push(format!("{}{}", first, second));
+        }
+    }
+    tags
+}
+
+/// Spawn one jump tag at `pos`, styled from the config (text color, font size
+/// and optional background pill behind the letters).
+fn spawn_jump_tag(commands: &mut Commands, tag_str: &str, pos: Vec3, config: &GlyphConfig) {
+    let font_size = config.jump_tag_font_size;
+    let width = font_size * (0.5 + 0.6 * tag_str.len() as f32);
+    let mut tag = commands.spawn((
+        Text2d::new(tag_str.to_uppercase()),
+        TextFont {
+            font_size,
+            ..default()
+        },
+        TextColor(config.jump_tag_color()),
+        Transform::from_translation(pos),
+        JumpTag(tag_str.to_string()),
+    ));
+    if let Some(bg) = config.jump_tag_background() {
+        tag.with_children(|parent| {
+            parent.spawn((
+                Sprite::from_color(bg, Vec2::new(width, font_size * 1.2)),
+                Transform::from_xyz(0.0, 0.0, -0.1),
+            ));
+        });
+    }
+}
 
 /// Sort order for jump tags: top-to-bottom, then left-to-right.
 /// This makes tag assignment spatially predictable — the top-left node is always
 /// 'a', the next one right is 'b', etc. — so users can build spatial muscle memory.
+/// Two-letter tags go to the targets furthest down.
 fn sort_by_position(a: &Vec2, b: &Vec2) -> std::cmp::Ordering {
     b.y.partial_cmp(&a.y)
         .unwrap_or(std::cmp::Ordering::Equal)
@@ -33,6 +89,7 @@ fn sort_by_position(a: &Vec2, b: &Vec2) -> std::cmp::Ordering {
 }
 
 /// OnEnter(VimEasymotion): assign letter tags to visible nodes or edges based on EasymotionTarget.
+/// More than 26 targets get two-letter tags.
 pub fn jump_tag_setup(
     mut commands: Commands,
     mut jump_map: ResMut<JumpMap>,
@@ -40,9 +97,10 @@ pub fn jump_tag_setup(
     spatial_index: Res<SpatialIndex>,
     camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     window_q: Query<&Window, With<PrimaryWindow>>,
-    transform_query: Query<&Transform, With<CanvasNode>>,
-    edge_query: Query<(Entity, &Edge)>,
-    node_transform_query: Query<&Transform, With<CanvasNode>>,
+    transform_query: Query<(&Transform, Option<&NodeSize>), With<CanvasNode>>,
+    edge_query: Query<(Entity, &Edge), Without<InactiveCanvas>>,
+    labels: EdgeLabelPlacer,
+    config: Res<GlyphConfig>,
 ) {
     let Ok((camera, cam_transform)) = camera_q.single() else {
         return;
@@ -74,14 +132,10 @@ pub fn jump_tag_setup(
         // Collect in-viewport edges with their label world positions.
         let mut visible: Vec<(Entity, Vec2)> = Vec::new();
         for (edge_entity, edge) in &edge_query {
-            let Ok(src) = node_transform_query.get(edge.source) else {
-                continue;
-            };
-            let Ok(tgt) = node_transform_query.get(edge.target) else {
+            let idx = idx_map.get(&edge_entity).copied().unwrap_or(0);
+            let Some((label_pos, _)) = labels.place(edge_entity, edge, idx) else {
                 continue;
             };
-            let idx = idx_map.get(&edge_entity).copied().unwrap_or(0);
-            let (label_pos, _) = edge_label_world_pos(src, tgt, idx);
             if label_pos.x >= min_x
                 && label_pos.x <= max_x
                 && label_pos.y >= min_y
@@ -94,101 +148,96 @@ pub fn jump_tag_setup(
         // Sort for consistent, spatially predictable tag assignment.
         visible.sort_by(|(_, a), (_, b)| sort_by_position(a, b));
 
-        if visible.len() > TAG_CHARS.len() {
+        let tags = jump_tags(visible.len());
+        if visible.len() > tags.len() {
             warn!(
                 "[EASYMOTION] {} visible edges but only {} tags available — zoom in to reach all",
                 visible.len(),
-                TAG_CHARS.len()
+                tags.len()
             );
         }
 
-        for ((edge_entity, label_pos), tag_char) in visible.iter().zip(TAG_CHARS.chars()) {
-            jump_map.char_to_entity.insert(tag_char, *edge_entity);
-            commands.spawn((
-                Text2d::new(tag_char.to_uppercase().to_string()),
-                TextFont {
-                    font_size: 28.0,
-                    ..default()
-                },
-                TextColor(TAG_LABEL_COLOR),
-                Transform::from_translation(label_pos.extend(2.0)),
-                JumpTag,
-            ));
+        for ((edge_entity, label_pos), tag) in visible.iter().zip(&tags) {
+            jump_map.tags.insert(tag.clone(), *edge_entity);
+            spawn_jump_tag(&mut commands, tag, label_pos.extend(2.0), &config);
         }
         info!(
             "[EASYMOTION] Edge tags assigned: {} of {} visible",
-            visible.len().min(TAG_CHARS.len()),
+            tags.len(),
             visible.len()
         );
     } else {
         // Node mode: collect visible nodes with their world positions, then sort.
         // Positions are resolved before the zip so no tag chars are wasted on
         // entities whose transforms happen to be missing.
-        let mut visible: Vec<(Entity, Vec2)> = spatial_index
+        let mut visible: Vec<(Entity, Vec2, f32)> = spatial_index
             .entities_in_bounds(min_x, max_x, min_y, max_y)
             .into_iter()
             .filter_map(|entity| {
-                let Ok(transform) = transform_query.get(entity) else {
+                let Ok((transform, size)) = transform_query.get(entity) else {
                     return None;
                 };
-                Some((entity, transform.translation.truncate()))
+                let half_height = size.copied().unwrap_or_default().current.y * 0.5;
+                Some((entity, transform.translation.truncate(), half_height))
             })
             .collect();
 
         // Consistent top-to-bottom, left-to-right ordering — 'a' is always the
         // top-left visible node regardless of insertion or HashMap iteration order.
-        visible.sort_by(|(_, a), (_, b)| sort_by_position(a, b));
+        visible.sort_by(|(_, a, _), (_, b, _)| sort_by_position(a, b));
 
-        if visible.len() > TAG_CHARS.len() {
+        let tags = jump_tags(visible.len());
+        if visible.len() > tags.len() {
             warn!(
                 "[EASYMOTION] {} visible nodes but only {} tags available — zoom in to reach all",
                 visible.len(),
-                TAG_CHARS.len()
+                tags.len()
             );
         }
 
-        for ((entity, pos), tag_char) in visible.iter().zip(TAG_CHARS.chars()) {
-            jump_map.char_to_entity.insert(tag_char, *entity);
-            // Place tag above the node (node half-height = 60, tag at +70) so it
-            // never overlaps the node's own text. z=2 renders above box and text.
-            let label_pos = Vec3::new(pos.x, pos.y + 70.0, 2.0);
-            commands.spawn((
-                Text2d::new(tag_char.to_uppercase().to_string()),
-                TextFont {
-                    font_size: 28.0,
-                    ..default()
-                },
-                TextColor(TAG_LABEL_COLOR),
-                Transform::from_translation(label_pos),
-                JumpTag,
-            ));
+        for ((entity, pos, half_height), tag) in visible.iter().zip(&tags) {
+            jump_map.tags.insert(tag.clone(), *entity);
+            // Place tag just above the node's top edge so it never overlaps the
+            // node's own text. z=2 renders above box and text.
+            let label_pos = Vec3::new(pos.x, pos.y + half_height + JUMP_TAG_GAP, 2.0);
+            spawn_jump_tag(&mut commands, tag, label_pos, &config);
         }
 
         info!(
             "[EASYMOTION] Node tags assigned: {} of {} visible",
-            visible.len().min(TAG_CHARS.len()),
+            tags.len(),
             visible.len()
         );
     }
 }
 
-/// in_state(VimEasymotion): one keypress selects the tagged target. Esc/Ctrl+[ cancels.
+/// in_state(VimEasymotion): typing a tag selects its target. The first letter of a
+/// two-letter tag hides the tags it rules out. Esc/Ctrl+[ cancels.
 /// Node mode: teleports Selected to node (or creates edge if ce). EdgeLabel mode: sets SelectedEdge, enters VimInsert.
+/// VisualToggle mode: toggles the node in `VisualSelection` and returns to VimVisual.
 pub fn vim_easymotion_system(
     keys: Res<ButtonInput<KeyCode>>,
     mut next_state: ResMut<NextState<InputMode>>,
     mut connect_source: ResMut<EasymotionConnectSource>,
     mut selected_edge: ResMut<SelectedEdge>,
+    mut visual: ResMut<VisualSelection>,
     target: Res<EasymotionTarget>,
-    jump_map: Res<JumpMap>,
+    mut jump_map: ResMut<JumpMap>,
+    mut tag_query: Query<(&JumpTag, &mut Visibility)>,
     mut commands: Commands,
     selected_query: Query<Entity, With<Selected>>,
+    mut jumps: ResMut<JumpList>,
+    camera_query: Query<&Transform, With<MainCamera>>,
 ) {
     let ctrl = crate::core::helpers::ctrl_pressed(&keys);
     if keys.just_pressed(KeyCode::Escape) || (ctrl && keys.just_pressed(KeyCode::BracketLeft)) {
         connect_source.0 = None;
         selected_edge.0 = None;
-        next_state.set(InputMode::VimNormal);
+        if *target == EasymotionTarget::VisualToggle {
+            next_state.set(InputMode::VimVisual);
+        } else {
+            next_state.set(InputMode::VimNormal);
+        }
         info!("[EASYMOTION] cancelled");
         return;
     }
@@ -196,7 +245,14 @@ pub fn vim_easymotion_system(
         let Some(tag_char) = keycode_to_char(key) else {
             continue;
         };
-        let Some(&target_entity) = jump_map.char_to_entity.get(&tag_char) else {
+        let Some(target_entity) = jump_map.type_char(tag_char) else {
+            for (tag, mut visibility) in &mut tag_query {
+                *visibility = if tag.0.starts_with(&jump_map.typed) {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
             continue;
         };
 
@@ -213,6 +269,18 @@ pub fn vim_easymotion_system(
             return;
         }
 
+        if *target == EasymotionTarget::VisualToggle {
+            let added = visual.toggle(target_entity);
+            next_state.set(InputMode::VimVisual);
+            info!(
+                "[EASYMOTION] Visual {} {:?} via '{}'",
+                if added { "added" } else { "removed" },
+                target_entity,
+                tag_char
+            );
+            return;
+        }
+
         // Node mode
         if let Some(source) = connect_source.0.take() {
             if source != target_entity {
@@ -228,6 +296,13 @@ pub fn vim_easymotion_system(
             }
             commands.entity(target_entity).insert(Selected);
         } else {
+            jumps.record(JumpEntry {
+                camera: camera_query
+                    .single()
+                    .map(|t| t.translation.truncate())
+                    .unwrap_or_default(),
+                selected: selected_query.single().ok(),
+            });
             if let Ok(prev) = selected_query.single() {
                 commands.entity(prev).remove::<Selected>();
             }
@@ -251,6 +326,35 @@ pub fn jump_tag_cleanup(
     for entity in &tag_query {
         commands.entity(entity).despawn();
     }
-    jump_map.char_to_entity.clear();
+    jump_map.clear();
     info!("[EASYMOTION] Tags cleaned up");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jump_tags_stay_single_letters_up_to_26() {
+        assert_eq!(jump_tags(3), ["a", "b", "c"]);
+        assert_eq!(jump_tags(26).last().map(String::as_str), Some("z"));
+    }
+
+    #[test]
+    fn jump_tags_add_two_letter_prefixes_beyond_26() {
+        let tags = jump_tags(27);
+        assert_eq!(tags.len(), 27);
+        assert_eq!(&tags[24..], ["y", "za", "zb"]);
+
+        let tags = jump_tags(100);
+        assert_eq!(tags.len(), 100);
+        let unique: std::collections::HashSet<&String> = tags.iter().collect();
+        assert_eq!(unique.len(), 100);
+        let singles: Vec<&String> = tags.iter().filter(|t| t.len() == 1).collect();
+        assert!(
+            tags.iter().all(|t| singles.iter().all(|s| t == *s || !t.starts_with(s.as_str()))),
+            "no single-letter tag is a prefix of another"
+        );
+        assert_eq!(jump_tags(1000).len(), 26 * 26);
+    }
+}
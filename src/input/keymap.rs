@@ -0,0 +1,262 @@
+//! Remappable normal-mode keys: the `[keymap]` table in `~/.glyphrc`.
+//!
+//! Action names map to key names (`move_left = "a"`). The table is resolved
+//! once at startup into `KeyBindings`; unknown action or key names are logged
+//! and the default binding is kept.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// A normal/visual mode command that can be bound to a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyAction {
+    MoveLeft,
+    MoveDown,
+    MoveUp,
+    MoveRight,
+    Insert,
+    NewNode,
+    Append,
+    /// `o` below, Shift+`o` above.
+    OpenLine,
+    Jump,
+    Delete,
+    Yank,
+    Paste,
+    Connect,
+    Goto,
+    /// Second key of `ge` / `ce`.
+    EdgeTarget,
+    Undo,
+    SetMark,
+    JumpMark,
+    Visual,
+    /// `zc` collapse / `zo` expand.
+    Fold,
+    /// `.` repeats the last change.
+    Repeat,
+}
+
+impl KeyAction {
+    pub const ALL: [KeyAction; 21] = [
+        KeyAction::MoveLeft,
+        KeyAction::MoveDown,
+        KeyAction::MoveUp,
+        KeyAction::MoveRight,
+        KeyAction::Insert,
+        KeyAction::NewNode,
+        KeyAction::Append,
+        KeyAction::OpenLine,
+        KeyAction::Jump,
+        KeyAction::Delete,
+        KeyAction::Yank,
+        KeyAction::Paste,
+        KeyAction::Connect,
+        KeyAction::Goto,
+        KeyAction::EdgeTarget,
+        KeyAction::Undo,
+        KeyAction::SetMark,
+        KeyAction::JumpMark,
+        KeyAction::Visual,
+        KeyAction::Fold,
+        KeyAction::Repeat,
+    ];
+
+    /// Name used in the `[keymap]` table.
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyAction::MoveLeft => "move_left",
+            KeyAction::MoveDown => "move_down",
+            KeyAction::MoveUp => "move_up",
+            KeyAction::MoveRight => "move_right",
+            KeyAction::Insert => "insert",
+            KeyAction::NewNode => "new_node",
+            KeyAction::Append => "append",
+            KeyAction::OpenLine => "open_line",
+            KeyAction::Jump => "jump",
+            KeyAction::Delete => "delete",
+            KeyAction::Yank => "yank",
+            KeyAction::Paste => "paste",
+            KeyAction::Connect => "connect",
+            KeyAction::Goto => "goto",
+            KeyAction::EdgeTarget => "edge",
+            KeyAction::Undo => "undo",
+            KeyAction::SetMark => "set_mark",
+            KeyAction::JumpMark => "jump_mark",
+            KeyAction::Visual => "visual",
+            KeyAction::Fold => "fold",
+            KeyAction::Repeat => "repeat",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<KeyAction> {
+        KeyAction::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    /// The binding Glyph ships with.
+    pub fn default_key(self) -> KeyCode {
+        match self {
+            KeyAction::MoveLeft => KeyCode::KeyH,
+            KeyAction::MoveDown => KeyCode::KeyJ,
+            KeyAction::MoveUp => KeyCode::KeyK,
+            KeyAction::MoveRight => KeyCode::KeyL,
+            KeyAction::Insert => KeyCode::KeyI,
+            KeyAction::NewNode => KeyCode::KeyN,
+            KeyAction::Append => KeyCode::KeyA,
+            KeyAction::OpenLine => KeyCode::KeyO,
+            KeyAction::Jump => KeyCode::KeyF,
+            KeyAction::Delete => KeyCode::KeyD,
+            KeyAction::Yank => KeyCode::KeyY,
+            KeyAction::Paste => KeyCode::KeyP,
+            KeyAction::Connect => KeyCode::KeyC,
+            KeyAction::Goto => KeyCode::KeyG,
+            KeyAction::EdgeTarget => KeyCode::KeyE,
+            KeyAction::Undo => KeyCode::KeyU,
+            KeyAction::SetMark => KeyCode::KeyM,
+            KeyAction::JumpMark => KeyCode::Quote,
+            KeyAction::Visual => KeyCode::KeyV,
+            KeyAction::Fold => KeyCode::KeyZ,
+            KeyAction::Repeat => KeyCode::Period,
+        }
+    }
+}
+
+/// Parse a key name from the config: a single letter or digit, or a named key
+/// such as `"left"`, `"space"` or `"quote"`. Case-insensitive.
+pub fn parse_key_name(name: &str) -> Option<KeyCode> {
+    const LETTERS: [KeyCode; 26] = [
+        KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE,
+        KeyCode::KeyF, KeyCode::KeyG, KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ,
+        KeyCode::KeyK, KeyCode::KeyL, KeyCode::KeyM, KeyCode::KeyN, KeyCode::KeyO,
+        KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR, KeyCode::KeyS, KeyCode::KeyT,
+        KeyCode::KeyU, KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX, KeyCode::KeyY,
+        KeyCode::KeyZ,
+    ];
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+        KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+    ];
+
+    let name = name.trim().to_ascii_lowercase();
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_lowercase() {
+            return Some(LETTERS[(c as u8 - b'a') as usize]);
+        }
+        if c.is_ascii_digit() {
+            return Some(DIGITS[(c as u8 - b'0') as usize]);
+        }
+    }
+    let key = match name.as_str() {
+        "left" => KeyCode::ArrowLeft,
+        "right" => KeyCode::ArrowRight,
+        "up" => KeyCode::ArrowUp,
+        "down" => KeyCode::ArrowDown,
+        "space" => KeyCode::Space,
+        "tab" => KeyCode::Tab,
+        "enter" => KeyCode::Enter,
+        "'" | "quote" => KeyCode::Quote,
+        ";" | "semicolon" => KeyCode::Semicolon,
+        "," | "comma" => KeyCode::Comma,
+        "." | "period" => KeyCode::Period,
+        "/" | "slash" => KeyCode::Slash,
+        "-" | "minus" => KeyCode::Minus,
+        "=" | "equal" => KeyCode::Equal,
+        "[" | "bracketleft" => KeyCode::BracketLeft,
+        "]" | "bracketright" => KeyCode::BracketRight,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Resolved key for every `KeyAction`.
+#[derive(Resource, Debug, Clone)]
+pub struct KeyBindings {
+    keys: HashMap<KeyAction, KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            keys: KeyAction::ALL.into_iter().map(|a| (a, a.default_key())).collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Apply the `[keymap]` table on top of the defaults.
+    pub fn from_keymap(keymap: &HashMap<String, String>) -> Self {
+        let mut bindings = Self::default();
+        for (action_name, key_name) in keymap {
+            let Some(action) = KeyAction::from_name(action_name) else {
+                warn!("[KEYMAP] unknown action '{}' ignored", action_name);
+                continue;
+            };
+            let Some(key) = parse_key_name(key_name) else {
+                warn!("[KEYMAP] unknown key '{}' for '{}' ignored", key_name, action_name);
+                continue;
+            };
+            bindings.keys.insert(action, key);
+        }
+        bindings
+    }
+
+    pub fn key(&self, action: KeyAction) -> KeyCode {
+        self.keys.get(&action).copied().unwrap_or(action.default_key())
+    }
+
+    pub fn just_pressed(&self, keys: &ButtonInput<KeyCode>, action: KeyAction) -> bool {
+        keys.just_pressed(self.key(action))
+    }
+
+    pub fn pressed(&self, keys: &ButtonInput<KeyCode>, action: KeyAction) -> bool {
+        keys.pressed(self.key(action))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_match_vim_keys() {
+        let b = KeyBindings::default();
+        assert_eq!(b.key(KeyAction::MoveLeft), KeyCode::KeyH);
+        assert_eq!(b.key(KeyAction::Delete), KeyCode::KeyD);
+        assert_eq!(b.key(KeyAction::JumpMark), KeyCode::Quote);
+    }
+
+    #[test]
+    fn keymap_overrides_and_ignores_unknown_names() {
+        let keymap: HashMap<String, String> = [
+            ("move_left", "A"),
+            ("insert", "space"),
+            ("delete", "not-a-key"),
+            ("fly", "x"),
+        ]
+        .into_iter()
+        .map(|(a, k)| (a.to_string(), k.to_string()))
+        .collect();
+        let b = KeyBindings::from_keymap(&keymap);
+        assert_eq!(b.key(KeyAction::MoveLeft), KeyCode::KeyA);
+        assert_eq!(b.key(KeyAction::Insert), KeyCode::Space);
+        assert_eq!(b.key(KeyAction::Delete), KeyCode::KeyD);
+    }
+
+    #[test]
+    fn parse_key_names() {
+        assert_eq!(parse_key_name("z"), Some(KeyCode::KeyZ));
+        assert_eq!(parse_key_name("7"), Some(KeyCode::Digit7));
+        assert_eq!(parse_key_name("Left"), Some(KeyCode::ArrowLeft));
+        assert_eq!(parse_key_name("'"), Some(KeyCode::Quote));
+        assert_eq!(parse_key_name("ctrl+x"), None);
+        assert_eq!(parse_key_name(""), None);
+    }
+
+    #[test]
+    fn action_names_roundtrip() {
+        for action in KeyAction::ALL {
+            assert_eq!(KeyAction::from_name(action.name()), Some(action));
+        }
+    }
+}
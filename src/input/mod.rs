@@ -1,6 +1,9 @@
 //! Input handling: Vim modes, mouse selection, easymotion, and camera control.
 
 pub mod camera;
+pub mod connect;
 pub mod easymotion;
+pub mod keymap;
+pub mod prune;
 pub mod selection;
 pub mod vim;
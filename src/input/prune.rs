@@ -0,0 +1,149 @@
+//! `dD`: delete the selected node together with every node reachable only
+//! through it — the subtree `zc` would fold, see `render::collapse` — and
+//! their edges. One `u` brings them all back.
+
+use bevy::prelude::*;
+
+use crate::core::components::{
+    CanvasNode, Edge, FlowBypass, InactiveCanvas, NodeColor, Selected, TextData,
+};
+use crate::core::helpers::delete_node;
+use crate::core::history::{Action, UndoHistory};
+use crate::core::resources::StatusMessage;
+use crate::render::collapse::exclusive_subtree;
+
+/// Message sent by `dD`.
+#[derive(Message)]
+pub struct DeleteSubtreeRequest;
+
+/// Handles `DeleteSubtreeRequest`. Nodes that another root also reaches are
+/// kept. The deleted nodes and their edges are pushed as one `Batch` of
+/// `DeleteNode`s.
+pub fn handle_delete_subtree_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<DeleteSubtreeRequest>,
+    nodes: Query<
+        (&Transform, &TextData, &NodeColor),
+        (With<CanvasNode>, Without<InactiveCanvas>),
+    >,
+    selected: Query<Entity, (With<Selected>, With<CanvasNode>)>,
+    edge_query: Query<(Entity, &Edge)>,
+    bypass_edges: Query<(), With<FlowBypass>>,
+    mut history: ResMut<UndoHistory>,
+    mut status: ResMut<StatusMessage>,
+) {
+    for DeleteSubtreeRequest in requests.read() {
+        let Ok(root) = selected.single() else {
+            status.set("error: dD needs a selected node");
+            continue;
+        };
+        let real_edges: Vec<&Edge> = edge_query
+            .iter()
+            .filter(|(e, _)| !bypass_edges.contains(*e))
+            .map(|(_, edge)| edge)
+            .collect();
+        let pairs: Vec<(Entity, Entity)> =
+            real_edges.iter().map(|e| (e.source, e.target)).collect();
+        let mut doomed = exclusive_subtree(root, &pairs);
+        doomed.insert(root);
+
+        let mut actions = Vec::new();
+        for &entity in &doomed {
+            let Ok((transform, text, color)) = nodes.get(entity) else {
+                continue;
+            };
+            actions.push(Action::DeleteNode {
+                entity,
+                pos: transform.translation.truncate(),
+                text: text.content.clone(),
+                color: color.0,
+                edges: real_edges
+                    .iter()
+                    .filter(|e| e.source == entity || e.target == entity)
+                    .map(|e| (e.source, e.target, e.label.clone()))
+                    .collect(),
+            });
+            delete_node(&mut commands, entity, &edge_query);
+        }
+        history.push(Action::Batch(actions));
+        let total = nodes.iter().len();
+        if doomed.len() == total {
+            status.set(format!("Deleted all {} nodes (u to undo)", total));
+        } else {
+            status.set(format!("Deleted {} nodes", doomed.len()));
+        }
+        info!(
+            "[PRUNE] deleted {:?} and {} nodes below it",
+            root,
+            doomed.len() - 1
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn subtree_delete_spares_nodes_other_roots_reach() {
+        let mut world = World::new();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<Messages<DeleteSubtreeRequest>>();
+        let mut node = |name: &str| {
+            world
+                .spawn((
+                    CanvasNode,
+                    Transform::default(),
+                    TextData {
+                        content: name.to_string(),
+                    },
+                    NodeColor(Color::WHITE),
+                ))
+                .id()
+        };
+        let (root, only, shared, other) = (node("root"), node("only"), node("shared"), node("x"));
+        for (source, target) in [(root, only), (only, shared), (other, shared)] {
+            world.spawn(Edge {
+                source,
+                target,
+                label: None,
+            });
+        }
+        // A `:flow off` bypass edge does not make x part of root's subtree.
+        world.spawn((
+            Edge {
+                source: root,
+                target: other,
+                label: None,
+            },
+            FlowBypass,
+        ));
+        world.entity_mut(root).insert(Selected);
+
+        world.write_message(DeleteSubtreeRequest);
+        world
+            .run_system_once(handle_delete_subtree_requests)
+            .unwrap();
+
+        assert!(world.get_entity(root).is_err());
+        assert!(world.get_entity(only).is_err());
+        assert!(world.get_entity(shared).is_ok(), "x still reaches it");
+        assert!(world.get_entity(other).is_ok());
+        assert_eq!(world.query::<&Edge>().iter(&world).count(), 1);
+        assert_eq!(world.resource::<StatusMessage>().text, "Deleted 2 nodes");
+        let history = world.resource::<UndoHistory>();
+        let Some(Action::Batch(actions)) = history.undo_stack.last() else {
+            panic!("expected one Batch step");
+        };
+        assert_eq!(actions.len(), 2);
+        for action in actions {
+            let Action::DeleteNode { entity, edges, .. } = action else {
+                panic!("expected DeleteNode");
+            };
+            assert!(*entity == root || *entity == only);
+            assert!(edges.iter().all(|(_, target, _)| *target != other));
+        }
+    }
+}
@@ -1,19 +1,41 @@
 //! Mouse selection and node drag/drop systems.
 
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
+use std::collections::{HashMap, HashSet, VecDeque};
 
-use crate::core::components::{CanvasNode, Dragging, Edge, MainCamera, Selected};
-use crate::core::helpers::spawn_canvas_node;
-use crate::render::edges::{edge_label_world_pos, LABEL_HIT_HALF};
-use crate::core::resources::SelectedEdge;
+use crate::core::components::{
+    CanvasNode, CurveOffset, Dragging, Edge, InactiveCanvas, MainCamera, NodeSize, Selected,
+};
+use crate::core::helpers::{snap_to_grid, spawn_canvas_node};
+use crate::core::config::Theme;
+use crate::core::history::{Action, UndoHistory};
+use crate::render::edges::{
+    curve_offset_through, edge_at_pos, EdgeLabelPlacer, EdgeShapeCache, EdgeSources,
+    LABEL_HIT_HALF,
+};
+use crate::core::resources::{HoveredEdge, SelectedEdge, VisualSelection};
 use crate::core::state::InputMode;
 
 /// Tracks the source node when drawing an edge (Shift+drag from node).
 #[derive(Resource, Default)]
 pub struct DrawingEdge(pub Option<Entity>);
 
-const NODE_HALF: Vec2 = Vec2::new(80.0, 60.0);
+/// Nodes for click hit-testing; the box grows with wrapped labels (`NodeSize`).
+type NodeHitQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Transform, Option<&'static NodeSize>),
+    (With<CanvasNode>, Without<InactiveCanvas>),
+>;
+
+/// True if `pos` lies inside the node's box.
+fn hits_node(pos: Vec2, transform: &Transform, size: Option<&NodeSize>) -> bool {
+    let half = size.copied().unwrap_or_default().current * 0.5;
+    let d = (pos - transform.translation.truncate()).abs();
+    d.x <= half.x && d.y <= half.y
+}
 
 fn cursor_world_pos(
     window_q: &Query<&Window, With<PrimaryWindow>>,
@@ -25,21 +47,96 @@ fn cursor_world_pos(
     camera.viewport_to_world_2d(cam_transform, cursor).ok()
 }
 
-fn node_at_pos(
-    node_query: &Query<(Entity, &Transform), With<CanvasNode>>,
-    pos: Vec2,
-) -> Option<Entity> {
-    for (entity, transform) in node_query.iter() {
-        let node_pos = transform.translation.truncate();
-        if pos.x >= node_pos.x - NODE_HALF.x
-            && pos.x <= node_pos.x + NODE_HALF.x
-            && pos.y >= node_pos.y - NODE_HALF.y
-            && pos.y <= node_pos.y + NODE_HALF.y
-        {
-            return Some(entity);
-        }
+fn node_at_pos(node_query: &NodeHitQuery, pos: Vec2) -> Option<Entity> {
+    node_query
+        .iter()
+        .find(|(_, transform, size)| hits_node(pos, transform, *size))
+        .map(|(entity, ..)| entity)
+}
+
+/// Radius of the connection handles drawn on a hovered node (world units).
+const HANDLE_RADIUS: f32 = 5.0;
+/// How close to a handle a click must land to start an edge from it.
+const HANDLE_HIT_RADIUS: f32 = 10.0;
+
+/// Connection handles of a node at `center` of `size`: the midpoints of its
+/// top, right, bottom and left sides.
+pub fn connect_handles(center: Vec2, size: Vec2) -> [Vec2; 4] {
+    let half = size * 0.5;
+    [
+        center + Vec2::new(0.0, half.y),
+        center + Vec2::new(half.x, 0.0),
+        center - Vec2::new(0.0, half.y),
+        center - Vec2::new(half.x, 0.0),
+    ]
+}
+
+/// The node with a connection handle under `pos`, if any.
+fn handle_at_pos(node_query: &NodeHitQuery, pos: Vec2) -> Option<Entity> {
+    node_query
+        .iter()
+        .find(|(_, transform, size)| {
+            let size = size.copied().unwrap_or_default().current;
+            connect_handles(transform.translation.truncate(), size)
+                .iter()
+                .any(|h| h.distance(pos) <= HANDLE_HIT_RADIUS)
+        })
+        .map(|(entity, ..)| entity)
+}
+
+/// Rubber-band selection in Standard mode: present from a left-press on empty
+/// canvas until the button is released. `end` follows the cursor.
+#[derive(Resource)]
+pub struct BoxSelect {
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+/// Drags shorter than this (world units) are plain clicks, not a box.
+const BOX_MIN_DRAG: f32 = 6.0;
+
+/// Nodes whose centers lie inside the rectangle spanned by corners `a` and `b`.
+pub fn nodes_in_rect(
+    a: Vec2,
+    b: Vec2,
+    nodes: impl IntoIterator<Item = (Entity, Vec2)>,
+) -> Vec<Entity> {
+    let rect = Rect::from_corners(a, b);
+    nodes
+        .into_iter()
+        .filter(|(_, center)| rect.contains(*center))
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+/// Bending an edge in Standard mode: present from a press on the selected
+/// edge's midpoint handle until the button is released.
+#[derive(Resource)]
+pub struct CurveDrag {
+    pub edge: Entity,
+    /// The edge's `CurveOffset` before the drag, for undo.
+    pub start: Option<Vec2>,
+    /// Cursor and curve midpoint at the press; the midpoint moves with the cursor.
+    pub grab: Vec2,
+    pub handle: Vec2,
+}
+
+/// Midpoint handle of edge `entity`: where its curve is halfway along.
+/// Self-loops have none.
+fn curve_handle(
+    edges: &Query<(Entity, &Edge)>,
+    labels: &EdgeLabelPlacer,
+    entity: Entity,
+) -> Option<Vec2> {
+    let (_, edge) = edges.get(entity).ok()?;
+    if edge.source == edge.target {
+        return None;
     }
-    None
+    let idx = edges
+        .iter()
+        .filter(|(_, e)| e.source == edge.source && e.target == edge.target)
+        .position(|(e, _)| e == entity)?;
+    labels.curve_midpoint(entity, edge, idx)
 }
 
 /// Tracks last click on empty space for double-click detection.
@@ -52,15 +149,68 @@ pub struct LastEmptyClick {
 const DBL_CLICK_MS: f64 = 400.0;
 const DBL_CLICK_DIST: f32 = 25.0;
 
+/// Every node linked to `start` through `edges` (either direction), `start` included.
+pub fn connected_component(start: Entity, edges: &[(Entity, Entity)]) -> HashSet<Entity> {
+    let mut adjacency: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for &(a, b) in edges {
+        adjacency.entry(a).or_default().push(b);
+        adjacency.entry(b).or_default().push(a);
+    }
+    let mut seen = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for &next in adjacency.get(&node).into_iter().flatten() {
+            if seen.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    seen
+}
+
+/// Edges, where their labels sit and their drawn curves: what the edge
+/// hit-tests depend on.
+#[derive(SystemParam)]
+pub struct EdgeLabelHits<'w, 's> {
+    edges: Query<'w, 's, (Entity, &'static Edge)>,
+    labels: EdgeLabelPlacer<'w, 's>,
+    shapes: EdgeSources<'w, 's>,
+}
+
+/// Make `edge` the `SelectedEdge`: nodes lose `Selected` and `Dragging`.
+fn select_edge(
+    commands: &mut Commands,
+    edge: Entity,
+    selected_q: &Query<Entity, With<Selected>>,
+    dragging_q: &Query<Entity, With<Dragging>>,
+    selected_edge: &mut SelectedEdge,
+    next_state: &mut NextState<InputMode>,
+) {
+    for prev in selected_q {
+        commands.entity(prev).remove::<Selected>();
+    }
+    for prev in dragging_q {
+        commands.entity(prev).remove::<Dragging>();
+    }
+    selected_edge.0 = Some(edge);
+    next_state.set(InputMode::Standard);
+}
+
 /// Left-click to select a CanvasNode and begin dragging it.
 ///
 /// Skipped entirely in VimInsert so that typing is never interrupted by
 /// an accidental click.  In any other mode a click on a node:
 ///   1. Clears the previous selection.
-///   2. Inserts `Selected` and `Dragging { offset }` on the clicked entity.
+///   2. Inserts `Selected` and `Dragging { offset, start }` on the clicked entity.
 ///   3. Transitions to `Standard` mode.
-/// Click on edge label area selects that edge for inline label editing.
-/// A click on empty canvas deselects.
+///
+/// Click on edge label area selects that edge for inline label editing; so
+/// does a click anywhere near its curve that misses every node. In Standard
+/// mode a press on the selected edge's midpoint handle starts a `CurveDrag`.
+/// Alt+click drags the node's whole connected component instead.
+/// A click on empty canvas deselects; in Standard mode it also starts a
+/// `BoxSelect` unless it completes a double-click (which creates a node).
+/// In VimVisual a click on a node only toggles its `VisualSelection` membership.
 pub fn mouse_selection_system(
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     keys: Res<ButtonInput<KeyCode>>,
@@ -70,9 +220,9 @@ pub fn mouse_selection_system(
     mut commands: Commands,
     mut last_empty: ResMut<LastEmptyClick>,
     mut selected_edge: ResMut<SelectedEdge>,
-    node_query: Query<(Entity, &Transform), With<CanvasNode>>,
-    edge_query: Query<(Entity, &Edge)>,
-    node_transform_query: Query<&Transform, With<CanvasNode>>,
+    mut visual: ResMut<VisualSelection>,
+    node_query: NodeHitQuery,
+    edges: EdgeLabelHits,
     selected_q: Query<Entity, With<Selected>>,
     dragging_q: Query<Entity, With<Dragging>>,
     mut next_state: ResMut<NextState<InputMode>>,
@@ -85,6 +235,7 @@ pub fn mouse_selection_system(
     if *current_state.get() == InputMode::VimInsert {
         return;
     }
+    commands.remove_resource::<BoxSelect>();
     // Space+click: pan mode, don't select or drag
     if keys.pressed(KeyCode::Space) {
         return;
@@ -103,12 +254,35 @@ pub fn mouse_selection_system(
         return;
     };
 
+    if *current_state.get() == InputMode::VimVisual {
+        if let Some(entity) = node_at_pos(&node_query, world_pos) {
+            visual.toggle(entity);
+            info!("[VISUAL] toggled {:?}", entity);
+        }
+        return;
+    }
+
+    if *current_state.get() == InputMode::Standard {
+        if let Some(edge) = selected_edge.0 {
+            let handle = curve_handle(&edges.edges, &edges.labels, edge);
+            if let Some(handle) = handle.filter(|h| h.distance(world_pos) <= HANDLE_HIT_RADIUS) {
+                commands.insert_resource(CurveDrag {
+                    edge,
+                    start: edges.labels.curve(edge),
+                    grab: world_pos,
+                    handle,
+                });
+                return;
+            }
+        }
+    }
+
     let shift = crate::core::helpers::shift_pressed(&keys);
 
     // Edge label hit-test first (before nodes). Click on label area selects edge for inline editing.
     let mut groups: std::collections::HashMap<(Entity, Entity), Vec<Entity>> =
         std::collections::HashMap::new();
-    for (entity, edge) in &edge_query {
+    for (entity, edge) in &edges.edges {
         groups
             .entry((edge.source, edge.target))
             .or_default()
@@ -120,40 +294,42 @@ pub fn mouse_selection_system(
             idx_map.insert(*e, i);
         }
     }
-    for (edge_entity, edge) in &edge_query {
-        let Ok(src) = node_transform_query.get(edge.source) else {
-            continue;
-        };
-        let Ok(tgt) = node_transform_query.get(edge.target) else {
+    for (edge_entity, edge) in &edges.edges {
+        let idx = idx_map.get(&edge_entity).copied().unwrap_or(0);
+        let Some((label_pos, _)) = edges.labels.place(edge_entity, edge, idx) else {
             continue;
         };
-        let idx = idx_map.get(&edge_entity).copied().unwrap_or(0);
-        let (label_pos, _) = edge_label_world_pos(src, tgt, idx);
         if world_pos.x >= label_pos.x - LABEL_HIT_HALF.x
             && world_pos.x <= label_pos.x + LABEL_HIT_HALF.x
             && world_pos.y >= label_pos.y - LABEL_HIT_HALF.y
             && world_pos.y <= label_pos.y + LABEL_HIT_HALF.y
         {
-            for prev in &selected_q {
-                commands.entity(prev).remove::<Selected>();
-            }
-            for prev in &dragging_q {
-                commands.entity(prev).remove::<Dragging>();
-            }
-            selected_edge.0 = Some(edge_entity);
-            next_state.set(InputMode::Standard);
+            select_edge(
+                &mut commands,
+                edge_entity,
+                &selected_q,
+                &dragging_q,
+                &mut selected_edge,
+                &mut next_state,
+            );
             info!("[SELECT] edge {:?} for label edit", edge_entity);
             return;
         }
     }
 
-    for (entity, transform) in &node_query {
+    // A press on a hovered node's connection handle draws an edge, like Shift.
+    if *current_state.get() == InputMode::Standard {
+        if let Some(entity) = handle_at_pos(&node_query, world_pos) {
+            commands.insert_resource(DrawingEdge(Some(entity)));
+            selected_edge.0 = None;
+            info!("[EDGE] start draw from handle of {:?}", entity);
+            return;
+        }
+    }
+
+    for (entity, transform, size) in &node_query {
         let node_pos = transform.translation.truncate();
-        if world_pos.x >= node_pos.x - NODE_HALF.x
-            && world_pos.x <= node_pos.x + NODE_HALF.x
-            && world_pos.y >= node_pos.y - NODE_HALF.y
-            && world_pos.y <= node_pos.y + NODE_HALF.y
-        {
+        if hits_node(world_pos, transform, size) {
             if shift {
                 // Shift+click: start edge drawing instead of node drag
                 commands.insert_resource(DrawingEdge(Some(entity)));
@@ -171,9 +347,30 @@ pub fn mouse_selection_system(
             }
 
             let offset = world_pos - node_pos;
-            commands
-                .entity(entity)
-                .insert((Selected, Dragging { offset }));
+            commands.entity(entity).insert((
+                Selected,
+                Dragging {
+                    offset,
+                    start: node_pos,
+                },
+            ));
+            // Alt+drag carries the whole connected component along.
+            if crate::core::helpers::alt_pressed(&keys) {
+                let pairs: Vec<(Entity, Entity)> =
+                    edges.edges.iter().map(|(_, e)| (e.source, e.target)).collect();
+                for member in connected_component(entity, &pairs) {
+                    if member == entity {
+                        continue;
+                    }
+                    if let Ok((_, t, _)) = node_query.get(member) {
+                        let start = t.translation.truncate();
+                        commands.entity(member).insert(Dragging {
+                            offset: world_pos - start,
+                            start,
+                        });
+                    }
+                }
+            }
 
             next_state.set(InputMode::Standard);
             info!("[SELECT] {:?} @ {:?}", entity, node_pos);
@@ -181,6 +378,22 @@ pub fn mouse_selection_system(
         }
     }
 
+    // A click on a curve selects its edge; Shift is left for drawing edges.
+    if !shift {
+        if let Some(edge_entity) = edge_at_pos(&edges.shapes.shapes(), world_pos) {
+            select_edge(
+                &mut commands,
+                edge_entity,
+                &selected_q,
+                &dragging_q,
+                &mut selected_edge,
+                &mut next_state,
+            );
+            info!("[SELECT] edge {:?} by its curve", edge_entity);
+            return;
+        }
+    }
+
     // Click on empty space: double-click creates node, single-click deselects
     selected_edge.0 = None;
     let now = time.elapsed_secs_f64() * 1000.0;
@@ -201,18 +414,36 @@ pub fn mouse_selection_system(
         for prev in &selected_q {
             commands.entity(prev).remove::<Selected>();
         }
+        if *current_state.get() == InputMode::Standard && !shift {
+            commands.insert_resource(BoxSelect {
+                start: world_pos,
+                end: world_pos,
+            });
+        }
     }
 }
 
 /// While the left mouse button is held, move the dragged node to the cursor.
-/// Space+drag pans instead, so we skip when Space is held.
+/// Space+drag pans instead, so we skip when Space is held. Escape cancels
+/// the drag: nodes go back to where they were picked up and, with `Dragging`
+/// gone, the release drops nothing and records no move.
 pub fn node_drag_system(
+    mut commands: Commands,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     keys: Res<ButtonInput<KeyCode>>,
     window_q: Query<&Window, With<PrimaryWindow>>,
     camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    mut dragging_q: Query<(&mut Transform, &Dragging)>,
+    mut dragging_q: Query<(Entity, &mut Transform, &Dragging)>,
 ) {
+    if keys.just_pressed(KeyCode::Escape) && !dragging_q.is_empty() {
+        for (entity, mut transform, dragging) in &mut dragging_q {
+            transform.translation.x = dragging.start.x;
+            transform.translation.y = dragging.start.y;
+            commands.entity(entity).remove::<Dragging>();
+            info!("[DRAG] cancelled {:?}", entity);
+        }
+        return;
+    }
     if !mouse_buttons.pressed(MouseButton::Left) {
         return;
     }
@@ -233,24 +464,46 @@ pub fn node_drag_system(
         return;
     };
 
-    for (mut transform, dragging) in &mut dragging_q {
+    for (_, mut transform, dragging) in &mut dragging_q {
         let target = world_pos - dragging.offset;
         transform.translation.x = target.x;
         transform.translation.y = target.y;
     }
 }
 
-/// When the left mouse button is released, remove the Dragging marker.
+/// When the left mouse button is released, remove the Dragging marker and
+/// snap the node to the grid if `snap_grid` is set. A group drag (Alt) is
+/// recorded as one undo step.
 pub fn node_drop_system(
     mut commands: Commands,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
-    dragging_q: Query<Entity, With<Dragging>>,
+    config: Res<crate::core::config::GlyphConfig>,
+    mut history: ResMut<UndoHistory>,
+    mut dragging_q: Query<(Entity, &mut Transform, &Dragging)>,
 ) {
     if mouse_buttons.just_released(MouseButton::Left) {
-        for entity in &dragging_q {
+        let mut moves = Vec::new();
+        for (entity, mut transform, dragging) in &mut dragging_q {
+            if let Some(grid) = config.snap_grid() {
+                let snapped = snap_to_grid(transform.translation.truncate(), grid);
+                transform.translation.x = snapped.x;
+                transform.translation.y = snapped.y;
+            }
+            let to = transform.translation.truncate();
+            if to != dragging.start {
+                moves.push(Action::MoveNode {
+                    entity,
+                    from: dragging.start,
+                    to,
+                });
+            }
             commands.entity(entity).remove::<Dragging>();
             info!("[DROP] {:?}", entity);
         }
+        if dragging_q.iter().count() > 1 && !moves.is_empty() {
+            info!("[DROP] group of {} nodes", moves.len());
+            history.push(Action::Batch(moves));
+        }
     }
 }
 
@@ -300,14 +553,240 @@ pub fn edge_draw_preview_system(
     }
 }
 
+/// Mouse, keys and drags that hide the connection handles.
+#[derive(SystemParam)]
+pub struct PointerActivity<'w, 's> {
+    mouse_buttons: Res<'w, ButtonInput<MouseButton>>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    drawing: Res<'w, DrawingEdge>,
+    dragging: Query<'w, 's, (), With<Dragging>>,
+}
+
+impl PointerActivity<'_, '_> {
+    /// True while a node is dragged, an edge is drawn or the canvas pans.
+    fn busy(&self) -> bool {
+        self.mouse_buttons.pressed(MouseButton::Left)
+            || self.mouse_buttons.pressed(MouseButton::Middle)
+            || self.keys.pressed(KeyCode::Space)
+            || self.drawing.0.is_some()
+            || !self.dragging.is_empty()
+    }
+}
+
+/// In Standard mode, draw connection handles on the node under the cursor;
+/// press-dragging from one draws an edge without Shift. Hidden while a node
+/// is dragged, an edge is drawn or the canvas pans.
+pub fn draw_connect_handles_system(
+    mut gizmos: Gizmos,
+    pointer: PointerActivity,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    node_query: NodeHitQuery,
+    theme: Res<Theme>,
+) {
+    if pointer.busy() {
+        return;
+    }
+    let Some(cursor) = cursor_world_pos(&window_q, &camera_q) else {
+        return;
+    };
+    let hovered = node_query.iter().find(|(_, transform, size)| {
+        let half = size.copied().unwrap_or_default().current * 0.5 + HANDLE_HIT_RADIUS;
+        let d = (cursor - transform.translation.truncate()).abs();
+        d.x <= half.x && d.y <= half.y
+    });
+    let Some((_, transform, size)) = hovered else {
+        return;
+    };
+    let size = size.copied().unwrap_or_default().current;
+    let color = theme.selection(&InputMode::Standard);
+    for handle in connect_handles(transform.translation.truncate(), size) {
+        gizmos.circle_2d(handle, HANDLE_RADIUS, color);
+    }
+}
+
+/// In Standard mode, draw the midpoint handle of the selected edge; dragging
+/// it bends the edge.
+pub fn draw_curve_handle_system(
+    mut gizmos: Gizmos,
+    selected_edge: Res<SelectedEdge>,
+    edges: Query<(Entity, &Edge)>,
+    labels: EdgeLabelPlacer,
+    theme: Res<Theme>,
+) {
+    let Some(handle) = selected_edge.0.and_then(|e| curve_handle(&edges, &labels, e)) else {
+        return;
+    };
+    gizmos.circle_2d(handle, HANDLE_RADIUS, theme.selection(&InputMode::Standard));
+}
+
+/// While a `CurveDrag` is held, bend its edge so the curve's midpoint follows
+/// the cursor. On release, record the new bend for undo.
+pub fn curve_drag_system(
+    mut commands: Commands,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    drag: Option<Res<CurveDrag>>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    edges: Query<(&Edge, Option<&CurveOffset>)>,
+    nodes: Query<&Transform, With<CanvasNode>>,
+    mut history: ResMut<UndoHistory>,
+) {
+    let Some(drag) = drag else {
+        return;
+    };
+    let Ok((edge, current)) = edges.get(drag.edge) else {
+        commands.remove_resource::<CurveDrag>();
+        return;
+    };
+    if !mouse_buttons.pressed(MouseButton::Left) {
+        commands.remove_resource::<CurveDrag>();
+        let new = current.map(|c| c.0);
+        if new != drag.start {
+            history.push(Action::CurveEdge {
+                entity: drag.edge,
+                old: drag.start,
+                new,
+            });
+            info!("[EDGE] bent {:?}", drag.edge);
+        }
+        return;
+    }
+    let Some(cursor) = cursor_world_pos(&window_q, &camera_q) else {
+        return;
+    };
+    let (Ok(src), Ok(tgt)) = (nodes.get(edge.source), nodes.get(edge.target)) else {
+        return;
+    };
+    // A press that has not moved leaves the automatic curve alone.
+    if cursor == drag.grab && current.is_none() {
+        return;
+    }
+    let (p0, p2) = (src.translation.truncate(), tgt.translation.truncate());
+    let offset = curve_offset_through(p0, p2, drag.handle + cursor - drag.grab);
+    if current.map(|c| c.0) != Some(offset) {
+        commands.entity(drag.edge).insert(CurveOffset(offset));
+    }
+}
+
+/// Track the edge under the cursor in `HoveredEdge` so it is drawn brighter.
+/// Nothing is hovered while the pointer is busy or over a node.
+pub fn edge_hover_system(
+    mut commands: Commands,
+    pointer: PointerActivity,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    node_query: NodeHitQuery,
+    shapes: Res<EdgeShapeCache>,
+    hovered: Res<HoveredEdge>,
+) {
+    let cursor = cursor_world_pos(&window_q, &camera_q).filter(|_| !pointer.busy());
+    let new = cursor
+        .filter(|pos| node_at_pos(&node_query, *pos).is_none())
+        .and_then(|pos| edge_at_pos(&shapes.0, pos));
+    if new != hovered.0 {
+        commands.insert_resource(HoveredEdge(new));
+    }
+}
+
+/// Draw the rubber-band rectangle while a `BoxSelect` drag is in progress.
+pub fn box_select_preview_system(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    box_select: Option<ResMut<BoxSelect>>,
+    mut gizmos: Gizmos,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    theme: Res<crate::core::config::Theme>,
+) {
+    let Some(mut box_select) = box_select else {
+        return;
+    };
+    if !mouse_buttons.pressed(MouseButton::Left) {
+        return;
+    }
+    if let Some(cursor_pos) = cursor_world_pos(&window_q, &camera_q) {
+        box_select.end = cursor_pos;
+    }
+    if box_select.start.distance(box_select.end) < BOX_MIN_DRAG {
+        return;
+    }
+    let rect = Rect::from_corners(box_select.start, box_select.end);
+    gizmos.rect_2d(
+        Isometry2d::from_translation(rect.center()),
+        rect.size(),
+        theme.select_standard,
+    );
+}
+
+type BoxNodeQuery<'w, 's> =
+    Query<'w, 's, (Entity, &'static Transform), (With<CanvasNode>, Without<InactiveCanvas>)>;
+
+/// On mouse release: hand every node whose center is inside the box to
+/// VimVisual as its set, so `hjkl`, `d` and `:align` act on all. `Selected`
+/// stays on the one node it was on.
+pub fn box_select_drop_system(
+    mut commands: Commands,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    box_select: Option<Res<BoxSelect>>,
+    node_query: BoxNodeQuery,
+    mut visual: ResMut<VisualSelection>,
+    mut next_state: ResMut<NextState<InputMode>>,
+) {
+    if !mouse_buttons.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(box_select) = box_select else {
+        return;
+    };
+    commands.remove_resource::<BoxSelect>();
+    if box_select.start.distance(box_select.end) < BOX_MIN_DRAG {
+        return;
+    }
+    let inside = nodes_in_rect(
+        box_select.start,
+        box_select.end,
+        node_query.iter().map(|(e, t)| (e, t.translation.truncate())),
+    );
+    info!("[SELECT] box selected {} nodes", inside.len());
+    if inside.is_empty() {
+        return;
+    }
+    visual.0 = inside.into_iter().collect();
+    next_state.set(InputMode::VimVisual);
+}
+
+/// Spawn the edge finished by a Shift+drag and record it for undo.
+fn spawn_drawn_edge(
+    commands: &mut Commands,
+    history: &mut UndoHistory,
+    source: Entity,
+    target: Entity,
+) -> Entity {
+    let entity = commands
+        .spawn(Edge {
+            source,
+            target,
+            label: None,
+        })
+        .id();
+    history.push(Action::CreateEdge {
+        entity,
+        source,
+        target,
+        label: None,
+    });
+    entity
+}
+
 /// On mouse release: complete edge if over a node, else cancel.
 pub fn edge_draw_drop_system(
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     mut commands: Commands,
     mut drawing: ResMut<DrawingEdge>,
+    mut history: ResMut<UndoHistory>,
     window_q: Query<&Window, With<PrimaryWindow>>,
     camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    node_query: Query<(Entity, &Transform), With<CanvasNode>>,
+    node_query: NodeHitQuery,
 ) {
     if !mouse_buttons.just_released(MouseButton::Left) {
         return;
@@ -321,13 +800,163 @@ pub fn edge_draw_drop_system(
     };
     if let Some(target_entity) = node_at_pos(&node_query, cursor_pos) {
         if target_entity != source_entity {
-            commands.spawn(Edge {
-                source: source_entity,
-                target: target_entity,
-                label: None,
-            });
+            spawn_drawn_edge(&mut commands, &mut history, source_entity, target_entity);
             info!("[EDGE] created {:?} → {:?}", source_entity, target_entity);
         }
     }
     drawing.0 = None;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::history::{apply_action, UndoNodeQuery};
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn escape_cancels_a_drag_without_recording_a_move() {
+        let mut world = World::new();
+        world.init_resource::<UndoHistory>();
+        world.insert_resource(crate::core::config::GlyphConfig::default());
+        let mut mouse = ButtonInput::<MouseButton>::default();
+        mouse.press(MouseButton::Left);
+        world.insert_resource(mouse);
+        let mut keys = ButtonInput::<KeyCode>::default();
+        keys.press(KeyCode::Escape);
+        world.insert_resource(keys);
+        let start = Vec2::new(10.0, 20.0);
+        let node = world
+            .spawn((
+                Transform::from_xyz(300.0, -40.0, 0.0),
+                Dragging {
+                    offset: Vec2::ZERO,
+                    start,
+                },
+            ))
+            .id();
+
+        world.run_system_once(node_drag_system).unwrap();
+        assert_eq!(world.get::<Transform>(node).unwrap().translation.truncate(), start);
+        assert!(world.get::<Dragging>(node).is_none());
+
+        // Releasing the button afterwards drops nothing.
+        let mut mouse = world.resource_mut::<ButtonInput<MouseButton>>();
+        mouse.clear();
+        mouse.release(MouseButton::Left);
+        world.run_system_once(node_drop_system).unwrap();
+        assert!(world.resource::<UndoHistory>().undo_stack.is_empty());
+    }
+
+    #[test]
+    fn connected_component_follows_edges_both_ways() {
+        let e = |n: u64| Entity::from_bits(n);
+        // 1 → 2 ← 3, 3 → 4;  5 → 6 is a separate component; 7 is isolated.
+        let edges = [(e(1), e(2)), (e(3), e(2)), (e(3), e(4)), (e(5), e(6))];
+        assert_eq!(
+            connected_component(e(4), &edges),
+            HashSet::from([e(1), e(2), e(3), e(4)])
+        );
+        assert_eq!(connected_component(e(7), &edges), HashSet::from([e(7)]));
+    }
+
+    #[test]
+    fn nodes_in_rect_uses_centers_and_any_corner_order() {
+        let a = Entity::from_bits(1);
+        let b = Entity::from_bits(2);
+        let c = Entity::from_bits(3);
+        let nodes = [
+            (a, Vec2::new(10.0, 10.0)),
+            (b, Vec2::new(90.0, -40.0)),
+            (c, Vec2::new(150.0, 0.0)),
+        ];
+        let picked = nodes_in_rect(Vec2::new(100.0, -50.0), Vec2::new(0.0, 20.0), nodes);
+        assert_eq!(picked, vec![a, b]);
+        assert!(nodes_in_rect(Vec2::ZERO, Vec2::new(5.0, 5.0), nodes).is_empty());
+    }
+
+    #[test]
+    fn box_select_fills_the_visual_set_without_selecting() {
+        use bevy::ecs::system::RunSystemOnce;
+        let mut world = World::new();
+        world.init_resource::<VisualSelection>();
+        world.init_resource::<NextState<InputMode>>();
+        let mut mouse = ButtonInput::<MouseButton>::default();
+        mouse.press(MouseButton::Left);
+        mouse.release(MouseButton::Left);
+        world.insert_resource(mouse);
+        world.insert_resource(BoxSelect {
+            start: Vec2::new(-100.0, -100.0),
+            end: Vec2::new(100.0, 100.0),
+        });
+        let cursor = world.spawn((CanvasNode, Transform::from_xyz(500.0, 0.0, 0.0), Selected)).id();
+        let boxed = [
+            world.spawn((CanvasNode, Transform::from_xyz(0.0, 0.0, 0.0))).id(),
+            world.spawn((CanvasNode, Transform::from_xyz(50.0, 50.0, 0.0))).id(),
+        ];
+
+        world.run_system_once(box_select_drop_system).unwrap();
+
+        assert_eq!(world.resource::<VisualSelection>().0, HashSet::from(boxed));
+        assert!(boxed.iter().all(|e| world.get::<Selected>(*e).is_none()));
+        assert!(world.get::<Selected>(cursor).is_some());
+    }
+
+    #[test]
+    fn handles_sit_on_side_midpoints_and_catch_nearby_clicks() {
+        let center = Vec2::new(100.0, 50.0);
+        let size = Vec2::new(160.0, 120.0);
+        let [top, right, bottom, left] = connect_handles(center, size);
+        assert_eq!(top, Vec2::new(100.0, 110.0));
+        assert_eq!(right, Vec2::new(180.0, 50.0));
+        assert_eq!(bottom, Vec2::new(100.0, -10.0));
+        assert_eq!(left, Vec2::new(20.0, 50.0));
+
+        let mut world = World::new();
+        let node = world
+            .spawn((CanvasNode, Transform::from_translation(center.extend(0.0))))
+            .id();
+        let hit = move |pos: Vec2| {
+            move |nodes: NodeHitQuery| handle_at_pos(&nodes, pos)
+        };
+        let near_right = right + Vec2::new(4.0, -3.0);
+        assert_eq!(world.run_system_once(hit(near_right)).unwrap(), Some(node));
+        assert_eq!(world.run_system_once(hit(center)).unwrap(), None, "body drags");
+    }
+
+    #[test]
+    fn drawn_edge_can_be_undone() {
+        let mut world = World::new();
+        world.init_resource::<UndoHistory>();
+        let a = world.spawn_empty().id();
+        let b = world.spawn_empty().id();
+
+        let edge = world
+            .run_system_once(move |mut commands: Commands, mut history: ResMut<UndoHistory>| {
+                spawn_drawn_edge(&mut commands, &mut history, a, b)
+            })
+            .unwrap();
+        assert!(world.get::<Edge>(edge).is_some());
+
+        world
+            .run_system_once(
+                |mut commands: Commands,
+                 mut history: ResMut<UndoHistory>,
+                 mut query: UndoNodeQuery,
+                 edge_query: Query<(Entity, &Edge)>| {
+                    let mut action = history.pop_undo().unwrap();
+                    apply_action(&mut action, true, &mut commands, &mut query, &edge_query);
+                    history.push_redo(action);
+                },
+            )
+            .unwrap();
+
+        assert!(world.get_entity(edge).is_err(), "edge despawned by undo");
+        let history = world.resource::<UndoHistory>();
+        assert!(history.undo_stack.is_empty());
+        assert!(matches!(
+            history.redo_stack.last(),
+            Some(Action::CreateEdge { entity, source, target, label: None })
+                if *entity == edge && *source == a && *target == b
+        ));
+    }
+}
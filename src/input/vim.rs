@@ -5,12 +5,21 @@ use bevy::input::keyboard::Key;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 
-use crate::core::components::{Edge, NodeColor, Selected, SourceLocation, TextData};
-use crate::core::helpers::{delete_node, spawn_canvas_node, spawn_node_with_color};
+use crate::core::components::{
+    CanvasNode, CollapseHidden, Collapsed, Dragging, Edge, FlowBypass, InactiveCanvas, MainCamera,
+    NodeColor, Selected, SourceLocation, TextData,
+};
+use crate::core::helpers::{delete_node, snap_to_grid, spawn_canvas_node};
 use crate::core::history::{apply_action, Action, UndoHistory};
-use crate::core::resources::{SelectedEdge, StatusMessage};
+use crate::core::jumplist::{JumpEntry, JumpList};
+use crate::core::resources::{
+    InsertCaret, Register, SearchMatches, SelectedEdge, StatusMessage, VisualSelection,
+};
 use crate::core::state::InputMode;
+use crate::input::camera::request_camera_move;
 use crate::input::easymotion::EasymotionTarget;
+use crate::input::keymap::{KeyAction, KeyBindings};
+use crate::render::collapse::exclusive_subtree;
 use crate::ui::overlay::VimCmdLine;
 
 fn cursor_world_pos(
@@ -42,6 +51,9 @@ pub struct PendingOperations {
     pub ce: bool,
     pub mark_set: bool,
     pub mark_jump: bool,
+    pub z: bool,
+    /// Numeric repeat typed before a command (`5j`).
+    pub count: Option<u32>,
 }
 
 impl PendingOperations {
@@ -52,9 +64,62 @@ impl PendingOperations {
         self.ce = false;
         self.mark_set = false;
         self.mark_jump = false;
+        self.z = false;
+        self.count = None;
     }
 }
 
+/// The last change made in VimNormal, replayed by `.` against the current
+/// selection (or cursor). Motions, yanks and selection changes are never
+/// recorded.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LastChange {
+    #[default]
+    None,
+    /// `n`: new node at the cursor.
+    NewNode,
+    /// `dd` / `Delete`: delete the selected edge or node.
+    Delete,
+    /// `p`: paste the register at the cursor.
+    Paste,
+    /// `a`: new node linked from the selected one.
+    Append,
+    /// `o` / `O`: new node one flow row below / above.
+    OpenLine { above: bool },
+    /// `gr`: reverse the selected edge.
+    ReverseEdge,
+    /// `Y`: clone the selected node with its edges.
+    Duplicate,
+}
+
+/// Appends `digit` to a pending count. A leading `0` does not start a count.
+fn push_count_digit(count: Option<u32>, digit: u32) -> Option<u32> {
+    match count {
+        None if digit == 0 => None,
+        None => Some(digit),
+        Some(n) => Some(n.saturating_mul(10).saturating_add(digit)),
+    }
+}
+
+fn digit_just_pressed(keys: &ButtonInput<KeyCode>) -> Option<u32> {
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Digit0,
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+    DIGITS
+        .iter()
+        .position(|k| keys.just_pressed(*k))
+        .map(|d| d as u32)
+}
+
 #[derive(Resource, Default)]
 pub struct HjklHoldTime(pub f32);
 
@@ -70,6 +135,19 @@ pub struct StartMovePos(pub Option<Vec2>);
 #[derive(Resource, Default)]
 pub struct OriginalText(pub Option<String>);
 
+/// Members' positions when a VimVisual group move starts; become the `from`
+/// side of the `MoveNode` batch pushed when the keys are released.
+#[derive(Resource, Default)]
+pub struct VisualMoveStart(pub Option<Vec<(Entity, Vec2)>>);
+
+/// Visible nodes' world positions, readable alongside the `&mut Transform` queries.
+type NodePositionQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static GlobalTransform),
+    (With<CanvasNode>, Without<CollapseHidden>),
+>;
+
 #[derive(SystemParam)]
 pub struct VimNormalParams<'w, 's> {
     pub keys: Res<'w, ButtonInput<KeyCode>>,
@@ -85,6 +163,9 @@ pub struct VimNormalParams<'w, 's> {
     pub start_move_pos: ResMut<'w, StartMovePos>,
     pub status: ResMut<'w, StatusMessage>,
     pub config: Res<'w, crate::core::config::GlyphConfig>,
+    pub visual: ResMut<'w, VisualSelection>,
+    pub register: Option<Res<'w, Register>>,
+    pub bindings: Res<'w, KeyBindings>,
     pub query: Query<
         'w,
         's,
@@ -98,82 +179,151 @@ pub struct VimNormalParams<'w, 's> {
         (With<Selected>, Without<crate::core::components::MainCamera>),
     >,
     pub edge_query: Query<'w, 's, (Entity, &'static Edge)>,
+    /// `:flow off` bypass edges: display-only, never recorded for undo.
+    pub bypass_edges: Query<'w, 's, (), With<FlowBypass>>,
+    pub jumps: ResMut<'w, JumpList>,
+    pub canvas_nodes: Query<'w, 's, (), With<CanvasNode>>,
+    /// Node positions for `gg` / `G` and `n` / `N`.
+    pub node_positions: NodePositionQuery<'w, 's>,
+    pub search: ResMut<'w, SearchMatches>,
+    pub tabs: MessageWriter<'w, crate::core::tabs::TabRequest>,
+    pub pins: MessageWriter<'w, crate::render::pin::PinRequest>,
+    pub subtree_deletes: MessageWriter<'w, crate::input::prune::DeleteSubtreeRequest>,
+    pub last_change: ResMut<'w, LastChange>,
+    pub camera: Query<'w, 's, &'static mut Transform, (With<MainCamera>, Without<Selected>)>,
 }
 
 const BACKSPACE_INITIAL_DELAY: f32 = 0.4;
 const BACKSPACE_REPEAT_INTERVAL: f32 = 0.05;
 
-fn open_in_editor(file: &str, line: u32) {
+/// Program and arguments of an `editor_cmd` template such as
+/// `"nvim +{line} {file}"`. The template is split on whitespace before
+/// `{file}`, `{line}` and `{col}` are filled in, so paths with spaces stay one
+/// argument. `None` for an empty template.
+pub fn editor_command(template: &str, file: &str, line: u32, col: u32) -> Option<Vec<String>> {
+    let args: Vec<String> = template
+        .split_whitespace()
+        .map(|arg| {
+            arg.replace("{file}", file)
+                .replace("{line}", &line.to_string())
+                .replace("{col}", &col.to_string())
+        })
+        .collect();
+    (!args.is_empty()).then_some(args)
+}
+
+/// Open `file` at `line` for `gd`. With `editor_cmd` set its template is run
+/// directly; otherwise `$EDITOR` / `$VISUAL` is guessed at (VS Code-likes and
+/// Zed get a `file:line` argument, anything else runs in a macOS Terminal).
+fn open_in_editor(file: &str, line: u32, editor_cmd: Option<&str>) -> Result<(), String> {
+    let spawn = |program: &str, args: &[String]| {
+        std::process::Command::new(program)
+            .args(args)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| format!("could not run {}: {}", program, e))
+    };
+    if let Some(template) = editor_cmd {
+        let Some(args) = editor_command(template, file, line, 1) else {
+            return Err("editor_cmd is empty".to_string());
+        };
+        return spawn(&args[0], &args[1..]);
+    }
+
     let editor = std::env::var("EDITOR")
         .or_else(|_| std::env::var("VISUAL"))
         .unwrap_or_else(|_| "code".to_string());
 
     if editor.contains("code") || editor.contains("cursor") || editor.contains("windsurf") {
-        let _ = std::process::Command::new(&editor)
-            .args(["--goto", &format!("{}:{}", file, line)])
-            .spawn();
+        spawn(&editor, &["--goto".to_string(), format!("{}:{}", file, line)])
     } else if editor.contains("zed") {
-        let _ = std::process::Command::new(&editor)
-            .arg(format!("{}:{}", file, line))
-            .spawn();
+        spawn(&editor, &[format!("{}:{}", file, line)])
     } else {
         let safe_file = file.replace('\'', r"'\''");
         let cmd = format!("{} +{} '{}'", editor, line, safe_file);
-        let _ = std::process::Command::new("osascript")
-            .args([
-                "-e",
-                &format!("tell application \"Terminal\" to do script \"{}\"", cmd),
-            ])
-            .spawn();
+        spawn(
+            "osascript",
+            &[
+                "-e".to_string(),
+                format!("tell application \"Terminal\" to do script \"{}\"", cmd),
+            ],
+        )
     }
 }
 
-fn is_movement_pressed(keys: &ButtonInput<KeyCode>) -> bool {
-    keys.pressed(KeyCode::KeyH)
-        || keys.pressed(KeyCode::KeyL)
-        || keys.pressed(KeyCode::KeyK)
-        || keys.pressed(KeyCode::KeyJ)
-        || keys.pressed(KeyCode::ArrowLeft)
-        || keys.pressed(KeyCode::ArrowRight)
-        || keys.pressed(KeyCode::ArrowUp)
-        || keys.pressed(KeyCode::ArrowDown)
+const MOVEMENT_ACTIONS: [KeyAction; 4] = [
+    KeyAction::MoveLeft,
+    KeyAction::MoveRight,
+    KeyAction::MoveUp,
+    KeyAction::MoveDown,
+];
+const ARROW_KEYS: [KeyCode; 4] = [
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::ArrowUp,
+    KeyCode::ArrowDown,
+];
+
+fn is_movement_just_pressed(keys: &ButtonInput<KeyCode>, bindings: &KeyBindings) -> bool {
+    MOVEMENT_ACTIONS.iter().any(|a| bindings.just_pressed(keys, *a))
+        || keys.any_just_pressed(ARROW_KEYS)
+}
+
+/// Unit step for the held movement / arrow keys; opposite keys cancel out.
+fn movement_direction(keys: &ButtonInput<KeyCode>, bindings: &KeyBindings) -> Vec2 {
+    let mut dir = Vec2::ZERO;
+    if bindings.pressed(keys, KeyAction::MoveLeft) || keys.pressed(KeyCode::ArrowLeft) {
+        dir.x -= 1.0;
+    }
+    if bindings.pressed(keys, KeyAction::MoveRight) || keys.pressed(KeyCode::ArrowRight) {
+        dir.x += 1.0;
+    }
+    if bindings.pressed(keys, KeyAction::MoveUp) || keys.pressed(KeyCode::ArrowUp) {
+        dir.y += 1.0;
+    }
+    if bindings.pressed(keys, KeyAction::MoveDown) || keys.pressed(KeyCode::ArrowDown) {
+        dir.y -= 1.0;
+    }
+    dir
+}
+
+fn is_movement_pressed(keys: &ButtonInput<KeyCode>, bindings: &KeyBindings) -> bool {
+    MOVEMENT_ACTIONS.iter().any(|a| bindings.pressed(keys, *a)) || keys.any_pressed(ARROW_KEYS)
 }
 
 // ── Command handlers ────────────────────────────────────────────────────────
 
 fn handle_undo_redo(params: &mut VimNormalParams) {
     // u: Undo
-    if params.keys.just_pressed(KeyCode::KeyU) && !crate::core::helpers::ctrl_pressed(&params.keys)
+    if params.bindings.just_pressed(&params.keys, KeyAction::Undo) && !crate::core::helpers::ctrl_pressed(&params.keys)
     {
-        if let Some(action) = params.history.pop_undo() {
+        if let Some(mut action) = params.history.pop_undo() {
             info!("[UNDO] popped action: {:?}", action);
-            let action_copy = action.clone();
-            params.history.push_redo(action);
             let mut query = params.query.reborrow();
             apply_action(
-                &action_copy,
+                &mut action,
                 true,
                 &mut params.commands,
                 &mut query,
                 &params.edge_query,
             );
+            params.history.push_redo(action);
         }
     }
 
     // Ctrl+R: Redo
     if params.keys.just_pressed(KeyCode::KeyR) && crate::core::helpers::ctrl_pressed(&params.keys) {
-        if let Some(action) = params.history.pop_redo() {
+        if let Some(mut action) = params.history.pop_redo() {
             info!("[REDO] popped action: {:?}", action);
-            let action_copy = action.clone();
-            params.history.undo_stack.push(action);
             let mut query = params.query.reborrow();
             apply_action(
-                &action_copy,
+                &mut action,
                 false,
                 &mut params.commands,
                 &mut query,
                 &params.edge_query,
             );
+            params.history.undo_stack.push(action);
         }
     }
 }
@@ -189,8 +339,14 @@ fn handle_command_mode_entry(params: &mut VimNormalParams) -> bool {
     false
 }
 
-fn delete_selected_edge_or_node(params: &mut VimNormalParams) {
+/// Delete the selected edge, or else the selected node. False if neither is
+/// selected.
+fn delete_selected_edge_or_node(params: &mut VimNormalParams) -> bool {
     if let Some(edge_entity) = params.selected_edge.0 {
+        if params.bypass_edges.contains(edge_entity) {
+            params.status.set("Edge only shown by :flow off; :flow on to edit the real path");
+            return true;
+        }
         if let Ok((_, edge)) = params.edge_query.get(edge_entity) {
             params.history.push(Action::DeleteEdge {
                 source: edge.source,
@@ -204,27 +360,168 @@ fn delete_selected_edge_or_node(params: &mut VimNormalParams) {
         params.query.iter().next()
     {
         let mut node_edges = Vec::new();
-        for (_, edge) in params.edge_query.iter() {
+        for (edge_entity, edge) in params.edge_query.iter() {
+            if params.bypass_edges.contains(edge_entity) {
+                continue;
+            }
             if edge.source == entity || edge.target == entity {
                 node_edges.push((edge.source, edge.target, edge.label.clone()));
             }
         }
         params.history.push(Action::DeleteNode {
+            entity,
             pos: transform.translation.truncate(),
             text: text_data.content.clone(),
             color: node_color.0,
             edges: node_edges,
         });
+        params.commands.insert_resource(Register {
+            text: text_data.content.clone(),
+            color: node_color.0,
+        });
         delete_node(&mut params.commands, entity, &params.edge_query);
+    } else {
+        return false;
+    }
+    *params.last_change = LastChange::Delete;
+    true
+}
+
+fn handle_count(params: &mut VimNormalParams) -> bool {
+    let ctrl = crate::core::helpers::ctrl_pressed(&params.keys);
+    if params.keys.just_pressed(KeyCode::Escape)
+        || (ctrl && params.keys.just_pressed(KeyCode::BracketLeft))
+    {
+        params.pending.clear_all();
+        return true;
+    }
+    // Marks take the next key verbatim, digits included.
+    if params.pending.mark_set || params.pending.mark_jump {
+        return false;
+    }
+    if crate::core::helpers::shift_pressed(&params.keys) {
+        return false;
+    }
+    if let Some(digit) = digit_just_pressed(&params.keys) {
+        params.pending.count = push_count_digit(params.pending.count, digit);
+        return true;
+    }
+    false
+}
+
+/// `zc`: fold away the nodes reachable only through the selected node.
+/// `zo`: unfold them again. Display-only, see `render::collapse`.
+/// `zp`: pin or unpin the selected node, see `render::pin`.
+/// `zz`: centre the camera on the selection, see [`selection_center`].
+fn handle_fold(params: &mut VimNormalParams) -> bool {
+    if params.bindings.just_pressed(&params.keys, KeyAction::Fold) {
+        let centre = params.pending.z;
+        params.pending.clear_all();
+        if !centre {
+            params.pending.z = true;
+            return true;
+        }
+        let Some(pos) = selection_center(params) else {
+            params.status.set("Nothing to center on");
+            return true;
+        };
+        if let Some(mut cam) = params.camera.iter_mut().next() {
+            request_camera_move(&mut params.commands, &params.config, &mut cam, pos);
+        }
+        return true;
+    }
+    if !params.pending.z {
+        return false;
+    }
+    if params.keys.just_pressed(KeyCode::KeyP) {
+        params.pending.clear_all();
+        params.pins.write(crate::render::pin::PinRequest);
+        return true;
     }
+    let close = params.keys.just_pressed(KeyCode::KeyC);
+    if !close && !params.keys.just_pressed(KeyCode::KeyO) {
+        return false;
+    }
+    params.pending.clear_all();
+    let Some((entity, ..)) = params.query.iter().next() else {
+        return true;
+    };
+    if !close {
+        params.commands.entity(entity).remove::<Collapsed>();
+        params.status.set("Expanded");
+        return true;
+    }
+    let edges: Vec<(Entity, Entity)> =
+        params.edge_query.iter().map(|(_, e)| (e.source, e.target)).collect();
+    let hidden = exclusive_subtree(entity, &edges).len();
+    if hidden == 0 {
+        params.status.set("Nothing to collapse");
+        return true;
+    }
+    params.commands.entity(entity).insert(Collapsed);
+    params.status.set(format!("Collapsed {} nodes", hidden));
+    true
+}
+
+/// Where `zz` centres the camera: the selected node, else the midpoint of the
+/// selected edge's curve, else the middle of the bounding box of all visible
+/// nodes. `None` on an empty canvas.
+fn selection_center(params: &VimNormalParams) -> Option<Vec2> {
+    if let Some((_, transform, ..)) = params.query.iter().next() {
+        return Some(transform.translation.truncate());
+    }
+    let pos = |e: Entity| {
+        params
+            .node_positions
+            .get(e)
+            .ok()
+            .map(|(_, t)| t.translation().truncate())
+    };
+    let edge = params.selected_edge.0.and_then(|e| params.edge_query.get(e).ok());
+    if let Some((entity, edge)) = edge {
+        if let (Some(p0), Some(p2)) = (pos(edge.source), pos(edge.target)) {
+            let idx = params
+                .edge_query
+                .iter()
+                .filter(|(_, e)| e.source == edge.source && e.target == edge.target)
+                .position(|(e, _)| e == entity)
+                .unwrap_or(0);
+            return Some(crate::render::edges::edge_midpoint(p0, p2, idx));
+        }
+    }
+    let points = params.node_positions.iter().map(|(_, t)| t.translation().truncate());
+    let (min, max) = points.fold(None, |acc: Option<(Vec2, Vec2)>, p| match acc {
+        Some((min, max)) => Some((min.min(p), max.max(p))),
+        None => Some((p, p)),
+    })?;
+    Some((min + max) * 0.5)
 }
 
 fn handle_dd_delete(params: &mut VimNormalParams) -> bool {
-    if params.keys.just_pressed(KeyCode::KeyD) {
+    // `dD`: the selected node and its exclusive subtree, see `input::prune`.
+    if params.pending.dd
+        && crate::core::helpers::shift_pressed(&params.keys)
+        && params.bindings.just_pressed(&params.keys, KeyAction::Delete)
+    {
+        params.pending.clear_all();
+        params
+            .subtree_deletes
+            .write(crate::input::prune::DeleteSubtreeRequest);
+        return true;
+    }
+    if params.bindings.just_pressed(&params.keys, KeyAction::Delete) {
+        // Delete has no repeat; a count typed before it is simply dropped.
+        params.pending.count = None;
         if params.pending.ge {
             params.pending.ge = false;
             if let Some((_, _, _, _, Some(src))) = params.query.iter().next() {
-                open_in_editor(&src.file, src.line);
+                let here = current_jump(params);
+                params.jumps.record(here);
+                let editor_cmd = params.config.editor_cmd.as_deref();
+                if let Err(e) = open_in_editor(&src.file, src.line, editor_cmd) {
+                    params.status.set(format!("error: gd: {}", e));
+                    warn!("[GD] {}", e);
+                }
             }
             return true;
         }
@@ -249,13 +546,19 @@ fn handle_node_creation(
     window_q: &Query<&Window, With<PrimaryWindow>>,
     camera_ro_q: &Query<(&Camera, &GlobalTransform), With<crate::core::components::MainCamera>>,
 ) -> bool {
-    if !params.keys.just_pressed(KeyCode::KeyN) {
+    if !params.bindings.just_pressed(&params.keys, KeyAction::NewNode) {
         return false;
     }
     params.pending.clear_all();
     let pos = cursor_world_pos(window_q, camera_ro_q).unwrap_or_else(|| {
         viewport_center_world(window_q, camera_ro_q).unwrap_or(Vec2::ZERO)
     });
+    create_node(params, pos);
+    true
+}
+
+/// `n`: a new selected node at `pos`, entering Insert.
+fn create_node(params: &mut VimNormalParams, pos: Vec2) {
     for (entity, ..) in params.query.iter() {
         params.commands.entity(entity).remove::<Selected>();
     }
@@ -273,7 +576,7 @@ fn handle_node_creation(
         color: params.config.node_color(),
     });
     params.next_state.set(InputMode::VimInsert);
-    true
+    *params.last_change = LastChange::NewNode;
 }
 
 fn handle_insert_mode(
@@ -281,7 +584,7 @@ fn handle_insert_mode(
     window_q: &Query<&Window, With<PrimaryWindow>>,
     camera_ro_q: &Query<(&Camera, &GlobalTransform), With<crate::core::components::MainCamera>>,
 ) -> bool {
-    if !params.keys.just_pressed(KeyCode::KeyI) {
+    if !params.bindings.just_pressed(&params.keys, KeyAction::Insert) {
         return false;
     }
     params.pending.clear_all();
@@ -312,20 +615,24 @@ fn handle_insert_mode(
 }
 
 fn handle_easymotion(params: &mut VimNormalParams) -> bool {
-    if params.keys.just_pressed(KeyCode::KeyF) {
+    if params.bindings.just_pressed(&params.keys, KeyAction::Jump) {
         params.pending.clear_all();
         params.commands.insert_resource(EasymotionTarget::Node);
         params.next_state.set(InputMode::VimEasymotion);
         return true;
     }
 
-    if params.keys.just_pressed(KeyCode::KeyE) && params.pending.ge {
+    // `L` labels edges in one key; `ge` still works. Either way no `g` stays pending.
+    let shift = crate::core::helpers::shift_pressed(&params.keys);
+    if (shift && params.bindings.just_pressed(&params.keys, KeyAction::MoveRight))
+        || (params.bindings.just_pressed(&params.keys, KeyAction::EdgeTarget) && params.pending.ge)
+    {
         params.pending.clear_all();
         params.commands.insert_resource(EasymotionTarget::EdgeLabel);
         params.next_state.set(InputMode::VimEasymotion);
         return true;
     }
-    if params.keys.just_pressed(KeyCode::KeyG) {
+    if params.bindings.just_pressed(&params.keys, KeyAction::Goto) {
         params.pending.dd = false;
         params.pending.y = false;
         params.pending.ce = false;
@@ -335,33 +642,197 @@ fn handle_easymotion(params: &mut VimNormalParams) -> bool {
     false
 }
 
-fn handle_yy_duplicate(params: &mut VimNormalParams) -> bool {
-    if !params.keys.just_pressed(KeyCode::KeyY) {
+/// Topmost (`top`) or bottommost node by center y; ties go to the smallest x.
+fn extreme_node(nodes: impl IntoIterator<Item = (Entity, Vec2)>, top: bool) -> Option<Entity> {
+    nodes
+        .into_iter()
+        .min_by(|(_, a), (_, b)| {
+            let by_y = if top { b.y.total_cmp(&a.y) } else { a.y.total_cmp(&b.y) };
+            by_y.then(a.x.total_cmp(&b.x))
+        })
+        .map(|(entity, _)| entity)
+}
+
+/// `gg` / `G`: select the topmost / bottommost node and center the camera on it.
+fn handle_gg(params: &mut VimNormalParams) -> bool {
+    if !params.bindings.just_pressed(&params.keys, KeyAction::Goto) {
+        return false;
+    }
+    let top = if crate::core::helpers::shift_pressed(&params.keys) {
+        false
+    } else if params.pending.ge {
+        true
+    } else {
+        return false;
+    };
+    params.pending.clear_all();
+    let nodes = params
+        .node_positions
+        .iter()
+        .map(|(e, t)| (e, t.translation().truncate()));
+    let Some(target) = extreme_node(nodes, top) else {
+        return true;
+    };
+    if jump_to_node(params, target) {
+        info!("[GOTO] {} → {:?}", if top { "gg" } else { "G" }, target);
+    }
+    true
+}
+
+/// Record a jump, make `target` the only `Selected` node and centre the
+/// camera on it. False if `target` is not a visible node.
+fn jump_to_node(params: &mut VimNormalParams, target: Entity) -> bool {
+    let Ok((_, transform)) = params.node_positions.get(target) else {
+        return false;
+    };
+    let pos = transform.translation().truncate();
+    let here = current_jump(params);
+    params.jumps.record(here);
+    for (prev, ..) in params.query.iter() {
+        params.commands.entity(prev).remove::<Selected>();
+    }
+    params.commands.entity(target).insert(Selected);
+    params.selected_edge.0 = None;
+    if let Some(mut cam_transform) = params.camera.iter_mut().next() {
+        request_camera_move(&mut params.commands, &params.config, &mut cam_transform, pos);
+    }
+    true
+}
+
+/// `n` / `N` while a `:grep` or `/` search is active: select the next /
+/// previous match in reading order (wrapping) and centre on it. Without an
+/// active search `n` falls through to node creation.
+fn handle_search_step(params: &mut VimNormalParams) -> bool {
+    if !params.search.is_active()
+        || !params.bindings.just_pressed(&params.keys, KeyAction::NewNode)
+    {
+        return false;
+    }
+    params.pending.clear_all();
+    let forward = !crate::core::helpers::shift_pressed(&params.keys);
+    // Skip matches that were deleted or folded away since the search ran.
+    for _ in 0..params.search.ordered.len() {
+        let Some(target) = params.search.step(forward) else {
+            break;
+        };
+        if jump_to_node(params, target) {
+            let (i, n) = (params.search.idx + 1, params.search.ordered.len());
+            params.status.set(format!("[{}/{}] {}", i, n, params.search.query));
+            info!("[SEARCH] {} {}/{} → {:?}", params.search.query, i, n, target);
+            return true;
+        }
+    }
+    params.status.set(format!("search: no visible matches for {}", params.search.query));
+    true
+}
+
+/// Message sent by `:reverse`: flip the `SelectedEdge`.
+#[derive(Message)]
+pub struct ReverseEdgeRequest;
+
+/// Swap `edge`'s source and target and record an undoable `ReverseEdge`.
+fn reverse_edge(
+    commands: &mut Commands,
+    history: &mut UndoHistory,
+    status: &mut StatusMessage,
+    entity: Entity,
+    edge: &Edge,
+) {
+    let action = Action::ReverseEdge {
+        entity,
+        source: edge.source,
+        target: edge.target,
+    };
+    let (source, target) = (edge.target, edge.source);
+    commands.entity(entity).insert(Edge {
+        source,
+        target,
+        label: edge.label.clone(),
+    });
+    history.push(action);
+    status.set("Edge reversed");
+    info!("[EDGE] reversed {:?}", entity);
+}
+
+/// `gr`: reverse the selected edge.
+fn handle_reverse_edge(params: &mut VimNormalParams) -> bool {
+    if !params.pending.ge
+        || !params.keys.just_pressed(KeyCode::KeyR)
+        || crate::core::helpers::ctrl_pressed(&params.keys)
+    {
+        return false;
+    }
+    params.pending.clear_all();
+    reverse_selected_edge(params);
+    true
+}
+
+fn reverse_selected_edge(params: &mut VimNormalParams) {
+    let selected = params.selected_edge.0.and_then(|e| params.edge_query.get(e).ok());
+    match selected {
+        Some((entity, edge)) => {
+            let edge = edge.clone();
+            reverse_edge(&mut params.commands, &mut params.history, &mut params.status, entity, &edge);
+            *params.last_change = LastChange::ReverseEdge;
+        }
+        None => params.status.set("No edge selected"),
+    }
+}
+
+/// `gt` / `gT`: switch to the next / previous tab.
+fn handle_tab_step(params: &mut VimNormalParams) -> bool {
+    if !params.pending.ge
+        || !params.keys.just_pressed(KeyCode::KeyT)
+        || crate::core::helpers::ctrl_pressed(&params.keys)
+    {
+        return false;
+    }
+    params.pending.clear_all();
+    params.tabs.write(if crate::core::helpers::shift_pressed(&params.keys) {
+        crate::core::tabs::TabRequest::Prev
+    } else {
+        crate::core::tabs::TabRequest::Next
+    });
+    true
+}
+
+/// Handles `ReverseEdgeRequest` from the command line.
+pub fn handle_reverse_edge_requests(
+    mut requests: MessageReader<ReverseEdgeRequest>,
+    mut commands: Commands,
+    mut history: ResMut<UndoHistory>,
+    mut status: ResMut<StatusMessage>,
+    selected_edge: Res<SelectedEdge>,
+    edge_query: Query<&Edge>,
+) {
+    for _ in requests.read() {
+        match selected_edge.0.and_then(|e| edge_query.get(e).ok().map(|edge| (e, edge))) {
+            Some((entity, edge)) => {
+                reverse_edge(&mut commands, &mut history, &mut status, entity, edge);
+            }
+            None => status.set("No edge selected"),
+        }
+    }
+}
+
+fn handle_yy_yank(params: &mut VimNormalParams) -> bool {
+    if !params.bindings.just_pressed(&params.keys, KeyAction::Yank) {
         return false;
     }
     params.pending.dd = false;
     params.pending.ge = false;
     params.pending.ce = false;
-    if params.pending.y {
+    if crate::core::helpers::shift_pressed(&params.keys) {
         params.pending.y = false;
-        if let Some((entity, transform, text_data, node_color, _)) = params.query.iter().next() {
-            let pos = transform.translation.truncate() + Vec2::new(50.0, 50.0);
-            let new_entity = spawn_node_with_color(
-                &mut params.commands,
-                pos.x,
-                pos.y,
-                &text_data.content,
-                node_color.0,
-            );
-            params.commands.entity(entity).remove::<Selected>();
-            params.commands.entity(new_entity).insert(Selected);
-            params.history.push(Action::CreateNode {
-                entity: new_entity,
-                pos,
+        duplicate_with_edges(params);
+    } else if params.pending.y {
+        params.pending.y = false;
+        if let Some((_, _, text_data, node_color, _)) = params.query.iter().next() {
+            params.commands.insert_resource(Register {
                 text: text_data.content.clone(),
                 color: node_color.0,
             });
-            params.next_state.set(InputMode::VimInsert);
+            params.status.set("Yanked node");
         }
     } else {
         params.pending.y = true;
@@ -369,8 +840,101 @@ fn handle_yy_duplicate(params: &mut VimNormalParams) -> bool {
     true
 }
 
+fn handle_paste(
+    params: &mut VimNormalParams,
+    window_q: &Query<&Window, With<PrimaryWindow>>,
+    camera_ro_q: &Query<(&Camera, &GlobalTransform), With<crate::core::components::MainCamera>>,
+) -> bool {
+    if !params.bindings.just_pressed(&params.keys, KeyAction::Paste) {
+        return false;
+    }
+    params.pending.clear_all();
+    let pos = cursor_world_pos(window_q, camera_ro_q).unwrap_or_else(|| {
+        viewport_center_world(window_q, camera_ro_q).unwrap_or(Vec2::ZERO)
+    });
+    paste_register(params, pos);
+    true
+}
+
+/// `p`: the register as a new selected node at `pos`. Nothing without a register.
+fn paste_register(params: &mut VimNormalParams, pos: Vec2) {
+    let Some(register) = params.register.as_deref().cloned() else {
+        return;
+    };
+    for (entity, ..) in params.query.iter() {
+        params.commands.entity(entity).remove::<Selected>();
+    }
+    params.selected_edge.0 = None;
+    let entity = spawn_canvas_node(
+        &mut params.commands,
+        pos,
+        register.text.clone(),
+        register.color,
+        true,
+    );
+    params.history.push(Action::CreateNode {
+        entity,
+        pos,
+        text: register.text,
+        color: register.color,
+    });
+    *params.last_change = LastChange::Paste;
+}
+
+/// How far `Y` places the clone from the original node.
+const DUPLICATE_OFFSET: Vec2 = Vec2::new(60.0, -60.0);
+
+/// `Y`: clone the selected node next to it with copies of its incident edges,
+/// as one undo step. The clone is a user copy, so a crawled node's
+/// `SourceLocation` stays behind.
+fn duplicate_with_edges(params: &mut VimNormalParams) {
+    let Some((original, transform, text_data, node_color, _)) = params.query.iter().next() else {
+        params.status.set("No node selected");
+        return;
+    };
+    let pos = transform.translation.truncate() + DUPLICATE_OFFSET;
+    let (text, color) = (text_data.content.clone(), node_color.0);
+    params.commands.entity(original).remove::<Selected>();
+    params.selected_edge.0 = None;
+    let clone = spawn_canvas_node(&mut params.commands, pos, text.clone(), color, true);
+    let mut actions = vec![Action::CreateNode {
+        entity: clone,
+        pos,
+        text,
+        color,
+    }];
+    let swap = |e: Entity| if e == original { clone } else { e };
+    for (edge_entity, edge) in params.edge_query.iter() {
+        if edge.source != original && edge.target != original {
+            continue;
+        }
+        if params.bypass_edges.contains(edge_entity) {
+            continue;
+        }
+        let (source, target, label) = (swap(edge.source), swap(edge.target), edge.label.clone());
+        let entity = params
+            .commands
+            .spawn(Edge {
+                source,
+                target,
+                label: label.clone(),
+            })
+            .id();
+        actions.push(Action::CreateEdge {
+            entity,
+            source,
+            target,
+            label,
+        });
+    }
+    let edges = actions.len() - 1;
+    params.history.push(Action::Batch(actions));
+    params.status.set(format!("Duplicated node with {} edge(s)", edges));
+    *params.last_change = LastChange::Duplicate;
+}
+
 fn handle_ce_create_edge(params: &mut VimNormalParams) -> bool {
-    if params.keys.just_pressed(KeyCode::KeyE) && params.pending.ce {
+    if params.bindings.just_pressed(&params.keys, KeyAction::EdgeTarget) && params.pending.ce {
         params.pending.clear_all();
         if let Some((source_entity, ..)) = params.query.iter().next() {
             params
@@ -381,7 +945,7 @@ fn handle_ce_create_edge(params: &mut VimNormalParams) -> bool {
         }
         return true;
     }
-    if params.keys.just_pressed(KeyCode::KeyC) {
+    if params.bindings.just_pressed(&params.keys, KeyAction::Connect) {
         params.pending.dd = false;
         params.pending.ge = false;
         params.pending.y = false;
@@ -392,10 +956,16 @@ fn handle_ce_create_edge(params: &mut VimNormalParams) -> bool {
 }
 
 fn handle_append_node(params: &mut VimNormalParams) -> bool {
-    if !params.keys.just_pressed(KeyCode::KeyA) {
+    if !params.bindings.just_pressed(&params.keys, KeyAction::Append) {
         return false;
     }
     params.pending.clear_all();
+    append_node(params);
+    true
+}
+
+/// `a`: a new node right of the selected one, linked from it, entering Insert.
+fn append_node(params: &mut VimNormalParams) {
     if let Some((source_entity, source_transform, ..)) = params.query.iter_mut().next() {
         let new_pos = (source_transform.translation + Vec3::new(200.0, 0.0, 0.0)).truncate();
         params.commands.entity(source_entity).remove::<Selected>();
@@ -427,26 +997,144 @@ fn handle_append_node(params: &mut VimNormalParams) -> bool {
             label: None,
         });
         params.next_state.set(InputMode::VimInsert);
+        *params.last_change = LastChange::Append;
+    }
+}
+
+/// `o` / Shift+`o`: new node one flow row below / above the selection, linked
+/// selected → below or above → selected. Unconnected at the viewport center
+/// when nothing is selected.
+fn handle_open_line(
+    params: &mut VimNormalParams,
+    window_q: &Query<&Window, With<PrimaryWindow>>,
+    camera_ro_q: &Query<(&Camera, &GlobalTransform), With<crate::core::components::MainCamera>>,
+) -> bool {
+    if !params.bindings.just_pressed(&params.keys, KeyAction::OpenLine)
+        || crate::core::helpers::ctrl_or_cmd_pressed(&params.keys)
+    {
+        return false;
+    }
+    params.pending.clear_all();
+    let above = crate::core::helpers::shift_pressed(&params.keys);
+    let center = viewport_center_world(window_q, camera_ro_q).unwrap_or(Vec2::ZERO);
+    open_line(params, above, center);
+    true
+}
+
+/// `o` / `O` body; `center` places the node when nothing is selected.
+fn open_line(params: &mut VimNormalParams, above: bool, center: Vec2) {
+    *params.last_change = LastChange::OpenLine { above };
+    let color = params.config.node_color();
+    let selected = params
+        .query
+        .iter()
+        .next()
+        .map(|(entity, transform, ..)| (entity, transform.translation.truncate()));
+    let Some((selected_entity, selected_pos)) = selected else {
+        let pos = center;
+        let entity = spawn_canvas_node(&mut params.commands, pos, "", color, true);
+        params.history.push(Action::CreateNode {
+            entity,
+            pos,
+            text: "".to_string(),
+            color,
+        });
+        params.next_state.set(InputMode::VimInsert);
+        return;
+    };
+
+    let row = if above { params.config.flow_row_height } else { -params.config.flow_row_height };
+    let new_pos = selected_pos + Vec2::new(0.0, row);
+    params.commands.entity(selected_entity).remove::<Selected>();
+    params.selected_edge.0 = None;
+    let new_node = spawn_canvas_node(&mut params.commands, new_pos, "", color, true);
+    let (source, target) = if above {
+        (new_node, selected_entity)
+    } else {
+        (selected_entity, new_node)
+    };
+    let edge_entity = params
+        .commands
+        .spawn(Edge {
+            source,
+            target,
+            label: None,
+        })
+        .id();
+    // One step, so `u` never leaves the node without its edge.
+    params.history.push(Action::Batch(vec![
+        Action::CreateNode {
+            entity: new_node,
+            pos: new_pos,
+            text: "".to_string(),
+            color,
+        },
+        Action::CreateEdge {
+            entity: edge_entity,
+            source,
+            target,
+            label: None,
+        },
+    ]));
+    params.next_state.set(InputMode::VimInsert);
+}
+
+/// `.`: replay `LastChange` against the current selection, or at the cursor
+/// for changes that create a free-standing node.
+fn handle_dot_repeat(
+    params: &mut VimNormalParams,
+    window_q: &Query<&Window, With<PrimaryWindow>>,
+    camera_ro_q: &Query<(&Camera, &GlobalTransform), With<crate::core::components::MainCamera>>,
+) -> bool {
+    if !params.bindings.just_pressed(&params.keys, KeyAction::Repeat)
+        || crate::core::helpers::shift_pressed(&params.keys)
+    {
+        return false;
+    }
+    params.pending.clear_all();
+    let center = viewport_center_world(window_q, camera_ro_q).unwrap_or(Vec2::ZERO);
+    let cursor = cursor_world_pos(window_q, camera_ro_q).unwrap_or(center);
+    match *params.last_change {
+        LastChange::None => params.status.set("Nothing to repeat"),
+        LastChange::NewNode => create_node(params, cursor),
+        LastChange::Delete => {
+            delete_selected_edge_or_node(params);
+        }
+        LastChange::Paste => paste_register(params, cursor),
+        LastChange::Append => append_node(params),
+        LastChange::OpenLine { above } => open_line(params, above, center),
+        LastChange::ReverseEdge => reverse_selected_edge(params),
+        LastChange::Duplicate => duplicate_with_edges(params),
     }
     true
 }
 
+fn handle_visual_entry(params: &mut VimNormalParams) -> bool {
+    if !params.bindings.just_pressed(&params.keys, KeyAction::Visual) {
+        return false;
+    }
+    params.pending.clear_all();
+    params.visual.0.clear();
+    // Start the set from the current node so `v` then `d` acts on it.
+    if let Some((entity, ..)) = params.query.iter().next() {
+        params.visual.0.insert(entity);
+    }
+    params.next_state.set(InputMode::VimVisual);
+    true
+}
+
 fn handle_marks(
     params: &mut VimNormalParams,
     window_q: &Query<&Window, With<PrimaryWindow>>,
     camera_ro_q: &Query<(&Camera, &GlobalTransform), With<crate::core::components::MainCamera>>,
-    camera_mut_q: &mut Query<
-        &mut Transform,
-        (With<crate::core::components::MainCamera>, Without<Selected>),
-    >,
 ) -> bool {
-    if params.keys.just_pressed(KeyCode::KeyM) {
+    if params.bindings.just_pressed(&params.keys, KeyAction::SetMark) {
         params.pending.clear_all();
         params.pending.mark_set = true;
         return true;
     }
 
-    if params.keys.just_pressed(KeyCode::Quote) {
+    if params.bindings.just_pressed(&params.keys, KeyAction::JumpMark) {
         params.pending.clear_all();
         params.pending.mark_jump = true;
         return true;
@@ -474,9 +1162,11 @@ fn handle_marks(
         if let Some(key) = params.keys.get_just_pressed().next() {
             if let Some(ch) = crate::core::helpers::keycode_to_char(key) {
                 if let Some(pos) = crate::core::marks::get_mark(&params.marks, ch) {
-                    if let Some(mut cam_transform) = camera_mut_q.iter_mut().next() {
-                        cam_transform.translation.x = pos.x;
-                        cam_transform.translation.y = pos.y;
+                    let here = current_jump(params);
+                    params.jumps.record(here);
+                    if let Some(mut cam_transform) = params.camera.iter_mut().next() {
+                        let commands = &mut params.commands;
+                        request_camera_move(commands, &params.config, &mut cam_transform, pos);
                         info!("[MARK] jumped to mark '{}' at {:?}", ch, pos);
                     }
                 }
@@ -488,11 +1178,61 @@ fn handle_marks(
     false
 }
 
+/// Camera centre and selected node, recorded in the jump list before a jump.
+fn current_jump(params: &VimNormalParams) -> JumpEntry {
+    JumpEntry {
+        camera: params
+            .camera
+            .iter()
+            .next()
+            .map(|t| t.translation.truncate())
+            .unwrap_or_default(),
+        selected: params.query.iter().next().map(|(e, ..)| e),
+    }
+}
+
+/// Ctrl+O: back to the position before the last jump. Ctrl+I: forward again.
+/// The recorded node is re-selected if it still exists.
+fn handle_jump_list(params: &mut VimNormalParams) -> bool {
+    if !crate::core::helpers::ctrl_pressed(&params.keys) {
+        return false;
+    }
+    let back = params.keys.just_pressed(KeyCode::KeyO);
+    if !back && !params.keys.just_pressed(KeyCode::KeyI) {
+        return false;
+    }
+    let nodes = &params.canvas_nodes;
+    params.jumps.prune(|e| nodes.contains(e));
+    let entry = if back {
+        let here = current_jump(params);
+        params.jumps.back(here)
+    } else {
+        params.jumps.forward()
+    };
+    params.pending.clear_all();
+    let Some(entry) = entry else {
+        return true;
+    };
+    if let Some(mut cam_transform) = params.camera.iter_mut().next() {
+        let to = entry.camera;
+        request_camera_move(&mut params.commands, &params.config, &mut cam_transform, to);
+    }
+    if let Some(target) = entry.selected {
+        for (prev, ..) in params.query.iter() {
+            params.commands.entity(prev).remove::<Selected>();
+        }
+        params.commands.entity(target).insert(Selected);
+        params.selected_edge.0 = None;
+    }
+    info!("[JUMP] → {:?}", entry);
+    true
+}
+
 fn handle_hjkl_movement(params: &mut VimNormalParams) {
     // Capture move end
     if let Some(start_pos) = params.start_move_pos.0 {
         if let Some((entity, transform, ..)) = params.query.iter().next() {
-            if !is_movement_pressed(&params.keys) {
+            if !is_movement_pressed(&params.keys, &params.bindings) {
                 let end_pos = transform.translation.truncate();
                 if (start_pos - end_pos).length() > 0.1 {
                     params.history.push(Action::MoveNode {
@@ -509,28 +1249,36 @@ fn handle_hjkl_movement(params: &mut VimNormalParams) {
 
     // Node movement
     if let Some((_, mut node_transform, ..)) = params.query.iter_mut().next() {
-        if is_movement_pressed(&params.keys) {
+        if is_movement_pressed(&params.keys, &params.bindings) {
             if params.start_move_pos.0.is_none() {
                 params.start_move_pos.0 = Some(node_transform.translation.truncate());
             }
             params.hjkl_hold.0 += params.time.delta_secs();
-            let speed = if params.hjkl_hold.0 > params.config.hjkl_accel_threshold {
+            let held = params.hjkl_hold.0 > params.config.hjkl_accel_threshold;
+            let dir = movement_direction(&params.keys, &params.bindings);
+            if let Some(grid) = params.config.snap_grid() {
+                // Grid mode: one cell per press, repeating once the key is held.
+                if !held && !is_movement_just_pressed(&params.keys, &params.bindings) {
+                    return;
+                }
+                let cells = params.pending.count.take().unwrap_or(1) as f32;
+                let pos = snap_to_grid(node_transform.translation.truncate(), grid)
+                    + dir * grid * cells;
+                node_transform.translation.x = pos.x;
+                node_transform.translation.y = pos.y;
+                return;
+            }
+            let mut speed = if held {
                 params.config.hjkl_base_speed * params.config.hjkl_accel_mult
             } else {
                 params.config.hjkl_base_speed
             };
-            if params.keys.pressed(KeyCode::KeyH) || params.keys.pressed(KeyCode::ArrowLeft) {
-                node_transform.translation.x -= speed;
-            }
-            if params.keys.pressed(KeyCode::KeyL) || params.keys.pressed(KeyCode::ArrowRight) {
-                node_transform.translation.x += speed;
-            }
-            if params.keys.pressed(KeyCode::KeyK) || params.keys.pressed(KeyCode::ArrowUp) {
-                node_transform.translation.y += speed;
-            }
-            if params.keys.pressed(KeyCode::KeyJ) || params.keys.pressed(KeyCode::ArrowDown) {
-                node_transform.translation.y -= speed;
+            // `5j`: the first step of the move is repeated `count` times.
+            if let Some(count) = params.pending.count.take() {
+                speed *= count as f32;
             }
+            node_transform.translation.x += dir.x * speed;
+            node_transform.translation.y += dir.y * speed;
         }
     }
 }
@@ -541,24 +1289,177 @@ pub fn vim_normal_system(
     mut params: VimNormalParams,
     window_q: Query<&Window, With<PrimaryWindow>>,
     camera_ro_q: Query<(&Camera, &GlobalTransform), With<crate::core::components::MainCamera>>,
-    mut camera_mut_q: Query<
-        &mut Transform,
-        (With<crate::core::components::MainCamera>, Without<Selected>),
-    >,
 ) {
     handle_undo_redo(&mut params);
+    if handle_jump_list(&mut params) { return; }
     if handle_command_mode_entry(&mut params) { return; }
+    if handle_count(&mut params) { return; }
+    if handle_fold(&mut params) { return; }
     if handle_dd_delete(&mut params) { return; }
+    if handle_search_step(&mut params) { return; }
+    if handle_dot_repeat(&mut params, &window_q, &camera_ro_q) { return; }
     if handle_node_creation(&mut params, &window_q, &camera_ro_q) { return; }
     if handle_insert_mode(&mut params, &window_q, &camera_ro_q) { return; }
+    if handle_gg(&mut params) { return; }
     if handle_easymotion(&mut params) { return; }
-    if handle_yy_duplicate(&mut params) { return; }
+    if handle_reverse_edge(&mut params) { return; }
+    if handle_tab_step(&mut params) { return; }
+    if handle_yy_yank(&mut params) { return; }
+    if handle_paste(&mut params, &window_q, &camera_ro_q) { return; }
     if handle_ce_create_edge(&mut params) { return; }
     if handle_append_node(&mut params) { return; }
-    if handle_marks(&mut params, &window_q, &camera_ro_q, &mut camera_mut_q) { return; }
+    if handle_open_line(&mut params, &window_q, &camera_ro_q) { return; }
+    if handle_visual_entry(&mut params) { return; }
+    if handle_marks(&mut params, &window_q, &camera_ro_q) { return; }
     handle_hjkl_movement(&mut params);
 }
 
+// ── Visual mode ─────────────────────────────────────────────────────────────
+
+type VisualNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static mut Transform, &'static TextData, &'static NodeColor),
+    (With<CanvasNode>, Without<MainCamera>, Without<InactiveCanvas>),
+>;
+
+#[derive(SystemParam)]
+pub struct VimVisualParams<'w, 's> {
+    pub keys: Res<'w, ButtonInput<KeyCode>>,
+    pub time: Res<'w, Time>,
+    pub next_state: ResMut<'w, NextState<InputMode>>,
+    pub commands: Commands<'w, 's>,
+    pub visual: ResMut<'w, VisualSelection>,
+    pub hjkl_hold: ResMut<'w, HjklHoldTime>,
+    pub move_start: ResMut<'w, VisualMoveStart>,
+    pub history: ResMut<'w, UndoHistory>,
+    pub status: ResMut<'w, StatusMessage>,
+    pub config: Res<'w, crate::core::config::GlyphConfig>,
+    pub bindings: Res<'w, KeyBindings>,
+    pub nodes: VisualNodeQuery<'w, 's>,
+    pub edge_query: Query<'w, 's, (Entity, &'static Edge)>,
+    pub bypass_edges: Query<'w, 's, (), With<FlowBypass>>,
+}
+
+/// Delete every visual member as one `Batch` of `DeleteNode`s, so a single
+/// `u` brings back the whole batch together with its edges.
+fn delete_visual_selection(params: &mut VimVisualParams) {
+    let members = std::mem::take(&mut params.visual.0);
+    let mut actions = Vec::new();
+    for entity in members {
+        let Ok((_, transform, text_data, node_color)) = params.nodes.get(entity) else {
+            continue;
+        };
+        let edges = params
+            .edge_query
+            .iter()
+            .filter(|(e, edge)| {
+                !params.bypass_edges.contains(*e)
+                    && (edge.source == entity || edge.target == entity)
+            })
+            .map(|(_, edge)| (edge.source, edge.target, edge.label.clone()))
+            .collect();
+        actions.push(Action::DeleteNode {
+            entity,
+            pos: transform.translation.truncate(),
+            text: text_data.content.clone(),
+            color: node_color.0,
+            edges,
+        });
+        delete_node(&mut params.commands, entity, &params.edge_query);
+    }
+    let deleted = actions.len();
+    if deleted == 0 {
+        return;
+    }
+    params.history.push(Action::Batch(actions));
+    params.status.set(format!("Deleted {} nodes", deleted));
+    info!("[VISUAL] deleted {} nodes", deleted);
+}
+
+fn handle_visual_movement(params: &mut VimVisualParams) {
+    if !is_movement_pressed(&params.keys, &params.bindings) {
+        if let Some(start) = params.move_start.0.take() {
+            let moves: Vec<Action> = start
+                .into_iter()
+                .filter_map(|(entity, from)| {
+                    let (_, transform, ..) = params.nodes.get(entity).ok()?;
+                    let to = transform.translation.truncate();
+                    (from.distance(to) > 0.1).then_some(Action::MoveNode { entity, from, to })
+                })
+                .collect();
+            if !moves.is_empty() {
+                params.history.push(Action::Batch(moves));
+            }
+            params.hjkl_hold.0 = 0.0;
+        }
+        return;
+    }
+    if params.visual.0.is_empty() {
+        return;
+    }
+    if params.move_start.0.is_none() {
+        let start = params
+            .visual
+            .0
+            .iter()
+            .filter_map(|&e| params.nodes.get(e).ok())
+            .map(|(e, transform, ..)| (e, transform.translation.truncate()))
+            .collect();
+        params.move_start.0 = Some(start);
+    }
+    params.hjkl_hold.0 += params.time.delta_secs();
+    let speed = if params.hjkl_hold.0 > params.config.hjkl_accel_threshold {
+        params.config.hjkl_base_speed * params.config.hjkl_accel_mult
+    } else {
+        params.config.hjkl_base_speed
+    };
+    let delta = movement_direction(&params.keys, &params.bindings) * speed;
+    let members: Vec<Entity> = params.visual.0.iter().copied().collect();
+    for entity in members {
+        if let Ok((_, mut transform, ..)) = params.nodes.get_mut(entity) {
+            transform.translation.x += delta.x;
+            transform.translation.y += delta.y;
+        }
+    }
+}
+
+/// in_state(VimVisual): `f` toggles a node via easymotion, hjkl moves every
+/// member, `d` deletes them all, `:` opens the command line on the set,
+/// Esc/Ctrl+[/`v` clears the set and returns to VimNormal.
+pub fn vim_visual_system(mut params: VimVisualParams) {
+    let ctrl = crate::core::helpers::ctrl_pressed(&params.keys);
+    if params.keys.just_pressed(KeyCode::Escape)
+        || (ctrl && params.keys.just_pressed(KeyCode::BracketLeft))
+        || params.bindings.just_pressed(&params.keys, KeyAction::Visual)
+    {
+        params.visual.0.clear();
+        params.next_state.set(InputMode::VimNormal);
+        return;
+    }
+    // `:` keeps the set so `:align` / `:distribute` can act on it.
+    if crate::core::helpers::shift_pressed(&params.keys)
+        && params.keys.just_pressed(KeyCode::Semicolon)
+    {
+        params.next_state.set(InputMode::VimCommand);
+        return;
+    }
+    if params.bindings.just_pressed(&params.keys, KeyAction::Jump) {
+        params.commands.insert_resource(EasymotionTarget::VisualToggle);
+        params.next_state.set(InputMode::VimEasymotion);
+        return;
+    }
+    if params.bindings.just_pressed(&params.keys, KeyAction::Delete)
+        || params.keys.just_pressed(KeyCode::Delete)
+        || params.keys.just_pressed(KeyCode::Backspace)
+    {
+        delete_visual_selection(&mut params);
+        params.next_state.set(InputMode::VimNormal);
+        return;
+    }
+    handle_visual_movement(&mut params);
+}
+
 // ── Insert mode ─────────────────────────────────────────────────────────────
 
 pub fn vim_insert_system(
@@ -567,6 +1468,7 @@ pub fn vim_insert_system(
     time: Res<Time>,
     mut backspace_hold: ResMut<BackspaceHoldTime>,
     mut original_text: ResMut<OriginalText>,
+    mut caret: ResMut<InsertCaret>,
     mut history: ResMut<UndoHistory>,
     mut next_state: ResMut<NextState<InputMode>>,
     selected_edge: Res<SelectedEdge>,
@@ -621,42 +1523,51 @@ pub fn vim_insert_system(
         return;
     }
 
-    if let Some(edge_entity) = selected_edge.0 {
-        if let Ok(mut edge) = edge_query.get_mut(edge_entity) {
-            if edge.label.is_none() {
-                edge.label = Some(String::new());
-            }
-            let label = edge.label.as_mut().unwrap();
-
-            let backspace_pressed =
-                keys.pressed(Key::Backspace) || (ctrl && keycodes.pressed(KeyCode::KeyH));
-            let backspace_just =
-                keys.just_pressed(Key::Backspace) || (ctrl && keycodes.just_pressed(KeyCode::KeyH));
-            if backspace_pressed {
-                let mut do_delete = backspace_just;
-                if backspace_just {
-                    backspace_hold.0 = 0.0;
-                } else {
-                    backspace_hold.0 += time.delta_secs();
-                    if backspace_hold.0 >= BACKSPACE_INITIAL_DELAY {
-                        do_delete = true;
-                        backspace_hold.0 -= BACKSPACE_REPEAT_INTERVAL;
-                    }
-                }
-                if do_delete {
-                    label.pop();
-                }
-                return;
-            }
-            backspace_hold.0 = 0.0;
+    // Edit through `bypass_change_detection` so idle frames don't re-wrap the
+    // label; `set_changed` below flags real edits and caret moves.
+    let mut edge = selected_edge
+        .0
+        .and_then(|e| edge_query.get_mut(e).ok().map(|edge| (e, edge)));
+    let mut node = if edge.is_none() {
+        query.iter_mut().next()
+    } else {
+        None
+    };
+    let (entity, text) = match (&mut edge, &mut node) {
+        (Some((e, edge)), _) => (
+            *e,
+            edge.bypass_change_detection()
+                .label
+                .get_or_insert_with(String::new),
+        ),
+        (None, Some((e, data))) => (*e, &mut data.bypass_change_detection().content),
+        (None, None) => return,
+    };
 
-            for key in keys.get_just_pressed() {
-                if let Key::Character(c) = key {
-                    label.push_str(c.as_str());
-                }
-            }
-            return;
-        }
+    let len = text.chars().count();
+    let mut changed = false;
+    if caret.entity != Some(entity) {
+        *caret = InsertCaret {
+            entity: Some(entity),
+            pos: len,
+        };
+        changed = true;
+    }
+    let old_pos = caret.pos.min(len);
+    caret.pos = if keys.just_pressed(Key::Home) {
+        0
+    } else if keys.just_pressed(Key::End) {
+        len
+    } else if keys.just_pressed(Key::ArrowLeft) {
+        old_pos.saturating_sub(1)
+    } else if keys.just_pressed(Key::ArrowRight) {
+        (old_pos + 1).min(len)
+    } else {
+        old_pos
+    };
+    changed |= caret.pos != old_pos;
+    if keys.just_pressed(Key::Delete) {
+        changed |= caret.delete(text);
     }
 
     let backspace_pressed =
@@ -675,19 +1586,35 @@ pub fn vim_insert_system(
             }
         }
         if do_delete {
-            if let Some((_, mut text_data)) = query.iter_mut().next() {
-                text_data.content.pop();
+            changed |= caret.backspace(text);
+        }
+    } else {
+        backspace_hold.0 = 0.0;
+        // Ctrl chords are commands (Ctrl+V pastes), not text.
+        if !ctrl {
+            for key in keys.get_just_pressed() {
+                if let Key::Character(c) = key {
+                    caret.insert(text, c.as_str());
+                    changed = true;
+                }
             }
         }
-        return;
     }
-    backspace_hold.0 = 0.0;
 
-    for key in keys.get_just_pressed() {
-        if let Key::Character(c) = key {
-            if let Some((_, mut text_data)) = query.iter_mut().next() {
-                text_data.content.push_str(c.as_str());
-            }
+    if changed {
+        match (edge, node) {
+            (Some((_, mut edge)), _) => edge.set_changed(),
+            (None, Some((_, mut data))) => data.set_changed(),
+            (None, None) => {}
+        }
+    }
+}
+
+/// OnExit(VimInsert): drop the caret and re-sync the node label without its bar.
+pub fn clear_insert_caret(mut caret: ResMut<InsertCaret>, mut texts: Query<&mut TextData>) {
+    if let Some(entity) = caret.entity.take() {
+        if let Ok(mut text_data) = texts.get_mut(entity) {
+            text_data.set_changed();
         }
     }
 }
@@ -699,16 +1626,380 @@ pub fn standard_mode_system(
     mut commands: Commands,
     mut selected_edge: ResMut<SelectedEdge>,
     mut next_state: ResMut<NextState<InputMode>>,
+    mut history: ResMut<UndoHistory>,
+    edge_query: Query<(Entity, &Edge), Without<FlowBypass>>,
+    dragging: Query<(), With<Dragging>>,
 ) {
     let ctrl = crate::core::helpers::ctrl_pressed(&keys);
-    if keys.just_pressed(KeyCode::Escape) || (ctrl && keys.just_pressed(KeyCode::BracketLeft)) {
+    if keys.just_pressed(KeyCode::Escape) && !dragging.is_empty() {
+        // Escape mid-drag cancels the drag (`node_drag_system`) instead.
+    } else if keys.just_pressed(KeyCode::Escape)
+        || (ctrl && keys.just_pressed(KeyCode::BracketLeft))
+    {
         next_state.set(InputMode::VimNormal);
     } else if keys.just_pressed(KeyCode::KeyI) && selected_edge.0.is_some() {
         next_state.set(InputMode::VimInsert);
     } else if (keys.just_pressed(KeyCode::Delete) || keys.just_pressed(KeyCode::Backspace))
         && selected_edge.0.is_some()
     {
+        // A `:flow off` bypass edge is display-only and stays.
         let edge_entity = selected_edge.0.take().unwrap();
-        commands.entity(edge_entity).despawn();
+        if let Ok((_, edge)) = edge_query.get(edge_entity) {
+            history.push(Action::DeleteEdge {
+                source: edge.source,
+                target: edge.target,
+                label: edge.label.clone(),
+            });
+            commands.entity(edge_entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_accumulates_digits() {
+        let c = push_count_digit(None, 1);
+        let c = push_count_digit(c, 2);
+        assert_eq!(push_count_digit(c, 0), Some(120));
+    }
+
+    #[test]
+    fn leading_zero_is_ignored() {
+        assert_eq!(push_count_digit(None, 0), None);
+        assert_eq!(push_count_digit(push_count_digit(None, 0), 5), Some(5));
+    }
+
+    #[test]
+    fn count_saturates_instead_of_overflowing() {
+        assert_eq!(push_count_digit(Some(u32::MAX), 9), Some(u32::MAX));
+    }
+
+    #[test]
+    fn clear_all_resets_count() {
+        let mut pending = PendingOperations {
+            count: Some(5),
+            ..Default::default()
+        };
+        pending.clear_all();
+        assert!(pending.count.is_none());
+    }
+
+    #[test]
+    fn standard_mode_edge_delete_can_be_undone() {
+        use crate::core::history::UndoNodeQuery;
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<NextState<InputMode>>();
+        let a = world.spawn_empty().id();
+        let b = world.spawn_empty().id();
+        let edge = world
+            .spawn(Edge {
+                source: a,
+                target: b,
+                label: Some("calls".to_string()),
+            })
+            .id();
+        world.insert_resource(SelectedEdge(Some(edge)));
+        let mut keys = ButtonInput::<KeyCode>::default();
+        keys.press(KeyCode::Delete);
+        world.insert_resource(keys);
+
+        world.run_system_once(standard_mode_system).unwrap();
+        assert!(world.get_entity(edge).is_err(), "edge despawned");
+        assert_eq!(world.resource::<UndoHistory>().undo_stack.len(), 1);
+
+        world
+            .run_system_once(
+                |mut commands: Commands,
+                 mut history: ResMut<UndoHistory>,
+                 mut query: UndoNodeQuery,
+                 edge_query: Query<(Entity, &Edge)>| {
+                    let mut action = history.pop_undo().unwrap();
+                    apply_action(&mut action, true, &mut commands, &mut query, &edge_query);
+                },
+            )
+            .unwrap();
+
+        let mut edges = world.query::<&Edge>();
+        let restored: Vec<&Edge> = edges.iter(&world).collect();
+        assert_eq!(restored.len(), 1);
+        assert_eq!((restored[0].source, restored[0].target), (a, b));
+        assert_eq!(restored[0].label.as_deref(), Some("calls"));
+    }
+
+    #[test]
+    fn insert_types_at_the_caret_and_home_end_jump() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world.init_resource::<Time>();
+        world.init_resource::<BackspaceHoldTime>();
+        world.init_resource::<OriginalText>();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<NextState<InputMode>>();
+        world.init_resource::<SelectedEdge>();
+        let node = world.spawn((TextData { content: "ac".into() }, Selected)).id();
+        world.insert_resource(InsertCaret {
+            entity: Some(node),
+            pos: 1,
+        });
+        let mut keys = ButtonInput::<Key>::default();
+        keys.press(Key::Character("b".into()));
+        world.insert_resource(keys);
+        world.run_system_once(vim_insert_system).unwrap();
+        assert_eq!(world.get::<TextData>(node).unwrap().content, "abc");
+        assert_eq!(world.resource::<InsertCaret>().pos, 2);
+
+        let mut keys = ButtonInput::<Key>::default();
+        keys.press(Key::Home);
+        world.insert_resource(keys);
+        world.run_system_once(vim_insert_system).unwrap();
+        assert_eq!(world.resource::<InsertCaret>().pos, 0);
+        let mut keys = ButtonInput::<Key>::default();
+        keys.press(Key::End);
+        world.insert_resource(keys);
+        world.run_system_once(vim_insert_system).unwrap();
+        assert_eq!(world.resource::<InsertCaret>().pos, 3);
+    }
+
+    #[test]
+    fn reverse_edge_swaps_and_undo_restores() {
+        use crate::core::history::UndoNodeQuery;
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<Messages<ReverseEdgeRequest>>();
+        let a = world.spawn_empty().id();
+        let b = world.spawn_empty().id();
+        let edge = world
+            .spawn(Edge {
+                source: a,
+                target: b,
+                label: Some("calls".to_string()),
+            })
+            .id();
+        world.insert_resource(SelectedEdge(Some(edge)));
+        world.write_message(ReverseEdgeRequest);
+
+        world.run_system_once(handle_reverse_edge_requests).unwrap();
+        let flipped = world.get::<Edge>(edge).unwrap();
+        assert_eq!((flipped.source, flipped.target), (b, a));
+        assert_eq!(flipped.label.as_deref(), Some("calls"));
+        assert_eq!(world.resource::<UndoHistory>().undo_stack.len(), 1);
+
+        world
+            .run_system_once(
+                |mut commands: Commands,
+                 mut history: ResMut<UndoHistory>,
+                 mut query: UndoNodeQuery,
+                 edge_query: Query<(Entity, &Edge)>| {
+                    let mut action = history.pop_undo().unwrap();
+                    apply_action(&mut action, true, &mut commands, &mut query, &edge_query);
+                },
+            )
+            .unwrap();
+        let restored = world.get::<Edge>(edge).unwrap();
+        assert_eq!((restored.source, restored.target), (a, b));
+    }
+
+    /// World with every resource `vim_normal_system` reads.
+    fn normal_mode_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world.init_resource::<Time>();
+        world.init_resource::<NextState<InputMode>>();
+        world.init_resource::<SelectedEdge>();
+        world.init_resource::<PendingOperations>();
+        world.init_resource::<HjklHoldTime>();
+        world.init_resource::<VimCmdLine>();
+        world.init_resource::<crate::core::marks::Marks>();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<StartMovePos>();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<crate::core::config::GlyphConfig>();
+        world.init_resource::<VisualSelection>();
+        world.init_resource::<KeyBindings>();
+        world.init_resource::<JumpList>();
+        world.init_resource::<SearchMatches>();
+        world.init_resource::<Messages<crate::core::tabs::TabRequest>>();
+        world.init_resource::<Messages<crate::render::pin::PinRequest>>();
+        world.init_resource::<Messages<crate::input::prune::DeleteSubtreeRequest>>();
+        world.init_resource::<LastChange>();
+        world
+    }
+
+    fn press(world: &mut World, key: KeyCode) {
+        use bevy::ecs::system::RunSystemOnce;
+        let mut keys = world.resource_mut::<ButtonInput<KeyCode>>();
+        keys.clear();
+        keys.press(key);
+        world.run_system_once(vim_normal_system).unwrap();
+        world.resource_mut::<ButtonInput<KeyCode>>().release(key);
+    }
+
+    #[test]
+    fn editor_command_fills_placeholders_per_argument() {
+        let args = editor_command("code --goto {file}:{line}:{col}", "/a b/main.rs", 12, 1);
+        assert_eq!(args.unwrap(), vec!["code", "--goto", "/a b/main.rs:12:1"]);
+        let args = editor_command("  nvim +{line}   {file} ", "x.rs", 3, 1);
+        assert_eq!(args.unwrap(), vec!["nvim", "+3", "x.rs"]);
+        assert_eq!(editor_command("   ", "x.rs", 3, 1), None);
+    }
+
+    #[test]
+    fn zz_centres_on_the_selection_or_the_whole_canvas() {
+        let mut world = normal_mode_world();
+        world.resource_mut::<crate::core::config::GlyphConfig>().smooth_camera = false;
+        let camera = world.spawn((MainCamera, Transform::default())).id();
+        let mut node = |x: f32, y: f32| {
+            let at = Transform::from_xyz(x, y, 0.0);
+            let text = TextData {
+                content: String::new(),
+            };
+            let color = NodeColor(Color::WHITE);
+            world.spawn((CanvasNode, at, GlobalTransform::from(at), text, color)).id()
+        };
+        let (a, _) = (node(300.0, 200.0), node(-100.0, -400.0));
+        let camera_at =
+            |world: &World| world.get::<Transform>(camera).unwrap().translation.truncate();
+
+        press(&mut world, KeyCode::KeyZ);
+        press(&mut world, KeyCode::KeyZ);
+        assert_eq!(camera_at(&world), Vec2::new(100.0, -100.0), "middle of all nodes");
+
+        world.entity_mut(a).insert(Selected);
+        press(&mut world, KeyCode::KeyZ);
+        press(&mut world, KeyCode::KeyZ);
+        assert_eq!(camera_at(&world), Vec2::new(300.0, 200.0));
+        assert!(!world.resource::<PendingOperations>().z);
+    }
+
+    #[test]
+    fn dot_repeats_dd_on_the_next_selected_node() {
+        let mut world = normal_mode_world();
+        let mut node = |text: &str| {
+            let text = TextData {
+                content: text.to_string(),
+            };
+            world.spawn((CanvasNode, Transform::default(), text, NodeColor(Color::WHITE))).id()
+        };
+        let (a, b) = (node("a"), node("b"));
+
+        press(&mut world, KeyCode::Period);
+        assert_eq!(world.resource::<StatusMessage>().text, "Nothing to repeat");
+
+        world.entity_mut(a).insert(Selected);
+        press(&mut world, KeyCode::KeyD);
+        press(&mut world, KeyCode::KeyD);
+        assert!(world.get_entity(a).is_err());
+        assert_eq!(*world.resource::<LastChange>(), LastChange::Delete);
+
+        // Motions and yanks leave the recorded change alone.
+        world.entity_mut(b).insert(Selected);
+        press(&mut world, KeyCode::KeyY);
+        press(&mut world, KeyCode::KeyY);
+        press(&mut world, KeyCode::Period);
+        assert!(world.get_entity(b).is_err());
+        assert_eq!(world.resource::<UndoHistory>().undo_stack.len(), 2);
+    }
+
+    #[test]
+    fn shift_y_clones_the_node_with_its_edges_as_one_step() {
+        let mut world = normal_mode_world();
+        let mut node = |text: &str| {
+            let text = TextData {
+                content: text.to_string(),
+            };
+            world.spawn((CanvasNode, Transform::default(), text, NodeColor(Color::WHITE))).id()
+        };
+        let (a, b, c) = (node("a"), node("b"), node("c"));
+        let source = SourceLocation {
+            file: "main.rs".into(),
+            line: 1,
+        };
+        world.entity_mut(a).insert((Selected, source));
+        for (source, target) in [(a, b), (c, a), (b, c)] {
+            let label = None;
+            world.spawn(Edge { source, target, label });
+        }
+
+        world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::ShiftLeft);
+        press(&mut world, KeyCode::KeyY);
+        let clone = world
+            .query_filtered::<Entity, With<Selected>>()
+            .single(&world)
+            .unwrap();
+        assert_ne!(clone, a);
+        assert!(world.get::<SourceLocation>(clone).is_none());
+        assert_eq!(world.get::<TextData>(clone).unwrap().content, "a");
+        let mut pairs: Vec<_> = world
+            .query::<&Edge>()
+            .iter(&world)
+            .filter(|e| e.source == clone || e.target == clone)
+            .map(|e| (e.source, e.target))
+            .collect();
+        pairs.sort();
+        let mut expected = vec![(clone, b), (c, clone)];
+        expected.sort();
+        assert_eq!(pairs, expected);
+        let history = world.resource::<UndoHistory>();
+        assert_eq!(history.undo_stack.len(), 1);
+        assert!(matches!(&history.undo_stack[0], Action::Batch(a) if a.len() == 3));
+    }
+
+    #[test]
+    fn open_line_is_one_undo_step() {
+        let mut world = normal_mode_world();
+        let text = TextData {
+            content: "a".to_string(),
+        };
+        let a = world.spawn((CanvasNode, Transform::default(), text, NodeColor(Color::WHITE))).id();
+        world.entity_mut(a).insert(Selected);
+
+        press(&mut world, KeyCode::KeyO);
+        assert_eq!(world.query::<&Edge>().iter(&world).count(), 1);
+        let history = world.resource::<UndoHistory>();
+        assert_eq!(history.undo_stack.len(), 1);
+        let Action::Batch(actions) = &history.undo_stack[0] else {
+            panic!("expected one Batch step");
+        };
+        assert!(matches!(actions[..], [Action::CreateNode { .. }, Action::CreateEdge { .. }]));
+    }
+
+    #[test]
+    fn shift_l_enters_edge_label_easymotion_and_clears_pending_g() {
+        let mut world = normal_mode_world();
+        press(&mut world, KeyCode::KeyG);
+        assert!(world.resource::<PendingOperations>().ge);
+
+        world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::ShiftLeft);
+        press(&mut world, KeyCode::KeyL);
+        assert!(!world.resource::<PendingOperations>().ge);
+        assert!(*world.resource::<EasymotionTarget>() == EasymotionTarget::EdgeLabel);
+        assert!(matches!(
+            world.resource::<NextState<InputMode>>(),
+            NextState::Pending(InputMode::VimEasymotion)
+        ));
+    }
+
+    #[test]
+    fn extreme_node_picks_top_and_bottom_with_x_tiebreak() {
+        let (a, b, c) = (Entity::from_bits(1), Entity::from_bits(2), Entity::from_bits(3));
+        let nodes = [
+            (a, Vec2::new(50.0, 100.0)),
+            (b, Vec2::new(-20.0, 100.0)),
+            (c, Vec2::new(0.0, -300.0)),
+        ];
+        assert_eq!(extreme_node(nodes, true), Some(b));
+        assert_eq!(extreme_node(nodes, false), Some(c));
+        assert_eq!(extreme_node([], true), None);
     }
 }
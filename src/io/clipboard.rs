@@ -0,0 +1,125 @@
+//! System clipboard: `:yank` copies the selected node's text (or the selected
+//! edge's label) and Ctrl+V in Insert mode pastes at the caret. Clipboard
+//! failures (no display server, empty or non-text contents) only set the
+//! status line.
+
+use bevy::prelude::*;
+
+use crate::core::components::{Edge, Selected, TextData};
+use crate::core::helpers::ctrl_pressed;
+use crate::core::resources::{InsertCaret, SelectedEdge, StatusMessage};
+
+/// Message sent by `:yank`.
+#[derive(Message)]
+pub struct YankRequest;
+
+/// The OS clipboard, opened on first use. Kept open because on X11 the copied
+/// text is only served while the handle lives.
+#[derive(Resource, Default)]
+pub struct SystemClipboard(Option<arboard::Clipboard>);
+
+impl SystemClipboard {
+    fn get(&mut self) -> Result<&mut arboard::Clipboard, String> {
+        if self.0.is_none() {
+            self.0 = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+        }
+        Ok(self.0.as_mut().unwrap())
+    }
+
+    pub fn copy(&mut self, text: &str) -> Result<(), String> {
+        self.get()?.set_text(text).map_err(|e| e.to_string())
+    }
+
+    pub fn paste(&mut self) -> Result<String, String> {
+        self.get()?.get_text().map_err(|e| e.to_string())
+    }
+}
+
+/// Clipboard text as it goes into a single-line label: line breaks become
+/// spaces and surrounding whitespace is dropped.
+pub fn clipboard_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Handles `YankRequest`: copies the selected edge's label, else the selected
+/// node's text.
+pub fn handle_yank_requests(
+    mut requests: MessageReader<YankRequest>,
+    mut clipboard: ResMut<SystemClipboard>,
+    selected_edge: Res<SelectedEdge>,
+    edges: Query<&Edge>,
+    nodes: Query<&TextData, With<Selected>>,
+    mut status: ResMut<StatusMessage>,
+) {
+    for _ in requests.read() {
+        let text = match selected_edge.0.and_then(|e| edges.get(e).ok()) {
+            Some(edge) => edge.label.clone().unwrap_or_default(),
+            None => match nodes.iter().next() {
+                Some(text_data) => text_data.content.clone(),
+                None => {
+                    status.set("error: :yank needs a selected node or edge");
+                    continue;
+                }
+            },
+        };
+        match clipboard.copy(&text) {
+            Ok(()) => status.set(format!("Copied {} characters", text.chars().count())),
+            Err(e) => {
+                status.set(format!("error: clipboard: {}", e));
+                warn!("[CLIPBOARD] copy failed: {}", e);
+            }
+        }
+    }
+}
+
+/// Ctrl+V in Insert mode: append the clipboard text to the label being
+/// edited. `vim_insert_system` records the edit for undo on Esc.
+pub fn clipboard_paste_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut clipboard: ResMut<SystemClipboard>,
+    selected_edge: Res<SelectedEdge>,
+    mut edges: Query<&mut Edge>,
+    mut nodes: Query<(Entity, &mut TextData), With<Selected>>,
+    mut caret: ResMut<InsertCaret>,
+    mut status: ResMut<StatusMessage>,
+) {
+    if !(ctrl_pressed(&keys) && keys.just_pressed(KeyCode::KeyV)) {
+        return;
+    }
+    let text = match clipboard.paste() {
+        Ok(text) => clipboard_line(&text),
+        Err(e) => {
+            status.set(format!("error: clipboard: {}", e));
+            warn!("[CLIPBOARD] paste failed: {}", e);
+            return;
+        }
+    };
+    if let Some((entity, mut edge)) = selected_edge
+        .0
+        .and_then(|e| edges.get_mut(e).ok().map(|edge| (e, edge)))
+    {
+        paste_at_caret(&mut caret, entity, edge.label.get_or_insert_default(), &text);
+    } else if let Some((entity, mut text_data)) = nodes.iter_mut().next() {
+        paste_at_caret(&mut caret, entity, &mut text_data.content, &text);
+    }
+}
+
+/// Insert at the caret when it is on this label, else append.
+fn paste_at_caret(caret: &mut InsertCaret, entity: Entity, label: &mut String, text: &str) {
+    if caret.entity == Some(entity) {
+        caret.insert(label, text);
+    } else {
+        label.push_str(text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clipboard_text_is_flattened_to_one_line() {
+        assert_eq!(clipboard_line("  fn main()\r\n  {\n}\n"), "fn main() { }");
+        assert_eq!(clipboard_line("\n"), "");
+    }
+}
@@ -0,0 +1,345 @@
+//! Canvas diff: `glyph --headless --diff a.glyph b.glyph`
+//!
+//! Compares two saved canvases without opening a window and prints the nodes
+//! added, removed, moved or retexted and the edges added or removed. Saved ids
+//! follow node positions, so nodes are matched by text (the nearest one when
+//! several share it), then a leftover pair at the same spot counts as one
+//! node whose text changed. The exit code is 1 when the files differ, so CI
+//! can catch unintended diagram changes.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write as _;
+use std::path::Path;
+
+use super::file_io::{read_snapshot, CanvasSnapshot, SerializableNode};
+
+/// Nodes closer than this (world units) have not moved.
+const MOVE_EPSILON: f32 = 0.5;
+
+/// A node that is in both canvases but sits elsewhere.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovedNode {
+    pub text: String,
+    pub from: [f32; 2],
+    pub to: [f32; 2],
+}
+
+/// A node whose text changed in place.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetextedNode {
+    pub from: String,
+    pub to: String,
+}
+
+/// An edge, named by the text of its endpoints.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EdgeChange {
+    pub source: String,
+    pub target: String,
+    pub label: Option<String>,
+}
+
+/// Everything that changed from one canvas to another. Lists are sorted.
+#[derive(Debug, Default, PartialEq)]
+pub struct CanvasDiff {
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    pub moved_nodes: Vec<MovedNode>,
+    pub retexted_nodes: Vec<RetextedNode>,
+    pub added_edges: Vec<EdgeChange>,
+    pub removed_edges: Vec<EdgeChange>,
+}
+
+impl CanvasDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.moved_nodes.is_empty()
+            && self.retexted_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+impl fmt::Display for EdgeChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} -> {:?}", self.source, self.target)?;
+        if let Some(label) = &self.label {
+            write!(f, " [{:?}]", label)?;
+        }
+        Ok(())
+    }
+}
+
+/// One line per change, `+` added, `-` removed, `~` moved or retexted.
+impl fmt::Display for CanvasDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for text in &self.removed_nodes {
+            writeln!(f, "- node {:?}", text)?;
+        }
+        for text in &self.added_nodes {
+            writeln!(f, "+ node {:?}", text)?;
+        }
+        for node in &self.retexted_nodes {
+            writeln!(f, "~ node {:?} -> {:?}", node.from, node.to)?;
+        }
+        for node in &self.moved_nodes {
+            let ([x0, y0], [x1, y1]) = (node.from, node.to);
+            writeln!(
+                f,
+                "~ node {:?} moved ({}, {}) -> ({}, {})",
+                node.text, x0, y0, x1, y1
+            )?;
+        }
+        for edge in &self.removed_edges {
+            writeln!(f, "- edge {}", edge)?;
+        }
+        for edge in &self.added_edges {
+            writeln!(f, "+ edge {}", edge)?;
+        }
+        Ok(())
+    }
+}
+
+fn pos(node: &SerializableNode) -> (f32, f32) {
+    (node.x, node.y)
+}
+
+fn distance(a: &SerializableNode, b: &SerializableNode) -> f32 {
+    let ((ax, ay), (bx, by)) = (pos(a), pos(b));
+    (ax - bx).hypot(ay - by)
+}
+
+/// Pairs of indices into `a.nodes` and `b.nodes` that are the same node.
+fn match_nodes(a: &CanvasSnapshot, b: &CanvasSnapshot) -> Vec<(usize, usize)> {
+    let mut unmatched_b: Vec<usize> = (0..b.nodes.len()).collect();
+    let mut pairs = Vec::new();
+    let mut leftover_a = Vec::new();
+    for (i, node) in a.nodes.iter().enumerate() {
+        let nearest = unmatched_b
+            .iter()
+            .enumerate()
+            .filter(|(_, j)| b.nodes[**j].text == node.text)
+            .min_by(|(_, x), (_, y)| {
+                distance(node, &b.nodes[**x]).total_cmp(&distance(node, &b.nodes[**y]))
+            })
+            .map(|(k, _)| k);
+        match nearest {
+            Some(k) => pairs.push((i, unmatched_b.remove(k))),
+            None => leftover_a.push(i),
+        }
+    }
+    for i in leftover_a {
+        let same_spot = unmatched_b
+            .iter()
+            .position(|j| distance(&a.nodes[i], &b.nodes[*j]) < MOVE_EPSILON);
+        if let Some(k) = same_spot {
+            pairs.push((i, unmatched_b.remove(k)));
+        }
+    }
+    pairs
+}
+
+/// Edges of `snapshot` as (source, target, label) over node indices.
+fn edge_keys(snapshot: &CanvasSnapshot) -> Vec<(usize, usize, Option<String>)> {
+    let index: HashMap<u64, usize> = snapshot
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.id, i))
+        .collect();
+    snapshot
+        .edges
+        .iter()
+        .filter_map(|e| {
+            let source = *index.get(&e.source_id)?;
+            let target = *index.get(&e.target_id)?;
+            Some((source, target, e.label.clone()))
+        })
+        .collect()
+}
+
+fn edge_change(
+    snapshot: &CanvasSnapshot,
+    (s, t, label): &(usize, usize, Option<String>),
+) -> EdgeChange {
+    EdgeChange {
+        source: snapshot.nodes[*s].text.clone(),
+        target: snapshot.nodes[*t].text.clone(),
+        label: label.clone(),
+    }
+}
+
+/// What changed from `a` to `b`. Edges count as the same when their matched
+/// endpoints and label agree; an edge of a removed node is removed.
+pub fn diff_snapshots(a: &CanvasSnapshot, b: &CanvasSnapshot) -> CanvasDiff {
+    let pairs = match_nodes(a, b);
+    let a_to_b: HashMap<usize, usize> = pairs.iter().copied().collect();
+    let mut diff = CanvasDiff::default();
+
+    for &(i, j) in &pairs {
+        let (from, to) = (&a.nodes[i], &b.nodes[j]);
+        if from.text != to.text {
+            diff.retexted_nodes.push(RetextedNode {
+                from: from.text.clone(),
+                to: to.text.clone(),
+            });
+        } else if distance(from, to) >= MOVE_EPSILON {
+            diff.moved_nodes.push(MovedNode {
+                text: to.text.clone(),
+                from: [from.x, from.y],
+                to: [to.x, to.y],
+            });
+        }
+    }
+    let matched_b: Vec<usize> = pairs.iter().map(|(_, j)| *j).collect();
+    diff.removed_nodes = (0..a.nodes.len())
+        .filter(|i| !a_to_b.contains_key(i))
+        .map(|i| a.nodes[i].text.clone())
+        .collect();
+    diff.added_nodes = (0..b.nodes.len())
+        .filter(|j| !matched_b.contains(j))
+        .map(|j| b.nodes[j].text.clone())
+        .collect();
+
+    let mut b_edges = edge_keys(b);
+    for edge in edge_keys(a) {
+        let (s, t, label) = &edge;
+        let in_b = match (a_to_b.get(s), a_to_b.get(t)) {
+            (Some(bs), Some(bt)) => b_edges
+                .iter()
+                .position(|(x, y, l)| x == bs && y == bt && l == label),
+            _ => None,
+        };
+        match in_b {
+            Some(k) => {
+                b_edges.remove(k);
+            }
+            None => diff.removed_edges.push(edge_change(a, &edge)),
+        }
+    }
+    diff.added_edges = b_edges.iter().map(|e| edge_change(b, e)).collect();
+
+    diff.added_nodes.sort();
+    diff.removed_nodes.sort();
+    diff.moved_nodes.sort_by(|x, y| x.text.cmp(&y.text));
+    diff.retexted_nodes.sort_by(|x, y| x.from.cmp(&y.from));
+    diff.added_edges.sort();
+    diff.removed_edges.sort();
+    diff
+}
+
+/// Print the diff from `a` to `b` on stdout. Returns the process exit code:
+/// 0 when identical, 1 when they differ, 2 when a file cannot be read.
+pub fn print_diff(a: &str, b: &str) -> i32 {
+    let read = |path: &str| read_snapshot(Path::new(path)).map_err(|e| format!("{}: {}", path, e));
+    let (a, b) = match (read(a), read(b)) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("diff: {}", e);
+            return 2;
+        }
+    };
+    let diff = diff_snapshots(&a, &b);
+    if diff.is_empty() {
+        return 0;
+    }
+    // A closed pipe (`| head`) is not an error worth a panic.
+    let _ = write!(std::io::stdout().lock(), "{}", diff);
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::file_io::SerializableEdge;
+
+    fn snapshot(nodes: &[(&str, f32, f32)], edges: &[(u64, u64, Option<&str>)]) -> CanvasSnapshot {
+        let json = serde_json::json!({
+            "nodes": nodes
+                .iter()
+                .enumerate()
+                .map(|(id, (text, x, y))| serde_json::json!({"id": id, "x": x, "y": y, "text": text}))
+                .collect::<Vec<_>>(),
+            "edges": Vec::<serde_json::Value>::new(),
+        });
+        let mut snapshot: CanvasSnapshot = serde_json::from_value(json).unwrap();
+        snapshot.edges = edges
+            .iter()
+            .map(|(source_id, target_id, label)| SerializableEdge {
+                source_id: *source_id,
+                target_id: *target_id,
+                label: label.map(str::to_string),
+                stroke: Default::default(),
+                curve: None,
+                reversed: false,
+            })
+            .collect();
+        snapshot
+    }
+
+    #[test]
+    fn identical_canvases_have_no_diff() {
+        let a = snapshot(&[("a", 0.0, 0.0), ("b", 100.0, 0.0)], &[(0, 1, Some("x"))]);
+        // Ids differ but the nodes are the same.
+        let b = snapshot(&[("b", 100.0, 0.0), ("a", 0.0, 0.0)], &[(1, 0, Some("x"))]);
+        let diff = diff_snapshots(&a, &b);
+        assert!(diff.is_empty(), "{:?}", diff);
+        assert_eq!(diff.to_string(), "");
+    }
+
+    #[test]
+    fn diff_reports_node_and_edge_changes() {
+        let a = snapshot(
+            &[
+                ("keep", 0.0, 0.0),
+                ("gone", 50.0, 50.0),
+                ("old", 200.0, 0.0),
+                ("mover", 9.0, 9.0),
+            ],
+            &[(0, 1, None), (0, 2, Some("yes"))],
+        );
+        let b = snapshot(
+            &[
+                ("keep", 0.0, 0.0),
+                ("new", 300.0, 300.0),
+                ("renamed", 200.0, 0.0),
+                ("mover", 9.0, 90.0),
+            ],
+            &[(0, 2, Some("yes")), (0, 1, None)],
+        );
+        let diff = diff_snapshots(&a, &b);
+        assert_eq!(diff.added_nodes, vec!["new"]);
+        assert_eq!(diff.removed_nodes, vec!["gone"]);
+        assert_eq!(
+            diff.retexted_nodes,
+            vec![RetextedNode {
+                from: "old".into(),
+                to: "renamed".into()
+            }]
+        );
+        assert_eq!(diff.moved_nodes[0].to, [9.0, 90.0]);
+        // keep -> old/renamed survives the retext; keep -> gone is replaced.
+        assert_eq!(
+            diff.removed_edges,
+            vec![EdgeChange {
+                source: "keep".into(),
+                target: "gone".into(),
+                label: None
+            }]
+        );
+        assert_eq!(diff.added_edges.len(), 1);
+        assert_eq!(diff.added_edges[0].target, "new");
+        assert!(diff.to_string().contains("~ node \"old\" -> \"renamed\""));
+    }
+
+    #[test]
+    fn duplicate_texts_pair_with_the_nearest_node() {
+        let a = snapshot(&[("dup", 0.0, 0.0), ("dup", 500.0, 0.0)], &[]);
+        let b = snapshot(&[("dup", 510.0, 0.0), ("dup", 0.0, 0.0)], &[]);
+        let diff = diff_snapshots(&a, &b);
+        assert_eq!(diff.moved_nodes.len(), 1);
+        assert_eq!(diff.moved_nodes[0].from, [500.0, 0.0]);
+    }
+}
@@ -0,0 +1,123 @@
+//! Graphviz export: `:export graph.dot`
+//!
+//! Writes the canvas as a `digraph` so crawled call graphs can be rendered or
+//! diffed outside Glyph. Positions are dropped; Graphviz does its own layout.
+
+use bevy::prelude::*;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::core::components::{Edge, NodeColor, TextData};
+use crate::io::file_io::SaveSources;
+
+/// Escape a string for use inside a double-quoted DOT attribute.
+pub fn escape_dot(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// `#rrggbb` for a DOT `fillcolor`, plus a readable text color for that fill.
+fn dot_colors(color: &Color) -> (String, &'static str) {
+    let c = color.to_srgba();
+    let to_byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let fill = format!("#{:02x}{:02x}{:02x}", to_byte(c.red), to_byte(c.green), to_byte(c.blue));
+    let luma = 0.299 * c.red + 0.587 * c.green + 0.114 * c.blue;
+    (fill, if luma < 0.5 { "white" } else { "black" })
+}
+
+/// Render nodes and edges as a DOT `digraph`. Node ids are entity indices;
+/// edges whose endpoints are not in `nodes` are skipped.
+pub fn graph_to_dot<'a>(
+    nodes: impl IntoIterator<Item = (Entity, &'a TextData, &'a NodeColor)>,
+    edges: impl IntoIterator<Item = &'a Edge>,
+) -> String {
+    let mut out = String::from("digraph glyph {\n    node [shape=box, style=filled];\n");
+    let mut known = HashSet::new();
+    for (entity, text, color) in nodes {
+        known.insert(entity);
+        let (fill, font) = dot_colors(&color.0);
+        let _ = writeln!(
+            out,
+            "    n{} [label=\"{}\", fillcolor=\"{}\", fontcolor=\"{}\"];",
+            entity.index_u32(),
+            escape_dot(&text.content),
+            fill,
+            font
+        );
+    }
+    for edge in edges {
+        if !known.contains(&edge.source) || !known.contains(&edge.target) {
+            continue;
+        }
+        let _ = write!(out, "    n{} -> n{}", edge.source.index_u32(), edge.target.index_u32());
+        if let Some(label) = &edge.label {
+            let _ = write!(out, " [label=\"{}\"]", escape_dot(label));
+        }
+        out.push_str(";\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Core DOT export — writes the canvas to `path`.
+pub fn export_dot(path: &Path, canvas: &SaveSources) -> Result<(), String> {
+    let dot = graph_to_dot(
+        canvas.nodes.iter().map(|(e, _, td, c, ..)| (e, td, c)),
+        canvas.edges.iter().map(|(_, edge, ..)| edge),
+    );
+    std::fs::write(path, dot).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::world::World;
+
+    #[test]
+    fn escape_dot_quotes_and_backslashes() {
+        assert_eq!(escape_dot(r#"say "hi" \o/"#), r#"say \"hi\" \\o/"#);
+        assert_eq!(escape_dot("a\nb"), "a\\nb");
+    }
+
+    #[test]
+    fn graph_to_dot_writes_nodes_and_labelled_edges() {
+        let mut world = World::new();
+        let a = world.spawn_empty().id();
+        let b = world.spawn_empty().id();
+        let dangling = world.spawn_empty().id();
+        let ta = TextData { content: "main".into() };
+        let tb = TextData { content: "say \"x\"".into() };
+        let dark = NodeColor(Color::srgb(0.0, 0.0, 0.0));
+        let light = NodeColor(Color::srgb(1.0, 1.0, 1.0));
+        let edges = [
+            Edge { source: a, target: b, label: Some("calls".into()) },
+            Edge { source: a, target: dangling, label: None },
+        ];
+        let dot = graph_to_dot([(a, &ta, &dark), (b, &tb, &light)], edges.iter());
+
+        assert!(dot.starts_with("digraph glyph {"));
+        assert!(dot.contains(&format!(
+            "n{} [label=\"main\", fillcolor=\"#000000\", fontcolor=\"white\"];",
+            a.index_u32()
+        )));
+        assert!(dot.contains("label=\"say \\\"x\\\"\""));
+        assert!(dot.contains("fillcolor=\"#ffffff\", fontcolor=\"black\""));
+        assert!(dot.contains(&format!(
+            "n{} -> n{} [label=\"calls\"];",
+            a.index_u32(),
+            b.index_u32()
+        )));
+        assert!(!dot.contains(&format!("-> n{}", dangling.index_u32())));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+}
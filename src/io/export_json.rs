@@ -0,0 +1,150 @@
+//! Call-graph JSON export: `glyph --headless --crawl ./src --print-json`
+//!
+//! Crawls a directory without starting the app and prints the `FlowMap` plus
+//! source locations as a `{ "nodes": [...], "edges": [...] }` document, so
+//! scripts can consume the call graph directly.
+
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::io::Write as _;
+
+use crate::core::config::GlyphConfig;
+use crate::crawler::parsers::walker::DECISION_SEP;
+use crate::crawler::{CallGraph, CrawlerRouter, SourceMap};
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct JsonNode {
+    /// Namespaced id: `relative/path.rs::function_name`.
+    pub id: String,
+    /// Display text, as shown on the canvas.
+    pub name: String,
+    /// Absolute source file, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// 1-indexed line of the definition, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// Control-flow decision node rather than a function.
+    pub decision: bool,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct JsonEdge {
+    pub source: String,
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CallGraphDocument {
+    pub nodes: Vec<JsonNode>,
+    pub edges: Vec<JsonEdge>,
+}
+
+/// Build the document. Like `:crawl`, only defined functions (graph keys) become
+/// nodes and edges to anything else are dropped. Output is sorted by id.
+pub fn call_graph_document(graph: &CallGraph, source_map: &SourceMap) -> CallGraphDocument {
+    let defined: BTreeSet<&String> = graph.keys().collect();
+    let nodes = defined
+        .iter()
+        .map(|id| {
+            let decision = id.contains(DECISION_SEP);
+            let after_ns = id.split_once("::").map_or(id.as_str(), |(_, rest)| rest);
+            let name = after_ns.split_once(DECISION_SEP).map_or(after_ns, |(_, text)| text);
+            let location = source_map.get(*id);
+            JsonNode {
+                id: (*id).clone(),
+                name: name.to_string(),
+                file: location.map(|(file, ..)| file.clone()),
+                line: location.map(|(_, line, _)| *line),
+                decision,
+            }
+        })
+        .collect();
+
+    let mut edges = Vec::new();
+    for source in &defined {
+        for edge in &graph[*source] {
+            if defined.contains(&edge.target) {
+                edges.push(JsonEdge {
+                    source: (*source).clone(),
+                    target: edge.target.clone(),
+                    label: edge.label.clone(),
+                });
+            }
+        }
+    }
+    CallGraphDocument { nodes, edges }
+}
+
+/// Crawl `path` (a directory or a single source file) and print the call graph as JSON on stdout. Returns the process
+/// exit code: nonzero when the path is missing or no functions were found.
+pub fn print_call_graph(path: &str, config: &GlyphConfig) -> i32 {
+    let abs_root = std::path::Path::new(path)
+        .canonicalize()
+        .unwrap_or_else(|_| std::path::PathBuf::from(path));
+    if !abs_root.exists() {
+        eprintln!("crawl: no such file or directory: {}", path);
+        return 1;
+    }
+    let include_calls: HashSet<String> = config.crawl_include_calls.iter().cloned().collect();
+    let root = abs_root.to_string_lossy();
+    let (graph, source_map) =
+        CrawlerRouter::crawl(&[&root], false, &include_calls, &[], false, false);
+    if graph.is_empty() {
+        eprintln!("crawl: no functions found in {}", path);
+        return 1;
+    }
+    let json = match serde_json::to_string_pretty(&call_graph_document(&graph, &source_map)) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("Failed to serialize call graph: {}", e);
+            return 1;
+        }
+    };
+    // A closed pipe (`| head`) is not an error worth a panic.
+    let _ = writeln!(std::io::stdout().lock(), "{}", json);
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crawler::FlowEdge;
+
+    fn flow(target: &str, label: Option<&str>) -> FlowEdge {
+        FlowEdge {
+            target: target.to_string(),
+            label: label.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn document_keeps_defined_nodes_with_locations() {
+        let decision = format!("a.rs::_decision_0{}if ok", DECISION_SEP);
+        let mut graph = CallGraph::new();
+        graph.insert(
+            "a.rs::main".to_string(),
+            vec![flow(&decision, None), flow("unwrap", None), flow("b.rs::run", None)],
+        );
+        graph.insert(decision.clone(), vec![flow("b.rs::run", Some("yes"))]);
+        graph.insert("b.rs::run".to_string(), vec![]);
+        let mut source_map = SourceMap::new();
+        source_map.insert("a.rs::main".to_string(), ("/src/a.rs".to_string(), 3, 5));
+
+        let doc = call_graph_document(&graph, &source_map);
+        let ids: Vec<&str> = doc.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["a.rs::_decision_0\u{1f}if ok", "a.rs::main", "b.rs::run"]);
+        assert_eq!(doc.nodes[0].name, "if ok");
+        assert!(doc.nodes[0].decision);
+        assert_eq!(doc.nodes[1].file.as_deref(), Some("/src/a.rs"));
+        assert_eq!(doc.nodes[1].line, Some(3));
+        assert_eq!(doc.nodes[2].line, None);
+
+        // The `unwrap` call is not a defined function, so it has no edge.
+        assert_eq!(doc.edges.len(), 3);
+        assert_eq!(doc.edges[0].label.as_deref(), Some("yes"));
+        assert!(doc.edges.iter().all(|e| e.target != "unwrap"));
+    }
+}
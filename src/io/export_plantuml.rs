@@ -0,0 +1,113 @@
+//! PlantUML export: `:export arch.puml`
+//!
+//! Writes the canvas as a component diagram for architecture docs: each node
+//! is a `[Label]` component and each edge `[A] --> [B] : label`. Positions
+//! and colors are dropped; PlantUML does its own layout.
+
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::core::components::{Edge, TextData};
+use crate::io::file_io::SaveSources;
+
+/// Make `s` safe inside a `[...]` component name or after `:` on an arrow.
+/// Brackets would end the name early, so they become parentheses; line
+/// breaks become PlantUML's `\n`.
+pub fn escape_plantuml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '[' => out.push('('),
+            ']' => out.push(')'),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Render nodes and edges as a PlantUML component diagram. Components are
+/// named by label; a label already taken gets ` (2)`, ` (3)`, … so each node
+/// stays its own component. Edges whose endpoints are not in `nodes` are
+/// skipped.
+pub fn graph_to_plantuml<'a>(
+    nodes: impl IntoIterator<Item = (Entity, &'a TextData)>,
+    edges: impl IntoIterator<Item = &'a Edge>,
+) -> String {
+    let mut out = String::from("@startuml\n");
+    let mut names: HashMap<Entity, String> = HashMap::new();
+    let mut taken = HashSet::new();
+    for (entity, text) in nodes {
+        let label = escape_plantuml(text.content.trim());
+        let label = if label.is_empty() { "unnamed".to_string() } else { label };
+        let mut name = label.clone();
+        let mut n = 2;
+        while !taken.insert(name.clone()) {
+            name = format!("{} ({})", label, n);
+            n += 1;
+        }
+        let _ = writeln!(out, "[{}]", name);
+        names.insert(entity, name);
+    }
+    for edge in edges {
+        let (Some(source), Some(target)) = (names.get(&edge.source), names.get(&edge.target))
+        else {
+            continue;
+        };
+        let _ = write!(out, "[{}] --> [{}]", source, target);
+        if let Some(label) = edge.label.as_deref().filter(|l| !l.is_empty()) {
+            let _ = write!(out, " : {}", escape_plantuml(label));
+        }
+        out.push('\n');
+    }
+    out.push_str("@enduml\n");
+    out
+}
+
+/// Core PlantUML export — writes the canvas to `path`.
+pub fn export_plantuml(path: &Path, canvas: &SaveSources) -> Result<(), String> {
+    let puml = graph_to_plantuml(
+        canvas.nodes.iter().map(|(e, _, td, ..)| (e, td)),
+        canvas.edges.iter().map(|(_, edge, ..)| edge),
+    );
+    std::fs::write(path, puml).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::world::World;
+
+    #[test]
+    fn graph_to_plantuml_names_duplicates_and_escapes_brackets() {
+        let mut world = World::new();
+        let [api, db, db2, dangling] = std::array::from_fn(|_| world.spawn_empty().id());
+        let texts = [
+            TextData { content: "api [v2]".into() },
+            TextData { content: "db".into() },
+            TextData { content: "db".into() },
+        ];
+        let edges = [
+            Edge { source: api, target: db, label: Some("reads".into()) },
+            Edge { source: api, target: db2, label: None },
+            Edge { source: db, target: dangling, label: None },
+        ];
+        let puml = graph_to_plantuml(
+            [(api, &texts[0]), (db, &texts[1]), (db2, &texts[2])],
+            edges.iter(),
+        );
+        assert_eq!(
+            puml,
+            "@startuml\n\
+             [api (v2)]\n\
+             [db]\n\
+             [db (2)]\n\
+             [api (v2)] --> [db] : reads\n\
+             [api (v2)] --> [db (2)]\n\
+             @enduml\n"
+        );
+    }
+}
@@ -1,12 +1,24 @@
 //! Phase 6: Offline save/load pipeline. Serializes canvas to .glyph files.
 
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
-
-use crate::core::components::{CanvasNode, Edge, MainCamera, NodeColor, TextData};
-use crate::core::helpers::spawn_node_with_color;
+use std::time::SystemTime;
+
+use crate::core::components::{
+    CanvasNode, CurveOffset, DecisionNode, Edge, EdgeStroke, FlowBypass, InactiveCanvas,
+    MainCamera, NodeColor, NodeShape, NodeSize, Pinned, ReversedCall, SourceLocation, TextData,
+};
+use crate::core::helpers::{spawn_file_label, spawn_node_with_color};
+use crate::core::config::GlyphConfig;
+use crate::core::history::{Action, UndoHistory};
+use crate::core::marks::Marks;
+pub(crate) use crate::core::snapshot::default_color;
+pub use crate::core::snapshot::{
+    CanvasSnapshot, PendingCanvasRestore, SerializableEdge, SerializableNode,
+    SerializedCameraPrefs, SerializedColor, SerializedSource,
+};
 
 /// Default path for keyboard shortcut save/load when no file is open.
 pub const WORKSPACE_PATH: &str = "workspace.glyph";
@@ -14,7 +26,6 @@ pub const WORKSPACE_PATH: &str = "workspace.glyph";
 /// Folder for user workflows. Created on first use.
 pub const WORKFLOWS_DIR: &str = "workflows";
 const RECENT_FILE: &str = "workflows/.recent.json";
-const MAX_RECENT: usize = 10;
 
 /// Returns the workflows directory path. Creates it if missing; if it already exists, we use it as ours.
 pub fn workflows_dir() -> PathBuf {
@@ -30,11 +41,15 @@ pub fn workflows_dir() -> PathBuf {
 #[derive(Resource, Default)]
 pub struct RecentFiles(pub Vec<PathBuf>);
 
+fn recent_file() -> PathBuf {
+    std::env::current_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join(RECENT_FILE)
+}
+
 /// Load recent files from disk. Call on startup.
 pub fn load_recent() -> Vec<PathBuf> {
-    let path = std::env::current_dir()
-        .unwrap_or_else(|_| PathBuf::from("."))
-        .join(RECENT_FILE);
+    let path = recent_file();
     let Ok(data) = std::fs::read_to_string(&path) else {
         return Vec::new();
     };
@@ -49,8 +64,7 @@ pub fn load_recent() -> Vec<PathBuf> {
 
 /// Save recent files to disk.
 pub fn save_recent(paths: &[PathBuf]) {
-    let base = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-    let path = base.join(RECENT_FILE);
+    let path = recent_file();
     if let Some(parent) = path.parent() {
         if let Err(e) = std::fs::create_dir_all(parent) {
             warn!("[IO] Failed to create parent dir for recent: {}", e);
@@ -67,84 +81,76 @@ pub fn save_recent(paths: &[PathBuf]) {
     }
 }
 
-/// Add a path to recent, dedupe, trim to MAX_RECENT. Call after load/save.
-pub fn add_to_recent(recent: &mut RecentFiles, path: PathBuf) {
+/// Add a path to recent, dedupe, trim to `max` (`max_recent` in the
+/// config). Call after load/save.
+pub fn add_to_recent(recent: &mut RecentFiles, path: PathBuf, max: usize) {
     recent.0.retain(|p| p != &path);
     recent.0.insert(0, path);
-    if recent.0.len() > MAX_RECENT {
-        recent.0.truncate(MAX_RECENT);
-    }
+    recent.0.truncate(max);
     save_recent(&recent.0);
 }
 
-/// Default node color when loading files without color (backwards compat).
-const DEFAULT_NODE_COLOR: [f32; 3] = [0.70, 0.85, 0.95];
-
-#[derive(Serialize, Deserialize, Clone)]
-pub struct SerializedColor {
-    pub r: f32,
-    pub g: f32,
-    pub b: f32,
+/// `paths` without files that no longer exist, trimmed to `max`.
+pub fn prune_recent(mut paths: Vec<PathBuf>, max: usize) -> Vec<PathBuf> {
+    paths.retain(|p| p.exists());
+    paths.truncate(max);
+    paths
 }
 
-impl SerializedColor {
-    pub fn from_bevy(color: &Color) -> Self {
-        let srgba = color.to_srgba();
-        Self {
-            r: srgba.red,
-            g: srgba.green,
-            b: srgba.blue,
-        }
-    }
-    pub fn to_bevy(&self) -> Color {
-        Color::srgb(self.r, self.g, self.b)
+/// Startup: load the recent files, dropping dead paths so File → Open Recent
+/// only lists files that can be opened. The pruned list is written back.
+pub fn load_recent_system(mut recent: ResMut<RecentFiles>, config: Res<GlyphConfig>) {
+    let _ = workflows_dir(); // ensure workflows folder exists
+    let loaded = load_recent();
+    let count = loaded.len();
+    recent.0 = prune_recent(loaded, config.max_recent);
+    if recent.0.len() != count {
+        info!("[IO] Pruned {} recent files", count - recent.0.len());
+        save_recent(&recent.0);
     }
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct SerializableNode {
-    pub id: u64,
-    pub x: f32,
-    pub y: f32,
-    pub text: String,
-    #[serde(default = "default_color")]
-    pub color: SerializedColor,
-}
-
-fn default_color() -> SerializedColor {
-    SerializedColor {
-        r: DEFAULT_NODE_COLOR[0],
-        g: DEFAULT_NODE_COLOR[1],
-        b: DEFAULT_NODE_COLOR[2],
+/// Empty the recent files list and delete `workflows/.recent.json`.
+pub fn clear_recent(recent: &mut RecentFiles) {
+    recent.0.clear();
+    match std::fs::remove_file(recent_file()) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => warn!("[IO] Failed to delete recent files: {}", e),
     }
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct SerializableEdge {
-    pub source_id: u64,
-    pub target_id: u64,
-    #[serde(default)]
-    pub label: Option<String>,
-}
+/// Current file path for save (None = untitled) and its modification time
+/// when it was last loaded or saved here.
+#[derive(Resource, Default)]
+pub struct CurrentFile(pub Option<std::path::PathBuf>, pub Option<SystemTime>);
 
-#[derive(Serialize, Deserialize, Clone)]
-pub struct SerializedCameraPrefs {
-    pub x: f32,
-    pub y: f32,
-    pub scale: f32,
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct CanvasSnapshot {
-    pub nodes: Vec<SerializableNode>,
-    pub edges: Vec<SerializableEdge>,
-    #[serde(default)]
-    pub camera: Option<SerializedCameraPrefs>,
-}
+impl CurrentFile {
+    /// Make `path` the current file, remembering its on-disk modification time.
+    pub fn set(&mut self, path: PathBuf) {
+        self.1 = modified_time(&path);
+        self.0 = Some(path);
+    }
 
-/// Current file path for save. None = untitled.
-#[derive(Resource, Default)]
-pub struct CurrentFile(pub Option<std::path::PathBuf>);
+    /// `Err` when `path` is the current file and something else wrote it
+    /// after it was loaded or last saved here.
+    pub fn check_unchanged(&self, path: &Path) -> Result<(), String> {
+        if self.0.as_deref() != Some(path) {
+            return Ok(());
+        }
+        match (self.1, modified_time(path)) {
+            (Some(known), Some(on_disk)) if on_disk != known => Err(format!(
+                "{} changed on disk since it was loaded (:w! to overwrite)",
+                path.display()
+            )),
+            _ => Ok(()),
+        }
+    }
+}
 
 /// Pending file dialog result from background thread. Check each frame.
 /// Wrapped in Mutex because Receiver is Send but not Sync.
@@ -179,87 +185,149 @@ pub fn camera_prefs_from_parts(transform: &Transform, proj: &Projection) -> Seri
     }
 }
 
-/// Core save logic — writes to the given path.
-pub fn save_to_path(
-    path: &Path,
-    node_query: &Query<(Entity, &Transform, &TextData, &NodeColor), With<CanvasNode>>,
-    edge_query: &Query<(Entity, &Edge)>,
-    camera_prefs: Option<SerializedCameraPrefs>,
-) -> Result<(), String> {
+/// Node query shape consumed by [`snapshot_canvas`] for full-fidelity snapshots.
+pub type SnapshotNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Transform,
+        &'static TextData,
+        &'static NodeColor,
+        Option<&'static SourceLocation>,
+        Has<DecisionNode>,
+        Option<&'static NodeShape>,
+        Option<&'static NodeSize>,
+        Has<Pinned>,
+    ),
+    (With<CanvasNode>, Without<MainCamera>, Without<InactiveCanvas>),
+>;
+
+/// Build a snapshot from live node and edge data. Crawl metadata (source
+/// location, decision marker) and pins are carried along so the snapshot can
+/// restore them.
+/// Edges whose endpoints are not in `nodes` are dropped; edges turned around
+/// by `:callers` (the `bool`) are written in their call direction.
+///
+/// Ids follow the nodes sorted by `(x, y, text)` and edges are sorted by
+/// their endpoint ids, so saving the same canvas twice gives the same file
+/// whatever order the query yields.
+pub fn snapshot_canvas<'a>(
+    nodes: impl IntoIterator<
+        Item = (
+            Entity,
+            &'a Transform,
+            &'a TextData,
+            &'a NodeColor,
+            Option<&'a SourceLocation>,
+            bool,
+            Option<&'a NodeShape>,
+            Option<&'a NodeSize>,
+            bool,
+        ),
+    >,
+    edges: impl IntoIterator<
+        Item = (&'a Edge, Option<&'a EdgeStroke>, Option<&'a CurveOffset>, bool),
+    >,
+    camera: Option<SerializedCameraPrefs>,
+) -> CanvasSnapshot {
     let mut entity_to_id = HashMap::new();
-    let mut nodes = Vec::new();
-    let mut next_id: u64 = 0;
-
-    for (entity, transform, text_data, node_color) in node_query {
-        let id = next_id;
-        next_id += 1;
+    let mut out_nodes = Vec::new();
+
+    let mut nodes: Vec<_> = nodes.into_iter().collect();
+    nodes.sort_by(|(_, a, a_text, ..), (_, b, b_text, ..)| {
+        let (a, b) = (a.translation, b.translation);
+        a.x.total_cmp(&b.x)
+            .then(a.y.total_cmp(&b.y))
+            .then_with(|| a_text.content.cmp(&b_text.content))
+    });
+    for (id, (entity, transform, text_data, node_color, source, decision, shape, size, pinned)) in
+        (0_u64..).zip(nodes)
+    {
         entity_to_id.insert(entity, id);
-        nodes.push(SerializableNode {
+        out_nodes.push(SerializableNode {
             id,
             x: transform.translation.x,
             y: transform.translation.y,
             text: text_data.content.clone(),
             color: SerializedColor::from_bevy(&node_color.0),
+            source: source.map(|s| SerializedSource {
+                file: s.file.clone(),
+                line: s.line,
+            }),
+            decision,
+            shape: shape.copied().unwrap_or_default(),
+            size: size.and_then(|s| s.base).map(|b| b.to_array()),
+            pinned,
         });
     }
 
-    let mut edges = Vec::new();
-    for (_, edge) in edge_query {
+    let mut out_edges = Vec::new();
+    for (edge, stroke, curve, reversed) in edges {
         let Some(&source_id) = entity_to_id.get(&edge.source) else {
             continue;
         };
         let Some(&target_id) = entity_to_id.get(&edge.target) else {
             continue;
         };
-        edges.push(SerializableEdge {
+        let (source_id, target_id) = if reversed {
+            (target_id, source_id)
+        } else {
+            (source_id, target_id)
+        };
+        out_edges.push(SerializableEdge {
             source_id,
             target_id,
             label: edge.label.clone(),
+            stroke: stroke.copied().unwrap_or_default(),
+            curve: curve.map(|c| c.0.to_array()),
+            reversed,
         });
     }
-
-    let camera = camera_prefs;
-    let snapshot = CanvasSnapshot {
-        nodes,
-        edges,
+    out_edges.sort_by(|a, b| {
+        (a.source_id, a.target_id, &a.label)
+            .cmp(&(b.source_id, b.target_id, &b.label))
+            .then(a.stroke.width.total_cmp(&b.stroke.width))
+            .then(a.stroke.dashed.cmp(&b.stroke.dashed))
+            .then(a.curve.partial_cmp(&b.curve).unwrap_or(std::cmp::Ordering::Equal))
+            .then(a.reversed.cmp(&b.reversed))
+    });
+
+    CanvasSnapshot {
+        nodes: out_nodes,
+        edges: out_edges,
         camera,
-    };
-    let json = serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?;
-    std::fs::write(path, json).map_err(|e| e.to_string())?;
-    Ok(())
-}
-
-/// Core load logic — reads from the given path and spawns entities.
-pub fn load_from_path(
-    path: &Path,
-    mut commands: Commands,
-    mut spatial_index: ResMut<crate::core::resources::SpatialIndex>,
-    mut current_file: ResMut<CurrentFile>,
-    node_query: &Query<Entity, With<CanvasNode>>,
-    edge_entity_query: &Query<Entity, With<Edge>>,
-    camera_query: &mut Query<(&mut Transform, &mut Projection), With<MainCamera>>,
-) -> Result<(), String> {
-    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let snapshot: CanvasSnapshot = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
-
-    let nodes_to_despawn: Vec<Entity> = node_query.iter().collect();
-    let edges_to_despawn: Vec<Entity> = edge_entity_query.iter().collect();
-    for entity in nodes_to_despawn {
-        commands.entity(entity).despawn();
-    }
-    for entity in edges_to_despawn {
-        commands.entity(entity).despawn();
+        marks: BTreeMap::new(),
     }
-    spatial_index.clear();
-
-    current_file.0 = Some(path.to_path_buf());
+}
 
+/// Spawn every node and edge in `snapshot`, restoring crawl metadata where
+/// present. Returns the snapshot-id → entity map. Does not touch the camera.
+pub fn spawn_snapshot(commands: &mut Commands, snapshot: &CanvasSnapshot) -> HashMap<u64, Entity> {
     let mut id_to_entity = HashMap::new();
 
     for node in &snapshot.nodes {
         let color = node.color.to_bevy();
-        let entity = spawn_node_with_color(&mut commands, node.x, node.y, &node.text, color);
-
+        let entity = spawn_node_with_color(commands, node.x, node.y, &node.text, color);
+        if let Some(src) = &node.source {
+            commands.entity(entity).insert(SourceLocation {
+                file: src.file.clone(),
+                line: src.line,
+            });
+            spawn_file_label(commands, entity, &src.file);
+        }
+        if node.decision {
+            commands.entity(entity).insert(DecisionNode);
+        }
+        if !node.shape.is_rect() {
+            commands.entity(entity).insert(node.shape);
+        }
+        if let Some(size) = node.size {
+            commands.entity(entity).insert(NodeSize::with_base(Vec2::from_array(size)));
+        }
+        if node.pinned {
+            commands.entity(entity).insert(Pinned);
+        }
         id_to_entity.insert(node.id, entity);
     }
 
@@ -270,13 +338,158 @@ pub fn load_from_path(
         let Some(&target) = id_to_entity.get(&edge.target_id) else {
             continue;
         };
-        commands.spawn(Edge {
-            source,
-            target,
-            label: edge.label.clone(),
-        });
+        let (source, target) = if edge.reversed {
+            (target, source)
+        } else {
+            (source, target)
+        };
+        let entity = commands
+            .spawn(Edge {
+                source,
+                target,
+                label: edge.label.clone(),
+            })
+            .id();
+        if edge.reversed {
+            commands.entity(entity).insert(ReversedCall);
+        }
+        if !edge.stroke.is_default() {
+            commands.entity(entity).insert(edge.stroke);
+        }
+        if let Some(curve) = edge.curve {
+            commands.entity(entity).insert(CurveOffset(Vec2::from_array(curve)));
+        }
     }
 
+    id_to_entity
+}
+
+/// Edges of the active tab, minus display-only ones.
+pub type SaveEdgeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Edge,
+        Option<&'static EdgeStroke>,
+        Option<&'static CurveOffset>,
+        Has<ReversedCall>,
+    ),
+    (Without<FlowBypass>, Without<InactiveCanvas>),
+>;
+
+/// Edges with their strokes and bends as [`snapshot_canvas`] takes them, for
+/// undo checkpoints. `:flow off` bypass edges are left out.
+pub type SnapshotEdgeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Edge,
+        Option<&'static EdgeStroke>,
+        Option<&'static CurveOffset>,
+        Has<ReversedCall>,
+    ),
+    Without<FlowBypass>,
+>;
+
+/// Canvas state written by [`save_to_path`]: nodes, edges and marks.
+#[derive(SystemParam)]
+pub struct SaveSources<'w, 's> {
+    pub nodes: SnapshotNodeQuery<'w, 's>,
+    /// `:flow off` bypass edges are display-only and left out.
+    pub edges: SaveEdgeQuery<'w, 's>,
+    pub marks: Res<'w, Marks>,
+}
+
+/// Core save logic — writes to the given path, creating missing parent directories.
+pub fn save_to_path(
+    path: &Path,
+    canvas: &SaveSources,
+    camera_prefs: Option<SerializedCameraPrefs>,
+) -> Result<(), String> {
+    let mut snapshot = snapshot_canvas(
+        canvas.nodes.iter(),
+        canvas
+            .edges
+            .iter()
+            .map(|(_, edge, stroke, curve, reversed)| (edge, stroke, curve, reversed)),
+        camera_prefs,
+    );
+    snapshot.marks = canvas
+        .marks
+        .locations
+        .iter()
+        .map(|(key, pos)| (*key, [pos.x, pos.y]))
+        .collect();
+    write_snapshot(path, &snapshot)
+}
+
+/// Write `snapshot` as a `.glyph` file, creating missing parent directories.
+pub fn write_snapshot(path: &Path, snapshot: &CanvasSnapshot) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(snapshot).map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("{}: {}", parent.display(), e))?;
+    }
+    std::fs::write(path, json).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Read and parse a `.glyph` file.
+pub fn read_snapshot(path: &Path) -> Result<CanvasSnapshot, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&contents).map_err(|e| e.to_string())
+}
+
+/// [`save_to_path`] that refuses to clobber a current file changed on disk
+/// (see [`CurrentFile::check_unchanged`]) unless `force`. On success `path`
+/// becomes the current file.
+pub fn save_checked(
+    path: &Path,
+    canvas: &SaveSources,
+    camera_prefs: Option<SerializedCameraPrefs>,
+    current_file: &mut CurrentFile,
+    force: bool,
+) -> Result<(), String> {
+    if !force {
+        current_file.check_unchanged(path)?;
+    }
+    save_to_path(path, canvas, camera_prefs)?;
+    current_file.set(path.to_path_buf());
+    Ok(())
+}
+
+/// Every node and edge entity of the active tab, despawned by [`load_from_path`]
+/// before respawning.
+pub type CanvasEntityQuery<'w, 's> =
+    Query<'w, 's, Entity, (Or<(With<CanvasNode>, With<Edge>)>, Without<InactiveCanvas>)>;
+
+/// Core load logic — reads from the given path, spawns entities and replaces
+/// the marks with the file's.
+pub fn load_from_path(
+    path: &Path,
+    mut commands: Commands,
+    mut spatial_index: ResMut<crate::core::resources::SpatialIndex>,
+    mut current_file: ResMut<CurrentFile>,
+    marks: &mut Marks,
+    canvas_query: &CanvasEntityQuery,
+    camera_query: &mut Query<(&mut Transform, &mut Projection), With<MainCamera>>,
+) -> Result<(), String> {
+    let snapshot = read_snapshot(path)?;
+
+    for entity in canvas_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    spatial_index.clear();
+
+    current_file.set(path.to_path_buf());
+
+    spawn_snapshot(&mut commands, &snapshot);
+    marks.locations = snapshot
+        .marks
+        .iter()
+        .map(|(key, [x, y])| (*key, Vec2::new(*x, *y)))
+        .collect();
+
     if let Some(prefs) = &snapshot.camera {
         if let Ok((mut transform, mut proj)) = camera_query.single_mut() {
             transform.translation.x = prefs.x;
@@ -296,8 +509,7 @@ pub fn save_canvas_system(
     keys: Res<ButtonInput<KeyCode>>,
     mut current_file: ResMut<CurrentFile>,
     mut status: ResMut<crate::core::resources::StatusMessage>,
-    node_query: Query<(Entity, &Transform, &TextData, &NodeColor), With<CanvasNode>>,
-    edge_query: Query<(Entity, &Edge)>,
+    canvas: SaveSources,
     camera_query: Query<(&Transform, &Projection), With<MainCamera>>,
 ) {
     if !keys.just_pressed(KeyCode::KeyS) || !is_save_modifier_pressed(&keys) {
@@ -313,9 +525,8 @@ pub fn save_canvas_system(
         .single()
         .ok()
         .map(|(t, p)| camera_prefs_from_parts(t, p));
-    match save_to_path(&path, &node_query, &edge_query, cam_prefs) {
+    match save_checked(&path, &canvas, cam_prefs, &mut current_file, false) {
         Ok(()) => {
-            current_file.0 = Some(path.clone());
             let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("file");
             status.set(format!("Saved {}", name));
             info!("[SAVE] Saved to {}", path.display());
@@ -333,13 +544,15 @@ pub fn save_canvas_system(
 pub fn process_pending_load_system(
     mut pending: ResMut<PendingLoad>,
     mut recent: ResMut<RecentFiles>,
+    config: Res<GlyphConfig>,
     mut status: ResMut<crate::core::resources::StatusMessage>,
     commands: Commands,
     spatial_index: ResMut<crate::core::resources::SpatialIndex>,
     current_file: ResMut<CurrentFile>,
+    mut marks: ResMut<Marks>,
     mut camera_query: Query<(&mut Transform, &mut Projection), With<MainCamera>>,
-    node_query: Query<Entity, With<CanvasNode>>,
-    edge_entity_query: Query<Entity, With<Edge>>,
+    canvas_query: CanvasEntityQuery,
+    mut checkpoint: CanvasCheckpoint,
 ) {
     let Some(path) = pending.0.take() else {
         return;
@@ -349,12 +562,13 @@ pub fn process_pending_load_system(
         commands,
         spatial_index,
         current_file,
-        &node_query,
-        &edge_entity_query,
+        &mut marks,
+        &canvas_query,
         &mut camera_query,
     ) {
         Ok(()) => {
-            add_to_recent(&mut recent, path.clone());
+            checkpoint.record_before();
+            add_to_recent(&mut recent, path.clone(), config.max_recent);
             let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("file");
             status.set(format!("Loaded {}", name));
             info!("[LOAD] Loaded from {}", path.display());
@@ -367,6 +581,70 @@ pub fn process_pending_load_system(
     }
 }
 
+/// Despawns every node and edge and respawns the queued snapshot.
+pub fn process_pending_restore_system(
+    mut commands: Commands,
+    mut pending: ResMut<PendingCanvasRestore>,
+    mut spatial_index: ResMut<crate::core::resources::SpatialIndex>,
+    mut selected_edge: ResMut<crate::core::resources::SelectedEdge>,
+    canvas_query: CanvasEntityQuery,
+) {
+    let Some(snapshot) = pending.0.take() else {
+        return;
+    };
+    for entity in canvas_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    spatial_index.clear();
+    selected_edge.0 = None;
+    spawn_snapshot(&mut commands, &snapshot);
+    info!(
+        "[UNDO] Restored canvas: {} nodes, {} edges",
+        snapshot.nodes.len(),
+        snapshot.edges.len()
+    );
+}
+
+/// `before` side of the `Action::ReplaceCanvas` recorded for a wholesale
+/// replacement (`:crawl`, `:e`). The `after` side is taken by
+/// [`push_canvas_checkpoint_system`] once the new entities exist.
+#[derive(Resource, Default)]
+pub struct PendingCanvasCheckpoint(pub Option<CanvasSnapshot>);
+
+/// The active canvas, for systems about to replace it wholesale.
+#[derive(SystemParam)]
+pub struct CanvasCheckpoint<'w, 's> {
+    pending: ResMut<'w, PendingCanvasCheckpoint>,
+    nodes: SnapshotNodeQuery<'w, 's>,
+    edges: SnapshotEdgeQuery<'w, 's>,
+}
+
+impl CanvasCheckpoint<'_, '_> {
+    /// Snapshot the canvas as the `before` of the next checkpoint. Despawns
+    /// are deferred, so the replacing system still sees the old canvas here.
+    /// A second replacement in the same frame keeps the first `before`.
+    pub fn record_before(&mut self) {
+        if self.pending.0.is_none() {
+            self.pending.0 = Some(snapshot_canvas(self.nodes.iter(), self.edges.iter(), None));
+        }
+    }
+}
+
+/// Push the pending checkpoint with the canvas that replaced it, so one `u`
+/// brings the previous canvas back.
+pub fn push_canvas_checkpoint_system(
+    mut pending: ResMut<PendingCanvasCheckpoint>,
+    mut history: ResMut<UndoHistory>,
+    nodes: SnapshotNodeQuery,
+    edges: SnapshotEdgeQuery,
+) {
+    let Some(before) = pending.0.take() else {
+        return;
+    };
+    let after = snapshot_canvas(nodes.iter(), edges.iter(), None);
+    history.push(Action::ReplaceCanvas { before, after });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -408,10 +686,10 @@ mod tests {
         std::env::set_current_dir(dir.path()).unwrap();
         let mut recent = RecentFiles::default();
         for i in 0..15 {
-            add_to_recent(&mut recent, PathBuf::from(format!("f{}.glyph", i)));
+            add_to_recent(&mut recent, PathBuf::from(format!("f{}.glyph", i)), 10);
         }
         std::env::set_current_dir(&old).unwrap();
-        assert_eq!(recent.0.len(), MAX_RECENT);
+        assert_eq!(recent.0.len(), 10);
     }
 
     #[test]
@@ -421,15 +699,42 @@ mod tests {
         let old = std::env::current_dir().unwrap();
         std::env::set_current_dir(dir.path()).unwrap();
         let mut recent = RecentFiles::default();
-        add_to_recent(&mut recent, PathBuf::from("a.glyph"));
-        add_to_recent(&mut recent, PathBuf::from("b.glyph"));
-        add_to_recent(&mut recent, PathBuf::from("a.glyph")); // dedupe: a moves to front
+        add_to_recent(&mut recent, PathBuf::from("a.glyph"), 10);
+        add_to_recent(&mut recent, PathBuf::from("b.glyph"), 10);
+        add_to_recent(&mut recent, PathBuf::from("a.glyph"), 10); // dedupe: a moves to front
         std::env::set_current_dir(&old).unwrap();
         assert_eq!(recent.0.len(), 2);
         assert_eq!(recent.0[0], PathBuf::from("a.glyph"));
         assert_eq!(recent.0[1], PathBuf::from("b.glyph"));
     }
 
+    #[test]
+    fn prune_and_clear_recent() {
+        let _g = IO_DIR_LOCK.lock().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let live: Vec<PathBuf> = (0..3)
+            .map(|i| {
+                let path = dir.path().join(format!("f{}.glyph", i));
+                std::fs::write(&path, "{}").unwrap();
+                path
+            })
+            .collect();
+        let gone = dir.path().join("gone.glyph");
+        let listed = vec![live[0].clone(), gone, live[1].clone(), live[2].clone()];
+        let pruned = prune_recent(listed, 2);
+
+        let mut recent = RecentFiles::default();
+        add_to_recent(&mut recent, live[0].clone(), 10);
+        clear_recent(&mut recent);
+        let reloaded = load_recent();
+        let file_left = dir.path().join(RECENT_FILE).exists();
+        std::env::set_current_dir(&old).unwrap();
+        assert_eq!(pruned, vec![live[0].clone(), live[1].clone()]);
+        assert!(recent.0.is_empty() && reloaded.is_empty() && !file_left);
+    }
+
     #[test]
     fn workflows_dir_creates_and_returns_path() {
         let _g = IO_DIR_LOCK.lock().unwrap();
@@ -456,6 +761,11 @@ mod tests {
                         g: 0.6,
                         b: 0.7,
                     },
+                    source: None,
+                    decision: false,
+                    shape: NodeShape::Rect,
+                    size: None,
+                    pinned: false,
                 },
                 SerializableNode {
                     id: 1,
@@ -467,41 +777,160 @@ mod tests {
                         g: 0.85,
                         b: 0.95,
                     },
+                    source: None,
+                    decision: false,
+                    shape: NodeShape::Diamond,
+                    size: Some([240.0, 90.0]),
+                    pinned: true,
                 },
             ],
             edges: vec![SerializableEdge {
                 source_id: 0,
                 target_id: 1,
                 label: Some("calls".to_string()),
+                stroke: EdgeStroke {
+                    width: 3.0,
+                    dashed: true,
+                },
+                curve: Some([40.0, -20.0]),
+                reversed: false,
             }],
             camera: Some(SerializedCameraPrefs {
                 x: 0.0,
                 y: 0.0,
                 scale: 1.0,
             }),
+            marks: BTreeMap::from([('a', [5.0, -5.0])]),
         };
         let json = serde_json::to_string_pretty(&snapshot).unwrap();
         let loaded: CanvasSnapshot = serde_json::from_str(&json).unwrap();
         assert_eq!(loaded.nodes.len(), 2);
         assert_eq!(loaded.nodes[0].text, "node1");
         assert_eq!(loaded.edges[0].label.as_deref(), Some("calls"));
+        assert_eq!(loaded.edges[0].stroke.width, 3.0);
+        assert!(loaded.edges[0].stroke.dashed);
+        assert_eq!(loaded.edges[0].curve, Some([40.0, -20.0]));
+        assert_eq!(loaded.marks.get(&'a'), Some(&[5.0, -5.0]));
+        assert_eq!(loaded.nodes[0].shape, NodeShape::Rect);
+        assert_eq!(loaded.nodes[1].shape, NodeShape::Diamond);
+        assert_eq!(json.matches("\"shape\"").count(), 1, "rect shapes are omitted");
+        assert_eq!(loaded.nodes[0].size, None);
+        assert_eq!(loaded.nodes[1].size, Some([240.0, 90.0]));
+        assert!(!loaded.nodes[0].pinned && loaded.nodes[1].pinned);
+        assert_eq!(json.matches("\"pinned\"").count(), 1, "unpinned nodes are omitted");
+    }
+
+    #[test]
+    fn snapshot_without_marks_still_loads() {
+        let loaded: CanvasSnapshot = serde_json::from_str(r#"{"nodes": [], "edges": []}"#).unwrap();
+        assert!(loaded.marks.is_empty());
+    }
+
+    #[test]
+    fn snapshot_ids_do_not_depend_on_query_order() {
+        let entities = [Entity::from_bits(1), Entity::from_bits(2), Entity::from_bits(3)];
+        let transforms = [
+            Transform::from_xyz(100.0, 0.0, 0.0),
+            Transform::from_xyz(-50.0, 10.0, 0.0),
+            Transform::from_xyz(-50.0, -10.0, 0.0),
+        ];
+        let texts = ["c", "b", "a"].map(|t| TextData { content: t.into() });
+        let color = NodeColor(Color::WHITE);
+        let edges = [
+            Edge {
+                source: entities[0],
+                target: entities[1],
+                label: None,
+            },
+            Edge {
+                source: entities[2],
+                target: entities[0],
+                label: None,
+            },
+        ];
+        let save = |order: [usize; 3], edge_order: [usize; 2]| {
+            let nodes = order.map(|i| {
+                (entities[i], &transforms[i], &texts[i], &color, None, false, None, None, false)
+            });
+            let edges = edge_order.map(|i| (&edges[i], None, None, i == 1));
+            let snapshot = snapshot_canvas(nodes, edges, None);
+            serde_json::to_string_pretty(&snapshot).unwrap()
+        };
+
+        let json = save([0, 1, 2], [0, 1]);
+        assert_eq!(json, save([2, 0, 1], [1, 0]));
+        let snapshot: CanvasSnapshot = serde_json::from_str(&json).unwrap();
+        let texts: Vec<_> = snapshot.nodes.iter().map(|n| (n.id, n.text.as_str())).collect();
+        assert_eq!(texts, [(0, "a"), (1, "b"), (2, "c")]);
+        let ids: Vec<_> = snapshot.edges.iter().map(|e| (e.source_id, e.target_id)).collect();
+        // The second edge is shown reversed (`:callers`) and saved as called.
+        assert_eq!(ids, [(0, 2), (1, 2)]);
+        assert!(snapshot.edges[1].reversed);
+    }
+
+    #[test]
+    fn save_creates_missing_parent_directories() {
+        use bevy::ecs::system::RunSystemOnce;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sub/dir/diagram.glyph");
+        let mut world = World::new();
+        world.init_resource::<Marks>();
+        let target = path.clone();
+        let saved = world
+            .run_system_once(move |canvas: SaveSources| save_to_path(&target, &canvas, None))
+            .unwrap();
+        assert_eq!(saved, Ok(()));
+        assert!(path.exists());
+
+        // A file where a directory should be is reported, not panicked on.
+        let blocked = dir.path().join("diagram.glyph/inner.glyph");
+        std::fs::write(dir.path().join("diagram.glyph"), "{}").unwrap();
+        let saved = world
+            .run_system_once(move |canvas: SaveSources| save_to_path(&blocked, &canvas, None))
+            .unwrap();
+        assert!(saved.is_err());
+    }
+
+    #[test]
+    fn current_file_detects_changes_made_elsewhere() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shared.glyph");
+        std::fs::write(&path, "{}").unwrap();
+        let mut current = CurrentFile::default();
+        current.set(path.clone());
+        assert!(current.check_unchanged(&path).is_ok());
+
+        // Someone else saves the file later.
+        let later = current.1.unwrap() + std::time::Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(current.check_unchanged(&path).is_err());
+        // Other paths are never blocked, and re-recording clears the conflict.
+        assert!(current.check_unchanged(&dir.path().join("other.glyph")).is_ok());
+        current.set(path.clone());
+        assert!(current.check_unchanged(&path).is_ok());
     }
 }
 
-/// Load canvas on Ctrl+O (or Cmd+O). Reads workspace.glyph directly.
-/// Menu bar Open still opens a file picker for multi-file.
+/// Load canvas on Ctrl+O (or Cmd+O). Queues workspace.glyph for
+/// `process_pending_load_system`; menu bar Open still opens a file picker.
+/// In Vim Normal mode plain Ctrl+O walks the jump list instead, so only Cmd+O loads.
 pub fn load_canvas_system(
     keys: Res<ButtonInput<KeyCode>>,
-    commands: Commands,
-    spatial_index: ResMut<crate::core::resources::SpatialIndex>,
-    current_file: ResMut<CurrentFile>,
-    mut camera_query: Query<(&mut Transform, &mut Projection), With<MainCamera>>,
-    node_query: Query<Entity, With<CanvasNode>>,
-    edge_entity_query: Query<Entity, With<Edge>>,
+    mode: Res<State<crate::core::state::InputMode>>,
+    mut pending: ResMut<PendingLoad>,
 ) {
     if !keys.just_pressed(KeyCode::KeyO) || !is_save_modifier_pressed(&keys) {
         return;
     }
+    let cmd_pressed = keys.pressed(KeyCode::SuperLeft) || keys.pressed(KeyCode::SuperRight);
+    if *mode.get() == crate::core::state::InputMode::VimNormal && !cmd_pressed {
+        return;
+    }
 
     let path = std::path::Path::new(WORKSPACE_PATH);
     if !path.exists() {
@@ -511,17 +940,5 @@ pub fn load_canvas_system(
         );
         return;
     }
-
-    match load_from_path(
-        path,
-        commands,
-        spatial_index,
-        current_file,
-        &node_query,
-        &edge_entity_query,
-        &mut camera_query,
-    ) {
-        Ok(()) => info!("[LOAD] Loaded from {}", WORKSPACE_PATH),
-        Err(e) => error!("[LOAD] Failed to deserialize: {}", e),
-    }
+    pending.0 = Some(path.to_path_buf());
 }
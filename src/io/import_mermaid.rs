@@ -0,0 +1,291 @@
+//! Mermaid import: `:import diagram.mmd`
+//!
+//! Reads a Mermaid `flowchart` (or `graph`) and replaces the canvas with its
+//! nodes and edges, laid out top-down with the crawler's hierarchy levels.
+//! Supports `A[label]`, `A(label)`, `A --> B` and `A -->|text| B`; `subgraph`,
+//! `style` and similar directives are skipped.
+
+use bevy::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::core::config::GlyphConfig;
+use crate::core::history::{Action, UndoHistory};
+use crate::crawler::{hierarchy_levels, CallGraph, FlowEdge};
+use crate::io::file_io::{
+    snapshot_canvas, CanvasSnapshot, PendingCanvasRestore, SerializableEdge, SerializableNode,
+    SerializedColor, SnapshotEdgeQuery, SnapshotNodeQuery,
+};
+
+/// Message sent by `:import <file>.mmd`.
+#[derive(Message)]
+pub struct ImportRequest {
+    pub path: String,
+}
+
+/// Parsed flowchart: nodes as (id, label) in first-seen order, edges as
+/// (source index, target index, label).
+#[derive(Debug, Default, PartialEq)]
+pub struct MermaidGraph {
+    pub nodes: Vec<(String, String)>,
+    pub edges: Vec<(usize, usize, Option<String>)>,
+}
+
+/// Directives that are valid Mermaid but have no canvas equivalent yet.
+const IGNORED_DIRECTIVES: &[&str] = &[
+    "subgraph", "end", "style", "classDef", "class", "linkStyle", "click", "direction",
+];
+
+impl MermaidGraph {
+    /// Index of node `id`, adding it on first sight. A bracketed label
+    /// replaces the default (the id itself) wherever it appears.
+    fn node(&mut self, id: &str, label: Option<&str>) -> usize {
+        let idx = match self.nodes.iter().position(|(n, _)| n == id) {
+            Some(idx) => idx,
+            None => {
+                self.nodes.push((id.to_string(), id.to_string()));
+                self.nodes.len() - 1
+            }
+        };
+        if let Some(label) = label {
+            self.nodes[idx].1 = label.to_string();
+        }
+        idx
+    }
+}
+
+/// Split `A[label]` / `A(label)` / `A` into id and optional label.
+fn parse_node_ref(s: &str) -> Result<(&str, Option<&str>), String> {
+    let s = s.trim();
+    let id_len = s
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(s.len());
+    let (id, rest) = s.split_at(id_len);
+    if id.is_empty() {
+        return Err(format!("expected a node id in '{}'", s));
+    }
+    let rest = rest.trim();
+    if rest.is_empty() {
+        return Ok((id, None));
+    }
+    let close = match rest.chars().next() {
+        Some('[') => ']',
+        Some('(') => ')',
+        _ => return Err(format!("unsupported node shape '{}'", s)),
+    };
+    if !rest.ends_with(close) {
+        return Err(format!("unclosed label in '{}'", s));
+    }
+    // Strip nested shape brackets such as `((round))` or `([stadium])`, then quotes.
+    let label = rest
+        .trim_start_matches(['[', '('])
+        .trim_end_matches([']', ')'])
+        .trim()
+        .trim_matches('"');
+    Ok((id, Some(label)))
+}
+
+/// Parse a Mermaid flowchart. The first meaningful line must be
+/// `flowchart …` or `graph …`; `%%` comments and ignored directives are skipped.
+pub fn parse_mermaid(src: &str) -> Result<MermaidGraph, String> {
+    let mut graph = MermaidGraph::default();
+    let mut seen_header = false;
+    for (lineno, raw) in src.lines().enumerate() {
+        let line = raw.trim().trim_end_matches(';').trim();
+        if line.is_empty() || line.starts_with("%%") {
+            continue;
+        }
+        let first = line.split_whitespace().next().unwrap_or("");
+        if !seen_header {
+            if first != "flowchart" && first != "graph" {
+                return Err("not a Mermaid flowchart (expected 'flowchart' or 'graph')".into());
+            }
+            seen_header = true;
+            continue;
+        }
+        if IGNORED_DIRECTIVES.contains(&first) {
+            continue;
+        }
+        let err = |e: String| format!("line {}: {}", lineno + 1, e);
+
+        let mut parts = line.split("-->");
+        let (id, label) = parse_node_ref(parts.next().unwrap_or("")).map_err(err)?;
+        let mut prev = graph.node(id, label);
+        for part in parts {
+            let part = part.trim();
+            let (edge_label, target) = match part.strip_prefix('|') {
+                Some(rest) => {
+                    let end = rest.find('|').ok_or_else(|| err("unclosed |edge text|".into()))?;
+                    (Some(rest[..end].trim().to_string()), &rest[end + 1..])
+                }
+                None => (None, part),
+            };
+            let (id, label) = parse_node_ref(target).map_err(err)?;
+            let next = graph.node(id, label);
+            graph.edges.push((prev, next, edge_label));
+            prev = next;
+        }
+    }
+    if !seen_header {
+        return Err("empty Mermaid file".into());
+    }
+    Ok(graph)
+}
+
+/// Lay out a parsed flowchart with the crawler's hierarchy levels: roots on
+/// the top row, each level one `flow_row_height` lower.
+pub fn mermaid_to_snapshot(graph: &MermaidGraph, config: &GlyphConfig) -> CanvasSnapshot {
+    let ids: Vec<String> = graph.nodes.iter().map(|(id, _)| id.clone()).collect();
+    let mut call_graph = CallGraph::new();
+    for id in &ids {
+        call_graph.entry(id.clone()).or_default();
+    }
+    for (s, t, _) in &graph.edges {
+        call_graph.entry(ids[*s].clone()).or_default().push(FlowEdge {
+            target: ids[*t].clone(),
+            label: None,
+        });
+    }
+    let levels = hierarchy_levels(&call_graph, &ids);
+
+    let mut rows: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, id) in ids.iter().enumerate() {
+        rows.entry(levels.get(id).copied().unwrap_or(0)).or_default().push(i);
+    }
+    let color = SerializedColor::from_bevy(&config.node_color());
+    let mut nodes: Vec<SerializableNode> = Vec::with_capacity(ids.len());
+    for (lvl, members) in &rows {
+        let y = -(*lvl as f32) * config.flow_row_height;
+        for (col, &i) in members.iter().enumerate() {
+            nodes.push(SerializableNode {
+                id: i as u64,
+                x: (col as f32 - members.len() as f32 * 0.5) * config.flow_node_spacing,
+                y,
+                text: graph.nodes[i].1.clone(),
+                color: color.clone(),
+                source: None,
+                decision: false,
+                shape: Default::default(),
+                size: None,
+                pinned: false,
+            });
+        }
+    }
+    nodes.sort_by_key(|n| n.id);
+
+    let edges = graph
+        .edges
+        .iter()
+        .map(|(s, t, label)| SerializableEdge {
+            source_id: *s as u64,
+            target_id: *t as u64,
+            label: label.clone(),
+            stroke: Default::default(),
+            curve: None,
+            reversed: false,
+        })
+        .collect();
+    CanvasSnapshot {
+        nodes,
+        edges,
+        camera: None,
+        marks: BTreeMap::new(),
+    }
+}
+
+/// Handles `ImportRequest`: parse the file, push a `ReplaceCanvas` checkpoint
+/// and queue the imported canvas.
+pub fn handle_import_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<ImportRequest>,
+    mut history: ResMut<UndoHistory>,
+    mut status: ResMut<crate::core::resources::StatusMessage>,
+    config: Res<GlyphConfig>,
+    node_query: SnapshotNodeQuery,
+    edge_query: SnapshotEdgeQuery,
+) {
+    for req in requests.read() {
+        let parsed = std::fs::read_to_string(&req.path)
+            .map_err(|e| e.to_string())
+            .and_then(|src| parse_mermaid(&src));
+        let graph = match parsed {
+            Ok(graph) => graph,
+            Err(e) => {
+                warn!("[IMPORT] {}: {}", req.path, e);
+                status.set(format!("Import failed: {}", e));
+                continue;
+            }
+        };
+
+        let before = snapshot_canvas(node_query.iter(), edge_query.iter(), None);
+        let after = mermaid_to_snapshot(&graph, &config);
+        info!(
+            "[IMPORT] {}: {} nodes, {} edges",
+            req.path,
+            after.nodes.len(),
+            after.edges.len()
+        );
+        status.set(format!(
+            "Imported {} nodes, {} edges",
+            after.nodes.len(),
+            after.edges.len()
+        ));
+        commands.insert_resource(PendingCanvasRestore(Some(after.clone())));
+        history.push(Action::ReplaceCanvas { before, after });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_shapes_chains_and_edge_text() {
+        let src = r#"
+flowchart TD
+    %% a comment
+    A[Start] --> B(Check)
+    B -->|yes| C["Done"];
+    B -->|no| A
+    C --> D --> E
+"#;
+        let g = parse_mermaid(src).unwrap();
+        let labels: Vec<&str> = g.nodes.iter().map(|(_, l)| l.as_str()).collect();
+        assert_eq!(labels, ["Start", "Check", "Done", "D", "E"]);
+        assert_eq!(g.edges.len(), 5);
+        assert_eq!(g.edges[1], (1, 2, Some("yes".to_string())));
+        assert_eq!(g.edges[2], (1, 0, Some("no".to_string())));
+        assert_eq!(g.edges[4], (3, 4, None));
+    }
+
+    #[test]
+    fn skips_subgraph_and_style_lines() {
+        let src = "graph LR\nsubgraph one\nA --> B\nend\nstyle A fill:#f9f\n";
+        let g = parse_mermaid(src).unwrap();
+        assert_eq!(g.nodes.len(), 2);
+        assert_eq!(g.edges.len(), 1);
+    }
+
+    #[test]
+    fn rejects_other_diagrams_and_bad_lines() {
+        assert!(parse_mermaid("sequenceDiagram\nA->>B: hi\n").is_err());
+        assert!(parse_mermaid("").is_err());
+        let err = parse_mermaid("flowchart TD\nA[open --> B\n").unwrap_err();
+        assert!(err.starts_with("line 2"), "{}", err);
+    }
+
+    #[test]
+    fn snapshot_places_levels_in_rows() {
+        let g = parse_mermaid("flowchart TD\nA --> B\nA --> C\nB --> D\n").unwrap();
+        let config = GlyphConfig::default();
+        let snap = mermaid_to_snapshot(&g, &config);
+        let y = |id: u64| snap.nodes.iter().find(|n| n.id == id).unwrap().y;
+        assert_eq!(y(0), 0.0);
+        assert_eq!(y(1), -config.flow_row_height);
+        assert_eq!(y(1), y(2));
+        assert_eq!(y(3), -2.0 * config.flow_row_height);
+        let b = &snap.nodes[1];
+        let c = &snap.nodes[2];
+        assert_ne!(b.x, c.x, "siblings are spread across the row");
+        assert_eq!(snap.edges.len(), 3);
+    }
+}
@@ -1,5 +1,14 @@
-//! File I/O, stdin piping, and headless export.
+//! File I/O, stdin piping, headless export, diff and templates.
 
+pub mod clipboard;
+pub mod diff;
+pub mod export_dot;
+pub mod export_json;
+pub mod export_plantuml;
 pub mod file_io;
 pub mod headless;
+pub mod import_mermaid;
+pub mod prefs;
 pub mod stdin;
+pub mod template;
+pub mod window_state;
@@ -0,0 +1,180 @@
+//! Session preferences kept between launches in `workflows/.prefs.json`:
+//! force layout on or off, theme, snap grid, the camera, and whether to
+//! reopen the most recent file. Unlike `GlyphConfig` (`~/.glyphrc`), this is
+//! state the app writes itself on exit. `--fresh` or `GLYPH_FRESH=1` starts
+//! without it and leaves the file alone.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use super::file_io::{camera_prefs_from_parts, SerializedCameraPrefs};
+use crate::core::components::MainCamera;
+use crate::core::config::{GlyphConfig, ThemeName};
+use crate::render::layout::ForceLayoutActive;
+
+const PREFS_FILE: &str = "workflows/.prefs.json";
+/// Set to anything but `0` to ignore saved preferences, like `--fresh`.
+pub const FRESH_ENV: &str = "GLYPH_FRESH";
+
+/// Last session's preferences. Fields missing from the file keep the config.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct UserPrefs {
+    pub force_layout: bool,
+    pub theme: Option<ThemeName>,
+    /// Snap grid size; `0` records that snapping was off.
+    pub snap_grid: Option<f32>,
+    pub camera: Option<SerializedCameraPrefs>,
+    /// Open the most recent file on launch. Only ever set by hand.
+    pub reopen_last: bool,
+}
+
+impl UserPrefs {
+    /// Put the remembered theme and snap grid into `config`.
+    pub fn apply_to_config(&self, config: &mut GlyphConfig) {
+        if let Some(theme) = self.theme {
+            config.theme = theme;
+        }
+        if let Some(grid) = self.snap_grid {
+            config.snap_grid = (grid > 0.0).then_some(grid);
+        }
+    }
+}
+
+/// True when `--fresh` was passed or `GLYPH_FRESH` is set (and not `0`).
+pub fn start_fresh(flag: bool) -> bool {
+    flag || std::env::var(FRESH_ENV).is_ok_and(|v| v != "0")
+}
+
+fn prefs_file() -> PathBuf {
+    std::env::current_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join(PREFS_FILE)
+}
+
+/// Preferences saved by the last session; defaults if there are none.
+pub fn load_prefs() -> UserPrefs {
+    let Ok(data) = std::fs::read_to_string(prefs_file()) else {
+        return UserPrefs::default();
+    };
+    match serde_json::from_str(&data) {
+        Ok(prefs) => prefs,
+        Err(e) => {
+            warn!("[IO] Failed to parse preferences: {}", e);
+            UserPrefs::default()
+        }
+    }
+}
+
+fn save_prefs(prefs: &UserPrefs) {
+    let path = prefs_file();
+    if let Some(parent) = path.parent() {
+        if let Err(e) = std::fs::create_dir_all(parent) {
+            warn!("[IO] Failed to create parent dir for preferences: {}", e);
+            return;
+        }
+    }
+    match serde_json::to_string_pretty(prefs) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                warn!("[IO] Failed to write preferences: {}", e);
+            }
+        }
+        Err(e) => warn!("[IO] Failed to serialize preferences: {}", e),
+    }
+}
+
+/// Move the camera where the last session left it. A file opened on launch
+/// then applies its own camera.
+pub fn apply_camera_prefs_system(
+    prefs: Option<Res<UserPrefs>>,
+    mut camera_q: Query<(&mut Transform, &mut Projection), With<MainCamera>>,
+) {
+    let Some(camera) = prefs.as_ref().and_then(|p| p.camera.as_ref()) else {
+        return;
+    };
+    let Ok((mut transform, mut proj)) = camera_q.single_mut() else {
+        return;
+    };
+    transform.translation.x = camera.x;
+    transform.translation.y = camera.y;
+    if let Projection::Orthographic(ortho) = proj.as_mut() {
+        ortho.scale = camera.scale.clamp(0.1, 10.0);
+    }
+}
+
+/// The preferences to write for the current session state.
+fn session_prefs(
+    prefs: &UserPrefs,
+    config: &GlyphConfig,
+    force_layout: &ForceLayoutActive,
+    camera: Option<SerializedCameraPrefs>,
+) -> UserPrefs {
+    UserPrefs {
+        force_layout: force_layout.active,
+        theme: Some(config.theme),
+        snap_grid: Some(config.snap_grid().unwrap_or(0.0)),
+        camera: camera.or_else(|| prefs.camera.clone()),
+        reopen_last: prefs.reopen_last,
+    }
+}
+
+/// Save the session's preferences when the app exits. Without a `UserPrefs`
+/// resource (a fresh start) nothing is written.
+pub fn save_prefs_system(
+    mut exits: MessageReader<AppExit>,
+    prefs: Option<Res<UserPrefs>>,
+    config: Res<GlyphConfig>,
+    force_layout: Res<ForceLayoutActive>,
+    camera_q: Query<(&Transform, &Projection), With<MainCamera>>,
+) {
+    if exits.read().last().is_none() {
+        return;
+    }
+    let Some(prefs) = prefs else {
+        return;
+    };
+    let camera = camera_q
+        .single()
+        .ok()
+        .map(|(t, p)| camera_prefs_from_parts(t, p));
+    save_prefs(&session_prefs(&prefs, &config, &force_layout, camera));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefs_roundtrip_through_the_session() {
+        let mut config = GlyphConfig::default();
+        let old: UserPrefs = serde_json::from_str(r#"{"reopen_last":true}"#).unwrap();
+        old.apply_to_config(&mut config);
+        assert_eq!(
+            config.theme,
+            ThemeName::Dark,
+            "missing fields keep the config"
+        );
+
+        config.theme = ThemeName::Light;
+        config.snap_grid = None;
+        let layout = ForceLayoutActive {
+            active: true,
+            iterations: 3,
+        };
+        let saved = session_prefs(&old, &config, &layout, None);
+        assert!(saved.force_layout && saved.reopen_last);
+        assert_eq!(saved.snap_grid, Some(0.0));
+
+        let json = serde_json::to_string(&saved).unwrap();
+        let loaded: UserPrefs = serde_json::from_str(&json).unwrap();
+        let mut next = GlyphConfig {
+            snap_grid: Some(20.0),
+            ..GlyphConfig::default()
+        };
+        loaded.apply_to_config(&mut next);
+        assert_eq!(next.theme, ThemeName::Light);
+        assert_eq!(next.snap_grid, None, "grid off is remembered");
+    }
+}
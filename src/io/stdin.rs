@@ -1,32 +1,44 @@
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::core::components::MainCamera;
 
 #[derive(Resource)]
 pub struct StdinSnapshot(pub crate::io::file_io::CanvasSnapshot);
 
-pub fn load_stdin_snapshot_system(mut commands: Commands, snapshot: Option<Res<StdinSnapshot>>) {
+/// Spawn the piped snapshot. The camera uses the snapshot's saved prefs when
+/// present, otherwise it is fitted to all nodes so headless exports of
+/// off-origin content are never empty. Must run after the camera is spawned.
+pub fn load_stdin_snapshot_system(
+    mut commands: Commands,
+    snapshot: Option<Res<StdinSnapshot>>,
+    mut camera_query: Query<(&mut Transform, &mut Projection), With<MainCamera>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
     let Some(snap) = snapshot else {
         return;
     };
-    let mut id_to_entity = std::collections::HashMap::new();
-
-    for node in &snap.0.nodes {
-        let color = node.color.to_bevy();
-        let entity =
-            crate::core::helpers::spawn_node_with_color(&mut commands, node.x, node.y, &node.text, color);
-        id_to_entity.insert(node.id, entity);
-    }
+    crate::io::file_io::spawn_snapshot(&mut commands, &snap.0);
 
-    for edge in &snap.0.edges {
-        let Some(&source) = id_to_entity.get(&edge.source_id) else {
-            continue;
-        };
-        let Some(&target) = id_to_entity.get(&edge.target_id) else {
-            continue;
+    let Ok((mut transform, mut proj)) = camera_query.single_mut() else {
+        return;
+    };
+    let (centre, scale) = if let Some(prefs) = &snap.0.camera {
+        (Vec2::new(prefs.x, prefs.y), prefs.scale.clamp(0.1, 10.0))
+    } else {
+        let viewport = window_query
+            .single()
+            .map(|w| w.resolution.size())
+            .unwrap_or(Vec2::new(1280.0, 720.0));
+        let points: Vec<Vec2> = snap.0.nodes.iter().map(|n| Vec2::new(n.x, n.y)).collect();
+        let Some(fit) = crate::input::camera::fit_to_points(&points, viewport) else {
+            return;
         };
-        commands.spawn(crate::core::components::Edge {
-            source,
-            target,
-            label: edge.label.clone(),
-        });
+        fit
+    };
+    transform.translation.x = centre.x;
+    transform.translation.y = centre.y;
+    if let Projection::Orthographic(ref mut ortho) = *proj {
+        ortho.scale = scale;
     }
 }
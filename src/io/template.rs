@@ -0,0 +1,368 @@
+//! Templates: `:template save <name>` writes the selected cluster to
+//! `workflows/templates/<name>.json` in the `.glyph` format, and
+//! `:template insert <name>` stamps a fresh copy of it at the mouse cursor.
+//! The Command Palette lists saved templates too.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::file_io::{
+    snapshot_canvas, spawn_snapshot, workflows_dir, CanvasSnapshot, SaveEdgeQuery,
+    SnapshotNodeQuery,
+};
+use crate::core::components::{CanvasNode, CurveOffset, Edge, MainCamera, Selected};
+use crate::core::history::{Action, UndoHistory};
+use crate::core::resources::StatusMessage;
+use crate::input::selection::connected_component;
+
+/// Folder under `workflows/` holding the templates.
+const TEMPLATES_DIR: &str = "templates";
+
+/// Message sent by `:template` and the palette.
+#[derive(Message)]
+pub enum TemplateRequest {
+    /// Save the visual `members`, or the selected node's connected component
+    /// when there are none.
+    Save {
+        name: String,
+        members: Vec<Entity>,
+    },
+    Insert(String),
+}
+
+pub fn templates_dir() -> PathBuf {
+    workflows_dir().join(TEMPLATES_DIR)
+}
+
+/// `dir/<name>.json`; `Err` unless the name is letters, digits, `-` and `_`,
+/// so a template cannot be written outside `dir`.
+fn template_path(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!(
+            "bad template name {:?} (use letters, digits, - and _)",
+            name
+        ));
+    }
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+/// Names of the templates in `dir`, sorted.
+pub fn list_templates(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| {
+            let path = e.ok()?.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// `snapshot` as a template: centered on the origin, without camera, marks
+/// or source locations (a stamped copy is a user node, like `Y`).
+fn to_template(mut snapshot: CanvasSnapshot) -> CanvasSnapshot {
+    let center = bbox_center(&snapshot);
+    for node in &mut snapshot.nodes {
+        node.x -= center.x;
+        node.y -= center.y;
+        node.source = None;
+    }
+    snapshot.camera = None;
+    snapshot.marks = BTreeMap::new();
+    snapshot
+}
+
+fn bbox_center(snapshot: &CanvasSnapshot) -> Vec2 {
+    let (mut min, mut max) = (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY));
+    for node in &snapshot.nodes {
+        let p = Vec2::new(node.x, node.y);
+        min = min.min(p);
+        max = max.max(p);
+    }
+    if snapshot.nodes.is_empty() {
+        Vec2::ZERO
+    } else {
+        (min + max) * 0.5
+    }
+}
+
+pub fn save_template(dir: &Path, name: &str, snapshot: CanvasSnapshot) -> Result<PathBuf, String> {
+    let path = template_path(dir, name)?;
+    std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let json = serde_json::to_string_pretty(&to_template(snapshot)).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+pub fn load_template(dir: &Path, name: &str) -> Result<CanvasSnapshot, String> {
+    let path = template_path(dir, name)?;
+    let data = std::fs::read_to_string(&path).map_err(|_| format!("no template {:?}", name))?;
+    serde_json::from_str(&data).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Nodes and edges a template is cut from.
+#[derive(SystemParam)]
+pub struct TemplateSources<'w, 's> {
+    nodes: SnapshotNodeQuery<'w, 's>,
+    edges: SaveEdgeQuery<'w, 's>,
+    selected: Query<'w, 's, Entity, (With<Selected>, With<CanvasNode>)>,
+}
+
+impl TemplateSources<'_, '_> {
+    /// Snapshot of `members` and the edges between them, or of the selected
+    /// node's connected component when `members` is empty.
+    fn snapshot(&self, members: &[Entity]) -> Option<CanvasSnapshot> {
+        let members: Vec<Entity> = if members.is_empty() {
+            let root = self.selected.single().ok()?;
+            let edges: Vec<(Entity, Entity)> = self
+                .edges
+                .iter()
+                .map(|(_, e, ..)| (e.source, e.target))
+                .collect();
+            connected_component(root, &edges).into_iter().collect()
+        } else {
+            members.to_vec()
+        };
+        let nodes = self.nodes.iter().filter(|(e, ..)| members.contains(e));
+        let edges = self
+            .edges
+            .iter()
+            .map(|(_, edge, stroke, curve, reversed)| (edge, stroke, curve, reversed));
+        let snapshot = snapshot_canvas(nodes, edges, None);
+        (!snapshot.nodes.is_empty()).then_some(snapshot)
+    }
+}
+
+/// Where `:template insert` stamps: the mouse cursor, else the middle of the view.
+#[derive(SystemParam)]
+pub struct InsertPoint<'w, 's> {
+    window: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    camera: Query<'w, 's, (&'static Camera, &'static GlobalTransform), With<MainCamera>>,
+}
+
+impl InsertPoint<'_, '_> {
+    fn world(&self) -> Vec2 {
+        let Ok(window) = self.window.single() else {
+            return Vec2::ZERO;
+        };
+        let Ok((camera, cam_transform)) = self.camera.single() else {
+            return Vec2::ZERO;
+        };
+        let screen = window.cursor_position().unwrap_or(window.size() * 0.5);
+        camera
+            .viewport_to_world_2d(cam_transform, screen)
+            .unwrap_or(Vec2::ZERO)
+    }
+}
+
+/// Spawn `template` centered on `at` with fresh entities. Returns the
+/// `CreateNode` / `CreateEdge` actions, nodes first.
+fn stamp(commands: &mut Commands, template: &CanvasSnapshot, at: Vec2) -> Vec<Action> {
+    let mut placed = template.clone();
+    for node in &mut placed.nodes {
+        node.x += at.x;
+        node.y += at.y;
+    }
+    let edges = std::mem::take(&mut placed.edges);
+    let id_to_entity = spawn_snapshot(commands, &placed);
+
+    let mut actions: Vec<Action> = placed
+        .nodes
+        .iter()
+        .filter_map(|node| {
+            Some(Action::CreateNode {
+                entity: *id_to_entity.get(&node.id)?,
+                pos: Vec2::new(node.x, node.y),
+                text: node.text.clone(),
+                color: node.color.to_bevy(),
+            })
+        })
+        .collect();
+    for edge in edges {
+        let (Some(&source), Some(&target)) = (
+            id_to_entity.get(&edge.source_id),
+            id_to_entity.get(&edge.target_id),
+        ) else {
+            continue;
+        };
+        let entity = commands
+            .spawn(Edge {
+                source,
+                target,
+                label: edge.label.clone(),
+            })
+            .id();
+        if !edge.stroke.is_default() {
+            commands.entity(entity).insert(edge.stroke);
+        }
+        if let Some(curve) = edge.curve {
+            commands.entity(entity).insert(CurveOffset(Vec2::from_array(curve)));
+        }
+        actions.push(Action::CreateEdge {
+            entity,
+            source,
+            target,
+            label: edge.label,
+        });
+    }
+    actions
+}
+
+/// Handles `TemplateRequest`. An insert is one `Action::Batch`, so one `u`
+/// removes the whole stamp.
+pub fn handle_template_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<TemplateRequest>,
+    sources: TemplateSources,
+    at: InsertPoint,
+    mut history: ResMut<UndoHistory>,
+    mut status: ResMut<StatusMessage>,
+) {
+    for request in requests.read() {
+        match request {
+            TemplateRequest::Save { name, members } => {
+                let Some(snapshot) = sources.snapshot(members) else {
+                    status.set("error: :template save needs a selected node");
+                    continue;
+                };
+                let count = snapshot.nodes.len();
+                match save_template(&templates_dir(), name, snapshot) {
+                    Ok(path) => {
+                        info!("[TEMPLATE] saved {} nodes to {}", count, path.display());
+                        status.set(format!("Saved template {} ({} nodes)", name, count));
+                    }
+                    Err(e) => status.set(format!("error: :template save: {}", e)),
+                }
+            }
+            TemplateRequest::Insert(name) => {
+                let template = match load_template(&templates_dir(), name) {
+                    Ok(template) => template,
+                    Err(e) => {
+                        status.set(format!("error: :template insert: {}", e));
+                        continue;
+                    }
+                };
+                let actions = stamp(&mut commands, &template, at.world());
+                info!("[TEMPLATE] inserted {} ({} actions)", name, actions.len());
+                status.set(format!(
+                    "Inserted template {} ({} nodes)",
+                    name,
+                    template.nodes.len()
+                ));
+                history.push(Action::Batch(actions));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::components::{NodeColor, TextData};
+    use crate::core::history::{apply_action, UndoNodeQuery};
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn template_names_stay_inside_the_folder() {
+        let dir = Path::new("t");
+        assert_eq!(
+            template_path(dir, "my-cluster_2").unwrap(),
+            dir.join("my-cluster_2.json")
+        );
+        assert!(template_path(dir, "../evil").is_err());
+        assert!(template_path(dir, "").is_err());
+    }
+
+    #[test]
+    fn saved_cluster_stamps_with_relative_positions() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut world = World::new();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<UndoHistory>();
+        let mut node = |text: &str, x: f32, y: f32| {
+            world
+                .spawn((
+                    CanvasNode,
+                    Transform::from_xyz(x, y, 0.0),
+                    TextData {
+                        content: text.to_string(),
+                    },
+                    NodeColor(Color::WHITE),
+                ))
+                .id()
+        };
+        let (a, b, _lone) = (
+            node("a", 100.0, 100.0),
+            node("b", 300.0, 100.0),
+            node("x", 0.0, 0.0),
+        );
+        world.spawn(Edge {
+            source: a,
+            target: b,
+            label: Some("calls".into()),
+        });
+        world.entity_mut(a).insert(Selected);
+
+        let snapshot = world
+            .run_system_once(|sources: TemplateSources| sources.snapshot(&[]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshot.nodes.len(), 2, "only the connected component");
+        save_template(dir.path(), "pair", snapshot).unwrap();
+        assert_eq!(list_templates(dir.path()), vec!["pair"]);
+
+        let template = load_template(dir.path(), "pair").unwrap();
+        let actions = world
+            .run_system_once(move |mut commands: Commands| {
+                stamp(&mut commands, &template, Vec2::new(-500.0, 0.0))
+            })
+            .unwrap();
+        let created: Vec<Vec2> = actions
+            .iter()
+            .filter_map(|a| match a {
+                Action::CreateNode { pos, .. } => Some(*pos),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(created.len(), 2);
+        assert!(created.contains(&Vec2::new(-600.0, 0.0)));
+        assert!(created.contains(&Vec2::new(-400.0, 0.0)));
+        assert!(matches!(
+            actions.last(),
+            Some(Action::CreateEdge { label: Some(l), .. }) if l == "calls"
+        ));
+        assert_eq!(world.query::<&Edge>().iter(&world).count(), 2);
+
+        // One undo removes the whole stamp, though none of it is selected.
+        let mut batch = Action::Batch(actions);
+        world
+            .run_system_once(
+                move |mut commands: Commands,
+                      mut query: UndoNodeQuery,
+                      edges: Query<(Entity, &Edge)>| {
+                    apply_action(&mut batch, true, &mut commands, &mut query, &edges);
+                },
+            )
+            .unwrap();
+        let texts: Vec<String> = world
+            .query::<&TextData>()
+            .iter(&world)
+            .map(|t| t.content.clone())
+            .collect();
+        assert_eq!(texts.len(), 3, "no stamped nodes left: {:?}", texts);
+        assert_eq!(world.query::<&Edge>().iter(&world).count(), 1);
+    }
+}
@@ -0,0 +1,239 @@
+//! Window geometry kept between sessions in `workflows/.window.json`: size,
+//! position and whether the window was maximized. `run()` applies it before
+//! the window opens; a position no monitor shows any more is pulled back on
+//! screen once the monitors are known.
+
+use bevy::ecs::system::NonSendMarker;
+use bevy::prelude::*;
+use bevy::window::{Monitor, PrimaryWindow, WindowPosition};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+const WINDOW_FILE: &str = "workflows/.window.json";
+
+/// Saved primary-window geometry. `width` / `height` are logical pixels,
+/// `position` is the physical top-left corner.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct WindowGeometry {
+    pub width: f32,
+    pub height: f32,
+    #[serde(default)]
+    pub position: Option<[i32; 2]>,
+    #[serde(default)]
+    pub maximized: bool,
+}
+
+impl WindowGeometry {
+    /// Set up `window` to open with this geometry.
+    pub fn apply(&self, window: &mut Window) {
+        window.resolution.set(self.width, self.height);
+        if let Some([x, y]) = self.position {
+            window.position = WindowPosition::At(IVec2::new(x, y));
+        }
+        if self.maximized {
+            window.set_maximized(true);
+        }
+    }
+}
+
+fn window_file() -> PathBuf {
+    std::env::current_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join(WINDOW_FILE)
+}
+
+/// Geometry saved by the last session, if any. Call on startup.
+pub fn load_window_geometry() -> Option<WindowGeometry> {
+    let data = std::fs::read_to_string(window_file()).ok()?;
+    match serde_json::from_str(&data) {
+        Ok(geometry) => Some(geometry),
+        Err(e) => {
+            warn!("[IO] Failed to parse window geometry: {}", e);
+            None
+        }
+    }
+}
+
+fn save_window_geometry(geometry: &WindowGeometry) {
+    let path = window_file();
+    if let Some(parent) = path.parent() {
+        if let Err(e) = std::fs::create_dir_all(parent) {
+            warn!("[IO] Failed to create parent dir for window geometry: {}", e);
+            return;
+        }
+    }
+    match serde_json::to_string(geometry) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                warn!("[IO] Failed to write window geometry: {}", e);
+            }
+        }
+        Err(e) => warn!("[IO] Failed to serialize window geometry: {}", e),
+    }
+}
+
+/// Latest geometry of the primary window, written out on exit. The window
+/// entity may already be gone by then, so it is tracked while it lives.
+#[derive(Resource, Default)]
+pub struct TrackedWindowGeometry(pub Option<WindowGeometry>);
+
+type ChangedPrimaryWindow<'w, 's> =
+    Query<'w, 's, (Entity, &'static Window), (With<PrimaryWindow>, Changed<Window>)>;
+
+/// Record the primary window's geometry whenever it changes. While it is
+/// maximized the size and position from before stay, so un-maximizing next
+/// session returns to them.
+pub fn track_window_geometry_system(
+    windows: ChangedPrimaryWindow,
+    mut tracked: ResMut<TrackedWindowGeometry>,
+    _main_thread: NonSendMarker,
+) {
+    let Ok((entity, window)) = windows.single() else {
+        return;
+    };
+    if !window.visible {
+        return;
+    }
+    let maximized = bevy::winit::WINIT_WINDOWS
+        .with_borrow(|winit| winit.get_window(entity).is_some_and(|w| w.is_maximized()));
+    let position = match window.position {
+        WindowPosition::At(pos) => Some([pos.x, pos.y]),
+        _ => None,
+    };
+    let geometry = match tracked.0 {
+        Some(previous) if maximized => WindowGeometry {
+            maximized,
+            ..previous
+        },
+        _ => WindowGeometry {
+            width: window.resolution.width(),
+            height: window.resolution.height(),
+            position,
+            maximized,
+        },
+    };
+    if tracked.0 != Some(geometry) {
+        tracked.0 = Some(geometry);
+    }
+}
+
+/// Save the tracked geometry when the app exits.
+pub fn save_window_geometry_system(
+    mut exits: MessageReader<AppExit>,
+    tracked: Res<TrackedWindowGeometry>,
+) {
+    if exits.read().last().is_none() {
+        return;
+    }
+    if let Some(geometry) = &tracked.0 {
+        save_window_geometry(geometry);
+    }
+}
+
+/// Where to put a window at `pos` of `size` (physical pixels) so it lies on
+/// one of `monitors`: the one it overlaps most, or the first. `None` if it
+/// already fits. A window larger than its monitor keeps its top-left corner
+/// on screen.
+fn fit_on_monitors(pos: IVec2, size: UVec2, monitors: &[IRect]) -> Option<IVec2> {
+    let window = IRect::from_corners(pos, pos + size.as_ivec2());
+    let overlap = |m: &IRect| {
+        let i = m.intersect(window);
+        if i.is_empty() {
+            0
+        } else {
+            i.width() * i.height()
+        }
+    };
+    let monitor = monitors
+        .iter()
+        .max_by_key(|m| overlap(m))
+        .filter(|m| overlap(m) > 0)
+        .or(monitors.first())?;
+    let max = (monitor.max - size.as_ivec2()).max(monitor.min);
+    let fitted = pos.clamp(monitor.min, max);
+    (fitted != pos).then_some(fitted)
+}
+
+/// Once the monitors are known, move a restored window that would open
+/// (partly) off-screen onto the nearest monitor. Runs until it has checked.
+pub fn keep_window_on_screen_system(
+    monitors: Query<&Monitor>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut checked: Local<bool>,
+) {
+    if *checked || monitors.is_empty() {
+        return;
+    }
+    *checked = true;
+    let Ok(mut window) = windows.single_mut() else {
+        return;
+    };
+    let WindowPosition::At(pos) = window.position else {
+        return;
+    };
+    let rects: Vec<IRect> = monitors
+        .iter()
+        .map(|m| {
+            IRect::from_corners(
+                m.physical_position,
+                m.physical_position + m.physical_size().as_ivec2(),
+            )
+        })
+        .collect();
+    let size = window.resolution.physical_size();
+    if let Some(fitted) = fit_on_monitors(pos, size, &rects) {
+        info!("[WINDOW] Saved position {} is off-screen, moving to {}", pos, fitted);
+        window.position = WindowPosition::At(fitted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(x: i32, y: i32, w: i32, h: i32) -> IRect {
+        IRect::new(x, y, x + w, y + h)
+    }
+
+    #[test]
+    fn window_on_a_monitor_stays_put() {
+        let monitors = [monitor(0, 0, 1920, 1080)];
+        assert_eq!(
+            fit_on_monitors(IVec2::new(100, 100), UVec2::new(800, 600), &monitors),
+            None
+        );
+    }
+
+    #[test]
+    fn window_of_an_unplugged_monitor_moves_to_the_first() {
+        let monitors = [monitor(0, 0, 1920, 1080)];
+        let fitted = fit_on_monitors(IVec2::new(2500, 200), UVec2::new(800, 600), &monitors);
+        assert_eq!(fitted, Some(IVec2::new(1120, 200)));
+    }
+
+    #[test]
+    fn window_overhanging_an_edge_is_pulled_onto_its_monitor() {
+        let monitors = [monitor(0, 0, 1920, 1080), monitor(1920, 0, 1280, 1024)];
+        let fitted = fit_on_monitors(IVec2::new(2900, -50), UVec2::new(800, 600), &monitors);
+        assert_eq!(fitted, Some(IVec2::new(2400, 0)));
+        let huge = fit_on_monitors(IVec2::new(-40, 10), UVec2::new(4000, 3000), &monitors);
+        assert_eq!(huge, Some(IVec2::new(0, 0)));
+    }
+
+    #[test]
+    fn geometry_roundtrips_and_tolerates_missing_fields() {
+        let geometry = WindowGeometry {
+            width: 1280.0,
+            height: 720.0,
+            position: Some([40, -10]),
+            maximized: true,
+        };
+        let json = serde_json::to_string(&geometry).unwrap();
+        assert_eq!(
+            serde_json::from_str::<WindowGeometry>(&json).unwrap(),
+            geometry
+        );
+        let old: WindowGeometry = serde_json::from_str(r#"{"width":800,"height":600}"#).unwrap();
+        assert_eq!((old.position, old.maximized), (None, false));
+    }
+}
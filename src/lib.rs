@@ -1,5 +1,6 @@
 //! Glyph — Vim-style 2D whiteboard. Library for testing and reuse.
 
+pub mod api;
 pub mod core;
 pub mod crawler;
 pub mod input;
@@ -21,19 +22,21 @@ use input::easymotion::{
     jump_tag_cleanup, jump_tag_setup, vim_easymotion_system, EasymotionTarget,
 };
 use input::selection::{
-    edge_draw_drop_system, edge_draw_preview_system, mouse_selection_system, node_drag_system,
+    box_select_drop_system, box_select_preview_system, edge_draw_drop_system,
+    edge_draw_preview_system, mouse_selection_system, node_drag_system,
     node_drop_system, DrawingEdge, LastEmptyClick,
 };
 use input::vim::{standard_mode_system, vim_insert_system, vim_normal_system};
 
 use io::file_io::{
-    load_canvas_system, load_recent, process_pending_load_system, save_canvas_system,
-    workflows_dir, CurrentFile, PendingLoad, RecentFiles,
+    load_canvas_system, load_recent, load_recent_system, process_pending_load_system,
+    save_canvas_system, CurrentFile, PendingLoad, RecentFiles,
 };
 
 use render::cluster::cluster_blobs_system;
 use render::edges::{
-    draw_edges_system, draw_selection_system, sync_edge_labels_system, sync_text_system,
+    draw_edges_system, draw_selection_system, draw_visual_selection_system, sync_edge_labels_system,
+    sync_text_system,
 };
 use render::layout::{force_directed_layout_system, ForceLayoutActive};
 
@@ -55,9 +58,50 @@ fn vim_input_available(palette: Res<CommandPaletteState>, state: Res<State<Input
 /// Build and run the Glyph app.
 pub fn run() {
     use std::io::{IsTerminal, Read};
-    let app_config = core::config::load_config();
+    let mut app_config = core::config::load_config();
     let undo_cap = app_config.undo_history_cap;
 
+    let mut is_headless = false;
+    let mut export_path = None;
+    let mut crawl_path = None;
+    let mut print_json = false;
+    let mut diff_paths = None;
+    let mut fresh = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--headless" {
+            is_headless = true;
+        } else if arg == "--export" {
+            export_path = args.next();
+        } else if arg == "--crawl" {
+            crawl_path = args.next();
+        } else if arg == "--print-json" {
+            print_json = true;
+        } else if arg == "--diff" {
+            diff_paths = Some((args.next(), args.next()));
+        } else if arg == "--fresh" {
+            fresh = true;
+        }
+    }
+
+    // `--diff a.glyph b.glyph`: print what changed and exit, no window.
+    if let Some(paths) = diff_paths {
+        let (Some(a), Some(b)) = paths else {
+            eprintln!("--diff requires two .glyph files");
+            std::process::exit(2);
+        };
+        std::process::exit(io::diff::print_diff(&a, &b));
+    }
+
+    // `--crawl <path> --print-json`: print the call graph and exit, no window.
+    if print_json {
+        let Some(path) = crawl_path else {
+            eprintln!("--print-json requires --crawl <path>");
+            std::process::exit(2);
+        };
+        std::process::exit(io::export_json::print_call_graph(&path, &app_config));
+    }
+
     let mut stdin_snapshot = None;
     if !std::io::stdin().is_terminal() {
         let mut buf = String::new();
@@ -72,27 +116,40 @@ pub fn run() {
         }
     }
 
-    let mut is_headless = false;
-    let mut export_path = None;
-    let mut args = std::env::args().skip(1);
-    while let Some(arg) = args.next() {
-        if arg == "--headless" {
-            is_headless = true;
-        } else if arg == "--export" {
-            export_path = args.next();
+    // Last session's theme, grid, force layout and camera, unless starting
+    // fresh. Headless runs neither read nor write them.
+    let prefs = (!is_headless && !io::prefs::start_fresh(fresh)).then(io::prefs::load_prefs);
+    let mut pending_load = None;
+    if let Some(prefs) = &prefs {
+        prefs.apply_to_config(&mut app_config);
+        if prefs.reopen_last && stdin_snapshot.is_none() {
+            pending_load = load_recent().into_iter().next().filter(|p| p.exists());
+        }
+    }
+    let force_layout = ForceLayoutActive {
+        active: prefs.as_ref().is_some_and(|p| p.force_layout),
+        iterations: 0,
+    };
+
+    let mut primary_window = Window {
+        title: "Glyph".to_string(),
+        visible: !is_headless,
+        ..default()
+    };
+    if !is_headless {
+        if let Some(geometry) = io::window_state::load_window_geometry() {
+            geometry.apply(&mut primary_window);
         }
     }
 
     let mut app = App::new();
     app.add_plugins(DefaultPlugins.set(WindowPlugin {
-        primary_window: Some(Window {
-            title: "Glyph".to_string(),
-            visible: !is_headless,
-            ..default()
-        }),
+        primary_window: Some(primary_window),
         ..default()
     }))
     .insert_resource(ClearColor(app_config.bg_color()))
+    .insert_resource(app_config.theme())
+    .insert_resource(input::keymap::KeyBindings::from_keymap(&app_config.keymap))
     .insert_resource(app_config)
     .insert_resource(io::headless::HeadlessExportConfig {
         is_headless,
@@ -103,50 +160,93 @@ pub fn run() {
     .init_resource::<JumpMap>()
     .init_resource::<SpatialIndex>()
     .init_resource::<CurrentFile>()
+    .init_resource::<core::tabs::Canvases>()
     .init_resource::<CommandPaletteState>()
     .init_resource::<VimCmdLine>()
     .init_resource::<ui::fuzzy::FuzzyFinderState>()
     .init_resource::<ui::shell::ShellCommandState>()
+    .init_resource::<ui::history_log::HistoryLogState>()
+    .init_resource::<ui::stats::StatsState>()
+    .init_resource::<io::clipboard::SystemClipboard>()
+    .init_resource::<render::call_path::PathState>()
+    .init_resource::<io::file_io::PendingCanvasCheckpoint>()
     .init_resource::<input::vim::PendingOperations>()
+    .init_resource::<input::vim::LastChange>()
     .init_resource::<input::vim::HjklHoldTime>()
     .init_resource::<input::vim::EasymotionConnectSource>()
     .init_resource::<input::vim::BackspaceHoldTime>()
     .init_resource::<input::vim::StartMovePos>()
     .init_resource::<input::vim::OriginalText>()
+    .init_resource::<core::resources::InsertCaret>()
+    .init_resource::<io::window_state::TrackedWindowGeometry>()
+    .init_resource::<input::vim::VisualMoveStart>()
     .init_resource::<EasymotionTarget>()
     .init_resource::<io::file_io::PendingFileDialog>()
-    .init_resource::<PendingLoad>()
-    .init_resource::<ForceLayoutActive>()
+    .insert_resource(PendingLoad(pending_load))
+    .init_resource::<io::file_io::PendingCanvasRestore>()
+    .insert_resource(force_layout)
     .init_resource::<RecentFiles>()
     .init_resource::<crawler::WatchState>()
+    .init_resource::<crawler::CrawlInfo>()
+    .init_resource::<crawler::flow::CrawledFlow>()
     .init_resource::<core::marks::Marks>()
+    .init_resource::<core::jumplist::JumpList>()
     .insert_resource(core::history::UndoHistory { cap: undo_cap, ..Default::default() })
     // new status message resource used for command feedback/errors
     .init_resource::<core::resources::StatusMessage>()
     .init_resource::<LastEmptyClick>()
     .init_resource::<DrawingEdge>()
-    .init_resource::<SelectedEdge>();
+    .init_resource::<SelectedEdge>()
+    .init_resource::<core::resources::HoveredEdge>()
+    .init_resource::<render::edges::EdgeShapeCache>()
+    .init_resource::<core::resources::VisualSelection>()
+    .init_resource::<core::resources::GrepFilter>()
+    .init_resource::<core::resources::SearchMatches>();
 
     if let Some(snap) = stdin_snapshot {
         app.insert_resource(io::stdin::StdinSnapshot(snap));
     }
+    if let Some(prefs) = prefs {
+        app.insert_resource(prefs);
+    }
 
-    app.add_systems(Startup, |mut recent: ResMut<RecentFiles>| {
-        let _ = workflows_dir(); // ensure workflows folder exists
-        recent.0 = load_recent();
-    })
+    app.add_systems(Startup, load_recent_system)
     .add_message::<crawler::CrawlRequest>()
     .add_message::<crawler::TraceRequest>()
+    .add_message::<crawler::simplify::SimplifyRequest>()
+    .add_message::<render::layout::LayoutRequest>()
+    .add_message::<io::import_mermaid::ImportRequest>()
+    .add_message::<render::shapes::ShapeRequest>()
+    .add_message::<render::align::AlignRequest>()
+    .add_message::<input::vim::ReverseEdgeRequest>()
+    .add_message::<crawler::flow::FlowRequest>()
+    .add_message::<crawler::callers::CallersRequest>()
+    .add_message::<io::template::TemplateRequest>()
+    .add_message::<render::recolor::RecolorRequest>()
+    .add_message::<render::edge_stroke::EdgeStrokeRequest>()
+    .add_message::<render::edge_curve::StraightenRequest>()
+    .add_message::<ui::history_log::HistoryRequest>()
+    .add_message::<ui::stats::StatsRequest>()
+    .add_message::<io::clipboard::YankRequest>()
+    .add_message::<render::call_path::PathRequest>()
+    .add_message::<input::connect::ConnectRequest>()
+    .add_message::<render::pin::PinRequest>()
+    .add_message::<input::prune::DeleteSubtreeRequest>()
+    .add_message::<core::tabs::TabRequest>()
     .add_systems(
         Startup,
         (
             setup_canvas,
             setup_gizmo_line_width,
-            io::stdin::load_stdin_snapshot_system,
+            io::stdin::load_stdin_snapshot_system.after(setup_canvas),
+            io::prefs::apply_camera_prefs_system
+                .after(setup_canvas)
+                .before(io::stdin::load_stdin_snapshot_system),
         ),
     )
     .add_systems(OnEnter(InputMode::VimEasymotion), jump_tag_setup)
     .add_systems(OnExit(InputMode::VimEasymotion), jump_tag_cleanup)
+    .add_systems(OnExit(InputMode::VimInsert), input::vim::clear_insert_caret)
     .add_systems(
         PostUpdate,
         (update_spatial_index_system, spatial_index_cleanup_system),
@@ -190,6 +290,7 @@ pub fn run() {
                 .run_if(vim_input_available)
                 .run_if(not(egui_wants_any_keyboard_input)),
             standard_mode_system
+                .before(node_drag_system)
                 .run_if(in_state(InputMode::Standard))
                 .run_if(vim_input_available)
                 .run_if(not(egui_wants_any_keyboard_input)),
@@ -205,7 +306,11 @@ pub fn run() {
         (
             force_directed_layout_system,
             cluster_blobs_system,
+            input::selection::edge_hover_system.run_if(vim_input_available),
             draw_edges_system,
+            input::selection::draw_connect_handles_system
+                .run_if(in_state(InputMode::Standard))
+                .run_if(vim_input_available),
             edge_draw_preview_system
                 .run_if(in_state(InputMode::Standard))
                 .run_if(vim_input_available)
@@ -215,15 +320,117 @@ pub fn run() {
                 .run_if(vim_input_available)
                 .run_if(not(egui_wants_any_keyboard_input)),
             draw_selection_system,
+            draw_visual_selection_system,
+            render::grid::draw_grid_system,
+            render::layout::handle_layout_requests,
             sync_text_system,
             sync_edge_labels_system,
+            render::shapes::handle_shape_requests,
+            render::align::handle_align_requests,
+            input::vim::handle_reverse_edge_requests,
+            render::shapes::sync_node_shapes_system,
+            render::grep::apply_grep_filter_system,
+            render::grep::sync_grep_matches_system,
+            render::collapse::sync_collapsed_system,
+        ),
+    )
+    .add_systems(
+        Update,
+        (box_select_preview_system, box_select_drop_system)
+            .chain()
+            .run_if(in_state(InputMode::Standard))
+            .run_if(vim_input_available)
+            .run_if(not(egui_wants_any_keyboard_input)),
+    )
+    .add_systems(
+        Update,
+        (
+            input::selection::curve_drag_system,
+            input::selection::draw_curve_handle_system.run_if(in_state(InputMode::Standard)),
         ),
     )
+    .add_systems(
+        Update,
+        (
+            render::theme::apply_theme_system,
+            render::theme::sync_label_colors_system,
+        )
+            .chain(),
+    )
+    .add_systems(Update, render::bundle::sync_bundle_badges_system)
+    .add_systems(Update, render::edges::draw_hovered_edge_direction_system)
+    .add_systems(Update, render::cluster::sync_cluster_blob_size_system)
+    .add_systems(
+        Update,
+        (render::pin::handle_pin_requests, render::pin::draw_pin_markers_system),
+    )
+    .add_systems(
+        Update,
+        render::shapes::sync_node_shadows_system.after(render::shapes::sync_node_shapes_system),
+    )
+    .add_systems(
+        Update,
+        render::edges::cache_edge_shapes_system
+            .before(input::selection::edge_hover_system)
+            .before(draw_edges_system)
+            .before(render::edge_mesh::sync_edge_meshes_system),
+    )
+    .add_systems(Update, render::edge_mesh::sync_edge_meshes_system)
+    .add_systems(
+        Update,
+        render::label_cull::cull_offscreen_labels_system.after(sync_edge_labels_system),
+    )
+    .add_systems(
+        Update,
+        (render::recolor::handle_recolor_requests, render::recolor::sync_node_fill_system).chain(),
+    )
+    .add_systems(Update, render::edge_stroke::handle_edge_stroke_requests)
+    .add_systems(Update, render::edge_curve::handle_straighten_requests)
+    .add_systems(
+        Update,
+        (core::tabs::handle_tab_requests, core::tabs::sync_canvas_tabs_system)
+            .chain()
+            .before(render::collapse::sync_collapsed_system),
+    )
+    .add_systems(
+        Update,
+        (
+            crawler::flow::handle_flow_requests,
+            crawler::callers::handle_callers_requests,
+            crawler::flow::sync_flow_system,
+        )
+            .chain()
+            .before(render::collapse::sync_collapsed_system),
+    )
     .add_systems(bevy_egui::EguiPrimaryContextPass, ui_top_bar_system)
     .add_systems(bevy_egui::EguiPrimaryContextPass, ui_command_palette_system)
     .add_systems(bevy_egui::EguiPrimaryContextPass, ui_bottom_bar_system)
     .add_systems(bevy_egui::EguiPrimaryContextPass, ui_legend_system)
+    .add_systems(bevy_egui::EguiPrimaryContextPass, ui::minimap::ui_minimap_system)
+    .add_systems(
+        bevy_egui::EguiPrimaryContextPass,
+        ui::tooltip::ui_node_tooltip_system
+            .run_if(vim_input_available)
+            .run_if(not(in_state(InputMode::VimInsert)))
+            .run_if(not(egui_wants_any_keyboard_input)),
+    )
     .add_systems(Update, process_pending_file_dialog_system)
+    .add_systems(
+        Update,
+        (
+            crawler::simplify::handle_simplify_requests,
+            io::import_mermaid::handle_import_requests,
+            io::file_io::process_pending_restore_system,
+        )
+            .chain(),
+    )
+    .add_systems(
+        Update,
+        input::vim::vim_visual_system
+            .run_if(in_state(InputMode::VimVisual))
+            .run_if(vim_input_available)
+            .run_if(not(egui_wants_any_keyboard_input)),
+    )
     .add_systems(Update, crate::ui::overlay::status_message_tick_system)
     .add_systems(
         Update,
@@ -247,6 +454,62 @@ pub fn run() {
         bevy_egui::EguiPrimaryContextPass,
         ui::shell::shell_command_ui_system,
     )
+    .add_systems(Update, ui::history_log::handle_history_requests)
+    .add_systems(Update, ui::stats::handle_stats_requests)
+    .add_systems(Update, input::camera::camera_tween_system)
+    .add_systems(Update, io::clipboard::handle_yank_requests)
+    .add_systems(Update, io::template::handle_template_requests)
+    .add_systems(Update, render::call_path::handle_path_requests)
+    .add_systems(Update, render::call_path::expire_path_highlight_system)
+    .add_systems(Update, input::connect::handle_connect_requests)
+    .add_systems(
+        Update,
+        (
+            io::window_state::track_window_geometry_system,
+            io::window_state::keep_window_on_screen_system,
+        ),
+    )
+    .add_systems(
+        Last,
+        (
+            io::window_state::save_window_geometry_system,
+            io::prefs::save_prefs_system,
+        ),
+    )
+    .add_systems(
+        Update,
+        crawler::finish_crawl_system.after(crawler::handle_crawl_requests),
+    )
+    .add_systems(
+        Update,
+        crawler::cancel_crawl_system
+            .run_if(in_state(InputMode::VimNormal))
+            .run_if(vim_input_available)
+            .run_if(not(egui_wants_any_keyboard_input)),
+    )
+    .add_systems(
+        Update,
+        io::file_io::push_canvas_checkpoint_system
+            .after(process_pending_load_system)
+            .after(crawler::finish_crawl_system),
+    )
+    .add_systems(
+        Update,
+        input::prune::handle_delete_subtree_requests
+            .after(vim_normal_system)
+            .before(io::file_io::push_canvas_checkpoint_system),
+    )
+    .add_systems(
+        Update,
+        io::clipboard::clipboard_paste_system
+            .run_if(in_state(InputMode::VimInsert))
+            .run_if(vim_input_available)
+            .run_if(not(egui_wants_any_keyboard_input)),
+    )
+    .add_systems(
+        bevy_egui::EguiPrimaryContextPass,
+        (ui::history_log::history_log_ui_system, ui::stats::stats_ui_system),
+    )
     .run();
 }
 
@@ -0,0 +1,422 @@
+//! `:align` / `:distribute` — line up the visual selection on a common edge
+//! or axis, or space it evenly between its outermost members. `:sort` stacks
+//! nodes in one column in alphabetical order.
+//!
+//! The position math works on plain `(center, size)` pairs so it can be tested
+//! without a Bevy world.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::core::components::{
+    CanvasNode, CollapseHidden, Edge, InactiveCanvas, NodeSize, Selected, TextData,
+};
+use crate::core::config::GlyphConfig;
+use crate::core::history::{Action, UndoHistory};
+use crate::core::resources::StatusMessage;
+use crate::input::selection::connected_component;
+
+/// Edge or axis that `:align` snaps nodes to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlignEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    /// Common x: centers stacked in a column.
+    HCenter,
+    /// Common y: centers lined up in a row.
+    VCenter,
+}
+
+impl AlignEdge {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "left" => Some(AlignEdge::Left),
+            "right" => Some(AlignEdge::Right),
+            "top" => Some(AlignEdge::Top),
+            "bottom" => Some(AlignEdge::Bottom),
+            "hcenter" => Some(AlignEdge::HCenter),
+            "vcenter" => Some(AlignEdge::VCenter),
+            _ => None,
+        }
+    }
+}
+
+/// Direction `:distribute` spaces nodes along.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlignOp {
+    Align(AlignEdge),
+    Distribute(Axis),
+    /// `:sort`, or `:sort!` for reverse order.
+    Sort { reverse: bool },
+}
+
+/// Message sent by `:align <edge>` / `:distribute h|v` / `:sort`. `members`
+/// is the visual selection the command was typed from; empty means every
+/// node, or for `:sort` the selected node's connected component if any.
+#[derive(Message)]
+pub struct AlignRequest {
+    pub op: AlignOp,
+    pub members: Vec<Entity>,
+}
+
+/// New centers for `items` (center, size) with every node snapped to `edge`
+/// of their shared bounding box. Returned in input order.
+pub fn align_positions(items: &[(Vec2, Vec2)], edge: AlignEdge) -> Vec<Vec2> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let min = items
+        .iter()
+        .map(|(c, s)| *c - *s * 0.5)
+        .reduce(Vec2::min)
+        .unwrap_or_default();
+    let max = items
+        .iter()
+        .map(|(c, s)| *c + *s * 0.5)
+        .reduce(Vec2::max)
+        .unwrap_or_default();
+    let mid = (min + max) * 0.5;
+    items
+        .iter()
+        .map(|&(c, s)| {
+            let half = s * 0.5;
+            match edge {
+                AlignEdge::Left => Vec2::new(min.x + half.x, c.y),
+                AlignEdge::Right => Vec2::new(max.x - half.x, c.y),
+                AlignEdge::Top => Vec2::new(c.x, max.y - half.y),
+                AlignEdge::Bottom => Vec2::new(c.x, min.y + half.y),
+                AlignEdge::HCenter => Vec2::new(mid.x, c.y),
+                AlignEdge::VCenter => Vec2::new(c.x, mid.y),
+            }
+        })
+        .collect()
+}
+
+/// New centers for `items` (center, size) spaced along `axis` so the gaps
+/// between neighbouring edges are equal. The outermost nodes stay put; with
+/// fewer than three nodes nothing moves. Returned in input order.
+pub fn distribute_positions(items: &[(Vec2, Vec2)], axis: Axis) -> Vec<Vec2> {
+    let mut out: Vec<Vec2> = items.iter().map(|(c, _)| *c).collect();
+    if items.len() < 3 {
+        return out;
+    }
+    let along = |v: Vec2| match axis {
+        Axis::Horizontal => v.x,
+        Axis::Vertical => v.y,
+    };
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| along(items[a].0).total_cmp(&along(items[b].0)));
+
+    let (first, last) = (order[0], order[order.len() - 1]);
+    let start = along(items[first].0) - along(items[first].1) * 0.5;
+    let end = along(items[last].0) + along(items[last].1) * 0.5;
+    let total: f32 = items.iter().map(|(_, s)| along(*s)).sum();
+    let gap = (end - start - total) / (items.len() - 1) as f32;
+
+    let mut cursor = start;
+    for &i in &order {
+        let size = along(items[i].1);
+        let center = cursor + size * 0.5;
+        match axis {
+            Axis::Horizontal => out[i].x = center,
+            Axis::Vertical => out[i].y = center,
+        }
+        cursor += size + gap;
+    }
+    out
+}
+
+/// Order in which `:sort` stacks `items` (text, center): alphabetical,
+/// ignoring case, or reversed with `reverse`. Equal texts keep their current
+/// top-to-bottom order.
+pub fn sort_order(items: &[(&str, Vec2)], reverse: bool) -> Vec<usize> {
+    let keys: Vec<String> = items.iter().map(|(text, _)| text.to_lowercase()).collect();
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| {
+        let by_text = keys[a].cmp(&keys[b]);
+        let by_text = if reverse { by_text.reverse() } else { by_text };
+        by_text.then(items[b].1.y.total_cmp(&items[a].1.y))
+    });
+    order
+}
+
+/// New centers for `items` (text, center) stacked in `sort_order` in one
+/// column, `row_height` apart. The column starts at the leftmost center and
+/// the topmost one. Returned in input order.
+pub fn sort_positions(items: &[(&str, Vec2)], reverse: bool, row_height: f32) -> Vec<Vec2> {
+    let mut out: Vec<Vec2> = items.iter().map(|(_, c)| *c).collect();
+    let Some(x) = out.iter().map(|c| c.x).reduce(f32::min) else {
+        return out;
+    };
+    let top = out.iter().map(|c| c.y).fold(f32::MIN, f32::max);
+    for (row, i) in sort_order(items, reverse).into_iter().enumerate() {
+        out[i] = Vec2::new(x, top - row as f32 * row_height);
+    }
+    out
+}
+
+/// What `:sort` needs besides the nodes: the selection and edges that pick
+/// the connected component, and the row height.
+#[derive(SystemParam)]
+pub struct SortScope<'w, 's> {
+    selected: Query<'w, 's, Entity, (With<Selected>, With<CanvasNode>)>,
+    active: Query<'w, 's, Entity, (With<CanvasNode>, Without<InactiveCanvas>)>,
+    edges: Query<'w, 's, &'static Edge>,
+    config: Res<'w, GlyphConfig>,
+}
+
+impl SortScope<'_, '_> {
+    /// Nodes `:sort` arranges when no visual set is given: the selected
+    /// node's connected component, or every node on the canvas.
+    fn members(&self) -> Vec<Entity> {
+        match self.selected.single() {
+            Ok(root) => {
+                let edges: Vec<(Entity, Entity)> =
+                    self.edges.iter().map(|e| (e.source, e.target)).collect();
+                connected_component(root, &edges).into_iter().collect()
+            }
+            Err(_) => self.active.iter().collect(),
+        }
+    }
+}
+
+type AlignNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Transform,
+        Option<&'static NodeSize>,
+        Option<&'static TextData>,
+    ),
+    (With<CanvasNode>, Without<InactiveCanvas>, Without<CollapseHidden>),
+>;
+
+/// Handles `AlignRequest`: moves the members (or every visible node on the
+/// active tab; folded nodes stay where they are) and pushes one
+/// `Action::Batch` of `MoveNode`s so a single `u` puts them back.
+pub fn handle_align_requests(
+    mut requests: MessageReader<AlignRequest>,
+    mut node_query: AlignNodeQuery,
+    scope: SortScope,
+    mut history: ResMut<UndoHistory>,
+    mut status: ResMut<StatusMessage>,
+) {
+    for req in requests.read() {
+        let members = match req.op {
+            AlignOp::Sort { .. } if req.members.is_empty() => scope.members(),
+            _ => req.members.clone(),
+        };
+        let nodes: Vec<(Entity, Vec2, Vec2, &str)> = node_query
+            .iter()
+            .filter(|(e, ..)| members.is_empty() || members.contains(e))
+            .map(|(e, t, size, text)| {
+                let size = size.copied().unwrap_or_default().current;
+                let text = text.map_or("", |t| t.content.as_str());
+                (e, t.translation.truncate(), size, text)
+            })
+            .collect();
+        if nodes.len() < 2 {
+            status.set(match req.op {
+                AlignOp::Sort { .. } => "Sort: needs at least 2 nodes",
+                _ => "Align: select at least 2 nodes in visual mode",
+            });
+            continue;
+        }
+        let items: Vec<(Vec2, Vec2)> = nodes.iter().map(|(_, c, s, _)| (*c, *s)).collect();
+        let targets = match req.op {
+            AlignOp::Align(edge) => align_positions(&items, edge),
+            AlignOp::Distribute(axis) => distribute_positions(&items, axis),
+            AlignOp::Sort { reverse } => {
+                let texts: Vec<(&str, Vec2)> = nodes.iter().map(|(_, c, _, t)| (*t, *c)).collect();
+                sort_positions(&texts, reverse, scope.config.flow_row_height)
+            }
+        };
+        let count = nodes.len();
+        let moved: Vec<(Entity, Vec2)> = nodes.iter().map(|(e, c, ..)| (*e, *c)).collect();
+
+        let mut moves = Vec::new();
+        for (&(entity, from), to) in moved.iter().zip(targets) {
+            if from.distance(to) < 0.01 {
+                continue;
+            }
+            if let Ok((_, mut transform, ..)) = node_query.get_mut(entity) {
+                transform.translation.x = to.x;
+                transform.translation.y = to.y;
+                moves.push(Action::MoveNode { entity, from, to });
+            }
+        }
+        info!("[ALIGN] {:?}: moved {} of {} nodes", req.op, moves.len(), count);
+        let verb = match req.op {
+            AlignOp::Align(_) => "Aligned",
+            AlignOp::Distribute(_) => "Distributed",
+            AlignOp::Sort { .. } => "Sorted",
+        };
+        status.set(format!("{} {} nodes", verb, count));
+        if !moves.is_empty() {
+            history.push(Action::Batch(moves));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::helpers::NODE_SIZE;
+
+    #[test]
+    fn align_snaps_to_shared_edges() {
+        let items = [
+            (Vec2::new(0.0, 0.0), Vec2::new(100.0, 50.0)),
+            (Vec2::new(200.0, 40.0), Vec2::new(60.0, 20.0)),
+        ];
+        let left = align_positions(&items, AlignEdge::Left);
+        assert_eq!(left[0], Vec2::new(0.0, 0.0));
+        assert_eq!(left[1], Vec2::new(-20.0, 40.0));
+        let top = align_positions(&items, AlignEdge::Top);
+        assert_eq!(top[0].y, 25.0);
+        assert_eq!(top[1].y, 40.0);
+        let hc = align_positions(&items, AlignEdge::HCenter);
+        assert_eq!(hc[0].x, hc[1].x);
+        assert_eq!(hc[0].x, 90.0);
+    }
+
+    #[test]
+    fn distribute_produces_equal_gaps() {
+        let items = [
+            (Vec2::new(500.0, 0.0), Vec2::new(100.0, 50.0)),
+            (Vec2::new(0.0, 10.0), Vec2::new(100.0, 50.0)),
+            (Vec2::new(90.0, 20.0), Vec2::new(40.0, 50.0)),
+            (Vec2::new(130.0, 30.0), Vec2::new(60.0, 50.0)),
+        ];
+        let out = distribute_positions(&items, Axis::Horizontal);
+        // Outermost nodes stay, y is untouched.
+        assert_eq!(out[0], items[0].0);
+        assert_eq!(out[1], items[1].0);
+        assert_eq!(out[2].y, 20.0);
+
+        let mut spans: Vec<(f32, f32)> = out
+            .iter()
+            .zip(&items)
+            .map(|(c, (_, s))| (c.x - s.x * 0.5, c.x + s.x * 0.5))
+            .collect();
+        spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let gaps: Vec<f32> = spans.windows(2).map(|w| w[1].0 - w[0].1).collect();
+        assert_eq!(gaps.len(), 3);
+        assert!(gaps.iter().all(|g| (g - gaps[0]).abs() < 1e-3), "{:?}", gaps);
+    }
+
+    #[test]
+    fn distribute_leaves_two_nodes_alone() {
+        let items = [(Vec2::ZERO, NODE_SIZE), (Vec2::new(0.0, 300.0), NODE_SIZE)];
+        assert_eq!(
+            distribute_positions(&items, Axis::Vertical),
+            vec![Vec2::ZERO, Vec2::new(0.0, 300.0)]
+        );
+    }
+
+    #[test]
+    fn sort_orders_by_text_and_breaks_ties_by_height() {
+        let items = [
+            ("pear", Vec2::new(0.0, 0.0)),
+            ("Apple", Vec2::new(50.0, 100.0)),
+            ("fig", Vec2::new(-30.0, 300.0)),
+            ("fig", Vec2::new(10.0, 400.0)),
+        ];
+        assert_eq!(sort_order(&items, false), vec![1, 3, 2, 0]);
+        assert_eq!(sort_order(&items, true), vec![0, 3, 2, 1]);
+
+        let out = sort_positions(&items, false, 380.0);
+        assert_eq!(out[1], Vec2::new(-30.0, 400.0), "first row at the top-left");
+        assert_eq!(out[3], Vec2::new(-30.0, 20.0));
+        assert_eq!(out[2], Vec2::new(-30.0, -360.0));
+        assert_eq!(out[0], Vec2::new(-30.0, -740.0));
+    }
+
+    #[test]
+    fn sort_without_a_visual_set_arranges_the_selected_component() {
+        use bevy::ecs::system::RunSystemOnce;
+        let mut world = World::new();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<GlyphConfig>();
+        world.init_resource::<Messages<AlignRequest>>();
+        let mut node = |text: &str, x: f32, y: f32| {
+            world
+                .spawn((
+                    CanvasNode,
+                    Transform::from_xyz(x, y, 0.0),
+                    TextData {
+                        content: text.to_string(),
+                    },
+                ))
+                .id()
+        };
+        let (b, a, loner) = (node("b", 0.0, 0.0), node("a", 200.0, -50.0), node("c", 900.0, 9.0));
+        world.spawn(Edge {
+            source: b,
+            target: a,
+            label: None,
+        });
+        world.entity_mut(b).insert(Selected);
+
+        world.write_message(AlignRequest {
+            op: AlignOp::Sort { reverse: false },
+            members: Vec::new(),
+        });
+        world.run_system_once(handle_align_requests).unwrap();
+
+        let pos = |world: &World, e| world.get::<Transform>(e).unwrap().translation.truncate();
+        assert_eq!(pos(&world, a), Vec2::ZERO);
+        let row = world.resource::<GlyphConfig>().flow_row_height;
+        assert_eq!(pos(&world, b), Vec2::new(0.0, -row));
+        assert_eq!(pos(&world, loner), Vec2::new(900.0, 9.0), "not in the component");
+        assert_eq!(world.resource::<StatusMessage>().text, "Sorted 2 nodes");
+    }
+
+    #[test]
+    fn align_without_members_leaves_other_tabs_and_folded_nodes_alone() {
+        use bevy::ecs::system::RunSystemOnce;
+        let mut world = World::new();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<GlyphConfig>();
+        world.init_resource::<Messages<AlignRequest>>();
+        let mut node = |x: f32, y: f32| {
+            world.spawn((CanvasNode, Transform::from_xyz(x, y, 0.0))).id()
+        };
+        let (a, b, hidden) = (node(0.0, 0.0), node(100.0, 40.0), node(300.0, 90.0));
+        let folded = node(500.0, -70.0);
+        world.entity_mut(hidden).insert(InactiveCanvas);
+        world.entity_mut(folded).insert(CollapseHidden);
+
+        world.write_message(AlignRequest {
+            op: AlignOp::Align(AlignEdge::VCenter),
+            members: Vec::new(),
+        });
+        world.run_system_once(handle_align_requests).unwrap();
+
+        let y = |world: &World, e| world.get::<Transform>(e).unwrap().translation.y;
+        assert_eq!(y(&world, a), y(&world, b));
+        assert_eq!(y(&world, hidden), 90.0);
+        assert_eq!(y(&world, folded), -70.0);
+        let history = world.resource::<UndoHistory>();
+        let Some(Action::Batch(moves)) = history.undo_stack.last() else {
+            panic!("expected one Batch step");
+        };
+        for m in moves {
+            let Action::MoveNode { entity, .. } = m else {
+                panic!("expected MoveNode");
+            };
+            assert!(*entity == a || *entity == b);
+        }
+    }
+}
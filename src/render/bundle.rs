@@ -0,0 +1,123 @@
+//! Edge bundling (`bundle_edges` / `:bundle`): parallel edges between the same
+//! pair of nodes are drawn once by `draw_edges_system`; this module keeps a
+//! `×N` badge at the midpoint of each such bundle.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::core::components::{CanvasNode, CollapseHidden, Edge};
+use crate::core::config::{GlyphConfig, Theme};
+use crate::render::edges::edge_midpoint;
+
+/// `×N` Text2d at the midpoint of a bundle; `pair` is its (source, target).
+#[derive(Component)]
+pub struct BundleBadge {
+    pub pair: (Entity, Entity),
+}
+
+type BadgeQuery<'w, 's> =
+    Query<'w, 's, (Entity, &'static BundleBadge, &'static mut Transform, &'static mut Text2d)>;
+
+/// Number of edges per (source, target) pair, keeping only pairs with more than one.
+pub fn bundle_counts<'a>(
+    edges: impl IntoIterator<Item = &'a Edge>,
+) -> HashMap<(Entity, Entity), usize> {
+    let mut counts: HashMap<(Entity, Entity), usize> = HashMap::new();
+    for edge in edges {
+        *counts.entry((edge.source, edge.target)).or_default() += 1;
+    }
+    counts.retain(|_, n| *n > 1);
+    counts
+}
+
+/// Spawn, move, relabel and despawn bundle badges to match the current edges.
+/// With bundling off every badge is removed.
+pub fn sync_bundle_badges_system(
+    mut commands: Commands,
+    config: Res<GlyphConfig>,
+    theme: Res<Theme>,
+    edge_query: Query<&Edge, Without<CollapseHidden>>,
+    node_query: Query<&Transform, (With<CanvasNode>, Without<BundleBadge>)>,
+    mut badges: BadgeQuery,
+) {
+    let mut counts = if config.bundle_edges {
+        bundle_counts(edge_query.iter())
+    } else {
+        HashMap::new()
+    };
+    let midpoint = |(source, target): (Entity, Entity)| {
+        let p0 = node_query.get(source).ok()?.translation.truncate();
+        let p2 = node_query.get(target).ok()?.translation.truncate();
+        // Self-loop bundles put the badge on the node itself.
+        Some(if source == target { p0 } else { edge_midpoint(p0, p2, 0) })
+    };
+
+    for (entity, badge, mut transform, mut text) in &mut badges {
+        let Some((n, pos)) = counts.remove(&badge.pair).zip(midpoint(badge.pair)) else {
+            commands.entity(entity).try_despawn();
+            continue;
+        };
+        transform.translation = pos.extend(1.5);
+        let label = format!("×{}", n);
+        if text.as_str() != label {
+            text.0 = label;
+        }
+    }
+    for (pair, n) in counts {
+        let Some(pos) = midpoint(pair) else {
+            continue;
+        };
+        commands.spawn((
+            Text2d::new(format!("×{}", n)),
+            TextFont {
+                font_size: 13.0,
+                ..default()
+            },
+            TextColor(theme.text),
+            Transform::from_translation(pos.extend(1.5)),
+            BundleBadge { pair },
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn edge(source: Entity, target: Entity) -> Edge {
+        Edge {
+            source,
+            target,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn badges_follow_bundles_and_the_config_flag() {
+        let mut world = World::new();
+        world.insert_resource(GlyphConfig {
+            bundle_edges: true,
+            ..Default::default()
+        });
+        world.init_resource::<Theme>();
+        let a = world.spawn((CanvasNode, Transform::from_xyz(0.0, 0.0, 0.0))).id();
+        let b = world.spawn((CanvasNode, Transform::from_xyz(300.0, 0.0, 0.0))).id();
+        for _ in 0..3 {
+            world.spawn(edge(a, b));
+        }
+        world.spawn(edge(b, a));
+
+        world.run_system_once(sync_bundle_badges_system).unwrap();
+        let mut badges = world.query::<(&BundleBadge, &Text2d)>();
+        let found: Vec<_> = badges
+            .iter(&world)
+            .map(|(badge, text)| (badge.pair, text.0.clone()))
+            .collect();
+        assert_eq!(found, vec![((a, b), "×3".to_string())]);
+
+        world.resource_mut::<GlyphConfig>().bundle_edges = false;
+        world.run_system_once(sync_bundle_badges_system).unwrap();
+        assert_eq!(badges.iter(&world).count(), 0);
+    }
+}
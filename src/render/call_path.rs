@@ -0,0 +1,174 @@
+//! `:path`: highlight the shortest directed path between two nodes. The first
+//! `:path` remembers the selected node as the source; the second, with the
+//! target selected, runs a BFS over the visible edges and tags the path's
+//! edges `PathHighlight` for `PATH_HIGHLIGHT_SECS`. `draw_edges_system` draws
+//! them thick in the traced color.
+
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::core::components::{
+    CanvasNode, CollapseHidden, Edge, FlowBypass, InactiveCanvas, PathHighlight, Selected,
+    TextData,
+};
+use crate::core::resources::StatusMessage;
+
+/// How long a found path stays highlighted.
+pub const PATH_HIGHLIGHT_SECS: f32 = 4.0;
+
+/// Node → outgoing `(edge, target)` pairs.
+pub type Adjacency = HashMap<Entity, Vec<(Entity, Entity)>>;
+
+/// Message sent by `:path`.
+#[derive(Message)]
+pub struct PathRequest;
+
+/// Source picked by the first `:path` and time left on the current highlight.
+#[derive(Resource, Default)]
+pub struct PathState {
+    pub source: Option<Entity>,
+    pub remaining: f32,
+}
+
+/// Adjacency of `(edge, source, target)` triples, in the order given.
+pub fn adjacency(edges: impl IntoIterator<Item = (Entity, Entity, Entity)>) -> Adjacency {
+    let mut adj = Adjacency::new();
+    for (edge, source, target) in edges {
+        adj.entry(source).or_default().push((edge, target));
+    }
+    adj
+}
+
+/// Edges of a shortest directed path `from` → `to` (fewest edges), in order.
+/// `Some(vec![])` when `from == to`; `None` when `to` is unreachable.
+pub fn shortest_path(adj: &Adjacency, from: Entity, to: Entity) -> Option<Vec<Entity>> {
+    let mut came_by: HashMap<Entity, (Entity, Entity)> = HashMap::new();
+    let mut seen = HashSet::from([from]);
+    let mut queue = VecDeque::from([from]);
+    while let Some(node) = queue.pop_front() {
+        if node == to {
+            let mut path = Vec::new();
+            let mut at = to;
+            while let Some(&(edge, prev)) = came_by.get(&at) {
+                path.push(edge);
+                at = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for &(edge, next) in adj.get(&node).into_iter().flatten() {
+            if seen.insert(next) {
+                came_by.insert(next, (edge, node));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+type PathNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static TextData, Has<Selected>),
+    (With<CanvasNode>, Without<InactiveCanvas>),
+>;
+
+/// Handles `PathRequest`: remember the source, or find and highlight the path
+/// from it to the selected node.
+pub fn handle_path_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<PathRequest>,
+    mut state: ResMut<PathState>,
+    nodes: PathNodeQuery,
+    edges: Query<(Entity, &Edge), (Without<CollapseHidden>, Without<FlowBypass>)>,
+    highlighted: Query<Entity, With<PathHighlight>>,
+    mut status: ResMut<StatusMessage>,
+) {
+    for _ in requests.read() {
+        let Some((target, target_text, _)) = nodes.iter().find(|(.., selected)| *selected) else {
+            status.set("error: :path needs a selected node");
+            continue;
+        };
+        let source = state.source.and_then(|e| nodes.get(e).ok());
+        let Some((source, source_text, _)) = source.filter(|(e, ..)| *e != target) else {
+            state.source = Some(target);
+            status.set(format!(
+                "Path from '{}': select the target and :path again",
+                target_text.content
+            ));
+            continue;
+        };
+        state.source = None;
+        let adj = adjacency(edges.iter().map(|(e, edge)| (e, edge.source, edge.target)));
+        let Some(path) = shortest_path(&adj, source, target) else {
+            status.set(format!(
+                "No path from '{}' to '{}'",
+                source_text.content, target_text.content
+            ));
+            continue;
+        };
+        for entity in &highlighted {
+            commands.entity(entity).try_remove::<PathHighlight>();
+        }
+        for &edge in &path {
+            commands.entity(edge).try_insert(PathHighlight);
+        }
+        state.remaining = PATH_HIGHLIGHT_SECS;
+        status.set(format!(
+            "Path '{}' → '{}': {} edge(s)",
+            source_text.content,
+            target_text.content,
+            path.len()
+        ));
+        info!("[PATH] {:?} → {:?}: {:?}", source, target, path);
+    }
+}
+
+/// Clear the path highlight once its time is up.
+pub fn expire_path_highlight_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut state: ResMut<PathState>,
+    highlighted: Query<Entity, With<PathHighlight>>,
+) {
+    if state.remaining <= 0.0 {
+        return;
+    }
+    state.remaining -= time.delta_secs();
+    if state.remaining <= 0.0 {
+        for entity in &highlighted {
+            commands.entity(entity).try_remove::<PathHighlight>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortest_path_takes_the_fewest_edges() {
+        let n: Vec<Entity> = (1..=5).map(Entity::from_bits).collect();
+        let e: Vec<Entity> = (10..=15).map(Entity::from_bits).collect();
+        // 0 → 1 → 2 → 3 and the shortcut 0 → 4 → 3; 3 → 0 closes a cycle.
+        let adj = adjacency([
+            (e[0], n[0], n[1]),
+            (e[1], n[1], n[2]),
+            (e[2], n[2], n[3]),
+            (e[3], n[0], n[4]),
+            (e[4], n[4], n[3]),
+            (e[5], n[3], n[0]),
+        ]);
+        assert_eq!(shortest_path(&adj, n[0], n[3]), Some(vec![e[3], e[4]]));
+        assert_eq!(
+            shortest_path(&adj, n[2], n[1]),
+            Some(vec![e[2], e[5], e[0]])
+        );
+        assert_eq!(shortest_path(&adj, n[1], n[1]), Some(vec![]));
+        // Edges are directed: 3 reaches 4 only back round through 0.
+        assert_eq!(shortest_path(&adj, n[3], n[4]), Some(vec![e[5], e[3]]));
+        let lone = Entity::from_bits(99);
+        assert_eq!(shortest_path(&adj, n[0], lone), None);
+        assert_eq!(shortest_path(&adj, lone, n[0]), None);
+    }
+}
@@ -12,8 +12,7 @@
 
 use bevy::prelude::*;
 
-use crate::core::components::{CanvasNode, SourceLocation};
-use crate::core::helpers::NODE_SIZE;
+use crate::core::components::{CanvasNode, NodeSize, SourceLocation};
 
 /// Marker on the colored backdrop sprite child of a crawled node.
 #[derive(Component)]
@@ -51,16 +50,30 @@ pub fn palette_rgb(file: &str) -> (f32, f32, f32) {
     PALETTE[h % PALETTE.len()]
 }
 
+/// How far `module_fill` moves a node's fill from the base color toward its
+/// file's palette entry; low enough that labels stay readable.
+const MODULE_TINT: f32 = 0.35;
+
+/// Fill of a crawled function node from `file` under `color_by_module`: the
+/// theme's crawl fill tinted toward the file's palette (legend) color.
+pub fn module_fill(file: &str, base: Color) -> Color {
+    let (r, g, b) = palette_rgb(file);
+    Srgba::from(base).mix(&Srgba::rgb(r, g, b), MODULE_TINT).into()
+}
+
 /// Runs once per new crawled node: attaches a colored halo child sprite.
 /// Uses the `HasClusterBlob` marker to run only for nodes that don't yet
 /// have a halo, so the query body executes O(nodes) total, not per frame.
 pub fn cluster_blobs_system(
     mut commands: Commands,
-    node_query: Query<(Entity, &SourceLocation), (With<CanvasNode>, Without<HasClusterBlob>)>,
+    node_query: Query<
+        (Entity, &SourceLocation, Option<&NodeSize>),
+        (With<CanvasNode>, Without<HasClusterBlob>),
+    >,
 ) {
-    for (entity, loc) in &node_query {
+    for (entity, loc, size) in &node_query {
         let color = halo_color(&loc.file);
-        let halo_size = NODE_SIZE + Vec2::splat(HALO_PAD * 2.0);
+        let halo_size = halo_size(size.copied().unwrap_or_default().current);
         commands.entity(entity)
             .insert(HasClusterBlob)
             .with_children(|parent| {
@@ -77,3 +90,54 @@ pub fn cluster_blobs_system(
             });
     }
 }
+
+/// Halo sprite size for a node `node_size` across.
+fn halo_size(node_size: Vec2) -> Vec2 {
+    node_size + Vec2::splat(HALO_PAD * 2.0)
+}
+
+/// Haloed nodes whose box was resized, or whose halo was just attached.
+type ResizedHaloNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static NodeSize, &'static Children),
+    (With<HasClusterBlob>, Or<(Changed<NodeSize>, Changed<Children>)>),
+>;
+
+/// Keeps each halo a fixed pad larger than its node as wrapped labels grow
+/// or shrink the node box.
+pub fn sync_cluster_blob_size_system(
+    nodes: ResizedHaloNodeQuery,
+    mut halos: Query<&mut Sprite, With<ClusterBlob>>,
+) {
+    for (size, children) in &nodes {
+        let want = Some(halo_size(size.current));
+        for child in children {
+            if let Ok(mut sprite) = halos.get_mut(*child) {
+                if sprite.custom_size != want {
+                    sprite.custom_size = want;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_fill_tints_the_base_per_file() {
+        let base = Color::srgb(0.2, 0.2, 0.2);
+        let a = module_fill("/src/a.rs", base);
+        assert_eq!(a, module_fill("/src/a.rs", base), "stable per file");
+        let (r, g, b) = palette_rgb("/src/a.rs");
+        let a = Srgba::from(a);
+        // Between the base and the palette color, closer to the base.
+        for (tinted, target) in [(a.red, r), (a.green, g), (a.blue, b)] {
+            let moved = (tinted - 0.2).abs();
+            assert!(moved <= (target - 0.2).abs() * 0.5 + 1e-4);
+        }
+        assert_ne!(Srgba::from(base), a);
+    }
+}
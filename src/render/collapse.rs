@@ -0,0 +1,163 @@
+//! Folding of crawled call graphs: `zc` hides every node reachable only
+//! through the selected node and shows a `+N` badge on it; `zo` brings them back.
+//!
+//! Purely visual — `Visibility` and marker components change, nothing is saved
+//! or pushed to the undo history. Entities tagged `FlowHidden` (`:flow off`)
+//! or `InactiveCanvas` (other tabs) are hidden through the same
+//! `CollapseHidden` marker.
+
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::core::components::{
+    CollapseHidden, Collapsed, Edge, FlowHidden, InactiveCanvas, NodeSize,
+};
+
+/// Marker on the `+N` Text2d child of a `Collapsed` node.
+#[derive(Component)]
+pub struct CollapseBadge;
+
+const BADGE_COLOR: Color = Color::srgb(1.0, 0.85, 0.1);
+
+/// Badge position in the top-right corner of a node `size` across.
+fn badge_offset(size: Option<&NodeSize>) -> Vec3 {
+    let half = size.copied().unwrap_or_default().current * 0.5;
+    Vec3::new(half.x - 14.0, half.y - 12.0, 2.0)
+}
+
+/// Nodes reachable from `root` along `edges` (source, target) that no other
+/// root — a source without incoming edges — reaches without passing through `root`.
+pub fn exclusive_subtree(root: Entity, edges: &[(Entity, Entity)]) -> HashSet<Entity> {
+    let mut adjacency: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    let mut has_incoming: HashSet<Entity> = HashSet::new();
+    for &(source, target) in edges {
+        adjacency.entry(source).or_default().push(target);
+        if source != target {
+            has_incoming.insert(target);
+        }
+    }
+    let reach = |starts: Vec<Entity>, blocked: Option<Entity>| {
+        let mut seen: HashSet<Entity> = starts.iter().copied().collect();
+        let mut queue: VecDeque<Entity> = starts.into();
+        while let Some(node) = queue.pop_front() {
+            for &next in adjacency.get(&node).into_iter().flatten() {
+                if Some(next) != blocked && seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        seen
+    };
+
+    let mut below = reach(vec![root], None);
+    below.remove(&root);
+    let other_roots: Vec<Entity> = adjacency
+        .keys()
+        .copied()
+        .filter(|n| *n != root && !has_incoming.contains(n))
+        .collect();
+    let elsewhere = reach(other_roots, Some(root));
+    below.retain(|n| !elsewhere.contains(n));
+    below
+}
+
+type MarkedHiddenQuery<'w, 's> =
+    Query<'w, 's, Entity, Or<(With<FlowHidden>, With<InactiveCanvas>)>>;
+
+/// Every frame: hide the exclusive subtrees of `Collapsed` nodes (plus edges
+/// touching them) and everything `FlowHidden` or `InactiveCanvas`, unhide whatever is no longer
+/// covered and keep the badges current.
+pub fn sync_collapsed_system(
+    mut commands: Commands,
+    collapsed: Query<Entity, With<Collapsed>>,
+    edges: Query<(Entity, &Edge)>,
+    hidden: Query<Entity, With<CollapseHidden>>,
+    marked_hidden: MarkedHiddenQuery,
+    mut badges: Query<(Entity, &ChildOf, &mut Text2d, &mut Transform), With<CollapseBadge>>,
+    sizes: Query<&NodeSize>,
+) {
+    if collapsed.is_empty() && hidden.is_empty() && marked_hidden.is_empty() && badges.is_empty() {
+        return;
+    }
+    let pairs: Vec<(Entity, Entity)> = edges.iter().map(|(_, e)| (e.source, e.target)).collect();
+    let mut counts: HashMap<Entity, usize> = HashMap::new();
+    let mut want: HashSet<Entity> = marked_hidden.iter().collect();
+    for root in &collapsed {
+        let subtree = exclusive_subtree(root, &pairs);
+        counts.insert(root, subtree.len());
+        want.extend(subtree);
+    }
+    let hidden_edges: Vec<Entity> = edges
+        .iter()
+        .filter(|(_, e)| want.contains(&e.source) || want.contains(&e.target))
+        .map(|(entity, _)| entity)
+        .collect();
+    want.extend(hidden_edges);
+
+    let current: HashSet<Entity> = hidden.iter().collect();
+    for &entity in want.difference(&current) {
+        commands.entity(entity).try_insert((CollapseHidden, Visibility::Hidden));
+    }
+    for &entity in current.difference(&want) {
+        commands
+            .entity(entity)
+            .try_remove::<CollapseHidden>()
+            .try_insert(Visibility::Inherited);
+    }
+
+    for (badge, child_of, mut text, mut transform) in &mut badges {
+        match counts.remove(&child_of.parent()) {
+            Some(n) => {
+                let label = format!("+{}", n);
+                if text.as_str() != label {
+                    text.0 = label;
+                }
+                let at = badge_offset(sizes.get(child_of.parent()).ok());
+                if transform.translation != at {
+                    transform.translation = at;
+                }
+            }
+            None => {
+                commands.entity(badge).try_despawn();
+            }
+        }
+    }
+    for (root, n) in counts {
+        let badge = (
+            Text2d::new(format!("+{}", n)),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(BADGE_COLOR),
+            Transform::from_translation(badge_offset(sizes.get(root).ok())),
+            CollapseBadge,
+        );
+        commands.entity(root).with_child(badge);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn e(n: u32) -> Entity {
+        Entity::from_bits(n as u64)
+    }
+
+    #[test]
+    fn subtree_excludes_nodes_shared_with_other_roots() {
+        // main → a → {b, c};  other → c;  b → d
+        let edges = [(e(6), e(1)), (e(1), e(2)), (e(1), e(3)), (e(4), e(3)), (e(2), e(5))];
+        let hidden = exclusive_subtree(e(1), &edges);
+        assert_eq!(hidden, HashSet::from([e(2), e(5)]));
+    }
+
+    #[test]
+    fn subtree_handles_cycles_and_leaves() {
+        // a → b → c → b (cycle under a), a → a (self loop)
+        let edges = [(e(1), e(2)), (e(2), e(3)), (e(3), e(2)), (e(1), e(1))];
+        assert_eq!(exclusive_subtree(e(1), &edges), HashSet::from([e(2), e(3)]));
+        assert!(exclusive_subtree(e(3), &[(e(1), e(3))]).is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+//! Hand-bent edges: dragging the selected edge's midpoint handle
+//! (`input::selection::curve_drag_system`) gives it a `CurveOffset`;
+//! `:straighten` removes it again through an undoable `Action::CurveEdge`,
+//! so the edge goes back to its automatic curve.
+
+use bevy::prelude::*;
+
+use crate::core::components::{CurveOffset, Edge};
+use crate::core::history::{Action, UndoHistory};
+use crate::core::resources::{SelectedEdge, StatusMessage};
+
+/// Message sent by `:straighten`.
+#[derive(Message)]
+pub struct StraightenRequest;
+
+/// Handles `StraightenRequest`: drops the selected edge's bend and records it.
+pub fn handle_straighten_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<StraightenRequest>,
+    selected_edge: Res<SelectedEdge>,
+    edges: Query<Option<&CurveOffset>, With<Edge>>,
+    mut history: ResMut<UndoHistory>,
+    mut status: ResMut<StatusMessage>,
+) {
+    if requests.read().count() == 0 {
+        return;
+    }
+    let Some((entity, curve)) = selected_edge.0.and_then(|e| Some((e, edges.get(e).ok()?))) else {
+        status.set("No edge selected");
+        return;
+    };
+    let Some(curve) = curve else {
+        status.set("Edge is not bent");
+        return;
+    };
+    history.push(Action::CurveEdge {
+        entity,
+        old: Some(curve.0),
+        new: None,
+    });
+    commands.entity(entity).remove::<CurveOffset>();
+    status.set("Edge straightened");
+    info!("[STRAIGHTEN] {:?}", entity);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn straighten_drops_the_bend_and_is_undoable() {
+        let mut world = World::new();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<Messages<StraightenRequest>>();
+        let (a, b) = (world.spawn_empty().id(), world.spawn_empty().id());
+        let bend = Vec2::new(30.0, -60.0);
+        let edge = world
+            .spawn((
+                Edge {
+                    source: a,
+                    target: b,
+                    label: None,
+                },
+                CurveOffset(bend),
+            ))
+            .id();
+        world.insert_resource(SelectedEdge(Some(edge)));
+
+        world.write_message(StraightenRequest);
+        world.run_system_once(handle_straighten_requests).unwrap();
+        assert!(world.get::<CurveOffset>(edge).is_none());
+        assert!(matches!(
+            world.resource::<UndoHistory>().undo_stack.last(),
+            Some(Action::CurveEdge { entity, old: Some(old), new: None })
+                if *entity == edge && *old == bend
+        ));
+
+        // An edge that is already straight records nothing.
+        world.write_message(StraightenRequest);
+        world.run_system_once(handle_straighten_requests).unwrap();
+        assert_eq!(world.resource::<UndoHistory>().undo_stack.len(), 1);
+        assert_eq!(world.resource::<StatusMessage>().text, "Edge is not bent");
+    }
+}
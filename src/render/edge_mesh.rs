@@ -0,0 +1,230 @@
+//! Mesh edges (`edge_meshes`): each edge gets a triangle-mesh ribbon along
+//! its curve in place of gizmo lines, so strokes have a real world-space width
+//! that scales with zoom, round joins and caps, and MSAA edges. The geometry
+//! comes from the same [`EdgeShapeCache`] the gizmo pass draws; a mesh
+//! is only re-tessellated when its shape changes (an endpoint moved, the
+//! stroke or routing changed), and only its material when just the color did.
+//!
+//! The mesh lives on the edge entity itself, with the vertices in world space
+//! and an identity `Transform`, so folding (`Visibility::Hidden` on the edge)
+//! and deleting the edge take the mesh with them.
+
+use bevy::asset::RenderAssetUsages;
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use crate::core::config::GlyphConfig;
+use crate::render::edges::{EdgeShape, EdgeShapeCache};
+
+/// World units of ribbon width per unit of `EdgeStroke::width`.
+const WIDTH_PER_UNIT: f32 = 2.0;
+/// Triangles in the disc drawn at each joint and end of a ribbon.
+const JOIN_SEGMENTS: usize = 8;
+/// Depth of the ribbons: behind node halos (-0.25) and shadows (-0.1).
+const EDGE_MESH_Z: f32 = -0.5;
+
+/// The shape an edge's mesh was last built from.
+#[derive(Component)]
+pub struct EdgeRibbon(EdgeShape);
+
+/// Triangle list under construction.
+#[derive(Default)]
+struct Ribbon {
+    positions: Vec<[f32; 3]>,
+    indices: Vec<u32>,
+}
+
+impl Ribbon {
+    fn vertex(&mut self, p: Vec2) -> u32 {
+        self.positions.push([p.x, p.y, EDGE_MESH_Z]);
+        self.positions.len() as u32 - 1
+    }
+
+    fn disc(&mut self, centre: Vec2, radius: f32) {
+        let c = self.vertex(centre);
+        let first = self.positions.len() as u32;
+        for i in 0..JOIN_SEGMENTS {
+            let a = i as f32 / JOIN_SEGMENTS as f32 * std::f32::consts::TAU;
+            self.vertex(centre + Vec2::from_angle(a) * radius);
+        }
+        for i in 0..JOIN_SEGMENTS as u32 {
+            let next = (i + 1) % JOIN_SEGMENTS as u32;
+            self.indices.extend([c, first + i, first + next]);
+        }
+    }
+
+    /// A `half_width` ribbon along `points`, one quad per piece with a disc at
+    /// every joint and end. Dashed ribbons skip every other piece.
+    fn polyline(&mut self, points: &[Vec2], half_width: f32, dashed: bool) {
+        for (i, piece) in points.windows(2).enumerate() {
+            if dashed && i % 2 == 1 {
+                continue;
+            }
+            let (a, b) = (piece[0], piece[1]);
+            let n = (b - a).normalize_or_zero().perp() * half_width;
+            if n == Vec2::ZERO {
+                continue;
+            }
+            let quad = [a + n, a - n, b - n, b + n].map(|p| self.vertex(p));
+            self.indices
+                .extend([quad[0], quad[1], quad[2], quad[0], quad[2], quad[3]]);
+            if i == 0 || dashed {
+                self.disc(a, half_width);
+            }
+            self.disc(b, half_width);
+        }
+    }
+
+    fn into_mesh(self) -> Mesh {
+        let count = self.positions.len();
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, self.positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; count])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; count])
+        .with_inserted_indices(Indices::U32(self.indices))
+    }
+}
+
+/// Ribbons for `shape`: the curve (twice, side by side, for a bundled pair)
+/// and the arrowhead.
+fn ribbon(shape: &EdgeShape) -> Ribbon {
+    let half_width = shape.stroke.width.max(0.5) * WIDTH_PER_UNIT * 0.5;
+    let mut ribbon = Ribbon::default();
+    let offsets: &[f32] = if shape.bundled { &[-2.0, 2.0] } else { &[0.0] };
+    for offset in offsets {
+        let shift = shape.spread * *offset * half_width;
+        let points: Vec<Vec2> = shape.points.iter().map(|p| *p + shift).collect();
+        ribbon.polyline(&points, half_width, shape.stroke.dashed);
+    }
+    let tip = shape.points[shape.points.len() - 1];
+    ribbon.polyline(&[shape.wings.0, tip, shape.wings.1], half_width, false);
+    ribbon
+}
+
+/// Mesh of the ribbons for `shape`.
+pub fn ribbon_mesh(shape: &EdgeShape) -> Mesh {
+    ribbon(shape).into_mesh()
+}
+
+type RibbonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut EdgeRibbon,
+        &'static Mesh2d,
+        &'static MeshMaterial2d<ColorMaterial>,
+    ),
+>;
+
+/// Keep a ribbon mesh on every visible edge while `edge_meshes` is set, and
+/// take them all off when it is cleared.
+pub fn sync_edge_meshes_system(
+    mut commands: Commands,
+    config: Res<GlyphConfig>,
+    cache: Res<EdgeShapeCache>,
+    mut ribbons: RibbonQuery,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let shapes: &[EdgeShape] = if config.edge_meshes { &cache.0 } else { &[] };
+    let mut drawn = HashSet::new();
+    for shape in shapes {
+        drawn.insert(shape.entity);
+        let Ok((_, mut built, mesh, material)) = ribbons.get_mut(shape.entity) else {
+            commands.entity(shape.entity).try_insert((
+                Mesh2d(meshes.add(ribbon_mesh(shape))),
+                MeshMaterial2d(materials.add(shape.color)),
+                Transform::IDENTITY,
+                EdgeRibbon(shape.clone()),
+            ));
+            continue;
+        };
+        if built.0 == *shape {
+            continue;
+        }
+        if built.0.color != shape.color {
+            if let Some(material) = materials.get_mut(&material.0) {
+                material.color = shape.color;
+            }
+        }
+        let recolor_only = EdgeShape {
+            color: built.0.color,
+            ..shape.clone()
+        } == built.0;
+        if !recolor_only {
+            if let Some(mesh) = meshes.get_mut(&mesh.0) {
+                *mesh = ribbon_mesh(shape);
+            }
+        }
+        built.0 = shape.clone();
+    }
+    for (entity, ..) in &ribbons {
+        if !drawn.contains(&entity) {
+            commands
+                .entity(entity)
+                .try_remove::<(EdgeRibbon, Mesh2d, MeshMaterial2d<ColorMaterial>)>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::components::EdgeStroke;
+
+    fn shape(points: Vec<Vec2>, width: f32, dashed: bool) -> EdgeShape {
+        EdgeShape {
+            entity: Entity::PLACEHOLDER,
+            wings: (
+                points[points.len() - 1] - Vec2::new(10.0, 5.0),
+                points[points.len() - 1] - Vec2::new(10.0, -5.0),
+            ),
+            points,
+            spread: Vec2::Y,
+            color: Color::WHITE,
+            stroke: EdgeStroke { width, dashed },
+            bundled: false,
+            bezier: None,
+        }
+    }
+
+    #[test]
+    fn ribbons_follow_the_curve_at_the_stroke_width() {
+        let points: Vec<Vec2> = (0..=4).map(|i| Vec2::new(i as f32 * 50.0, 0.0)).collect();
+        let solid = ribbon(&shape(points.clone(), 2.0, false));
+        let half_width = 2.0 * WIDTH_PER_UNIT * 0.5;
+        // Every vertex is within half a width of the curve or the arrowhead.
+        for p in &solid.positions {
+            assert_eq!(p[2], EDGE_MESH_Z);
+            let on_curve = p[1].abs() <= half_width + 1e-4 && (-5.0..=205.0).contains(&p[0]);
+            let on_arrow = p[0] >= 190.0 - half_width - 1e-4;
+            assert!(on_curve || on_arrow, "{:?} strays from the edge", p);
+        }
+        assert_eq!(solid.indices.len() % 3, 0);
+        assert!(solid
+            .indices
+            .iter()
+            .all(|i| (*i as usize) < solid.positions.len()));
+
+        let dashed = ribbon(&shape(points.clone(), 2.0, true));
+        assert!(
+            dashed.indices.len() < solid.indices.len(),
+            "dashes leave gaps"
+        );
+        // Away from the arrowhead, a thinner stroke makes a narrower ribbon.
+        let thin = ribbon(&shape(points, 1.0, false));
+        let widest = |r: &Ribbon| {
+            r.positions
+                .iter()
+                .filter(|p| p[0] < 150.0)
+                .map(|p| p[1].abs())
+                .fold(0.0, f32::max)
+        };
+        assert!(widest(&thin) < widest(&solid));
+    }
+}
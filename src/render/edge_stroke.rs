@@ -0,0 +1,141 @@
+//! Per-edge strokes: `:estyle dashed|solid|bold|thin` changes the
+//! `EdgeStroke` of the selected edge through an undoable
+//! `Action::RestyleEdge`. `draw_edges_system` does the drawing.
+
+use bevy::prelude::*;
+
+use crate::core::components::{Edge, EdgeStroke};
+use crate::core::history::{Action, UndoHistory};
+use crate::core::resources::{SelectedEdge, StatusMessage};
+
+/// Width `:estyle bold` gives an edge, in gizmo line widths.
+pub const BOLD_WIDTH: f32 = 3.0;
+
+/// One `:estyle` argument; each changes only its own half of the stroke.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrokeChange {
+    Dashed,
+    Solid,
+    Bold,
+    Thin,
+}
+
+impl StrokeChange {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dashed" => Some(Self::Dashed),
+            "solid" => Some(Self::Solid),
+            "bold" => Some(Self::Bold),
+            "thin" => Some(Self::Thin),
+            _ => None,
+        }
+    }
+
+    pub fn apply(self, stroke: EdgeStroke) -> EdgeStroke {
+        match self {
+            Self::Dashed => EdgeStroke {
+                dashed: true,
+                ..stroke
+            },
+            Self::Solid => EdgeStroke {
+                dashed: false,
+                ..stroke
+            },
+            Self::Bold => EdgeStroke {
+                width: BOLD_WIDTH,
+                ..stroke
+            },
+            Self::Thin => EdgeStroke {
+                width: 1.0,
+                ..stroke
+            },
+        }
+    }
+}
+
+/// Message sent by `:estyle`.
+#[derive(Message)]
+pub struct EdgeStrokeRequest(pub StrokeChange);
+
+/// Handles `EdgeStrokeRequest`: restyles the selected edge and records it.
+pub fn handle_edge_stroke_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<EdgeStrokeRequest>,
+    selected_edge: Res<SelectedEdge>,
+    edges: Query<Option<&EdgeStroke>, With<Edge>>,
+    mut history: ResMut<UndoHistory>,
+    mut status: ResMut<StatusMessage>,
+) {
+    // Strokes inserted this frame are not visible through `edges` yet.
+    let mut written: Option<(Entity, EdgeStroke)> = None;
+    for EdgeStrokeRequest(change) in requests.read() {
+        let Some((entity, stroke)) = selected_edge.0.and_then(|e| Some((e, edges.get(e).ok()?)))
+        else {
+            status.set("No edge selected");
+            continue;
+        };
+        let old = match written {
+            Some((e, stroke)) if e == entity => stroke,
+            _ => stroke.copied().unwrap_or_default(),
+        };
+        let new = change.apply(old);
+        if new == old {
+            continue;
+        }
+        history.push(Action::RestyleEdge { entity, old, new });
+        commands.entity(entity).insert(new);
+        written = Some((entity, new));
+        status.set(format!("Edge style: {:?}", change).to_lowercase());
+        info!("[ESTYLE] {:?} → {:?}", entity, new);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn estyle_changes_the_selected_edge_and_is_undoable() {
+        let mut world = World::new();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<Messages<EdgeStrokeRequest>>();
+        let (a, b) = (world.spawn_empty().id(), world.spawn_empty().id());
+        let edge = world
+            .spawn(Edge {
+                source: a,
+                target: b,
+                label: None,
+            })
+            .id();
+        world.insert_resource(SelectedEdge(Some(edge)));
+
+        world.write_message(EdgeStrokeRequest(StrokeChange::Dashed));
+        world.write_message(EdgeStrokeRequest(StrokeChange::Bold));
+        world.run_system_once(handle_edge_stroke_requests).unwrap();
+        let stroke = *world.get::<EdgeStroke>(edge).unwrap();
+        assert_eq!(
+            stroke,
+            EdgeStroke {
+                width: BOLD_WIDTH,
+                dashed: true
+            }
+        );
+
+        let history = world.resource::<UndoHistory>();
+        assert_eq!(history.undo_stack.len(), 2);
+        assert!(matches!(
+            history.undo_stack.last(),
+            Some(Action::RestyleEdge { entity, old, new })
+                if *entity == edge && old.dashed && !old.is_default() && *new == stroke
+        ));
+
+        // Without a selected edge nothing changes.
+        world.insert_resource(SelectedEdge(None));
+        world.write_message(EdgeStrokeRequest(StrokeChange::Thin));
+        world.run_system_once(handle_edge_stroke_requests).unwrap();
+        assert_eq!(world.get::<EdgeStroke>(edge), Some(&stroke));
+        assert_eq!(world.resource::<StatusMessage>().text, "No edge selected");
+    }
+}
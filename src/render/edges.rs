@@ -1,13 +1,37 @@
 //! Gizmo and text rendering systems.
 
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use std::f32::consts::PI;
 
-use crate::core::components::{Edge, EdgeLabel, Selected, TextData, TextLabel, TracedPath};
+use crate::core::components::{
+    CanvasNode, CollapseHidden, CurveOffset, Edge, EdgeLabel, EdgeStroke, FileLabel, GrepDimmed,
+    LineCountLabel, NodeMainSprite, NodeShadow, NodeShape, NodeSize, PathHighlight, Selected,
+    TextData, TextLabel, TracedPath,
+};
+use crate::core::config::{EdgeStyle, GlyphConfig, Theme};
+use crate::core::helpers::{
+    label_chars_per_line, with_caret, wrap_label, wrapped_node_size, LABEL_PADDING,
+};
+use crate::core::resources::{HoveredEdge, InsertCaret, SpatialIndex};
+use bevy::text::TextBounds;
 use crate::core::state::InputMode;
+use crate::render::edge_stroke::BOLD_WIDTH;
+use crate::render::grep::DIM_ALPHA;
+use crate::render::shapes::draw_node_outline;
 
+/// Half the gap between the two strokes of a bundled edge (`bundle_edges`).
+const BUNDLE_HALF_WIDTH: f32 = 1.5;
+/// Gap between the parallel curves that make up a wide `EdgeStroke`.
+const STROKE_SPACING: f32 = 2.0;
 /// Number of segments for approximating Bezier curves.
 const CURVE_SEGMENTS: usize = 24;
+/// Angle between each arrowhead wing and the curve (~20°).
+const ARROW_HALF_ANGLE: f32 = 20.0 * PI / 180.0;
+/// Curve parameter span kept by `EdgeStyle::Stub`, measured back from the arrow tip.
+const STUB_T_SPAN: f32 = 0.18;
+/// Segments used to draw a stub.
+const STUB_SEGMENTS: usize = 6;
 
 /// Quadratic Bezier: B(t) = (1-t)²P0 + 2(1-t)tP1 + t²P2
 fn bezier_point(p0: Vec2, p1: Vec2, p2: Vec2, t: f32) -> Vec2 {
@@ -21,98 +45,695 @@ fn bezier_tangent(p0: Vec2, p1: Vec2, p2: Vec2, t: f32) -> Vec2 {
     (2.0 * u * (p1 - p0) + 2.0 * t * (p2 - p1)).normalize_or_zero()
 }
 
-/// Draw curved edges using quadratic Bezier. Control point offset perpendicular for clear routing.
-/// Color for traced/highlighted edges and node outlines.
-const TRACED_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
-/// Default edge color (muted blue).
-const DEFAULT_EDGE_COLOR: Color = Color::srgb(0.22, 0.32, 0.48);
+/// Curve parameter where the edge meets the target node's outline (half
+/// extents `half`), found by stepping back from t = 1 until the sample leaves
+/// the target's box. Falls back to 1.0 when the nodes overlap.
+fn arrow_tip_t(p0: Vec2, p1: Vec2, p2: Vec2, half: Vec2) -> f32 {
+    let steps = CURVE_SEGMENTS * 4;
+    for i in (0..=steps).rev() {
+        let t = i as f32 / steps as f32;
+        let d = (bezier_point(p0, p1, p2, t) - p2).abs();
+        if d.x > half.x || d.y > half.y {
+            return t;
+        }
+    }
+    1.0
+}
 
-/// Edges between the same node pair alternate curve direction for efficient, non-overlapping layout.
-pub fn draw_edges_system(
-    mut gizmos: Gizmos,
-    edge_query: Query<(Entity, &Edge, Option<&TracedPath>)>,
-    transform_query: Query<&Transform>,
-) {
-    // Group edges by (source, target) so we alternate direction within each pair
-    let mut groups: std::collections::HashMap<(Entity, Entity), Vec<(Entity, bool)>> =
-        std::collections::HashMap::new();
-    for (entity, edge, traced) in &edge_query {
-        let key = (edge.source, edge.target);
+/// End points of the two wings of a caret arrowhead with its tip at `tip`,
+/// pointing along `dir`.
+fn arrowhead_wings(tip: Vec2, dir: Vec2, size: f32) -> (Vec2, Vec2) {
+    let back = -dir.normalize_or_zero() * size;
+    (
+        tip + Vec2::from_angle(ARROW_HALF_ANGLE).rotate(back),
+        tip + Vec2::from_angle(-ARROW_HALF_ANGLE).rotate(back),
+    )
+}
+
+/// Samples of the curve between `t0` and `t1`, `segments` pieces long.
+fn curve_span((p0, p1, p2): (Vec2, Vec2, Vec2), t0: f32, t1: f32, segments: usize) -> Vec<Vec2> {
+    (0..=segments)
+        .map(|i| bezier_point(p0, p1, p2, t0 + (t1 - t0) * i as f32 / segments as f32))
+        .collect()
+}
+
+/// Offsets of the parallel curves drawn for a stroke `width` gizmo lines
+/// wide. Gizmo line width is global (`setup_gizmo_line_width`), so wider
+/// edges are approximated by several curves side by side.
+fn stroke_offsets(width: f32) -> Vec<f32> {
+    let n = width.round().max(1.0) as usize;
+    let mid = (n - 1) as f32 * 0.5;
+    (0..n).map(|i| (i as f32 - mid) * STROKE_SPACING).collect()
+}
+
+/// Radius of the first self-loop on a node; further loops grow outwards.
+const SELF_LOOP_RADIUS: f32 = 28.0;
+/// Angle where a self-loop leaves the node (left) and comes back in (right).
+/// The loop centre sits half a radius above the top edge, so both ends lie on it.
+const SELF_LOOP_START: f32 = 210.0 * PI / 180.0;
+const SELF_LOOP_END: f32 = -30.0 * PI / 180.0;
+
+/// Centre and radius of the `idx`-th self-loop on a node at `p` whose top
+/// edge is `half_height` above it.
+fn self_loop_circle(p: Vec2, half_height: f32, idx: usize) -> (Vec2, f32) {
+    let r = SELF_LOOP_RADIUS * (1.0 + 0.5 * idx as f32);
+    (p + Vec2::new(0.0, half_height + r * 0.5), r)
+}
+
+/// Samples of a recursive edge: a clockwise arc over the top of the node,
+/// and the arrowhead wings curling back into it.
+fn self_loop_path(
+    p: Vec2,
+    half_height: f32,
+    idx: usize,
+    style: EdgeStyle,
+    arrow_size: f32,
+) -> (Vec<Vec2>, (Vec2, Vec2)) {
+    let (c, r) = self_loop_circle(p, half_height, idx);
+    let point = |a: f32| c + Vec2::new(a.cos(), a.sin()) * r;
+    let (start, segments) = match style {
+        EdgeStyle::Curve => (SELF_LOOP_START, CURVE_SEGMENTS),
+        EdgeStyle::Stub => (SELF_LOOP_END + PI / 3.0, STUB_SEGMENTS),
+    };
+    let points: Vec<Vec2> = (0..=segments)
+        .map(|i| point(start + (SELF_LOOP_END - start) * i as f32 / segments as f32))
+        .collect();
+    // Clockwise tangent at the end angle.
+    let dir = Vec2::new(SELF_LOOP_END.sin(), -SELF_LOOP_END.cos());
+    let wings = arrowhead_wings(points[segments], dir, arrow_size);
+    (points, wings)
+}
+
+/// Traced edges use the theme's traced color, the hovered edge its
+/// `edge_hover` color; edges faded by `:grep` keep their hue at `DIM_ALPHA`.
+fn edge_color(theme: &Theme, traced: bool, hovered: bool, dimmed: bool) -> Color {
+    let color = if traced {
+        theme.traced
+    } else if hovered {
+        theme.edge_hover
+    } else {
+        theme.edge
+    };
+    if dimmed {
+        color.with_alpha(DIM_ALPHA)
+    } else {
+        color
+    }
+}
+
+/// Edges to draw: folded-away ones (`CollapseHidden`) are skipped.
+type DrawEdgeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Edge,
+        Option<&'static TracedPath>,
+        Has<GrepDimmed>,
+        Option<&'static EdgeStroke>,
+        Has<PathHighlight>,
+        Option<&'static CurveOffset>,
+    ),
+    Without<CollapseHidden>,
+>;
+
+/// One edge of a group: entity, traced, dimmed, stroke and hand-set bend.
+type GroupedEdge = (Entity, bool, bool, EdgeStroke, Option<Vec2>);
+
+/// Edges by (source, target).
+type EdgeGroups = std::collections::HashMap<(Entity, Entity), Vec<GroupedEdge>>;
+
+/// Edge endpoints and routing obstacles: position, size and whether folded away.
+type EdgeNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Transform, Option<&'static NodeSize>, Has<CollapseHidden>),
+>;
+
+/// Factors tried on the control-point offset when routing around nodes.
+const DETOUR_STEPS: [f32; 5] = [1.0, 1.6, 2.4, 3.4, 4.8];
+/// Obstacle boxes grow by this much on each side so curves keep some clearance.
+const DETOUR_CLEARANCE: f32 = 8.0;
+/// How far beyond the chord's bounding box to look for obstacles: the widest
+/// detour bulges half its control offset out, plus room for large nodes.
+const DETOUR_MARGIN: f32 = 800.0;
+
+/// True if the curve passes through any of `obstacles` (centre, half extents).
+fn curve_hits((p0, p1, p2): (Vec2, Vec2, Vec2), obstacles: &[(Vec2, Vec2)]) -> bool {
+    (1..CURVE_SEGMENTS).any(|i| {
+        let p = bezier_point(p0, p1, p2, i as f32 / CURVE_SEGMENTS as f32);
+        obstacles.iter().any(|(centre, half)| {
+            let d = (p - *centre).abs();
+            d.x < half.x + DETOUR_CLEARANCE && d.y < half.y + DETOUR_CLEARANCE
+        })
+    })
+}
+
+/// Control point for a curve from `p0` to `p2` that bends around `obstacles`.
+/// Keeps `p1` when the curve is already clear; otherwise pushes the offset
+/// out step by step, trying the curve's own side before the other, and
+/// falls back to `p1` when nothing clears.
+pub fn route_control_point(p0: Vec2, p1: Vec2, p2: Vec2, obstacles: &[(Vec2, Vec2)]) -> Vec2 {
+    if !curve_hits((p0, p1, p2), obstacles) {
+        return p1;
+    }
+    let mid = (p0 + p2) * 0.5;
+    let offset = p1 - mid;
+    DETOUR_STEPS
+        .iter()
+        .flat_map(|k| [mid + offset * *k, mid - offset * *k])
+        .find(|c| !curve_hits((p0, *c, p2), obstacles))
+        .unwrap_or(p1)
+}
+
+/// Boxes of visible nodes near the chord `p0`–`p2`, other than its endpoints.
+fn edge_obstacles(
+    spatial: &SpatialIndex,
+    nodes: &EdgeNodeQuery,
+    (source, target): (Entity, Entity),
+    p0: Vec2,
+    p2: Vec2,
+) -> Vec<(Vec2, Vec2)> {
+    let (min, max) = (p0.min(p2) - DETOUR_MARGIN, p0.max(p2) + DETOUR_MARGIN);
+    spatial
+        .entities_in_bounds(min.x, max.x, min.y, max.y)
+        .into_iter()
+        .filter(|e| *e != source && *e != target)
+        .filter_map(|e| nodes.get(e).ok())
+        .filter(|(_, _, hidden)| !hidden)
+        .map(|(t, size, _)| {
+            let half = size.copied().unwrap_or_default().current * 0.5;
+            (t.translation.truncate(), half)
+        })
+        .collect()
+}
+
+/// One drawn curve: an edge, or a bundled pair drawn once.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EdgeShape {
+    /// The edge it is drawn for (the first of a bundled pair).
+    pub entity: Entity,
+    /// Curve samples from the start to the arrow tip.
+    pub points: Vec<Vec2>,
+    /// Ends of the arrowhead wings; the tip is the last point.
+    pub wings: (Vec2, Vec2),
+    /// Direction the parallel strokes of a wide or bundled edge spread along.
+    pub spread: Vec2,
+    pub color: Color,
+    pub stroke: EdgeStroke,
+    /// Drawn as a doubled stroke for a bundled pair (`bundle_edges`).
+    pub bundled: bool,
+    /// Bezier control points and the `t` of the arrow tip; `None` for a
+    /// self-loop.
+    pub bezier: Option<([Vec2; 3], f32)>,
+}
+
+/// What edge geometry depends on: the edges, their endpoints and obstacles,
+/// and the edge config and colors.
+#[derive(SystemParam)]
+pub struct EdgeSources<'w, 's> {
+    edges: DrawEdgeQuery<'w, 's>,
+    nodes: EdgeNodeQuery<'w, 's>,
+    spatial: Res<'w, SpatialIndex>,
+    pub config: Res<'w, GlyphConfig>,
+    theme: Res<'w, Theme>,
+    hovered: Res<'w, HoveredEdge>,
+}
+
+impl EdgeSources<'_, '_> {
+    /// Curves of every visible edge.
+    ///
+    /// Edges between the same node pair alternate curve direction for efficient, non-overlapping
+    /// layout. Every edge ends in an arrowhead where the curve meets the target node, so
+    /// direction is visible. `EdgeStyle::Stub` keeps only the end of each curve.
+    /// Self-edges (recursion) are loops over the top of the node.
+    /// With `bundle_edges` set, every pair is drawn once as a doubled stroke;
+    /// `render::bundle` adds the `×N` badge. With `edge_avoid_nodes` set, curves
+    /// that would cross another node bend around it (labels keep their place).
+    /// An `EdgeStroke` makes an edge dashed or wider. Edges on a `:path` are
+    /// bold in the traced color while the highlight lasts. The edge under the
+    /// mouse (`HoveredEdge`) is drawn brighter. An edge bent by hand
+    /// (`CurveOffset`) keeps its control point and is not routed.
+    pub fn shapes(&self) -> Vec<EdgeShape> {
+        let mut shapes = Vec::new();
+        for (pair, entities) in self.groups(|_| true) {
+            self.push_group_shapes(&mut shapes, pair, entities);
+        }
+        shapes
+    }
+
+    /// Curve of one edge, as [`Self::shapes`] draws it, computed from its own
+    /// node pair only. For a bundled pair it is the shared stroke.
+    pub fn shape_of(&self, entity: Entity) -> Option<EdgeShape> {
+        let (_, edge, ..) = self.edges.get(entity).ok()?;
+        let pair = (edge.source, edge.target);
+        let entities = self.groups(|e| (e.source, e.target) == pair).remove(&pair)?;
+        let mut shapes = Vec::new();
+        self.push_group_shapes(&mut shapes, pair, entities);
+        shapes.into_iter().find(|s| s.entity == entity || s.bundled)
+    }
+
+    /// Visible edges accepted by `keep`, grouped by (source, target) so
+    /// direction alternates within each pair.
+    fn groups(&self, keep: impl Fn(&Edge) -> bool) -> EdgeGroups {
+        let mut groups: EdgeGroups = std::collections::HashMap::new();
+        for (entity, edge, traced, dimmed, stroke, on_path, curve) in &self.edges {
+            if !keep(edge) {
+                continue;
+            }
+            let key = (edge.source, edge.target);
+            let mut stroke = stroke.copied().unwrap_or_default();
+            if on_path {
+                stroke.width = stroke.width.max(BOLD_WIDTH);
+            }
+            groups
+                .entry(key)
+                .or_default()
+                .push((entity, traced.is_some() || on_path, dimmed, stroke, curve.map(|c| c.0)));
+        }
         groups
-            .entry(key)
-            .or_default()
-            .push((entity, traced.is_some()));
     }
-    for ((source, target), entities) in groups {
-        let Ok(src) = transform_query.get(source) else {
-            continue;
+
+    /// Append the curves of the edges from `source` to `target`.
+    fn push_group_shapes(
+        &self,
+        shapes: &mut Vec<EdgeShape>,
+        (source, target): (Entity, Entity),
+        entities: Vec<GroupedEdge>,
+    ) {
+        let config = &self.config;
+        let hovered = self.hovered.0;
+        let Ok((src, _, _)) = self.nodes.get(source) else {
+            return;
         };
-        let Ok(tgt) = transform_query.get(target) else {
-            continue;
+        let Ok((tgt, tgt_size, _)) = self.nodes.get(target) else {
+            return;
         };
         let p0 = src.translation.truncate();
         let p2 = tgt.translation.truncate();
-        let mid = (p0 + p2) * 0.5;
-        let dir = (p2 - p0).normalize_or_zero();
-        let dist = p0.distance(p2);
-        // Larger offset so curves are clearly visible on the canvas.
-        let curve_mag = (dist * 0.35).clamp(35.0, 180.0);
-        let perp = Vec2::new(-dir.y, dir.x);
-        for (idx, (_, is_traced)) in entities.iter().enumerate() {
-            let color = if *is_traced {
-                TRACED_COLOR
-            } else {
-                DEFAULT_EDGE_COLOR
+        let tgt_half = tgt_size.copied().unwrap_or_default().current * 0.5;
+        // Bundled pairs draw one doubled stroke: red if any edge is traced,
+        // faded only if every edge is, as wide as the widest and dashed only
+        // if every edge is.
+        let bundled = config.bundle_edges && entities.len() > 1;
+        let entities = if bundled {
+            let traced = entities.iter().any(|(_, t, ..)| *t);
+            let dimmed = entities.iter().all(|(_, _, d, ..)| *d);
+            let stroke = EdgeStroke {
+                width: entities.iter().map(|(.., s, _)| s.width).fold(1.0, f32::max),
+                dashed: entities.iter().all(|(.., s, _)| s.dashed),
+            };
+            vec![(entities[0].0, traced, dimmed, stroke, entities[0].4)]
+        } else {
+            entities
+        };
+        if source == target {
+            for (idx, (entity, is_traced, dimmed, stroke, _)) in entities.into_iter().enumerate() {
+                let (style, size) = (config.edge_style, config.edge_arrow_size);
+                let (points, wings) = self_loop_path(p0, tgt_half.y, idx, style, size);
+                shapes.push(EdgeShape {
+                    entity,
+                    points,
+                    wings,
+                    spread: Vec2::ONE,
+                    color: edge_color(&self.theme, is_traced, hovered == Some(entity), dimmed),
+                    stroke,
+                    bundled,
+                    bezier: None,
+                });
+            }
+            return;
+        }
+        let perp = (p2 - p0).normalize_or_zero().perp();
+        let obstacles = if config.edge_avoid_nodes {
+            edge_obstacles(&self.spatial, &self.nodes, (source, target), p0, p2)
+        } else {
+            Vec::new()
+        };
+        for (idx, (entity, is_traced, dimmed, stroke, curve)) in entities.into_iter().enumerate() {
+            let p1 = match curve {
+                Some(_) => curve_control_point(p0, p2, idx, curve),
+                None => route_control_point(p0, edge_control_point(p0, p2, idx), p2, &obstacles),
+            };
+            let tip_t = arrow_tip_t(p0, p1, p2, tgt_half);
+            let (t0, segments) = match config.edge_style {
+                EdgeStyle::Curve => (0.0, CURVE_SEGMENTS),
+                EdgeStyle::Stub => ((tip_t - STUB_T_SPAN).max(0.0), STUB_SEGMENTS),
             };
-            let sign = if idx % 2 == 0 { 1.0 } else { -1.0 };
-            let p1 = mid + perp * curve_mag * sign;
-            let mut prev = p0;
-            for i in 1..=CURVE_SEGMENTS {
-                let t = i as f32 / CURVE_SEGMENTS as f32;
-                let pt = bezier_point(p0, p1, p2, t);
-                gizmos.line_2d(prev, pt, color);
-                prev = pt;
+            let points = curve_span((p0, p1, p2), t0, tip_t, segments);
+            let dir = bezier_tangent(p0, p1, p2, tip_t);
+            let wings = arrowhead_wings(points[segments], dir, config.edge_arrow_size);
+            shapes.push(EdgeShape {
+                entity,
+                points,
+                wings,
+                spread: perp,
+                color: edge_color(&self.theme, is_traced, hovered == Some(entity), dimmed),
+                stroke,
+                bundled,
+                bezier: Some(([p0, p1, p2], tip_t)),
+            });
+        }
+    }
+}
+
+/// This frame's [`EdgeSources::shapes`], shared by hover hit-testing, the
+/// gizmo pass and the edge meshes so the curves are routed once per frame.
+#[derive(Resource, Default)]
+pub struct EdgeShapeCache(pub Vec<EdgeShape>);
+
+/// Fill [`EdgeShapeCache`]; runs before everything that reads it.
+pub fn cache_edge_shapes_system(sources: EdgeSources, mut cache: ResMut<EdgeShapeCache>) {
+    cache.0 = sources.shapes();
+}
+
+/// Draw every edge with gizmos (see [`EdgeSources::shapes`]), unless
+/// `edge_meshes` hands them to `render::edge_mesh`. A dashed curve skips
+/// every other piece; a wide one is several curves side by side.
+pub fn draw_edges_system(mut gizmos: Gizmos, config: Res<GlyphConfig>, cache: Res<EdgeShapeCache>) {
+    if config.edge_meshes {
+        return;
+    }
+    for shape in &cache.0 {
+        let offsets: &[f32] = if shape.bundled {
+            &[-BUNDLE_HALF_WIDTH, BUNDLE_HALF_WIDTH]
+        } else {
+            &[0.0]
+        };
+        let strokes = stroke_offsets(shape.stroke.width);
+        let spread = shape.spread;
+        for shift in offsets.iter().flat_map(|o| strokes.iter().map(move |s| spread * (o + s))) {
+            for (i, piece) in shape.points.windows(2).enumerate() {
+                if !shape.stroke.dashed || i % 2 == 0 {
+                    gizmos.line_2d(piece[0] + shift, piece[1] + shift, shape.color);
+                }
             }
         }
+        let tip = shape.points[shape.points.len() - 1];
+        gizmos.line_2d(tip, shape.wings.0, shape.color);
+        gizmos.line_2d(tip, shape.wings.1, shape.color);
+    }
+}
+
+/// Seconds the hover dot takes to run from source to arrow tip.
+const DIRECTION_DOT_PERIOD: f32 = 1.2;
+/// Radius of the hover dot, in world units.
+const DIRECTION_DOT_RADIUS: f32 = 4.0;
+
+/// Where the direction dot sits `elapsed` seconds in on a curve whose arrow
+/// tip is at `tip_t`: it runs source → tip, then starts over.
+pub fn direction_dot_pos(bezier: ([Vec2; 3], f32), elapsed: f32) -> Vec2 {
+    let ([p0, p1, p2], tip_t) = bezier;
+    let t = (elapsed / DIRECTION_DOT_PERIOD).fract() * tip_t;
+    bezier_point(p0, p1, p2, t)
+}
+
+/// A dot travelling along the hovered edge (`HoveredEdge`) from source to
+/// target, so the two fanned curves of an `A→B` / `B→A` pair can be told
+/// apart. Nothing is computed while no edge is hovered.
+pub fn draw_hovered_edge_direction_system(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    hovered: Res<HoveredEdge>,
+    sources: EdgeSources,
+) {
+    let Some(entity) = hovered.0 else {
+        return;
+    };
+    let Some(bezier) = sources.shape_of(entity).and_then(|s| s.bezier) else {
+        return;
+    };
+    let pos = direction_dot_pos(bezier, time.elapsed_secs());
+    gizmos.circle_2d(pos, DIRECTION_DOT_RADIUS, sources.theme.edge_hover);
+}
+
+/// How far from a curve (world units) a click or hover still hits it.
+const EDGE_HIT_DISTANCE: f32 = 6.0;
+
+/// Distance from `p` to the segment `a`–`b`.
+fn segment_distance(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+/// The edge whose drawn curve passes nearest `pos`, if it is within
+/// `EDGE_HIT_DISTANCE` of the curve's outer stroke.
+pub fn edge_at_pos(shapes: &[EdgeShape], pos: Vec2) -> Option<Entity> {
+    shapes
+        .iter()
+        .filter_map(|shape| {
+            let half_width = stroke_offsets(shape.stroke.width)
+                .last()
+                .copied()
+                .unwrap_or(0.0)
+                + if shape.bundled { BUNDLE_HALF_WIDTH } else { 0.0 };
+            let d = shape
+                .points
+                .windows(2)
+                .map(|piece| segment_distance(pos, piece[0], piece[1]))
+                .fold(f32::INFINITY, f32::min);
+            (d <= EDGE_HIT_DISTANCE + half_width).then_some((shape.entity, d))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+/// Control point of the `idx`-th curve between `p0` and `p2`: offset
+/// perpendicular to the chord, alternating sides so parallel edges fan out.
+pub fn edge_control_point(p0: Vec2, p2: Vec2, idx: usize) -> Vec2 {
+    let mid = (p0 + p2) * 0.5;
+    let dir = (p2 - p0).normalize_or_zero();
+    // Larger offset so curves are clearly visible on the canvas.
+    let curve_mag = (p0.distance(p2) * 0.35).clamp(35.0, 180.0);
+    let sign = if idx.is_multiple_of(2) { 1.0 } else { -1.0 };
+    mid + dir.perp() * curve_mag * sign
+}
+
+/// Control point of the `idx`-th curve, or the chord midpoint plus `curve`
+/// for an edge bent by hand (`CurveOffset`).
+pub fn curve_control_point(p0: Vec2, p2: Vec2, idx: usize, curve: Option<Vec2>) -> Vec2 {
+    match curve {
+        Some(offset) => (p0 + p2) * 0.5 + offset,
+        None => edge_control_point(p0, p2, idx),
     }
 }
 
+/// Point halfway along the `idx`-th curve between `p0` and `p2`.
+pub fn edge_midpoint(p0: Vec2, p2: Vec2, idx: usize) -> Vec2 {
+    bezier_point(p0, edge_control_point(p0, p2, idx), p2, 0.5)
+}
+
+/// Hand-set bend that puts the curve's midpoint at `point`: the midpoint is
+/// halfway between the chord midpoint and the control point.
+pub fn curve_offset_through(p0: Vec2, p2: Vec2, point: Vec2) -> Vec2 {
+    (point - (p0 + p2) * 0.5) * 2.0
+}
+
 /// Label offset above the curve (world units). Same for hit-testing.
 const LABEL_OFFSET_ABOVE: f32 = 18.0;
 /// Half-extents of the label hit box (world units).
 pub const LABEL_HIT_HALF: Vec2 = Vec2::new(50.0, 12.0);
 
+/// Label spots tried in turn: curve parameter and multiple of `LABEL_OFFSET_ABOVE`.
+const LABEL_CANDIDATES: [(f32, f32); 6] =
+    [(0.5, 1.0), (0.35, 1.0), (0.65, 1.0), (0.5, 2.0), (0.5, 3.0), (0.5, 4.0)];
+/// Node boxes grow by this much on each side when checking label overlap.
+const LABEL_CLEARANCE: f32 = 4.0;
+/// How far from the curve midpoint to look for nodes a label could overlap.
+const LABEL_SEARCH_RADIUS: f32 = 300.0;
+
+/// True if a label box at `pos` overlaps any of `obstacles` (centre, half extents).
+fn label_hits(pos: Vec2, obstacles: &[(Vec2, Vec2)]) -> bool {
+    obstacles.iter().any(|(centre, half)| {
+        let d = (pos - *centre).abs();
+        d.x < half.x + LABEL_HIT_HALF.x + LABEL_CLEARANCE
+            && d.y < half.y + LABEL_HIT_HALF.y + LABEL_CLEARANCE
+    })
+}
+
 /// Compute label world position for an edge (above curve midpoint). Used by sync and hit-test.
-pub fn edge_label_world_pos(src: &Transform, tgt: &Transform, idx: usize) -> (Vec2, f32) {
+/// When the label would cover one of `obstacles` it slides along the curve
+/// (t = 0.35, then 0.65) or moves further off it until clear, keeping the
+/// midpoint when nothing clears. The angle follows the curve at the chosen spot.
+/// Self-loops (source and target at the same spot) put the label above the
+/// loop, which sits on the target's top edge (`tgt_half` is its half size).
+/// A hand-set `curve` moves the label with the bend, on its outer side.
+pub fn edge_label_world_pos(
+    src: &Transform,
+    tgt: &Transform,
+    tgt_half: Vec2,
+    idx: usize,
+    curve: Option<Vec2>,
+    obstacles: &[(Vec2, Vec2)],
+) -> (Vec2, f32) {
     let p0 = src.translation.truncate();
     let p2 = tgt.translation.truncate();
-    let mid = (p0 + p2) * 0.5;
-    let dir = (p2 - p0).normalize_or_zero();
-    let dist = p0.distance(p2);
-    let curve_mag = (dist * 0.35).clamp(35.0, 180.0);
-    let perp = Vec2::new(-dir.y, dir.x);
-    let sign = if idx % 2 == 0 { 1.0 } else { -1.0 };
-    let p1 = mid + perp * curve_mag * sign;
-    let mid_bezier = bezier_point(p0, p1, p2, 0.5);
-    let tangent = bezier_tangent(p0, p1, p2, 0.5);
+    if p0 == p2 {
+        let (c, r) = self_loop_circle(p0, tgt_half.y, idx);
+        return (c + Vec2::new(0.0, r + LABEL_OFFSET_ABOVE * 0.5), 0.0);
+    }
+    let perp = (p2 - p0).normalize_or_zero().perp();
+    let sign = match curve {
+        Some(offset) if offset.dot(perp) < 0.0 => -1.0,
+        Some(_) => 1.0,
+        None if idx % 2 == 0 => 1.0,
+        None => -1.0,
+    };
+    let p1 = curve_control_point(p0, p2, idx, curve);
+    let place = |(t, k): (f32, f32)| {
+        bezier_point(p0, p1, p2, t) + perp * sign * LABEL_OFFSET_ABOVE * k
+    };
+    let (t, k) = LABEL_CANDIDATES
+        .into_iter()
+        .find(|c| !label_hits(place(*c), obstacles))
+        .unwrap_or(LABEL_CANDIDATES[0]);
+    let tangent = bezier_tangent(p0, p1, p2, t);
     let mut angle = tangent.y.atan2(tangent.x);
     if tangent.x < 0.0 {
         angle += PI;
     }
-    let label_pos = mid_bezier + perp * sign * LABEL_OFFSET_ABOVE;
-    (label_pos, angle)
+    (place((t, k)), angle)
+}
+
+/// Label endpoints and the nodes labels keep off.
+type LabelNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Transform, Option<&'static NodeSize>, Has<CollapseHidden>),
+    Without<EdgeLabel>,
+>;
+
+/// Places edge labels clear of nearby nodes; see `edge_label_world_pos`.
+#[derive(SystemParam)]
+pub struct EdgeLabelPlacer<'w, 's> {
+    spatial: Res<'w, SpatialIndex>,
+    nodes: LabelNodeQuery<'w, 's>,
+    curves: Query<'w, 's, &'static CurveOffset>,
+}
+
+impl EdgeLabelPlacer<'_, '_> {
+    /// Hand-set bend of `entity`, if it has one (`CurveOffset`).
+    pub fn curve(&self, entity: Entity) -> Option<Vec2> {
+        self.curves.get(entity).ok().map(|c| c.0)
+    }
+
+    /// Point halfway along the `idx`-th edge's curve, following its
+    /// `CurveOffset`. `None` if an endpoint is gone.
+    pub fn curve_midpoint(&self, entity: Entity, edge: &Edge, idx: usize) -> Option<Vec2> {
+        let (src, _, _) = self.nodes.get(edge.source).ok()?;
+        let (tgt, _, _) = self.nodes.get(edge.target).ok()?;
+        let (p0, p2) = (src.translation.truncate(), tgt.translation.truncate());
+        let p1 = curve_control_point(p0, p2, idx, self.curve(entity));
+        Some(bezier_point(p0, p1, p2, 0.5))
+    }
+
+    /// Label position and angle of the `idx`-th edge between its endpoints,
+    /// avoiding the two visible nodes nearest the curve midpoint. `None` if
+    /// an endpoint is gone.
+    pub fn place(&self, entity: Entity, edge: &Edge, idx: usize) -> Option<(Vec2, f32)> {
+        let (src, _, _) = self.nodes.get(edge.source).ok()?;
+        let (tgt, tgt_size, _) = self.nodes.get(edge.target).ok()?;
+        let tgt_half = tgt_size.copied().unwrap_or_default().current * 0.5;
+        let mid = self.curve_midpoint(entity, edge, idx)?;
+        let (min, max) = (mid - LABEL_SEARCH_RADIUS, mid + LABEL_SEARCH_RADIUS);
+        let mut near: Vec<(Vec2, Vec2)> = self
+            .spatial
+            .entities_in_bounds(min.x, max.x, min.y, max.y)
+            .into_iter()
+            .filter(|e| *e != edge.source && *e != edge.target)
+            .filter_map(|e| self.nodes.get(e).ok())
+            .filter(|(_, _, hidden)| !hidden)
+            .map(|(t, size, _)| {
+                let half = size.copied().unwrap_or_default().current * 0.5;
+                (t.translation.truncate(), half)
+            })
+            .collect();
+        near.sort_by(|(a, _), (b, _)| a.distance_squared(mid).total_cmp(&b.distance_squared(mid)));
+        near.truncate(2);
+        Some(edge_label_world_pos(src, tgt, tgt_half, idx, self.curve(entity), &near))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::helpers::NODE_SIZE;
+
+    /// Half extents of a default-sized canvas node.
+    const NODE_HALF: Vec2 = Vec2::new(NODE_SIZE.x * 0.5, NODE_SIZE.y * 0.5);
+
+    #[test]
+    fn edge_at_pos_picks_the_nearest_curve() {
+        let mut world = World::new();
+        let (near, far) = (world.spawn_empty().id(), world.spawn_empty().id());
+        let shape = |entity, y: f32| EdgeShape {
+            entity,
+            points: vec![Vec2::new(0.0, y), Vec2::new(50.0, y + 20.0), Vec2::new(100.0, y)],
+            wings: (Vec2::ZERO, Vec2::ZERO),
+            spread: Vec2::Y,
+            color: Color::WHITE,
+            stroke: EdgeStroke::default(),
+            bundled: false,
+            bezier: None,
+        };
+        let shapes = [shape(near, 0.0), shape(far, 8.0)];
+        assert_eq!(edge_at_pos(&shapes, Vec2::new(25.0, 12.0)), Some(near));
+        assert_eq!(edge_at_pos(&shapes, Vec2::new(75.0, 22.0)), Some(far));
+        assert_eq!(edge_at_pos(&shapes, Vec2::new(50.0, -30.0)), None);
+    }
+
+    #[test]
+    fn direction_dot_runs_from_source_to_arrow_tip() {
+        let (p0, p2) = (Vec2::ZERO, Vec2::new(200.0, 0.0));
+        let p1 = edge_control_point(p0, p2, 0);
+        let bezier = ([p0, p1, p2], 0.9);
+        assert_eq!(direction_dot_pos(bezier, 0.0), p0);
+        let early = direction_dot_pos(bezier, DIRECTION_DOT_PERIOD * 0.2);
+        let late = direction_dot_pos(bezier, DIRECTION_DOT_PERIOD * 0.8);
+        assert!(early.x < late.x && late.x < p2.x);
+        // Each period starts over at the source.
+        let again = direction_dot_pos(bezier, DIRECTION_DOT_PERIOD * 1.2);
+        assert!(again.distance(early) < 0.01);
+    }
+
+    #[test]
+    fn shape_of_matches_the_full_pass() {
+        use bevy::ecs::system::RunSystemOnce;
+        let mut world = World::new();
+        world.init_resource::<SpatialIndex>();
+        world.init_resource::<GlyphConfig>();
+        world.init_resource::<Theme>();
+        world.init_resource::<HoveredEdge>();
+        let a = world.spawn(Transform::from_xyz(0.0, 0.0, 0.0)).id();
+        let b = world.spawn(Transform::from_xyz(300.0, 0.0, 0.0)).id();
+        let mut edge = |source, target| {
+            world
+                .spawn(Edge {
+                    source,
+                    target,
+                    label: None,
+                })
+                .id()
+        };
+        // The second a→b edge fans out to the other side of the first.
+        let (_, second, _) = (edge(a, b), edge(a, b), edge(b, a));
+
+        let (all, one) = world
+            .run_system_once(move |sources: EdgeSources| {
+                (sources.shapes(), sources.shape_of(second))
+            })
+            .unwrap();
+        assert_eq!(one.as_ref(), all.iter().find(|s| s.entity == second));
+        assert!(one.is_some());
+    }
 
     #[test]
     fn edge_label_world_pos_symmetric() {
         let src = Transform::from_xyz(0.0, 0.0, 0.0);
         let tgt = Transform::from_xyz(200.0, 0.0, 0.0);
-        let (pos0, _) = edge_label_world_pos(&src, &tgt, 0);
-        let (pos1, _) = edge_label_world_pos(&src, &tgt, 1);
+        let (pos0, _) = edge_label_world_pos(&src, &tgt, NODE_HALF, 0, None, &[]);
+        let (pos1, _) = edge_label_world_pos(&src, &tgt, NODE_HALF, 1, None, &[]);
         assert!((pos0.x - 100.0).abs() < 1.0, "label x near midpoint");
         assert!((pos1.x - 100.0).abs() < 1.0, "label x near midpoint");
         assert!(
@@ -125,7 +746,7 @@ mod tests {
     fn edge_label_world_pos_above_curve() {
         let src = Transform::from_xyz(0.0, 0.0, 0.0);
         let tgt = Transform::from_xyz(100.0, 100.0, 0.0);
-        let (pos, angle) = edge_label_world_pos(&src, &tgt, 0);
+        let (pos, angle) = edge_label_world_pos(&src, &tgt, NODE_HALF, 0, None, &[]);
         let mid = Vec2::new(50.0, 50.0);
         let dist = pos.distance(mid);
         assert!(dist > 10.0, "label offset from midpoint");
@@ -134,6 +755,113 @@ mod tests {
             "angle in valid range"
         );
     }
+
+    #[test]
+    fn edge_label_moves_off_a_node_on_its_spot() {
+        let src = Transform::from_xyz(0.0, 0.0, 0.0);
+        let tgt = Transform::from_xyz(1000.0, 0.0, 0.0);
+        let (free, free_angle) = edge_label_world_pos(&src, &tgt, NODE_HALF, 0, None, &[]);
+        let blocker = [(free, NODE_HALF)];
+        let (pos, angle) = edge_label_world_pos(&src, &tgt, NODE_HALF, 0, None, &blocker);
+        assert!(!label_hits(pos, &blocker), "label clears the node");
+        assert!(pos.x < free.x, "slides back along the curve to t = 0.35");
+        assert!(angle > free_angle, "rotation follows the rising part of the curve");
+
+        let wide = [(free, Vec2::new(400.0, 20.0))];
+        let (pushed, _) = edge_label_world_pos(&src, &tgt, NODE_HALF, 0, None, &wide);
+        assert!(!label_hits(pushed, &wide));
+        assert!(pushed.y > free.y, "moves further off the curve when sliding fails");
+    }
+
+    #[test]
+    fn arrow_tip_lies_on_target_boundary() {
+        let p0 = Vec2::new(0.0, 0.0);
+        let p2 = Vec2::new(600.0, 0.0);
+        let p1 = Vec2::new(300.0, 180.0);
+        let t = arrow_tip_t(p0, p1, p2, NODE_HALF);
+        assert!(t < 1.0, "tip should stop before the target centre");
+        let d = (bezier_point(p0, p1, p2, t) - p2).abs();
+        assert!(d.x > NODE_HALF.x || d.y > NODE_HALF.y, "tip outside target box");
+        assert!(d.x < NODE_HALF.x + 15.0 && d.y < NODE_HALF.y + 15.0, "tip close to target box");
+    }
+
+    #[test]
+    fn arrow_tip_overlapping_nodes_falls_back_to_end() {
+        let p = Vec2::new(10.0, 10.0);
+        assert_eq!(arrow_tip_t(p, p, p, NODE_HALF), 1.0);
+    }
+
+    #[test]
+    fn self_loop_ends_on_node_top_edge_and_label_sits_above() {
+        let p = Vec2::new(50.0, -20.0);
+        let (c, r) = self_loop_circle(p, NODE_HALF.y, 0);
+        let top = p.y + NODE_HALF.y;
+        for a in [SELF_LOOP_START, SELF_LOOP_END] {
+            let end = c + Vec2::new(a.cos(), a.sin()) * r;
+            assert!((end.y - top).abs() < 1e-3, "loop ends on the top edge");
+        }
+        let t = Transform::from_translation(p.extend(0.0));
+        let (label, angle) = edge_label_world_pos(&t, &t, NODE_HALF, 0, None, &[]);
+        assert!(label.y > c.y + r, "label above the loop");
+        assert_eq!(angle, 0.0);
+        assert!(self_loop_circle(p, NODE_HALF.y, 1).1 > r, "second loop is larger");
+        // A node grown by a wrapped label lifts the loop onto its taller top edge.
+        let (c, r) = self_loop_circle(p, 90.0, 0);
+        let end = c + Vec2::new(SELF_LOOP_END.cos(), SELF_LOOP_END.sin()) * r;
+        assert!((end.y - (p.y + 90.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn hand_set_bend_passes_through_the_dragged_point() {
+        let (p0, p2) = (Vec2::ZERO, Vec2::new(400.0, 0.0));
+        let grab = Vec2::new(250.0, -90.0);
+        let curve = Some(curve_offset_through(p0, p2, grab));
+        let p1 = curve_control_point(p0, p2, 0, curve);
+        assert!(bezier_point(p0, p1, p2, 0.5).distance(grab) < 1e-3);
+
+        // The label follows the bend to its (lower) outer side.
+        let (src, tgt) = (Transform::IDENTITY, Transform::from_xyz(400.0, 0.0, 0.0));
+        let (label, _) = edge_label_world_pos(&src, &tgt, NODE_HALF, 0, curve, &[]);
+        assert!(label.y < grab.y, "label below the dragged midpoint");
+    }
+
+    #[test]
+    fn routing_bends_around_a_node_on_the_curve() {
+        let (p0, p2) = (Vec2::ZERO, Vec2::new(800.0, 0.0));
+        let p1 = edge_control_point(p0, p2, 0);
+        assert_eq!(route_control_point(p0, p1, p2, &[]), p1);
+
+        // A node sitting on the curve's peak is avoided.
+        let peak = bezier_point(p0, p1, p2, 0.5);
+        let obstacles = [(peak, NODE_HALF)];
+        let routed = route_control_point(p0, p1, p2, &obstacles);
+        assert_ne!(routed, p1);
+        assert!(!curve_hits((p0, routed, p2), &obstacles));
+
+        // A node off to the side leaves the curve alone.
+        let far = [(Vec2::new(400.0, 900.0), NODE_HALF)];
+        assert_eq!(route_control_point(p0, p1, p2, &far), p1);
+    }
+
+    #[test]
+    fn wide_strokes_are_centred_parallel_offsets() {
+        assert_eq!(stroke_offsets(1.0), vec![0.0]);
+        assert_eq!(stroke_offsets(0.2), vec![0.0]);
+        assert_eq!(
+            stroke_offsets(3.0),
+            vec![-STROKE_SPACING, 0.0, STROKE_SPACING]
+        );
+        assert_eq!(stroke_offsets(2.0), vec![-1.0, 1.0]);
+    }
+
+    #[test]
+    fn arrowhead_wings_trail_the_tip_symmetrically() {
+        let tip = Vec2::new(100.0, 0.0);
+        let (l, r) = arrowhead_wings(tip, Vec2::X, 14.0);
+        assert!(l.x < tip.x && r.x < tip.x, "wings sit behind the tip");
+        assert!((l.y + r.y).abs() < 1e-4, "wings mirror across the curve");
+        assert!((l.distance(tip) - 14.0).abs() < 1e-3);
+    }
 }
 
 /// Spawn/update Text2d labels for edges at midpoint, offset above the curve.
@@ -142,8 +870,9 @@ pub fn sync_edge_labels_system(
     mut commands: Commands,
     edge_query: Query<(Entity, &Edge)>,
     children_query: Query<&Children>,
-    node_transform_query: Query<&Transform, Without<EdgeLabel>>,
+    labels: EdgeLabelPlacer,
     mut label_query: Query<(&mut Transform, &mut Text2d), With<EdgeLabel>>,
+    caret: Res<InsertCaret>,
 ) {
     let mut groups: std::collections::HashMap<(Entity, Entity), Vec<Entity>> =
         std::collections::HashMap::new();
@@ -160,15 +889,15 @@ pub fn sync_edge_labels_system(
         }
     }
     for (edge_entity, edge) in &edge_query {
-        let Ok(src) = node_transform_query.get(edge.source) else {
+        let idx = idx_map.get(&edge_entity).copied().unwrap_or(0);
+        let Some((label_pos, angle)) = labels.place(edge_entity, edge, idx) else {
             continue;
         };
-        let Ok(tgt) = node_transform_query.get(edge.target) else {
-            continue;
+        let label = edge.label.as_deref().unwrap_or("");
+        let label_text = match caret.at(edge_entity) {
+            Some(pos) => std::borrow::Cow::Owned(with_caret(label, pos)),
+            None => std::borrow::Cow::Borrowed(label),
         };
-        let idx = idx_map.get(&edge_entity).copied().unwrap_or(0);
-        let (label_pos, angle) = edge_label_world_pos(src, tgt, idx);
-        let label_text = edge.label.as_deref().unwrap_or("");
 
         let label_entity = children_query
             .get(edge_entity)
@@ -179,14 +908,17 @@ pub fn sync_edge_labels_system(
             if let Ok((mut transform, mut text2d)) = label_query.get_mut(label_entity) {
                 transform.translation = label_pos.extend(1.0);
                 transform.rotation = Quat::from_rotation_z(angle);
-                text2d.clear();
-                text2d.push_str(label_text);
+                // Only touch the text when it changed; a write re-shapes it.
+                if text2d.as_str() != label_text.as_ref() {
+                    text2d.clear();
+                    text2d.push_str(&label_text);
+                }
             }
         } else {
             commands.entity(edge_entity).insert(Visibility::default());
             let label_entity = commands
                 .spawn((
-                    Text2d::new(label_text),
+                    Text2d::new(label_text.into_owned()),
                     TextFont {
                         font_size: 12.0,
                         ..default()
@@ -202,46 +934,56 @@ pub fn sync_edge_labels_system(
     }
 }
 
+/// Position, shape and (wrapped) size of a node that gets an outline.
+type OutlinedNode = (
+    &'static Transform,
+    Option<&'static NodeShape>,
+    Option<&'static NodeSize>,
+);
+
+/// Outline box around a node of `size`, `margin` larger on each axis.
+fn outline_size(size: Option<&NodeSize>, margin: f32) -> Vec2 {
+    size.copied().unwrap_or_default().current + Vec2::splat(margin)
+}
+
+/// Active theme and input mode: what the selection outline color depends on.
+#[derive(SystemParam)]
+pub struct SelectionPalette<'w> {
+    pub theme: Res<'w, Theme>,
+    pub state: Res<'w, State<InputMode>>,
+}
+
 /// Draw a mode-coloured rectangle outline around the selected node, or a highlight at the selected edge label.
 ///
-/// VimNormal → blue   VimInsert → green   VimEasymotion → orange
-/// Standard  → purple
+/// Dark theme: VimNormal → blue   VimInsert → green   VimEasymotion → orange
+/// Standard  → purple   VimVisual → pink
 pub fn draw_selection_system(
     mut gizmos: Gizmos,
-    selected_query: Query<&Transform, With<Selected>>,
-    traced_nodes: Query<&Transform, With<TracedPath>>,
+    selected_query: Query<OutlinedNode, With<Selected>>,
+    traced_nodes: Query<OutlinedNode, With<TracedPath>>,
     selected_edge: Res<crate::core::resources::SelectedEdge>,
     edge_query: Query<(Entity, &Edge)>,
-    node_transform_query: Query<&Transform, Without<EdgeLabel>>,
-    state: Res<State<InputMode>>,
+    labels: EdgeLabelPlacer,
+    palette: SelectionPalette,
 ) {
-    let color = match state.get() {
-        InputMode::VimInsert => Color::srgb(0.2, 0.85, 0.4),
-        InputMode::VimEasymotion => Color::srgb(1.0, 0.6, 0.1),
-        InputMode::Standard => Color::srgb(0.85, 0.4, 0.9),
-        InputMode::VimNormal | InputMode::VimCommand => Color::srgb(0.3, 0.6, 1.0),
-    };
+    let color = palette.theme.selection(palette.state.get());
 
     if let Some(edge_entity) = selected_edge.0 {
         if let Ok((_, edge)) = edge_query.get(edge_entity) {
-            if let (Ok(src), Ok(tgt)) = (
-                node_transform_query.get(edge.source),
-                node_transform_query.get(edge.target),
-            ) {
-                let mut groups: std::collections::HashMap<(Entity, Entity), Vec<Entity>> =
-                    std::collections::HashMap::new();
-                for (e, ed) in &edge_query {
-                    groups.entry((ed.source, ed.target)).or_default().push(e);
-                }
-                let mut idx_map: std::collections::HashMap<Entity, usize> =
-                    std::collections::HashMap::new();
-                for (_, entities) in &groups {
-                    for (i, e) in entities.iter().enumerate() {
-                        idx_map.insert(*e, i);
-                    }
+            let mut groups: std::collections::HashMap<(Entity, Entity), Vec<Entity>> =
+                std::collections::HashMap::new();
+            for (e, ed) in &edge_query {
+                groups.entry((ed.source, ed.target)).or_default().push(e);
+            }
+            let mut idx_map: std::collections::HashMap<Entity, usize> =
+                std::collections::HashMap::new();
+            for (_, entities) in &groups {
+                for (i, e) in entities.iter().enumerate() {
+                    idx_map.insert(*e, i);
                 }
-                let idx = idx_map.get(&edge_entity).copied().unwrap_or(0);
-                let (label_pos, _) = edge_label_world_pos(src, tgt, idx);
+            }
+            let idx = idx_map.get(&edge_entity).copied().unwrap_or(0);
+            if let Some((label_pos, _)) = labels.place(edge_entity, edge, idx) {
                 gizmos.rect_2d(
                     Isometry2d::from_translation(label_pos),
                     LABEL_HIT_HALF * 2.0,
@@ -253,35 +995,116 @@ pub fn draw_selection_system(
     }
 
     // Draw a red outline around all traced nodes.
-    for transform in &traced_nodes {
-        gizmos.rect_2d(
-            Isometry2d::from_translation(transform.translation.truncate()),
-            Vec2::new(170.0, 130.0),
-            TRACED_COLOR,
+    for (transform, shape, size) in &traced_nodes {
+        draw_node_outline(
+            &mut gizmos,
+            shape,
+            transform.translation.truncate(),
+            outline_size(size, 10.0),
+            palette.theme.traced,
         );
     }
 
-    let Ok(transform) = selected_query.single() else {
+    let Ok((transform, shape, size)) = selected_query.single() else {
         return;
     };
 
-    gizmos.rect_2d(
-        Isometry2d::from_translation(transform.translation.truncate()),
-        Vec2::new(170.0, 130.0),
+    draw_node_outline(
+        &mut gizmos,
+        shape,
+        transform.translation.truncate(),
+        outline_size(size, 10.0),
         color,
     );
 }
 
-/// When TextData.content changes, push the new string into the child Text2d.
+/// Visual-member outline (pink in the dark theme) around every VimVisual member. Drawn in every mode so the set
+/// stays visible while easymotion is picking the next member.
+pub fn draw_visual_selection_system(
+    mut gizmos: Gizmos,
+    visual: Res<crate::core::resources::VisualSelection>,
+    transform_query: Query<OutlinedNode, With<CanvasNode>>,
+    theme: Res<Theme>,
+) {
+    for entity in &visual.0 {
+        if let Ok((transform, shape, size)) = transform_query.get(*entity) {
+            draw_node_outline(
+                &mut gizmos,
+                shape,
+                transform.translation.truncate(),
+                outline_size(size, 20.0),
+                theme.select_visual,
+            );
+        }
+    }
+}
+
+/// Nodes whose label changed; `NodeSize` is absent on nodes not spawned via the helpers.
+type ChangedLabelQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static TextData,
+        &'static Children,
+        Option<&'static mut NodeSize>,
+    ),
+    Changed<TextData>,
+>;
+type NodeSpriteQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut Sprite, Has<NodeShadow>),
+    Or<(With<NodeMainSprite>, With<NodeShadow>)>,
+>;
+
+/// Offset of the shadow box beyond the node box.
+const SHADOW_GROW: f32 = 8.0;
+/// Distance of the file label's baseline above the node's bottom edge (and of
+/// the line-count label below its top edge).
+const FILE_LABEL_INSET: f32 = 12.0;
+
+/// When TextData.content changes, word-wrap it into the child Text2d and
+/// resize the node box (and its shadow) to fit the wrapped lines. The label
+/// being edited in VimInsert shows a caret bar.
 pub fn sync_text_system(
-    changed_nodes: Query<(&TextData, &Children), Changed<TextData>>,
-    mut text_query: Query<&mut Text2d, With<TextLabel>>,
+    mut changed_nodes: ChangedLabelQuery,
+    mut text_query: Query<(&mut Text2d, &mut TextBounds), With<TextLabel>>,
+    mut sprite_query: NodeSpriteQuery,
+    mut file_label_query: Query<
+        (&mut Transform, Has<LineCountLabel>),
+        Or<(With<FileLabel>, With<LineCountLabel>)>,
+    >,
+    config: Res<GlyphConfig>,
+    caret: Res<InsertCaret>,
 ) {
-    for (text_data, children) in &changed_nodes {
+    for (entity, text_data, children, mut node_size) in &mut changed_nodes {
+        let base = match node_size.as_mut() {
+            Some(node_size) => *node_size.base.get_or_insert(config.node_size()),
+            None => config.node_size(),
+        };
+        let content = match caret.at(entity) {
+            Some(pos) => with_caret(&text_data.content, pos),
+            None => text_data.content.clone(),
+        };
+        let lines = wrap_label(&content, label_chars_per_line(base.x));
+        let size = wrapped_node_size(lines.len(), base, config.line_height);
+        if let Some(mut node_size) = node_size {
+            if node_size.current != size {
+                node_size.current = size;
+            }
+        }
         for child in children {
-            if let Ok(mut text2d) = text_query.get_mut(*child) {
+            if let Ok((mut text2d, mut bounds)) = text_query.get_mut(*child) {
                 text2d.clear();
-                text2d.push_str(&text_data.content);
+                text2d.push_str(&lines.join("\n"));
+                *bounds = TextBounds::from(size - Vec2::splat(2.0 * LABEL_PADDING));
+            } else if let Ok((mut sprite, is_shadow)) = sprite_query.get_mut(*child) {
+                let grow = if is_shadow { SHADOW_GROW } else { 0.0 };
+                sprite.custom_size = Some(size + Vec2::splat(grow));
+            } else if let Ok((mut transform, at_top)) = file_label_query.get_mut(*child) {
+                let y = size.y * 0.5 - FILE_LABEL_INSET;
+                transform.translation.y = if at_top { y } else { -y };
             }
         }
     }
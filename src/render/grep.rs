@@ -0,0 +1,248 @@
+//! `:grep` — fade out every node whose text does not match a pattern.
+//!
+//! Purely visual: non-matching nodes and their incident edges are drawn at
+//! `DIM_ALPHA`, nothing is despawned. `:grep` without a pattern or `:nohl`
+//! clears the filter. The matches also feed `SearchMatches` for `n` / `N`.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::core::components::{CanvasNode, Edge, GrepDimmed, InactiveCanvas, TextData};
+use crate::core::resources::{GrepFilter, SearchMatches};
+
+/// Alpha multiplier for nodes and edges outside the filter.
+pub const DIM_ALPHA: f32 = 0.15;
+
+/// Compiled `:grep` pattern.
+pub enum GrepPattern {
+    /// Lowercased needle, matched case-insensitively.
+    Substring(String),
+    Regex(regex::Regex),
+}
+
+impl GrepPattern {
+    /// `/re/` compiles `re` as a regex; anything else is a case-insensitive substring.
+    pub fn parse(pattern: &str) -> Result<GrepPattern, String> {
+        match pattern
+            .strip_prefix('/')
+            .and_then(|p| p.strip_suffix('/'))
+            .filter(|p| !p.is_empty())
+        {
+            Some(re) => regex::Regex::new(re)
+                .map(GrepPattern::Regex)
+                .map_err(|e| format!("invalid regex: {}", e)),
+            None => Ok(GrepPattern::Substring(pattern.to_lowercase())),
+        }
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        match self {
+            GrepPattern::Substring(needle) => text.to_lowercase().contains(needle),
+            GrepPattern::Regex(re) => re.is_match(text),
+        }
+    }
+}
+
+/// Everything drawn as part of a node or edge: child sprites, text and shape meshes.
+#[derive(SystemParam)]
+pub struct Fader<'w, 's> {
+    children: Query<'w, 's, &'static Children>,
+    sprites: Query<'w, 's, &'static mut Sprite>,
+    texts: Query<'w, 's, &'static mut TextColor>,
+    meshes: Query<'w, 's, &'static MeshMaterial2d<ColorMaterial>>,
+    materials: ResMut<'w, Assets<ColorMaterial>>,
+}
+
+impl Fader<'_, '_> {
+    /// Multiply the alpha of every child of `entity` by `factor`.
+    fn scale_alpha(&mut self, entity: Entity, factor: f32) {
+        let Ok(children) = self.children.get(entity) else {
+            return;
+        };
+        for child in children.iter() {
+            if let Ok(mut sprite) = self.sprites.get_mut(child) {
+                let a = sprite.color.alpha();
+                sprite.color.set_alpha(a * factor);
+            }
+            if let Ok(mut color) = self.texts.get_mut(child) {
+                let a = color.0.alpha();
+                color.0.set_alpha(a * factor);
+            }
+            if let Ok(handle) = self.meshes.get(child) {
+                if let Some(material) = self.materials.get_mut(&handle.0) {
+                    let a = material.color.alpha();
+                    material.color.set_alpha(a * factor);
+                }
+            }
+        }
+    }
+
+    fn set_dimmed(&mut self, commands: &mut Commands, entity: Entity, dim: bool) {
+        if dim {
+            self.scale_alpha(entity, DIM_ALPHA);
+            commands.entity(entity).insert(GrepDimmed);
+        } else {
+            self.scale_alpha(entity, 1.0 / DIM_ALPHA);
+            commands.entity(entity).remove::<GrepDimmed>();
+        }
+    }
+}
+
+/// Keep `GrepDimmed` (and the faded alpha) in sync with `GrepFilter`. Nodes are
+/// rechecked when the filter or any node text changes; edges follow their
+/// endpoints every frame so new edges pick up the filter.
+pub fn apply_grep_filter_system(
+    mut commands: Commands,
+    filter: Res<GrepFilter>,
+    mut pattern: Local<Option<GrepPattern>>,
+    nodes: Query<(Entity, &TextData, Has<GrepDimmed>), With<CanvasNode>>,
+    changed_text: Query<(), (With<CanvasNode>, Changed<TextData>)>,
+    edges: Query<(Entity, &Edge, Has<GrepDimmed>)>,
+    mut fader: Fader,
+) {
+    if filter.is_changed() {
+        *pattern = filter.0.as_deref().and_then(|p| GrepPattern::parse(p).ok());
+    }
+    if filter.is_changed() || !changed_text.is_empty() {
+        for (entity, text, dimmed) in &nodes {
+            let dim = pattern.as_ref().is_some_and(|p| !p.is_match(&text.content));
+            if dim != dimmed {
+                fader.set_dimmed(&mut commands, entity, dim);
+            }
+        }
+    }
+    for (entity, edge, dimmed) in &edges {
+        let endpoint_dimmed = |e: Entity| nodes.get(e).is_ok_and(|(_, _, d)| d);
+        let dim = endpoint_dimmed(edge.source) || endpoint_dimmed(edge.target);
+        if dim != dimmed {
+            fader.set_dimmed(&mut commands, entity, dim);
+        }
+    }
+}
+
+type SearchNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static TextData, &'static Transform),
+    (With<CanvasNode>, Without<InactiveCanvas>),
+>;
+
+/// Refill `SearchMatches` from the `:grep` pattern when the filter or any node
+/// text changes, keeping the current match if it still matches. Clearing the
+/// filter clears the search too, so `n` creates nodes again.
+pub fn sync_grep_matches_system(
+    filter: Res<GrepFilter>,
+    nodes: SearchNodeQuery,
+    changed_text: Query<(), (With<CanvasNode>, Changed<TextData>)>,
+    mut search: ResMut<SearchMatches>,
+) {
+    if !filter.is_changed() && changed_text.is_empty() {
+        return;
+    }
+    let Some(query) = filter.0.as_deref() else {
+        if filter.is_changed() {
+            search.clear();
+        }
+        return;
+    };
+    let Ok(pattern) = GrepPattern::parse(query) else {
+        return;
+    };
+    let matches = nodes
+        .iter()
+        .filter(|(_, text, _)| pattern.is_match(&text.content))
+        .map(|(e, _, t)| (e, t.translation.truncate()))
+        .collect();
+    let current = search.ordered.get(search.idx).copied();
+    *search = SearchMatches::new(query, matches);
+    if let Some(current) = current {
+        search.set_current(current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn substring_is_case_insensitive() {
+        let p = GrepPattern::parse("Parse").unwrap();
+        assert!(p.is_match("parse_args"));
+        assert!(p.is_match("reParser"));
+        assert!(!p.is_match("lexer"));
+    }
+
+    #[test]
+    fn slashes_enable_regex() {
+        let p = GrepPattern::parse("/^handle_.*s$/").unwrap();
+        assert!(p.is_match("handle_requests"));
+        assert!(!p.is_match("on_handle_requests"));
+        assert!(GrepPattern::parse("/(/").is_err());
+        // A lone slash is just a substring.
+        assert!(GrepPattern::parse("/").unwrap().is_match("a/b"));
+    }
+
+    #[test]
+    fn grep_fills_and_clears_search_matches() {
+        let mut world = World::new();
+        world.init_resource::<SearchMatches>();
+        world.insert_resource(GrepFilter(Some("parse".to_string())));
+        let mut node = |text: &str, x: f32, y: f32| {
+            let text = TextData { content: text.to_string() };
+            world.spawn((CanvasNode, text, Transform::from_xyz(x, y, 0.0))).id()
+        };
+        let low = node("parse_b", 0.0, -200.0);
+        node("render", 0.0, 0.0);
+        let high = node("parse_a", 0.0, 200.0);
+
+        world.run_system_once(sync_grep_matches_system).unwrap();
+        assert_eq!(world.resource::<SearchMatches>().ordered, vec![high, low]);
+
+        world.resource_mut::<GrepFilter>().0 = None;
+        world.run_system_once(sync_grep_matches_system).unwrap();
+        assert!(!world.resource::<SearchMatches>().is_active());
+    }
+
+    #[test]
+    fn non_matching_nodes_and_their_edges_are_dimmed() {
+        let mut world = World::new();
+        world.init_resource::<Assets<ColorMaterial>>();
+        world.insert_resource(GrepFilter(Some("parse".to_string())));
+        let node = |world: &mut World, text: &str| {
+            world
+                .spawn((CanvasNode, TextData { content: text.to_string() }))
+                .with_child(Sprite::from_color(Color::WHITE, Vec2::ONE))
+                .id()
+        };
+        let hit = node(&mut world, "parse_args");
+        let miss = node(&mut world, "render");
+        let edge = world
+            .spawn(Edge {
+                source: hit,
+                target: miss,
+                label: None,
+            })
+            .id();
+
+        // Nodes are marked on the first run, edges catch up on the next.
+        world.run_system_once(apply_grep_filter_system).unwrap();
+        world.run_system_once(apply_grep_filter_system).unwrap();
+        assert!(world.get::<GrepDimmed>(hit).is_none());
+        assert!(world.get::<GrepDimmed>(miss).is_some());
+        assert!(world.get::<GrepDimmed>(edge).is_some());
+        let sprite_alpha = |world: &World, e: Entity| {
+            let child = world.get::<Children>(e).unwrap()[0];
+            world.get::<Sprite>(child).unwrap().color.alpha()
+        };
+        assert!((sprite_alpha(&world, miss) - DIM_ALPHA).abs() < 1e-6);
+        assert_eq!(sprite_alpha(&world, hit), 1.0);
+
+        world.resource_mut::<GrepFilter>().0 = None;
+        world.run_system_once(apply_grep_filter_system).unwrap();
+        world.run_system_once(apply_grep_filter_system).unwrap();
+        assert!(world.get::<GrepDimmed>(miss).is_none());
+        assert!(world.get::<GrepDimmed>(edge).is_none());
+        assert!((sprite_alpha(&world, miss) - 1.0).abs() < 1e-5);
+    }
+}
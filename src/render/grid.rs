@@ -0,0 +1,139 @@
+//! Background grid: dots or lines at the snap grid size, drawn when
+//! `background_grid` is set (`:grid`) or, as lines, while `snap_grid` is set.
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::core::components::MainCamera;
+use crate::core::config::{GlyphConfig, GridStyle, Theme};
+use crate::input::camera::viewport_world_bounds;
+
+/// Grid lines are the theme's text color at this alpha: faint enough to sit
+/// behind nodes and edges without competing with them.
+const GRID_ALPHA: f32 = 0.04;
+/// Dots cover less ink than lines, so they are drawn a little stronger.
+const DOT_ALPHA: f32 = 0.12;
+/// Grid spacing in world units when no snap grid is set.
+pub const DEFAULT_SPACING: f32 = 40.0;
+/// Cells closer than this on screen (pixels) make the grid coarser.
+const MIN_CELL_PX: f32 = 6.0;
+/// Dots need more room than lines to read as a grid.
+const MIN_DOT_CELL_PX: f32 = 16.0;
+/// Most lines (both directions) or dots drawn in one frame.
+const MAX_LINES: usize = 400;
+const MAX_DOTS: usize = 2500;
+/// Half length of each stroke of a dot's cross, in pixels.
+const DOT_HALF_PX: f32 = 1.0;
+
+/// Spacing to draw `base`-unit cells with across a `view` (world size) at
+/// zoom `scale` (world units per pixel): doubled until cells are at least
+/// the style's minimum on screen apart and the draw count stays bounded, so
+/// zooming out thins the grid instead of flooding it.
+pub fn grid_spacing(style: GridStyle, base: f32, view: Vec2, scale: f32) -> f32 {
+    let (min_px, max_count) = match style {
+        GridStyle::Dots => (MIN_DOT_CELL_PX, MAX_DOTS),
+        GridStyle::Lines => (MIN_CELL_PX, MAX_LINES),
+    };
+    let count = |spacing: f32| {
+        let cells = view / spacing;
+        let (cols, rows) = (cells.x as usize + 1, cells.y as usize + 1);
+        match style {
+            GridStyle::Dots => cols.saturating_mul(rows),
+            GridStyle::Lines => cols + rows,
+        }
+    };
+    let mut spacing = base;
+    while spacing / scale < min_px || count(spacing) > max_count {
+        spacing *= 2.0;
+    }
+    spacing
+}
+
+/// Draw the background grid covering the visible viewport.
+pub fn draw_grid_system(
+    mut gizmos: Gizmos,
+    config: Res<GlyphConfig>,
+    theme: Res<Theme>,
+    camera_q: Query<(&Camera, &GlobalTransform, &Projection), With<MainCamera>>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+) {
+    let snap = config.snap_grid();
+    let style = match (config.background_grid, snap) {
+        (Some(style), _) => style,
+        (None, Some(_)) => GridStyle::Lines,
+        (None, None) => return,
+    };
+    let Ok((camera, cam_transform, projection)) = camera_q.single() else {
+        return;
+    };
+    let Ok(window) = window_q.single() else {
+        return;
+    };
+    let scale = match projection {
+        Projection::Orthographic(ortho) => ortho.scale,
+        _ => 1.0,
+    };
+    let (min_x, max_x, min_y, max_y) = viewport_world_bounds(camera, cam_transform, window.size());
+    let view = Vec2::new(max_x - min_x, max_y - min_y);
+    if !view.is_finite() {
+        return;
+    }
+    let base = snap.unwrap_or(DEFAULT_SPACING);
+    let grid = grid_spacing(style, base, view, scale.max(f32::EPSILON));
+
+    let first = Vec2::new((min_x / grid).floor() * grid, (min_y / grid).floor() * grid);
+    match style {
+        GridStyle::Lines => {
+            let color = theme.text.with_alpha(GRID_ALPHA);
+            let mut x = first.x;
+            while x <= max_x {
+                gizmos.line_2d(Vec2::new(x, min_y), Vec2::new(x, max_y), color);
+                x += grid;
+            }
+            let mut y = first.y;
+            while y <= max_y {
+                gizmos.line_2d(Vec2::new(min_x, y), Vec2::new(max_x, y), color);
+                y += grid;
+            }
+        }
+        GridStyle::Dots => {
+            // A tiny cross reads as a dot and is two gizmo lines, not a circle.
+            let color = theme.text.with_alpha(DOT_ALPHA);
+            let half = DOT_HALF_PX * scale;
+            let mut x = first.x;
+            while x <= max_x {
+                let mut y = first.y;
+                while y <= max_y {
+                    let p = Vec2::new(x, y);
+                    gizmos.line_2d(p - Vec2::X * half, p + Vec2::X * half, color);
+                    gizmos.line_2d(p - Vec2::Y * half, p + Vec2::Y * half, color);
+                    y += grid;
+                }
+                x += grid;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_coarsens_when_zoomed_out() {
+        let view = Vec2::new(1600.0, 900.0);
+        assert_eq!(grid_spacing(GridStyle::Lines, 20.0, view, 1.0), 20.0);
+        // At 4× zoom-out 20-unit cells are 5 px apart: one doubling.
+        let far = view * 4.0;
+        assert_eq!(grid_spacing(GridStyle::Lines, 20.0, far, 4.0), 40.0);
+
+        // Dots stay under their budget however far out the camera is.
+        for scale in [1.0, 3.0, 10.0, 50.0] {
+            let spacing = grid_spacing(GridStyle::Dots, 20.0, view * scale, scale);
+            let cells = view * scale / spacing;
+            let dots = (cells.x as usize + 1) * (cells.y as usize + 1);
+            assert!(dots <= MAX_DOTS, "{} dots at scale {}", dots, scale);
+            assert!(spacing / scale >= MIN_DOT_CELL_PX);
+        }
+    }
+}
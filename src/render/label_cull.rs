@@ -0,0 +1,102 @@
+//! Viewport culling of text: node labels, file labels and edge labels whose
+//! anchor is well outside the camera view are set `Visibility::Hidden`, so big
+//! crawls only lay out and draw the text on screen. Node sprites stay visible.
+//!
+//! Two margins give hysteresis: a label comes back once its anchor is within
+//! `SHOW_MARGIN` of the view and is hidden again only past `HIDE_MARGIN`, so
+//! labels at the edge don't flicker while panning.
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::core::components::{
+    CanvasNode, EdgeLabel, FileLabel, LineCountLabel, MainCamera, TextLabel,
+};
+use crate::input::camera::viewport_world_bounds;
+
+/// Labels within this many world units of the view are shown.
+const SHOW_MARGIN: f32 = 200.0;
+/// Labels further than this many world units outside the view are hidden.
+const HIDE_MARGIN: f32 = 400.0;
+
+/// World-space view rectangle `(min_x, max_x, min_y, max_y)`.
+pub type ViewBounds = (f32, f32, f32, f32);
+
+/// Whether a label anchored at `pos` should be visible, given whether it is now.
+pub fn label_visible(pos: Vec2, bounds: ViewBounds, visible: bool) -> bool {
+    let (min_x, max_x, min_y, max_y) = bounds;
+    let margin = if visible { HIDE_MARGIN } else { SHOW_MARGIN };
+    pos.x >= min_x - margin
+        && pos.x <= max_x + margin
+        && pos.y >= min_y - margin
+        && pos.y <= max_y + margin
+}
+
+type CulledLabelQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static ChildOf,
+        &'static Transform,
+        &'static mut Visibility,
+        Has<EdgeLabel>,
+    ),
+    Or<(With<TextLabel>, With<FileLabel>, With<LineCountLabel>, With<EdgeLabel>)>,
+>;
+
+/// Hide labels far outside the view and show them again as they come near.
+/// Node and file labels are anchored at their node; edge labels sit at their
+/// own (world-space) position.
+pub fn cull_offscreen_labels_system(
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    nodes: Query<&Transform, (With<CanvasNode>, Without<EdgeLabel>)>,
+    mut labels: CulledLabelQuery,
+) {
+    let (Ok((camera, cam_transform)), Ok(window)) = (camera_q.single(), window_q.single()) else {
+        return;
+    };
+    let bounds = viewport_world_bounds(camera, cam_transform, window.size());
+    if !bounds.0.is_finite() || !bounds.2.is_finite() {
+        return;
+    }
+    for (child_of, transform, mut visibility, is_edge_label) in &mut labels {
+        let anchor = if is_edge_label {
+            transform.translation
+        } else {
+            let Ok(node) = nodes.get(child_of.parent()) else {
+                continue;
+            };
+            node.translation
+        };
+        let visible = *visibility != Visibility::Hidden;
+        let wanted = label_visible(anchor.truncate(), bounds, visible);
+        if wanted != visible {
+            *visibility = if wanted {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_hide_and_show_with_hysteresis() {
+        let view = (0.0, 1000.0, 0.0, 800.0);
+        assert!(label_visible(Vec2::new(500.0, 400.0), view, false));
+        // Just off screen: shown labels stay, hidden ones stay hidden until closer.
+        let edge = Vec2::new(1300.0, 400.0);
+        assert!(label_visible(edge, view, true));
+        assert!(!label_visible(edge, view, false));
+        assert!(label_visible(Vec2::new(1150.0, 400.0), view, false));
+        // Far off screen: hidden either way.
+        let far = Vec2::new(500.0, -900.0);
+        assert!(!label_visible(far, view, true));
+        assert!(!label_visible(far, view, false));
+    }
+}
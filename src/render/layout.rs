@@ -1,8 +1,26 @@
-//! Force-directed layout — nodes repel each other, edges attract.
+//! Force-directed layout — nodes repel each other, edges attract — plus the
+//! one-shot `:layout circle` / `:layout grid` arrangements.
 
 use bevy::prelude::*;
 
-use crate::core::components::{CanvasNode, Dragging, Edge};
+use crate::core::components::{
+    CanvasNode, Dragging, Edge, FlowBypass, InactiveCanvas, MainCamera, Pinned,
+};
+use crate::core::config::GlyphConfig;
+use crate::core::helpers::NODE_SIZE;
+use crate::core::history::{Action, UndoHistory};
+use crate::core::resources::StatusMessage;
+
+/// Nodes of the active tab that layouts move.
+pub type LayoutNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static mut Transform),
+    (With<CanvasNode>, Without<InactiveCanvas>),
+>;
+
+/// Nodes force layout leaves in place: being dragged, or pinned.
+type HeldNodeQuery<'w, 's> = Query<'w, 's, Entity, Or<(With<Dragging>, With<Pinned>)>>;
 
 /// When active, force-directed layout runs each frame to untangle nodes.
 #[derive(Resource, Default)]
@@ -30,17 +48,19 @@ const DAMPING: f32 = 0.9;
 const DT: f32 = 1.0 / 50.0;
 
 /// Apply force-directed layout: repulsion between nodes, attraction along edges.
+/// Dragged and `Pinned` nodes stay put but still act on the others.
+/// `:flow off` bypass edges are display-only and do not pull.
 pub fn force_directed_layout_system(
     mut layout_active: ResMut<ForceLayoutActive>,
-    mut node_query: Query<(Entity, &mut Transform), With<CanvasNode>>,
-    edge_query: Query<&Edge>,
-    dragging_query: Query<Entity, With<Dragging>>,
+    mut node_query: LayoutNodeQuery,
+    edge_query: Query<&Edge, Without<FlowBypass>>,
+    held_query: HeldNodeQuery,
 ) {
     if !layout_active.active {
         return;
     }
 
-    let dragging: std::collections::HashSet<Entity> = dragging_query.iter().collect();
+    let held: std::collections::HashSet<Entity> = held_query.iter().collect();
 
     let positions: Vec<(Entity, Vec2)> = node_query
         .iter()
@@ -104,8 +124,12 @@ pub fn force_directed_layout_system(
         }
     }
 
-    // Convergence check
-    let total_force: f32 = forces.values().map(|f| f.length()).sum();
+    // Convergence check (held nodes never move, so their forces never settle)
+    let total_force: f32 = forces
+        .iter()
+        .filter(|(e, _)| !held.contains(*e))
+        .map(|(_, f)| f.length())
+        .sum();
     layout_active.iterations += 1;
 
     if total_force < CONVERGENCE_THRESHOLD || layout_active.iterations >= MAX_ITERATIONS {
@@ -120,7 +144,7 @@ pub fn force_directed_layout_system(
 
     // Apply forces
     for (entity, mut transform) in &mut node_query {
-        if dragging.contains(&entity) {
+        if held.contains(&entity) {
             continue;
         }
         let Some(&force) = forces.get(&entity) else {
@@ -131,3 +155,194 @@ pub fn force_directed_layout_system(
         transform.translation.y += delta.y;
     }
 }
+
+/// One-shot arrangement requested by `:layout <kind>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutKind {
+    Circle,
+    Grid,
+}
+
+/// Message sent by `:layout circle` / `:layout grid`.
+#[derive(Message)]
+pub struct LayoutRequest(pub LayoutKind);
+
+/// Arc length between neighbouring nodes on the circle.
+const CIRCLE_ARC_SPACING: f32 = NODE_SIZE.x * 1.5;
+
+/// `n` points evenly spaced on a circle around `center`. The radius grows with
+/// `n` so neighbours stay `CIRCLE_ARC_SPACING` apart; the first point is at the top.
+pub fn circle_positions(n: usize, center: Vec2) -> Vec<Vec2> {
+    if n <= 1 {
+        return vec![center; n];
+    }
+    let radius = (n as f32 * CIRCLE_ARC_SPACING / std::f32::consts::TAU).max(CIRCLE_ARC_SPACING);
+    (0..n)
+        .map(|i| {
+            let angle = std::f32::consts::FRAC_PI_2 - i as f32 * std::f32::consts::TAU / n as f32;
+            center + Vec2::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+/// `n` points in a roughly square row-major grid centered on `center`,
+/// filled left-to-right then top-to-bottom.
+pub fn grid_positions(n: usize, center: Vec2, spacing: f32) -> Vec<Vec2> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let cols = (n as f32).sqrt().ceil() as usize;
+    let rows = n.div_ceil(cols);
+    let origin = center
+        + Vec2::new(
+            -((cols - 1) as f32) * spacing / 2.0,
+            ((rows - 1) as f32) * spacing / 2.0,
+        );
+    (0..n)
+        .map(|i| origin + Vec2::new((i % cols) as f32 * spacing, -((i / cols) as f32) * spacing))
+        .collect()
+}
+
+/// Place every node evenly on a circle around `center`, keeping their current
+/// angular order. Returns `(entity, from, to)` for each node.
+pub fn apply_circular_layout(
+    node_query: &mut LayoutNodeQuery,
+    center: Vec2,
+) -> Vec<(Entity, Vec2, Vec2)> {
+    let mut nodes: Vec<(Entity, Vec2)> = node_query
+        .iter()
+        .map(|(e, t)| (e, t.translation.truncate()))
+        .collect();
+    // Clockwise from the top, matching `circle_positions`.
+    let clock = |p: Vec2| {
+        let d = p - center;
+        (std::f32::consts::FRAC_PI_2 - d.y.atan2(d.x)).rem_euclid(std::f32::consts::TAU)
+    };
+    nodes.sort_by(|(_, a), (_, b)| clock(*a).total_cmp(&clock(*b)));
+    let targets = circle_positions(nodes.len(), center);
+    move_nodes(node_query, &nodes, &targets)
+}
+
+/// Place every node in a row-major grid around `center`, keeping their
+/// current reading order (top-to-bottom, left-to-right).
+pub fn apply_grid_layout(
+    node_query: &mut LayoutNodeQuery,
+    center: Vec2,
+    spacing: f32,
+) -> Vec<(Entity, Vec2, Vec2)> {
+    let mut nodes: Vec<(Entity, Vec2)> = node_query
+        .iter()
+        .map(|(e, t)| (e, t.translation.truncate()))
+        .collect();
+    nodes.sort_by(|(_, a), (_, b)| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+    let targets = grid_positions(nodes.len(), center, spacing);
+    move_nodes(node_query, &nodes, &targets)
+}
+
+fn move_nodes(
+    node_query: &mut LayoutNodeQuery,
+    nodes: &[(Entity, Vec2)],
+    targets: &[Vec2],
+) -> Vec<(Entity, Vec2, Vec2)> {
+    let mut moves = Vec::with_capacity(nodes.len());
+    for (&(entity, from), &to) in nodes.iter().zip(targets) {
+        if let Ok((_, mut transform)) = node_query.get_mut(entity) {
+            transform.translation.x = to.x;
+            transform.translation.y = to.y;
+            moves.push((entity, from, to));
+        }
+    }
+    moves
+}
+
+/// Handles `LayoutRequest`: arrange around the viewport center and push one
+/// `Action::Batch` of `MoveNode`s so a single `u` restores the old layout.
+pub fn handle_layout_requests(
+    mut requests: MessageReader<LayoutRequest>,
+    mut node_query: LayoutNodeQuery,
+    camera_query: Query<&Transform, (With<MainCamera>, Without<CanvasNode>)>,
+    mut layout_active: ResMut<ForceLayoutActive>,
+    mut history: ResMut<UndoHistory>,
+    mut status: ResMut<StatusMessage>,
+    config: Res<GlyphConfig>,
+) {
+    let Some(LayoutRequest(kind)) = requests.read().last() else {
+        return;
+    };
+    let center = camera_query
+        .single()
+        .map(|t| t.translation.truncate())
+        .unwrap_or(Vec2::ZERO);
+    // A running force layout would immediately pull nodes out of place.
+    layout_active.active = false;
+    let moves = match kind {
+        LayoutKind::Circle => apply_circular_layout(&mut node_query, center),
+        LayoutKind::Grid => apply_grid_layout(&mut node_query, center, config.flow_node_spacing),
+    };
+    if moves.is_empty() {
+        return;
+    }
+    info!("[LAYOUT] {:?} layout applied to {} nodes", kind, moves.len());
+    status.set(format!("Layout: {} nodes", moves.len()));
+    history.push(Action::Batch(
+        moves
+            .into_iter()
+            .map(|(entity, from, to)| Action::MoveNode { entity, from, to })
+            .collect(),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circle_positions_are_evenly_spaced_around_center() {
+        let center = Vec2::new(100.0, -50.0);
+        let pts = circle_positions(8, center);
+        assert_eq!(pts.len(), 8);
+        let r = pts[0].distance(center);
+        assert!(pts.iter().all(|p| (p.distance(center) - r).abs() < 1e-3));
+        // First point sits straight above the center.
+        assert!((pts[0].x - center.x).abs() < 1e-3 && pts[0].y > center.y);
+        let gap = pts[0].distance(pts[1]);
+        assert!(pts.windows(2).all(|w| (w[0].distance(w[1]) - gap).abs() < 1e-2));
+    }
+
+    #[test]
+    fn circle_radius_grows_with_node_count() {
+        let small = circle_positions(10, Vec2::ZERO)[0].length();
+        let large = circle_positions(40, Vec2::ZERO)[0].length();
+        assert!(large > small * 3.0);
+        assert_eq!(circle_positions(1, Vec2::ONE), vec![Vec2::ONE]);
+    }
+
+    #[test]
+    fn grid_positions_are_row_major_and_centered() {
+        let pts = grid_positions(5, Vec2::ZERO, 100.0);
+        // 3 columns, 2 rows.
+        assert_eq!(pts[0], Vec2::new(-100.0, 50.0));
+        assert_eq!(pts[1], Vec2::new(0.0, 50.0));
+        assert_eq!(pts[3], Vec2::new(-100.0, -50.0));
+        assert!(grid_positions(0, Vec2::ZERO, 100.0).is_empty());
+    }
+
+    #[test]
+    fn force_layout_moves_around_pinned_nodes() {
+        use bevy::ecs::system::RunSystemOnce;
+        let mut world = World::new();
+        world.insert_resource(ForceLayoutActive {
+            active: true,
+            iterations: 0,
+        });
+        let pinned = world
+            .spawn((CanvasNode, Pinned, Transform::from_xyz(0.0, 0.0, 0.0)))
+            .id();
+        let free = world.spawn((CanvasNode, Transform::from_xyz(50.0, 0.0, 0.0))).id();
+        world.run_system_once(force_directed_layout_system).unwrap();
+
+        let x = |e: Entity| world.get::<Transform>(e).unwrap().translation.x;
+        assert_eq!(x(pinned), 0.0);
+        assert!(x(free) > 50.0, "the free node is pushed away from the pinned one");
+    }
+}
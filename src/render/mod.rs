@@ -1,5 +1,19 @@
-//! Rendering: edge/node drawing, force-directed layout, cluster blobs.
+//! Rendering: edge/node drawing and strokes, force-directed layout, cluster blobs, folding, pinning, themes, fills, label culling.
 
+pub mod align;
+pub mod bundle;
+pub mod call_path;
 pub mod cluster;
+pub mod collapse;
+pub mod edge_curve;
+pub mod edge_mesh;
+pub mod edge_stroke;
 pub mod edges;
+pub mod grep;
+pub mod grid;
+pub mod label_cull;
 pub mod layout;
+pub mod pin;
+pub mod recolor;
+pub mod shapes;
+pub mod theme;
@@ -0,0 +1,93 @@
+//! Pinning (`zp` / `:pin`): a pinned node keeps its place while force layout
+//! arranges the rest around it. A small pin is drawn at its top-right corner.
+
+use bevy::prelude::*;
+
+use crate::core::components::{
+    CanvasNode, CollapseHidden, InactiveCanvas, NodeSize, Pinned, Selected,
+};
+use crate::core::config::Theme;
+use crate::core::helpers::NODE_SIZE;
+use crate::core::resources::StatusMessage;
+
+/// Radius of the pin head.
+const PIN_HEAD_RADIUS: f32 = 5.0;
+/// Length of the pin needle below the head.
+const PIN_NEEDLE: f32 = 9.0;
+
+/// Message sent by `zp` and `:pin`: toggle the pin on the selected node.
+#[derive(Message)]
+pub struct PinRequest;
+
+type SelectedPinQuery<'w, 's> =
+    Query<'w, 's, (Entity, Has<Pinned>), (With<Selected>, With<CanvasNode>)>;
+
+/// Handles `PinRequest`: pins the selected node, or unpins it if pinned.
+pub fn handle_pin_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<PinRequest>,
+    selected: SelectedPinQuery,
+    mut status: ResMut<StatusMessage>,
+) {
+    for PinRequest in requests.read() {
+        let Ok((entity, pinned)) = selected.single() else {
+            status.set("error: :pin needs a selected node");
+            continue;
+        };
+        if pinned {
+            commands.entity(entity).remove::<Pinned>();
+            status.set("Unpinned");
+        } else {
+            commands.entity(entity).insert(Pinned);
+            status.set("Pinned");
+        }
+        info!("[PIN] {:?} pinned={}", entity, !pinned);
+    }
+}
+
+type PinnedNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Transform, Option<&'static NodeSize>),
+    (
+        With<Pinned>,
+        Without<InactiveCanvas>,
+        Without<CollapseHidden>,
+    ),
+>;
+
+/// Draw a pin (head and needle) on the top-right corner of pinned nodes.
+pub fn draw_pin_markers_system(mut gizmos: Gizmos, nodes: PinnedNodeQuery, theme: Res<Theme>) {
+    for (transform, size) in &nodes {
+        let half = size.map_or(NODE_SIZE, |s| s.current) * 0.5;
+        let head = transform.translation.truncate() + half - Vec2::splat(PIN_HEAD_RADIUS * 2.0);
+        let tip = head - Vec2::new(PIN_NEEDLE, PIN_NEEDLE) * std::f32::consts::FRAC_1_SQRT_2;
+        gizmos.line_2d(head, tip, theme.text);
+        gizmos.circle_2d(head, PIN_HEAD_RADIUS, theme.traced);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn pin_request_toggles_the_selected_node() {
+        let mut world = World::new();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<Messages<PinRequest>>();
+        let selected = world.spawn((CanvasNode, Selected)).id();
+        let other = world.spawn(CanvasNode).id();
+
+        world.write_message(PinRequest);
+        world.run_system_once(handle_pin_requests).unwrap();
+        assert!(world.get::<Pinned>(selected).is_some());
+        assert!(world.get::<Pinned>(other).is_none());
+
+        world.write_message(PinRequest);
+        world.run_system_once(handle_pin_requests).unwrap();
+        assert!(world.get::<Pinned>(selected).is_none());
+        assert_eq!(world.resource::<StatusMessage>().text, "Unpinned");
+    }
+}
@@ -0,0 +1,140 @@
+//! Per-node fills: `:color <#rrggbb|name>` and the Command Palette swatches
+//! recolor the selected node through an undoable `Action::RecolorNode`.
+//! `sync_node_fill_system` repaints the node whenever its `NodeColor` changes.
+
+use bevy::prelude::*;
+
+use crate::core::components::{CanvasNode, NodeColor, NodeMainSprite, Selected};
+use crate::core::history::{Action, UndoHistory};
+use crate::core::resources::StatusMessage;
+use crate::render::shapes::NodeShapeMesh;
+
+/// Names accepted by `:color`, in swatch order.
+pub const NAMED_COLORS: [(&str, &str); 9] = [
+    ("red", "#c0392b"),
+    ("orange", "#d35400"),
+    ("yellow", "#b7950b"),
+    ("green", "#27ae60"),
+    ("teal", "#16a085"),
+    ("blue", "#2e86c1"),
+    ("purple", "#8e44ad"),
+    ("pink", "#c2185b"),
+    ("gray", "#5d6d7e"),
+];
+
+type SelectedFillQuery<'w, 's> =
+    Query<'w, 's, (Entity, &'static mut NodeColor), (With<Selected>, With<CanvasNode>)>;
+
+/// Message sent by `:color` and the palette swatches.
+#[derive(Message)]
+pub struct RecolorRequest(pub Color);
+
+/// Parse `#rrggbb`, `rrggbb` (or the short `#rgb` forms) or one of `NAMED_COLORS`.
+pub fn parse_color(value: &str) -> Result<Color, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("missing color".to_string());
+    }
+    let hex = NAMED_COLORS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(value))
+        .map_or(value, |(_, hex)| hex);
+    bevy::color::Srgba::hex(hex)
+        .map(Color::Srgba)
+        .map_err(|_| format!("invalid color: {}", value))
+}
+
+/// Handles `RecolorRequest`: sets the fill of the selected node and records it.
+pub fn handle_recolor_requests(
+    mut requests: MessageReader<RecolorRequest>,
+    mut selected: SelectedFillQuery,
+    mut history: ResMut<UndoHistory>,
+    mut status: ResMut<StatusMessage>,
+) {
+    for RecolorRequest(new) in requests.read() {
+        let Ok((entity, mut color)) = selected.single_mut() else {
+            status.set("error: :color needs a selected node");
+            warn!("[COLOR] no node selected");
+            continue;
+        };
+        if color.0 == *new {
+            continue;
+        }
+        history.push(Action::RecolorNode {
+            entity,
+            old: color.0,
+            new: *new,
+        });
+        color.0 = *new;
+        status.set(format!("Color: {}", new.to_srgba().to_hex()));
+        info!("[COLOR] {:?} → {}", entity, new.to_srgba().to_hex());
+    }
+}
+
+/// Repaint the rect sprite or shape mesh of every node whose `NodeColor`
+/// changed. The current alpha is kept so `:grep` dimming survives.
+pub fn sync_node_fill_system(
+    changed: Query<(&NodeColor, &Children), Changed<NodeColor>>,
+    mut sprites: Query<&mut Sprite, With<NodeMainSprite>>,
+    shape_meshes: Query<&MeshMaterial2d<ColorMaterial>, With<NodeShapeMesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (color, children) in &changed {
+        for child in children {
+            if let Ok(mut sprite) = sprites.get_mut(*child) {
+                let alpha = sprite.color.alpha();
+                sprite.color = color.0.with_alpha(alpha);
+            } else if let Ok(handle) = shape_meshes.get(*child) {
+                if let Some(material) = materials.get_mut(&handle.0) {
+                    let alpha = material.color.alpha();
+                    material.color = color.0.with_alpha(alpha);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn parses_hex_with_or_without_hash_and_names() {
+        let rgb = Color::srgb_u8(0x11, 0x22, 0x33);
+        assert_eq!(parse_color("#112233"), Ok(rgb));
+        assert_eq!(parse_color("112233"), Ok(rgb));
+        assert_eq!(parse_color("Red"), parse_color("#c0392b"));
+        assert!(parse_color("#12345").is_err());
+        assert!(parse_color("chartreuse-ish").is_err());
+        assert!(parse_color("").is_err());
+    }
+
+    #[test]
+    fn recolor_is_undoable_and_repaints_the_sprite() {
+        let mut world = World::new();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<Messages<RecolorRequest>>();
+        world.init_resource::<Assets<ColorMaterial>>();
+        let old = Color::srgb(0.2, 0.2, 0.2);
+        let node = world
+            .spawn((CanvasNode, Selected, NodeColor(old)))
+            .with_child((Sprite::from_color(old.with_alpha(0.5), Vec2::ONE), NodeMainSprite))
+            .id();
+        let new = parse_color("green").unwrap();
+        world.write_message(RecolorRequest(new));
+        world.run_system_once(handle_recolor_requests).unwrap();
+        world.run_system_once(sync_node_fill_system).unwrap();
+
+        assert_eq!(world.get::<NodeColor>(node).unwrap().0, new);
+        let sprite_color = world.query::<&Sprite>().single(&world).unwrap().color;
+        assert_eq!(sprite_color, new.with_alpha(0.5));
+        let history = world.resource::<UndoHistory>();
+        assert!(matches!(
+            history.undo_stack.last(),
+            Some(Action::RecolorNode { entity, old: o, new: n })
+                if *entity == node && *o == old && *n == new
+        ));
+    }
+}
@@ -0,0 +1,239 @@
+//! Node shapes: `:shape rect|ellipse|diamond` on the selected node, undoable
+//! through `Action::ReshapeNode`.
+//!
+//! Rect nodes keep their sprites. Other shapes hide the rect sprites (fill and
+//! drop shadow) and get a filled mesh child instead; selection outlines follow
+//! the shape through [`draw_node_outline`]. The drop shadow is only drawn
+//! under rect nodes, and only while `node_shadows` is set.
+
+use bevy::prelude::*;
+
+use crate::core::components::{
+    CanvasNode, DecisionNode, NodeColor, NodeShadow, NodeShape, NodeSize, Selected,
+};
+use crate::core::config::GlyphConfig;
+use crate::core::history::{Action, UndoHistory};
+use crate::core::resources::StatusMessage;
+use crate::render::cluster::ClusterBlob;
+
+/// Message sent by `:shape <name>`.
+#[derive(Message)]
+pub struct ShapeRequest(pub NodeShape);
+
+/// Marker on the mesh child that fills a non-rect node.
+#[derive(Component)]
+pub struct NodeShapeMesh;
+
+/// Handles `ShapeRequest`: sets the shape of the selected node and records it.
+pub fn handle_shape_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<ShapeRequest>,
+    selected: Query<(Entity, Option<&NodeShape>), (With<Selected>, With<CanvasNode>)>,
+    mut history: ResMut<UndoHistory>,
+    mut status: ResMut<StatusMessage>,
+) {
+    // Shapes inserted this frame are not visible through `selected` yet.
+    let mut written: Option<NodeShape> = None;
+    for ShapeRequest(shape) in requests.read() {
+        let Ok((entity, current)) = selected.single() else {
+            status.set("error: :shape needs a selected node");
+            warn!("[SHAPE] no node selected");
+            continue;
+        };
+        let old = written.or(current.copied()).unwrap_or_default();
+        if old == *shape {
+            continue;
+        }
+        history.push(Action::ReshapeNode {
+            entity,
+            old,
+            new: *shape,
+        });
+        commands.entity(entity).insert(*shape);
+        written = Some(*shape);
+        status.set(format!("Shape: {}", shape.name()));
+        info!("[SHAPE] {:?} → {}", entity, shape.name());
+    }
+}
+
+/// The rect sprites a non-rect shape hides. Shadows are handled by
+/// [`sync_node_shadows_system`].
+type RectSpriteQuery<'w, 's> = Query<
+    'w,
+    's,
+    &'static mut Visibility,
+    (With<Sprite>, Without<ClusterBlob>, Without<NodeShadow>),
+>;
+
+/// Shaped nodes whose shape was set or whose box was resized.
+type ChangedShapeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static NodeShape,
+        &'static NodeColor,
+        Option<&'static NodeSize>,
+        &'static Children,
+    ),
+    Or<(Changed<NodeShape>, Changed<NodeSize>)>,
+>;
+
+/// Swap a node's rect sprites for a filled mesh (or back) whenever its
+/// `NodeShape` is inserted or changed. The mesh is rebuilt to the node's
+/// `NodeSize` when a wrapped label resizes the box.
+pub fn sync_node_shapes_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    changed: ChangedShapeQuery,
+    mut sprites: RectSpriteQuery,
+    shape_meshes: Query<(), With<NodeShapeMesh>>,
+) {
+    for (entity, shape, color, size, children) in &changed {
+        for child in children {
+            if shape_meshes.contains(*child) {
+                commands.entity(*child).despawn();
+            } else if let Ok(mut visibility) = sprites.get_mut(*child) {
+                *visibility = if shape.is_rect() {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+        }
+        let size = size.copied().unwrap_or_default().current;
+        let mesh = match shape {
+            NodeShape::Rect => continue,
+            NodeShape::Ellipse => meshes.add(Ellipse::new(size.x * 0.5, size.y * 0.5)),
+            NodeShape::Diamond => meshes.add(Rhombus::new(size.x, size.y)),
+        };
+        let material = materials.add(color.0);
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                Mesh2d(mesh),
+                MeshMaterial2d(material),
+                Transform::default(),
+                NodeShapeMesh,
+            ));
+        });
+    }
+}
+
+type ShadowQuery<'w, 's> =
+    Query<'w, 's, (&'static ChildOf, &'static mut Visibility), With<NodeShadow>>;
+
+/// Show each node's drop shadow only while `node_shadows` is on and the node
+/// is drawn as a rect; decision diamonds and other shapes never get one.
+pub fn sync_node_shadows_system(
+    config: Res<GlyphConfig>,
+    nodes: Query<(Option<&NodeShape>, Has<DecisionNode>), With<CanvasNode>>,
+    mut shadows: ShadowQuery,
+) {
+    for (child_of, mut visibility) in &mut shadows {
+        let Ok((shape, is_decision)) = nodes.get(child_of.parent()) else {
+            continue;
+        };
+        let rect = shape.is_none_or(NodeShape::is_rect);
+        let wanted = if config.node_shadows && rect && !is_decision {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+}
+
+/// Outline of a node `size` across, centred on `center`. `None` draws a rect.
+pub fn draw_node_outline(
+    gizmos: &mut Gizmos,
+    shape: Option<&NodeShape>,
+    center: Vec2,
+    size: Vec2,
+    color: Color,
+) {
+    let half = size * 0.5;
+    match shape.copied().unwrap_or_default() {
+        NodeShape::Rect => gizmos.rect_2d(Isometry2d::from_translation(center), size, color),
+        NodeShape::Ellipse => {
+            gizmos.ellipse_2d(Isometry2d::from_translation(center), half, color);
+        }
+        NodeShape::Diamond => gizmos.linestrip_2d(
+            [
+                center + Vec2::new(0.0, half.y),
+                center + Vec2::new(half.x, 0.0),
+                center - Vec2::new(0.0, half.y),
+                center - Vec2::new(half.x, 0.0),
+                center + Vec2::new(0.0, half.y),
+            ],
+            color,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn shape_request_applies_to_selected_node_only() {
+        let mut world = World::new();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<Messages<ShapeRequest>>();
+        let selected = world.spawn((CanvasNode, Selected)).id();
+        let other = world.spawn(CanvasNode).id();
+
+        world.write_message(ShapeRequest(NodeShape::Diamond));
+        world.run_system_once(handle_shape_requests).unwrap();
+
+        assert_eq!(world.get::<NodeShape>(selected), Some(&NodeShape::Diamond));
+        assert!(world.get::<NodeShape>(other).is_none());
+        assert!(matches!(
+            world.resource::<UndoHistory>().undo_stack.last(),
+            Some(Action::ReshapeNode { entity, old: NodeShape::Rect, new: NodeShape::Diamond })
+                if *entity == selected
+        ));
+
+        // Asking for the shape it already has records nothing.
+        world.write_message(ShapeRequest(NodeShape::Diamond));
+        world.run_system_once(handle_shape_requests).unwrap();
+        assert_eq!(world.resource::<UndoHistory>().undo_stack.len(), 1);
+    }
+
+    #[test]
+    fn shadows_follow_the_flag_and_skip_non_rect_nodes() {
+        let mut world = World::new();
+        world.insert_resource(GlyphConfig::default());
+        let shadow_of = |world: &mut World, node: Entity| {
+            world
+                .spawn((NodeShadow, Visibility::Inherited, ChildOf(node)))
+                .id()
+        };
+        let rect = world.spawn(CanvasNode).id();
+        let diamond = world.spawn((CanvasNode, NodeShape::Diamond)).id();
+        let decision = world.spawn((CanvasNode, DecisionNode)).id();
+        let shadows = [rect, diamond, decision].map(|node| shadow_of(&mut world, node));
+        let visible = |world: &World| {
+            shadows.map(|s| world.get::<Visibility>(s) == Some(&Visibility::Inherited))
+        };
+
+        world.run_system_once(sync_node_shadows_system).unwrap();
+        assert_eq!(visible(&world), [true, false, false]);
+
+        world.resource_mut::<GlyphConfig>().node_shadows = false;
+        world.run_system_once(sync_node_shadows_system).unwrap();
+        assert_eq!(visible(&world), [false, false, false]);
+    }
+
+    #[test]
+    fn shape_names_roundtrip() {
+        for shape in [NodeShape::Rect, NodeShape::Ellipse, NodeShape::Diamond] {
+            assert_eq!(NodeShape::from_name(shape.name()), Some(shape));
+        }
+        assert_eq!(NodeShape::from_name("hexagon"), None);
+    }
+}
@@ -0,0 +1,118 @@
+//! Runtime theme switching (`:theme dark|light`): rebuild the `Theme` resource
+//! from `GlyphConfig`, swap the clear color and move nodes that still wear the
+//! old theme's default fills over to the new ones.
+//!
+//! Edges and outlines are gizmos that read `Theme` every frame, so they follow
+//! without any work here.
+
+use bevy::prelude::*;
+
+use crate::core::components::{CanvasNode, NodeColor, NodeMainSprite, SourceLocation, TextLabel};
+use crate::core::config::{GlyphConfig, Theme};
+use crate::render::cluster::module_fill;
+use crate::render::shapes::NodeShapeMesh;
+
+type ThemedNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut NodeColor, &'static Children, Option<&'static SourceLocation>),
+    With<CanvasNode>,
+>;
+
+/// Colors closer than this per channel count as the same theme default
+/// (fills go through hex when saved and loaded).
+const FILL_EPSILON: f32 = 0.005;
+
+fn same_color(a: Color, b: Color) -> bool {
+    let (a, b) = (a.to_srgba(), b.to_srgba());
+    (a.red - b.red).abs() < FILL_EPSILON
+        && (a.green - b.green).abs() < FILL_EPSILON
+        && (a.blue - b.blue).abs() < FILL_EPSILON
+}
+
+/// The fill `color` should take under `new` if it is one of `old`'s default
+/// node fills; `None` for colors the user picked.
+pub fn themed_fill(old: &Theme, new: &Theme, color: Color) -> Option<Color> {
+    [
+        (old.node, new.node),
+        (old.crawl_node, new.crawl_node),
+        (old.decision, new.decision),
+    ]
+    .into_iter()
+    .find(|(from, _)| same_color(*from, color))
+    .map(|(_, to)| to)
+}
+
+/// When `GlyphConfig::theme` changes, replace the `Theme` resource, the clear
+/// color and the fills of default-colored nodes (rect sprite or shape mesh).
+pub fn apply_theme_system(
+    config: Res<GlyphConfig>,
+    mut theme: ResMut<Theme>,
+    mut clear: ResMut<ClearColor>,
+    mut nodes: ThemedNodeQuery,
+    mut sprites: Query<&mut Sprite, With<NodeMainSprite>>,
+    mut shape_meshes: Query<&mut MeshMaterial2d<ColorMaterial>, With<NodeShapeMesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if !config.is_changed() || config.theme == theme.name {
+        return;
+    }
+    let old = std::mem::replace(&mut *theme, config.theme());
+    clear.0 = theme.background;
+    let mut recolored = 0;
+    for (mut color, children, loc) in &mut nodes {
+        // Module-tinted crawl fills (`color_by_module`) follow the crawl fill.
+        let fill = themed_fill(&old, &theme, color.0).or_else(|| {
+            loc.filter(|loc| same_color(module_fill(&loc.file, old.crawl_node), color.0))
+                .map(|loc| module_fill(&loc.file, theme.crawl_node))
+        });
+        let Some(fill) = fill else {
+            continue;
+        };
+        color.0 = fill;
+        for child in children {
+            if let Ok(mut sprite) = sprites.get_mut(*child) {
+                sprite.color = fill;
+            } else if let Ok(mut material) = shape_meshes.get_mut(*child) {
+                material.0 = materials.add(fill);
+            }
+        }
+        recolored += 1;
+    }
+    info!("[THEME] {} ({} nodes recolored)", theme.name.name(), recolored);
+}
+
+/// Node labels use the theme's text color: new labels as they appear, all of
+/// them when the theme changes.
+pub fn sync_label_colors_system(
+    theme: Res<Theme>,
+    mut labels: Query<(&mut TextColor, Ref<TextLabel>)>,
+) {
+    for (mut color, label) in &mut labels {
+        if (theme.is_changed() || label.is_added()) && color.0 != theme.text {
+            color.0 = theme.text;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_fills_follow_the_theme() {
+        let (dark, light) = (Theme::dark(), Theme::light());
+        assert_eq!(themed_fill(&dark, &light, dark.node), Some(light.node));
+        assert_eq!(themed_fill(&dark, &light, dark.decision), Some(light.decision));
+        assert_eq!(themed_fill(&light, &dark, light.crawl_node), Some(dark.crawl_node));
+        // A hex round trip still matches.
+        let hex = Color::Srgba(bevy::color::Srgba::hex(dark.node.to_srgba().to_hex()).unwrap());
+        assert_eq!(themed_fill(&dark, &light, hex), Some(light.node));
+    }
+
+    #[test]
+    fn user_fills_are_kept() {
+        let custom = Color::srgb(0.9, 0.1, 0.1);
+        assert_eq!(themed_fill(&Theme::dark(), &Theme::light(), custom), None);
+    }
+}
@@ -1,14 +1,22 @@
 //! Fuzzy finder overlay — press `/` in VimNormal to search across all node text.
 //!
-//! Uses `fuzzy-matcher` (skim algorithm) for scoring. Results are ranked and
-//! selecting one jumps the camera to that node's position.
+//! Uses `fuzzy-matcher` (skim algorithm) for scoring. Crawled nodes are listed
+//! as `basename:line function` so a filename fragment finds them too. Results
+//! are ranked and selecting one jumps the camera to that node's position.
 
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 
-use crate::core::components::{CanvasNode, MainCamera, Selected, TextData};
+use crate::core::components::{
+    CanvasNode, InactiveCanvas, MainCamera, Selected, SourceLocation, TextData,
+};
+use crate::core::config::GlyphConfig;
+use crate::core::jumplist::{JumpEntry, JumpList};
+use crate::core::resources::SearchMatches;
+use crate::input::camera::request_camera_move;
 
 /// Resource controlling the fuzzy finder overlay state.
 #[derive(Resource, Default)]
@@ -37,14 +45,83 @@ pub fn fuzzy_toggle_system(
     }
 }
 
+/// One searchable entry in the finder.
+#[derive(Debug, Clone)]
+pub struct FuzzyCandidate {
+    pub entity: Entity,
+    pub pos: Vec2,
+    pub label: String,
+    /// Source line for crawled nodes; used to break score ties.
+    pub line: Option<u32>,
+}
+
+/// `basename:line text` for crawled nodes, plain node text otherwise.
+pub fn candidate_label(text: &str, source: Option<&SourceLocation>) -> String {
+    match source {
+        Some(src) => {
+            let base = std::path::Path::new(&src.file)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| src.file.clone());
+            format!("{}:{} {}", base, src.line, text)
+        }
+        None => text.to_string(),
+    }
+}
+
+/// Score candidates against `query`, best first. Equal scores keep crawled
+/// nodes in line order. An empty query keeps everything with score 0.
+pub fn rank_candidates(
+    candidates: Vec<FuzzyCandidate>,
+    query: &str,
+) -> Vec<(FuzzyCandidate, i64)> {
+    let matcher = SkimMatcherV2::default();
+    let mut scored: Vec<(FuzzyCandidate, i64)> = candidates
+        .into_iter()
+        .filter_map(|c| {
+            if query.is_empty() {
+                return Some((c, 0));
+            }
+            let score = matcher.fuzzy_match(&c.label, query)?;
+            Some((c, score))
+        })
+        .collect();
+    scored.sort_by(|a, b| {
+        b.1.cmp(&a.1).then_with(|| match (a.0.line, b.0.line) {
+            (Some(la), Some(lb)) => la.cmp(&lb),
+            _ => std::cmp::Ordering::Equal,
+        })
+    });
+    scored
+}
+
+/// Where a jump out of the finder is remembered: the jump list (`Ctrl+O`)
+/// and the matches `n` / `N` step through afterwards. `config` decides
+/// whether the camera eases there.
+#[derive(SystemParam)]
+pub struct FuzzyJumpRecord<'w> {
+    jumps: ResMut<'w, JumpList>,
+    search: ResMut<'w, SearchMatches>,
+    config: Res<'w, GlyphConfig>,
+}
+
+/// Nodes of the active tab the finder searches.
+type FuzzyNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Transform, &'static TextData, Option<&'static SourceLocation>),
+    (With<CanvasNode>, Without<InactiveCanvas>),
+>;
+
 /// The egui overlay that renders the fuzzy finder window.
 pub fn fuzzy_finder_ui_system(
     mut contexts: EguiContexts,
     mut finder: ResMut<FuzzyFinderState>,
     mut commands: Commands,
-    node_query: Query<(Entity, &Transform, &TextData), With<CanvasNode>>,
+    node_query: FuzzyNodeQuery,
     selected_q: Query<Entity, With<Selected>>,
     mut camera_q: Query<&mut Transform, (With<MainCamera>, Without<CanvasNode>)>,
+    mut record: FuzzyJumpRecord,
 ) {
     if !finder.is_open {
         return;
@@ -55,23 +132,22 @@ pub fn fuzzy_finder_ui_system(
     };
 
     // Collect and score all nodes
-    let matcher = SkimMatcherV2::default();
-    let mut scored: Vec<(Entity, Vec2, String, i64)> = Vec::new();
-
-    for (entity, transform, text_data) in &node_query {
-        let text = &text_data.content;
-        if finder.query.is_empty() {
-            scored.push((entity, transform.translation.truncate(), text.clone(), 0));
-        } else if let Some(score) = matcher.fuzzy_match(text, &finder.query) {
-            scored.push((
-                entity,
-                transform.translation.truncate(),
-                text.clone(),
-                score,
-            ));
-        }
-    }
-    scored.sort_by(|a, b| b.3.cmp(&a.3));
+    let candidates = node_query
+        .iter()
+        .map(|(entity, transform, text_data, source)| FuzzyCandidate {
+            entity,
+            pos: transform.translation.truncate(),
+            label: candidate_label(&text_data.content, source),
+            line: source.map(|s| s.line),
+        })
+        .collect();
+    let mut scored = rank_candidates(candidates, &finder.query);
+    // Every match, for `n` / `N` once a result is picked.
+    let matches: Vec<(Entity, Vec2)> = if finder.query.is_empty() {
+        Vec::new()
+    } else {
+        scored.iter().map(|(c, _)| (c.entity, c.pos)).collect()
+    };
     // Cap at 15 results for performance
     scored.truncate(15);
 
@@ -104,11 +180,11 @@ pub fn fuzzy_finder_ui_system(
 
             ui.add_space(4.0);
 
-            for (entity, pos, text, score) in &scored {
+            for (candidate, score) in &scored {
                 let label = if finder.query.is_empty() {
-                    text.clone()
+                    candidate.label.clone()
                 } else {
-                    format!("{} ({})", text, score)
+                    format!("{} ({})", candidate.label, score)
                 };
                 let display = if label.len() > 60 {
                     format!("{}…", &label[..59])
@@ -118,7 +194,7 @@ pub fn fuzzy_finder_ui_system(
                 if ui.selectable_label(false, &display).clicked()
                     || (enter && jump_target.is_none())
                 {
-                    jump_target = Some((*entity, *pos));
+                    jump_target = Some((candidate.entity, candidate.pos));
                     should_close = true;
                 }
             }
@@ -137,6 +213,17 @@ pub fn fuzzy_finder_ui_system(
     }
 
     if let Some((target_entity, target_pos)) = jump_target {
+        if !matches.is_empty() {
+            *record.search = SearchMatches::new(finder.query.clone(), matches);
+            record.search.set_current(target_entity);
+        }
+        record.jumps.record(JumpEntry {
+            camera: camera_q
+                .single()
+                .map(|t| t.translation.truncate())
+                .unwrap_or_default(),
+            selected: selected_q.iter().next(),
+        });
         // Deselect previous
         for prev in &selected_q {
             commands.entity(prev).remove::<Selected>();
@@ -145,9 +232,48 @@ pub fn fuzzy_finder_ui_system(
         commands.entity(target_entity).insert(Selected);
         // Jump camera
         if let Ok(mut cam_transform) = camera_q.single_mut() {
-            cam_transform.translation.x = target_pos.x;
-            cam_transform.translation.y = target_pos.y;
+            request_camera_move(&mut commands, &record.config, &mut cam_transform, target_pos);
         }
         info!("[FUZZY] Jumped to {:?} at {:?}", target_entity, target_pos);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::world::World;
+
+    fn src(file: &str, line: u32) -> SourceLocation {
+        SourceLocation { file: file.to_string(), line }
+    }
+
+    #[test]
+    fn crawled_nodes_are_labelled_with_basename_and_line() {
+        let s = src("/repo/src/crawler/mod.rs", 42);
+        assert_eq!(candidate_label("crawl", Some(&s)), "mod.rs:42 crawl");
+        assert_eq!(candidate_label("idea", None), "idea");
+    }
+
+    #[test]
+    fn filename_fragment_matches_and_ties_sort_by_line() {
+        let mut world = World::new();
+        let mut cand = |text: &str, source: Option<SourceLocation>| FuzzyCandidate {
+            entity: world.spawn_empty().id(),
+            pos: Vec2::ZERO,
+            label: candidate_label(text, source.as_ref()),
+            line: source.map(|s| s.line),
+        };
+        let candidates = vec![
+            cand("run", Some(src("/a/parser.rs", 30))),
+            cand("new", Some(src("/a/parser.rs", 10))),
+            cand("helper", Some(src("/a/other.rs", 5))),
+            cand("parser notes", None),
+        ];
+        let ranked = rank_candidates(candidates, "parser.rs");
+        let labels: Vec<&str> = ranked.iter().map(|(c, _)| c.label.as_str()).collect();
+        assert_eq!(labels, ["parser.rs:10 new", "parser.rs:30 run"]);
+
+        let all = rank_candidates(ranked.into_iter().map(|(c, _)| c).collect(), "");
+        assert_eq!(all.len(), 2);
+    }
+}
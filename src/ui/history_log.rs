@@ -0,0 +1,127 @@
+//! `:history`: the undo stack as readable lines, in a scrollable window or,
+//! with `:history > file.log`, written to disk. Read-only over `UndoHistory`.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::core::history::UndoHistory;
+use crate::core::resources::StatusMessage;
+
+/// Message sent by `:history`; `path` is set by `:history > file`.
+#[derive(Message)]
+pub struct HistoryRequest {
+    pub path: Option<String>,
+}
+
+/// Whether the history window is shown.
+#[derive(Resource, Default)]
+pub struct HistoryLogState {
+    pub is_open: bool,
+}
+
+/// Handles `HistoryRequest`: write the log to the file or open the window.
+pub fn handle_history_requests(
+    mut requests: MessageReader<HistoryRequest>,
+    history: Res<UndoHistory>,
+    mut state: ResMut<HistoryLogState>,
+    mut status: ResMut<StatusMessage>,
+) {
+    for request in requests.read() {
+        let Some(path) = &request.path else {
+            state.is_open = true;
+            continue;
+        };
+        let lines = history.log_lines();
+        let mut text = lines.join("\n");
+        text.push('\n');
+        match std::fs::write(path, text) {
+            Ok(()) => {
+                status.set(format!("Wrote {} history entries to {}", lines.len(), path));
+                info!("[HISTORY] wrote {}", path);
+            }
+            Err(e) => {
+                status.set(format!("error: {}: {}", path, e));
+                warn!("[HISTORY] {}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// The history window: one line per undoable action, newest at the bottom.
+/// Esc or the close button hides it.
+pub fn history_log_ui_system(
+    mut contexts: EguiContexts,
+    mut state: ResMut<HistoryLogState>,
+    history: Res<UndoHistory>,
+) {
+    if !state.is_open {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    let mut open = true;
+    egui::Window::new("History")
+        .open(&mut open)
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+        .default_width(480.0)
+        .show(ctx, |ui| {
+            let lines = history.log_lines();
+            if lines.is_empty() {
+                ui.label(
+                    egui::RichText::new("Nothing to undo")
+                        .color(egui::Color32::GRAY)
+                        .italics(),
+                );
+            }
+            egui::ScrollArea::vertical()
+                .max_height(360.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for line in &lines {
+                        ui.label(egui::RichText::new(line).monospace());
+                    }
+                });
+        });
+    if !open || ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+        state.is_open = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::history::Action;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn history_redirect_writes_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.log");
+        let mut world = World::new();
+        world.init_resource::<HistoryLogState>();
+        world.init_resource::<StatusMessage>();
+        world.init_resource::<Messages<HistoryRequest>>();
+        let mut history = UndoHistory::default();
+        let entity = world.spawn_empty().id();
+        history.push(Action::MoveNode {
+            entity,
+            from: Vec2::ZERO,
+            to: Vec2::new(50.0, 0.0),
+        });
+        world.insert_resource(history);
+
+        world.write_message(HistoryRequest {
+            path: Some(path.to_string_lossy().into_owned()),
+        });
+        world.run_system_once(handle_history_requests).unwrap();
+        let log = std::fs::read_to_string(&path).unwrap();
+        assert!(log.contains("MoveNode") && log.contains("(0,0)->(50,0)"));
+        assert!(!world.resource::<HistoryLogState>().is_open);
+
+        world.write_message(HistoryRequest { path: None });
+        world.run_system_once(handle_history_requests).unwrap();
+        assert!(world.resource::<HistoryLogState>().is_open);
+    }
+}
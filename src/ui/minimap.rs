@@ -0,0 +1,171 @@
+//! Minimap overlay (`:minimap`): a scaled-down view of every node box plus
+//! the current viewport in the bottom-right corner. Clicking or dragging in
+//! it recentres the camera on that world position.
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::core::components::{CanvasNode, CollapseHidden, MainCamera, NodeColor, NodeSize};
+use crate::core::config::GlyphConfig;
+use crate::input::camera::{request_camera_move, viewport_world_bounds, CameraTween};
+
+/// Size of the map area inside the window (logical pixels).
+const MINIMAP_SIZE: egui::Vec2 = egui::vec2(200.0, 140.0);
+/// World-space padding around the content so boxes never touch the border.
+const MINIMAP_PADDING: f32 = 60.0;
+/// Node boxes never shrink below this on the map, so tiny canvases stay readable.
+const MIN_BOX_PX: f32 = 2.0;
+
+type MinimapNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Transform, &'static NodeColor, Option<&'static NodeSize>),
+    (With<CanvasNode>, Without<CollapseHidden>, Without<MainCamera>),
+>;
+
+type MinimapCameraQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Camera, &'static GlobalTransform, &'static mut Transform),
+    With<MainCamera>,
+>;
+
+/// Uniform scale mapping a world rect onto the map, centred, y pointing down.
+#[derive(Clone, Copy, Debug)]
+pub struct MinimapProjection {
+    world_center: Vec2,
+    map_center: Vec2,
+    scale: f32,
+}
+
+impl MinimapProjection {
+    /// Fit `world` (padded) inside a map of `map_size` whose top-left is `map_min`.
+    pub fn fit(world: Rect, map_min: Vec2, map_size: Vec2) -> Self {
+        let extent = world.size() + Vec2::splat(MINIMAP_PADDING * 2.0);
+        let scale = (map_size.x / extent.x.max(1.0)).min(map_size.y / extent.y.max(1.0));
+        Self {
+            world_center: world.center(),
+            map_center: map_min + map_size * 0.5,
+            scale,
+        }
+    }
+
+    pub fn to_map(&self, world: Vec2) -> Vec2 {
+        let d = (world - self.world_center) * self.scale;
+        self.map_center + Vec2::new(d.x, -d.y)
+    }
+
+    pub fn to_world(&self, map: Vec2) -> Vec2 {
+        let d = (map - self.map_center) / self.scale;
+        self.world_center + Vec2::new(d.x, -d.y)
+    }
+}
+
+fn to_pos(v: Vec2) -> egui::Pos2 {
+    egui::pos2(v.x, v.y)
+}
+
+/// Opaque egui color for a Bevy color (also used by the palette swatches).
+pub fn to_color32(color: Color) -> egui::Color32 {
+    let c = color.to_srgba();
+    let to_byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    egui::Color32::from_rgb(to_byte(c.red), to_byte(c.green), to_byte(c.blue))
+}
+
+/// Draw the minimap when `GlyphConfig::minimap` is on and the canvas has nodes.
+/// The world bounds cover every visible node and the viewport, recomputed each frame.
+/// Clicking jumps the camera there; dragging pans it along.
+pub fn ui_minimap_system(
+    mut contexts: EguiContexts,
+    mut commands: Commands,
+    config: Res<GlyphConfig>,
+    nodes: MinimapNodeQuery,
+    mut camera_query: MinimapCameraQuery,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    if !config.minimap || nodes.is_empty() {
+        return;
+    }
+    let Ok((camera, cam_global, mut cam_transform)) = camera_query.single_mut() else {
+        return;
+    };
+    let Ok(window) = window_query.single() else {
+        return;
+    };
+    let (min_x, max_x, min_y, max_y) = viewport_world_bounds(camera, cam_global, window.size());
+    let viewport = Rect::new(min_x, min_y, max_x, max_y);
+
+    let boxes: Vec<(Rect, Color)> = nodes
+        .iter()
+        .map(|(t, color, size)| {
+            let size = size.copied().unwrap_or_default().current;
+            (Rect::from_center_size(t.translation.truncate(), size), color.0)
+        })
+        .collect();
+    let world = boxes.iter().fold(viewport, |acc, (r, _)| acc.union(*r));
+
+    let Ok(ctx) = contexts.ctx_mut() else { return };
+    egui::Window::new("Minimap")
+        .resizable(false)
+        .collapsible(true)
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-8.0, -36.0))
+        .show(ctx, |ui| {
+            let (rect, response) =
+                ui.allocate_exact_size(MINIMAP_SIZE, egui::Sense::click_and_drag());
+            let min = Vec2::new(rect.min.x, rect.min.y);
+            let proj = MinimapProjection::fit(world, min, Vec2::new(rect.width(), rect.height()));
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(rect, 2.0, egui::Color32::from_black_alpha(120));
+
+            let map_rect = |r: Rect| {
+                let (a, b) = (proj.to_map(r.min), proj.to_map(r.max));
+                egui::Rect::from_two_pos(to_pos(a), to_pos(b))
+            };
+            for (r, color) in &boxes {
+                let map = map_rect(*r);
+                let size = map.size().max(egui::Vec2::splat(MIN_BOX_PX));
+                let map = egui::Rect::from_center_size(map.center(), size);
+                painter.rect_filled(map, 0.0, to_color32(*color));
+            }
+            painter.rect_stroke(
+                map_rect(viewport),
+                0.0,
+                egui::Stroke::new(1.0, egui::Color32::WHITE),
+                egui::StrokeKind::Inside,
+            );
+
+            // A click jumps (eased like other jumps); a drag follows the pointer.
+            if let Some(pos) = response.interact_pointer_pos() {
+                let target = proj.to_world(Vec2::new(pos.x, pos.y));
+                if response.dragged() {
+                    commands.remove_resource::<CameraTween>();
+                    cam_transform.translation.x = target.x;
+                    cam_transform.translation.y = target.y;
+                } else if response.clicked() {
+                    request_camera_move(&mut commands, &config, &mut cam_transform, target);
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projection_fits_world_and_round_trips() {
+        let world = Rect::new(-500.0, -100.0, 1500.0, 100.0);
+        let proj = MinimapProjection::fit(world, Vec2::new(10.0, 20.0), Vec2::new(200.0, 140.0));
+        // Width is the limiting axis: the padded world spans the full map width.
+        let left = proj.to_map(Vec2::new(world.min.x - MINIMAP_PADDING, 0.0));
+        let right = proj.to_map(Vec2::new(world.max.x + MINIMAP_PADDING, 0.0));
+        assert!((left.x - 10.0).abs() < 1e-3 && (right.x - 210.0).abs() < 1e-3);
+        // World centre sits at the map centre and +y points up the screen.
+        assert_eq!(proj.to_map(world.center()), Vec2::new(110.0, 90.0));
+        assert!(proj.to_map(Vec2::new(500.0, 100.0)).y < 90.0);
+
+        let p = Vec2::new(321.0, -42.0);
+        assert!(proj.to_world(proj.to_map(p)).distance(p) < 1e-3);
+    }
+}
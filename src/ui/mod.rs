@@ -1,5 +1,10 @@
-//! UI overlays: command palette, status bars, fuzzy finder, shell command.
+//! UI overlays: command palette, status bars, fuzzy finder, minimap, shell command, history log,
+//! node tooltips, graph stats.
 
 pub mod fuzzy;
+pub mod history_log;
+pub mod minimap;
 pub mod overlay;
 pub mod shell;
+pub mod stats;
+pub mod tooltip;
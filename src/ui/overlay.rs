@@ -1,5 +1,6 @@
 //! Phase 7: Immediate-mode UI overlays via bevy_egui.
 
+use bevy::ecs::system::SystemParam;
 use bevy::input::keyboard::Key;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
@@ -7,13 +8,13 @@ use bevy_egui::{egui, EguiContexts};
 use std::sync::mpsc;
 
 use crate::core::components::SourceLocation;
-use crate::core::components::{CanvasNode, Edge, MainCamera, Selected};
+use crate::core::components::{CanvasNode, Edge, FlowBypass, InactiveCanvas, MainCamera, Selected};
 use crate::core::helpers::spawn_canvas_node;
 use crate::core::resources::SpatialIndex;
 use crate::core::state::InputMode;
 use crate::io::file_io::{
-    add_to_recent, camera_prefs_from_parts, save_to_path, workflows_dir, CurrentFile,
-    FileDialogResult, PendingFileDialog, PendingLoad, RecentFiles, WORKSPACE_PATH,
+    add_to_recent, camera_prefs_from_parts, clear_recent, save_checked, workflows_dir, CurrentFile,
+    FileDialogResult, PendingFileDialog, PendingLoad, RecentFiles, SaveSources, WORKSPACE_PATH,
 };
 
 /// Command palette state. Cmd+K toggles.
@@ -61,19 +62,11 @@ pub fn ui_top_bar_system(
     state: Res<State<InputMode>>,
     pending_dialog: ResMut<PendingFileDialog>,
     mut pending_load: ResMut<PendingLoad>,
-    current_file: Res<CurrentFile>,
-    recent: Res<RecentFiles>,
+    mut current_file: ResMut<CurrentFile>,
+    mut status: ResMut<crate::core::resources::StatusMessage>,
+    mut recent: ResMut<RecentFiles>,
     mut force_layout: ResMut<crate::render::layout::ForceLayoutActive>,
-    node_data_query: Query<
-        (
-            Entity,
-            &Transform,
-            &crate::core::components::TextData,
-            &crate::core::components::NodeColor,
-        ),
-        With<CanvasNode>,
-    >,
-    edge_query: Query<(Entity, &Edge)>,
+    canvas: SaveSources,
     camera_query: Query<(&Transform, &Projection), With<MainCamera>>,
 ) {
     let Ok(ctx) = contexts.ctx_mut() else {
@@ -113,6 +106,12 @@ pub fn ui_top_bar_system(
                                     ui.close();
                                 }
                             }
+                            ui.separator();
+                            if ui.button("Clear Recent Files").clicked() {
+                                clear_recent(&mut recent);
+                                status.set("Cleared recent files");
+                                ui.close();
+                            }
                         }
                     });
                     if ui.button("Save").clicked() {
@@ -135,9 +134,14 @@ pub fn ui_top_bar_system(
                                 .single()
                                 .ok()
                                 .map(|(t, p)| camera_prefs_from_parts(t, p));
-                            match save_to_path(&path, &node_data_query, &edge_query, cam_prefs) {
+                            let saved =
+                                save_checked(&path, &canvas, cam_prefs, &mut current_file, false);
+                            match saved {
                                 Ok(()) => info!("[SAVE] Saved to {}", path.display()),
-                                Err(e) => error!("[SAVE] {}", e),
+                                Err(e) => {
+                                    status.set(format!("Save failed: {}", e));
+                                    error!("[SAVE] {}", e);
+                                }
                             }
                         }
                         ui.close();
@@ -181,6 +185,7 @@ pub fn ui_top_bar_system(
                     InputMode::VimEasymotion => "JUMP",
                     InputMode::Standard => "STANDARD",
                     InputMode::VimCommand => "COMMAND",
+                    InputMode::VimVisual => "VISUAL",
                 };
                 ui.label(egui::RichText::new(mode_text).strong().color(match state.get() {
                     InputMode::VimInsert => egui::Color32::from_rgb(100, 200, 120),
@@ -188,22 +193,25 @@ pub fn ui_top_bar_system(
                     InputMode::Standard => egui::Color32::from_rgb(160, 140, 220),
                     InputMode::VimNormal => egui::Color32::WHITE,
                     InputMode::VimCommand => egui::Color32::from_rgb(220, 180, 80),
+                    InputMode::VimVisual => egui::Color32::from_rgb(255, 115, 190),
                 }));
                 ui.add_space(8.0);
-                let hint = if node_data_query.is_empty() {
+                let hint = if canvas.nodes.is_empty() {
                     "n: new node   f: jump   +/-: zoom   Cmd+K: commands   crawl ./src: call graph"
                 } else {
                     match state.get() {
                         InputMode::Standard =>
-                            "Esc/Ctrl+[: normal   Space+drag: pan   Shift+drag: draw edge",
+                            "Esc/Ctrl+[: normal   Space+drag: pan   Shift+drag or handle drag: draw edge",
                         InputMode::VimNormal =>
-                            "hjkl/arrows: move   f: jump   gd: open   ge: edge   i: insert   n: new   a: add   ce: connect   dd: del   +/-: zoom   :: command",
+                            "hjkl/arrows: move   f: jump   gd: open   gg/G: top/bottom   L/ge: label edge   gr: reverse edge   i: insert   n: new   a: add   o/O: below/above   ce: connect   dd: del   yy/p: yank/paste   v: visual   +/-: zoom   :: command",
                         InputMode::VimInsert =>
                             "Esc/Ctrl+[: normal   Ctrl+h: backspace",
                         InputMode::VimEasymotion =>
                             "Type letter to jump   Esc: cancel",
                         InputMode::VimCommand =>
-                            ":w · :w <path> · :e <path> · :crawl <path> [--no-flow] · :q   Esc/Ctrl+[: cancel   Enter: execute",
+                            ":w · :w <path> · :e <path> · :crawl <path>... [--no-flow] · :export <file.dot> · :set grid <n|off> · :set <key> <value> · :set <key>? · :layout circle|grid · :shape <name> · :color <#rrggbb|name> · :estyle dashed|bold · :align <edge> · :distribute h|v · :sort[!] · :reverse · :theme dark|light · :bundle · :flow on|off · :callers · :callees · :minimap · :grid dots|lines|off · :tabnew · :tabnext · :tabclose · :history · :template save|insert <name> · :yank · :path · :pin · :connect 'a 'b|<label> · :q   Esc/Ctrl+[: cancel   Enter: execute",
+                        InputMode::VimVisual =>
+                            "f/click: toggle node   hjkl: move all   d: delete all   Esc/Ctrl+[: normal",
                     }
                 };
                 ui.label(egui::RichText::new(hint).color(egui::Color32::GRAY));
@@ -227,16 +235,8 @@ pub fn process_pending_file_dialog_system(
     mut pending_load: ResMut<PendingLoad>,
     mut current_file: ResMut<CurrentFile>,
     mut recent: ResMut<RecentFiles>,
-    node_data_query: Query<
-        (
-            Entity,
-            &Transform,
-            &crate::core::components::TextData,
-            &crate::core::components::NodeColor,
-        ),
-        With<CanvasNode>,
-    >,
-    edge_query: Query<(Entity, &Edge)>,
+    config: Res<crate::core::config::GlyphConfig>,
+    canvas: SaveSources,
     camera_query: Query<(&Transform, &Projection), With<MainCamera>>,
 ) {
     let mut guard = match pending_dialog.0.try_lock() {
@@ -259,10 +259,10 @@ pub fn process_pending_file_dialog_system(
                 .single()
                 .ok()
                 .map(|(t, p)| camera_prefs_from_parts(t, p));
-            match save_to_path(&path, &node_data_query, &edge_query, cam_prefs) {
+            // The save dialog already asked before replacing an existing file.
+            match save_checked(&path, &canvas, cam_prefs, &mut current_file, true) {
                 Ok(()) => {
-                    current_file.0 = Some(path.clone());
-                    add_to_recent(&mut recent, path.clone());
+                    add_to_recent(&mut recent, path.clone(), config.max_recent);
                     info!("[SAVE] Saved to {}", path.display());
                 }
                 Err(e) => error!("[SAVE] {}", e),
@@ -275,6 +275,14 @@ pub fn process_pending_file_dialog_system(
     }
 }
 
+/// Messages sent from the command palette.
+#[derive(SystemParam)]
+pub struct PaletteRequests<'w> {
+    pub crawl: MessageWriter<'w, crate::crawler::CrawlRequest>,
+    pub recolor: MessageWriter<'w, crate::render::recolor::RecolorRequest>,
+    pub template: MessageWriter<'w, crate::io::template::TemplateRequest>,
+}
+
 /// Command palette window. Cmd+K to open.
 ///
 /// Keyboard-only usage:
@@ -290,20 +298,11 @@ pub fn ui_command_palette_system(
     mut current_file: ResMut<CurrentFile>,
     mut recent: ResMut<RecentFiles>,
     mut next_state: ResMut<NextState<crate::core::state::InputMode>>,
-    edge_query: Query<(Entity, &Edge)>,
-    node_data_query: Query<
-        (
-            Entity,
-            &Transform,
-            &crate::core::components::TextData,
-            &crate::core::components::NodeColor,
-        ),
-        With<CanvasNode>,
-    >,
+    canvas: SaveSources,
     selected_q: Query<Entity, With<Selected>>,
     window_q: Query<&Window, With<PrimaryWindow>>,
     camera_full_q: Query<(&Transform, &Projection, &Camera, &GlobalTransform), With<MainCamera>>,
-    mut crawl_events: MessageWriter<crate::crawler::CrawlRequest>,
+    mut requests: PaletteRequests,
     config: Res<crate::core::config::GlyphConfig>,
 ) {
     if !palette.is_open {
@@ -342,15 +341,21 @@ pub fn ui_command_palette_system(
             if enter_pressed && search_has_focus {
                 if q_raw.starts_with("crawl ") {
                     let arg = q_raw["crawl ".len()..].trim();
-                    let (path, no_flow) = if let Some(p) = arg.strip_suffix(" --no-flow") {
-                        (p.trim().to_string(), true)
-                    } else {
-                        (arg.to_string(), false)
-                    };
-                    if !path.is_empty() {
-                        crawl_events.write(crate::crawler::CrawlRequest { path, no_flow });
-                        palette.search_query.clear();
-                        palette.is_open = false;
+                    match crate::crawler::parse_crawl_args(arg) {
+                        Ok(args) => {
+                            requests.crawl.write(crate::crawler::CrawlRequest {
+                                paths: args.paths.iter().map(|p| p.to_string()).collect(),
+                                no_flow: args.no_flow,
+                                max_depth: args.max_depth,
+                                exclude: args.exclude,
+                                follow_links: args.follow_links,
+                                respect_gitignore: args.respect_gitignore,
+                                mode: args.mode,
+                            });
+                            palette.search_query.clear();
+                            palette.is_open = false;
+                        }
+                        Err(e) => warn!("[CRAWL] {}", e),
                     }
                     return;
                 } else if q_raw.starts_with("open ") {
@@ -374,10 +379,9 @@ pub fn ui_command_palette_system(
                             .single()
                             .ok()
                             .map(|(t, p, _, _)| camera_prefs_from_parts(t, p));
-                        match save_to_path(&path, &node_data_query, &edge_query, cam_prefs) {
+                        match save_checked(&path, &canvas, cam_prefs, &mut current_file, false) {
                             Ok(()) => {
-                                current_file.0 = Some(path.clone());
-                                add_to_recent(&mut recent, path.clone());
+                                add_to_recent(&mut recent, path.clone(), config.max_recent);
                                 info!("[SAVE] Saved to {}", path.display());
                             }
                             Err(e) => error!("[SAVE] {}", e),
@@ -417,9 +421,8 @@ pub fn ui_command_palette_system(
                         .single()
                         .ok()
                         .map(|(t, p, _, _)| camera_prefs_from_parts(t, p));
-                    match save_to_path(&path, &node_data_query, &edge_query, cam_prefs) {
+                    match save_checked(&path, &canvas, cam_prefs, &mut current_file, false) {
                         Ok(()) => {
-                            current_file.0 = Some(path.clone());
                             info!("[SAVE] Saved to {}", path.display());
                         }
                         Err(e) => error!("[SAVE] {}", e),
@@ -484,10 +487,11 @@ pub fn ui_command_palette_system(
                 if (btn.clicked() || enter) && !handled {
                     handled = true;
                     if let Ok(node_entity) = selected_q.single() {
-                        let to_despawn: Vec<_> = edge_query
+                        let to_despawn: Vec<_> = canvas
+                            .edges
                             .iter()
-                            .filter(|(_, e)| e.source == node_entity || e.target == node_entity)
-                            .map(|(e, _)| e)
+                            .filter(|(_, e, ..)| e.source == node_entity || e.target == node_entity)
+                            .map(|(e, ..)| e)
                             .collect();
                         for e in &to_despawn {
                             commands.entity(*e).despawn();
@@ -502,20 +506,55 @@ pub fn ui_command_palette_system(
                 let btn = ui.button("Clear Canvas");
                 let enter = std::mem::take(&mut first_remaining);
                 if (btn.clicked() || enter) && !handled {
-                    let to_despawn = node_data_query.iter().map(|(e, ..)| e).collect::<Vec<_>>();
+                    let to_despawn = canvas.nodes.iter().map(|(e, ..)| e).collect::<Vec<_>>();
                     for entity in to_despawn {
                         commands.entity(entity).despawn();
                     }
-                    for (e, _) in edge_query.iter() {
+                    for (e, ..) in canvas.edges.iter() {
                         commands.entity(e).despawn();
                     }
                     spatial_index.clear();
-                    current_file.0 = None;
+                    *current_file = CurrentFile::default();
                     palette.is_open = false;
                     info!("[CLEAR] Canvas cleared");
                 }
             }
 
+            // One button per `:template save`d template; inserts at the cursor.
+            for name in crate::io::template::list_templates(&crate::io::template::templates_dir()) {
+                let label = format!("Insert template: {}", name);
+                if !show(&label) {
+                    continue;
+                }
+                let btn = ui.button(&label);
+                let enter = std::mem::take(&mut first_remaining);
+                if (btn.clicked() || enter) && !handled {
+                    handled = true;
+                    requests
+                        .template
+                        .write(crate::io::template::TemplateRequest::Insert(name));
+                    palette.is_open = false;
+                }
+            }
+
+            if show("color") && !selected_q.is_empty() {
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Color");
+                    for (name, hex) in crate::render::recolor::NAMED_COLORS {
+                        let Ok(color) = crate::render::recolor::parse_color(hex) else {
+                            continue;
+                        };
+                        let swatch = egui::Button::new("")
+                            .fill(crate::ui::minimap::to_color32(color))
+                            .min_size(egui::vec2(18.0, 18.0));
+                        if ui.add(swatch).on_hover_text(name).clicked() {
+                            requests.recolor.write(crate::render::recolor::RecolorRequest(color));
+                        }
+                    }
+                });
+            }
+
             // Suppress unused warning when no button was matched.
             let _ = handled;
 
@@ -542,9 +581,55 @@ pub fn ui_command_palette_system(
 /// Parsed vim command representation, separated from execution for testability.
 #[derive(Debug, PartialEq)]
 pub enum VimCommand<'a> {
-    Write { path: Option<&'a str> },
+    /// `force` (`:w!`) overwrites a file changed on disk since it was loaded.
+    Write { path: Option<&'a str>, force: bool },
     Edit { path: &'a str },
-    Crawl { path: &'a str, no_flow: bool },
+    Crawl {
+        paths: Vec<&'a str>,
+        no_flow: bool,
+        max_depth: Option<usize>,
+        exclude: Vec<String>,
+        follow_links: bool,
+        respect_gitignore: bool,
+        mode: crate::crawler::CrawlMode,
+    },
+    Simplify,
+    Reverse,
+    Export { path: &'a str },
+    Import { path: &'a str },
+    Set { option: &'a str, value: &'a str },
+    Layout(crate::render::layout::LayoutKind),
+    Shape(crate::core::components::NodeShape),
+    Color(Color),
+    EdgeStroke(crate::render::edge_stroke::StrokeChange),
+    /// `:straighten`: reset the selected edge's hand-set bend.
+    Straighten,
+    Align(crate::render::align::AlignOp),
+    Theme(crate::core::config::ThemeName),
+    /// `None` toggles.
+    Bundle(Option<bool>),
+    Flow(Option<bool>),
+    /// `:callers` (`true`) or `:callees` (`false`).
+    Callers(bool),
+    Minimap(Option<bool>),
+    /// `:grid dots|lines|off`; `None` hides the background grid.
+    Grid(Option<crate::core::config::GridStyle>),
+    Tab(crate::core::tabs::TabRequest),
+    /// `:history`, or `:history > file` to write the log to disk.
+    History { path: Option<&'a str> },
+    /// `:template save <name>` (`insert: false`) or `:template insert <name>`.
+    Template { insert: bool, name: &'a str },
+    /// `:yank`: copy the selected node's text to the system clipboard.
+    Yank,
+    /// `:path`: pick the source, then highlight the path to the target.
+    Path,
+    /// `:pin`: toggle the selected node's pin.
+    Pin,
+    /// `:stats`: node/edge counts, roots, depth and busiest functions.
+    Stats,
+    /// `:connect 'a 'b` or `:connect <label>`.
+    Connect(crate::input::connect::ConnectTarget),
+    Grep { pattern: Option<&'a str> },
     Quit,
     Unknown(&'a str),
     Empty,
@@ -560,8 +645,9 @@ pub fn parse_vim_command(text: &str) -> VimCommand<'_> {
         None => (text, ""),
     };
     match cmd {
-        "w" | "write" => VimCommand::Write {
+        "w" | "write" | "w!" | "write!" => VimCommand::Write {
             path: if arg.is_empty() { None } else { Some(arg) },
+            force: cmd.ends_with('!'),
         },
         "e" | "edit" => {
             if arg.is_empty() {
@@ -574,21 +660,183 @@ pub fn parse_vim_command(text: &str) -> VimCommand<'_> {
             if arg.is_empty() {
                 VimCommand::Unknown("crawl (missing path)")
             } else {
-                let (path, no_flow) = if let Some(p) = arg.strip_suffix(" --no-flow") {
-                    (p.trim(), true)
-                } else if let Some(p) = arg.strip_prefix("--no-flow ") {
-                    (p.trim(), true)
-                } else {
-                    (arg, false)
-                };
-                VimCommand::Crawl { path, no_flow }
+                match crate::crawler::parse_crawl_args(arg) {
+                    Ok(args) => VimCommand::Crawl {
+                        paths: args.paths,
+                        no_flow: args.no_flow,
+                        max_depth: args.max_depth,
+                        exclude: args.exclude,
+                        follow_links: args.follow_links,
+                        respect_gitignore: args.respect_gitignore,
+                        mode: args.mode,
+                    },
+                    Err(_) => VimCommand::Unknown(
+                        "crawl (expected <path>... [--no-flow] [--depth N] [--exclude a,b] \
+                         [--follow-links] [--respect-gitignore] [--imports])",
+                    ),
+                }
+            }
+        }
+        "simplify" => VimCommand::Simplify,
+        "yank" => VimCommand::Yank,
+        "path" => VimCommand::Path,
+        "pin" => VimCommand::Pin,
+        "stats" => VimCommand::Stats,
+        "straighten" => VimCommand::Straighten,
+        "connect" => match crate::input::connect::ConnectTarget::parse(arg) {
+            Some(target) => VimCommand::Connect(target),
+            None => VimCommand::Unknown("connect (expected 'a 'b or a node label)"),
+        },
+        "reverse" => VimCommand::Reverse,
+        "export" => {
+            if arg.is_empty() {
+                VimCommand::Unknown("export (missing path)")
+            } else {
+                VimCommand::Export { path: arg }
+            }
+        }
+        "import" => {
+            if arg.is_empty() {
+                VimCommand::Unknown("import (missing path)")
+            } else {
+                VimCommand::Import { path: arg }
+            }
+        }
+        "layout" => match arg {
+            "circle" => VimCommand::Layout(crate::render::layout::LayoutKind::Circle),
+            "grid" => VimCommand::Layout(crate::render::layout::LayoutKind::Grid),
+            _ => VimCommand::Unknown("layout (expected circle or grid)"),
+        },
+        "align" => match crate::render::align::AlignEdge::from_name(arg) {
+            Some(edge) => VimCommand::Align(crate::render::align::AlignOp::Align(edge)),
+            None => VimCommand::Unknown(
+                "align (expected left, right, top, bottom, hcenter or vcenter)",
+            ),
+        },
+        "distribute" => match arg {
+            "h" => VimCommand::Align(crate::render::align::AlignOp::Distribute(
+                crate::render::align::Axis::Horizontal,
+            )),
+            "v" => VimCommand::Align(crate::render::align::AlignOp::Distribute(
+                crate::render::align::Axis::Vertical,
+            )),
+            _ => VimCommand::Unknown("distribute (expected h or v)"),
+        },
+        "sort" | "sort!" => {
+            if arg.is_empty() {
+                VimCommand::Align(crate::render::align::AlignOp::Sort {
+                    reverse: cmd.ends_with('!'),
+                })
+            } else {
+                VimCommand::Unknown("sort (takes no argument; :sort! reverses)")
+            }
+        }
+        "bundle" => match arg {
+            "" => VimCommand::Bundle(None),
+            "on" => VimCommand::Bundle(Some(true)),
+            "off" => VimCommand::Bundle(Some(false)),
+            _ => VimCommand::Unknown("bundle (expected on, off or nothing to toggle)"),
+        },
+        "grid" => match (arg, crate::core::config::GridStyle::from_name(arg)) {
+            ("off", _) => VimCommand::Grid(None),
+            (_, Some(style)) => VimCommand::Grid(Some(style)),
+            _ => VimCommand::Unknown("grid (expected dots, lines or off)"),
+        },
+        "minimap" => match arg {
+            "" => VimCommand::Minimap(None),
+            "on" => VimCommand::Minimap(Some(true)),
+            "off" => VimCommand::Minimap(Some(false)),
+            _ => VimCommand::Unknown("minimap (expected on, off or nothing to toggle)"),
+        },
+        "flow" => match arg {
+            "" => VimCommand::Flow(None),
+            "on" => VimCommand::Flow(Some(true)),
+            "off" => VimCommand::Flow(Some(false)),
+            _ => VimCommand::Unknown("flow (expected on, off or nothing to toggle)"),
+        },
+        "callers" => VimCommand::Callers(true),
+        "callees" => VimCommand::Callers(false),
+        "theme" => match crate::core::config::ThemeName::from_name(arg) {
+            Some(theme) => VimCommand::Theme(theme),
+            None => VimCommand::Unknown("theme (expected dark or light)"),
+        },
+        "shape" => match crate::core::components::NodeShape::from_name(arg) {
+            Some(shape) => VimCommand::Shape(shape),
+            None => VimCommand::Unknown("shape (expected rect, ellipse or diamond)"),
+        },
+        "color" => match crate::render::recolor::parse_color(arg) {
+            Ok(color) => VimCommand::Color(color),
+            Err(_) => VimCommand::Unknown("color (expected #rrggbb or a color name)"),
+        },
+        "estyle" => match crate::render::edge_stroke::StrokeChange::from_name(arg) {
+            Some(change) => VimCommand::EdgeStroke(change),
+            None => VimCommand::Unknown("estyle (expected dashed, solid, bold or thin)"),
+        },
+        "history" => match arg.strip_prefix('>').map(str::trim) {
+            None if arg.is_empty() => VimCommand::History { path: None },
+            Some(path) if !path.is_empty() => VimCommand::History { path: Some(path) },
+            _ => VimCommand::Unknown("history (expected nothing or > <file>)"),
+        },
+        "template" => match arg.split_once(' ').map(|(op, name)| (op, name.trim())) {
+            Some(("save", name)) if !name.is_empty() => VimCommand::Template {
+                insert: false,
+                name,
+            },
+            Some(("insert", name)) if !name.is_empty() => VimCommand::Template {
+                insert: true,
+                name,
+            },
+            _ => VimCommand::Unknown("template (expected save <name> or insert <name>)"),
+        },
+        "set" => {
+            let (option, value) = match arg.find(' ') {
+                Some(pos) => (&arg[..pos], arg[pos + 1..].trim()),
+                None => (arg, ""),
+            };
+            if option.is_empty() {
+                VimCommand::Unknown("set (missing option)")
+            } else {
+                VimCommand::Set { option, value }
             }
         }
+        "grep" => VimCommand::Grep {
+            pattern: if arg.is_empty() { None } else { Some(arg) },
+        },
+        "nohl" | "nohlsearch" => VimCommand::Grep { pattern: None },
+        "tabnew" => VimCommand::Tab(crate::core::tabs::TabRequest::New),
+        "tabn" | "tabnext" => VimCommand::Tab(crate::core::tabs::TabRequest::Next),
+        "tabp" | "tabprev" | "tabprevious" => VimCommand::Tab(crate::core::tabs::TabRequest::Prev),
+        "tabc" | "tabclose" => VimCommand::Tab(crate::core::tabs::TabRequest::Close),
         "q" | "quit" => VimCommand::Quit,
         _ => VimCommand::Unknown(text),
     }
 }
 
+/// Messages the command line hands off to other systems.
+#[derive(SystemParam)]
+pub struct CmdLineRequests<'w> {
+    pub crawl: MessageWriter<'w, crate::crawler::CrawlRequest>,
+    pub simplify: MessageWriter<'w, crate::crawler::simplify::SimplifyRequest>,
+    pub layout: MessageWriter<'w, crate::render::layout::LayoutRequest>,
+    pub import: MessageWriter<'w, crate::io::import_mermaid::ImportRequest>,
+    pub shape: MessageWriter<'w, crate::render::shapes::ShapeRequest>,
+    pub align: MessageWriter<'w, crate::render::align::AlignRequest>,
+    pub reverse: MessageWriter<'w, crate::input::vim::ReverseEdgeRequest>,
+    pub flow: MessageWriter<'w, crate::crawler::flow::FlowRequest>,
+    pub callers: MessageWriter<'w, crate::crawler::callers::CallersRequest>,
+    pub template: MessageWriter<'w, crate::io::template::TemplateRequest>,
+    pub recolor: MessageWriter<'w, crate::render::recolor::RecolorRequest>,
+    pub edge_stroke: MessageWriter<'w, crate::render::edge_stroke::EdgeStrokeRequest>,
+    pub straighten: MessageWriter<'w, crate::render::edge_curve::StraightenRequest>,
+    pub tab: MessageWriter<'w, crate::core::tabs::TabRequest>,
+    pub history: MessageWriter<'w, crate::ui::history_log::HistoryRequest>,
+    pub yank: MessageWriter<'w, crate::io::clipboard::YankRequest>,
+    pub path: MessageWriter<'w, crate::render::call_path::PathRequest>,
+    pub connect: MessageWriter<'w, crate::input::connect::ConnectRequest>,
+    pub pin: MessageWriter<'w, crate::render::pin::PinRequest>,
+    pub stats: MessageWriter<'w, crate::ui::stats::StatsRequest>,
+}
+
 /// Executes a parsed vim command. Called from `vim_cmdline_system` on Enter.
 fn execute_vim_command(
     text: &str,
@@ -596,18 +844,12 @@ fn execute_vim_command(
     recent: &mut RecentFiles,
     pending_load: &mut PendingLoad,
     status: &mut crate::core::resources::StatusMessage,
-    node_query: &Query<
-        (
-            Entity,
-            &Transform,
-            &crate::core::components::TextData,
-            &crate::core::components::NodeColor,
-        ),
-        With<CanvasNode>,
-    >,
-    edge_query: &Query<(Entity, &Edge)>,
+    canvas: &SaveSources,
     camera_query: &Query<(&Transform, &Projection), With<MainCamera>>,
-    crawl_events: &mut MessageWriter<crate::crawler::CrawlRequest>,
+    requests: &mut CmdLineRequests,
+    config: &mut crate::core::config::GlyphConfig,
+    grep: &mut crate::core::resources::GrepFilter,
+    visual_members: &[Entity],
 ) {
     if text.is_empty() {
         return;
@@ -617,7 +859,8 @@ fn execute_vim_command(
         None => (text, ""),
     };
     match cmd {
-        "w" | "write" => {
+        "w" | "write" | "w!" | "write!" => {
+            let force = cmd.ends_with('!');
             let path = if arg.is_empty() {
                 current_file
                     .0
@@ -630,10 +873,9 @@ fn execute_vim_command(
                 .single()
                 .ok()
                 .map(|(t, p)| camera_prefs_from_parts(t, p));
-            match save_to_path(&path, node_query, edge_query, cam_prefs) {
+            match save_checked(&path, canvas, cam_prefs, current_file, force) {
                 Ok(()) => {
-                    current_file.0 = Some(path.clone());
-                    add_to_recent(recent, path.clone());
+                    add_to_recent(recent, path.clone(), config.max_recent);
                     let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("file");
                     status.set(format!("Saved {}", name));
                     info!("[CMD] :w → saved to {}", path.display());
@@ -663,20 +905,318 @@ fn execute_vim_command(
                 status.set("error: :crawl requires a path");
                 warn!("[CMD] :crawl requires a path");
             } else {
-                let (path, no_flow) = if let Some(p) = arg.strip_suffix(" --no-flow") {
-                    (p.trim(), true)
-                } else if let Some(p) = arg.strip_prefix("--no-flow ") {
-                    (p.trim(), true)
+                match crate::crawler::parse_crawl_args(arg) {
+                    Ok(args) => {
+                        info!(
+                            "[CMD] :crawl {} (no_flow={}, depth={:?}, exclude={:?}, links={})",
+                            args.paths.join(" "),
+                            args.no_flow,
+                            args.max_depth,
+                            args.exclude,
+                            args.follow_links
+                        );
+                        requests.crawl.write(crate::crawler::CrawlRequest {
+                            paths: args.paths.iter().map(|p| p.to_string()).collect(),
+                            no_flow: args.no_flow,
+                            max_depth: args.max_depth,
+                            exclude: args.exclude,
+                            follow_links: args.follow_links,
+                            respect_gitignore: args.respect_gitignore,
+                            mode: args.mode,
+                        });
+                    }
+                    Err(e) => {
+                        status.set(format!("error: :crawl {}", e));
+                        warn!("[CMD] :crawl {}", e);
+                    }
+                }
+            }
+        }
+        "simplify" => {
+            requests.simplify.write(crate::crawler::simplify::SimplifyRequest);
+            info!("[CMD] :simplify");
+        }
+        "reverse" => {
+            requests.reverse.write(crate::input::vim::ReverseEdgeRequest);
+            info!("[CMD] :reverse");
+        }
+        "yank" => {
+            requests.yank.write(crate::io::clipboard::YankRequest);
+            info!("[CMD] :yank");
+        }
+        "path" => {
+            requests.path.write(crate::render::call_path::PathRequest);
+            info!("[CMD] :path");
+        }
+        "pin" => {
+            requests.pin.write(crate::render::pin::PinRequest);
+            info!("[CMD] :pin");
+        }
+        "stats" => {
+            requests.stats.write(crate::ui::stats::StatsRequest);
+            info!("[CMD] :stats");
+        }
+        "straighten" => {
+            requests.straighten.write(crate::render::edge_curve::StraightenRequest);
+            info!("[CMD] :straighten");
+        }
+        "connect" => match parse_vim_command(text) {
+            VimCommand::Connect(target) => {
+                requests.connect.write(crate::input::connect::ConnectRequest(target));
+                info!("[CMD] :connect {}", arg);
+            }
+            _ => {
+                status.set("error: usage :connect 'a 'b or :connect <label>");
+                warn!("[CMD] :connect — missing argument");
+            }
+        },
+        "history" => match parse_vim_command(text) {
+            VimCommand::History { path } => {
+                let path = path.map(str::to_string);
+                requests.history.write(crate::ui::history_log::HistoryRequest { path });
+                info!("[CMD] :history {}", arg);
+            }
+            _ => {
+                status.set("error: usage :history or :history > <file>");
+                warn!("[CMD] :history — bad argument: {}", arg);
+            }
+        },
+        "template" => match parse_vim_command(text) {
+            VimCommand::Template { insert, name } => {
+                use crate::io::template::TemplateRequest;
+                let name = name.to_string();
+                requests.template.write(if insert {
+                    TemplateRequest::Insert(name)
                 } else {
-                    (arg, false)
-                };
-                crawl_events.write(crate::crawler::CrawlRequest {
-                    path: path.to_string(),
-                    no_flow,
+                    TemplateRequest::Save {
+                        name,
+                        members: visual_members.to_vec(),
+                    }
+                });
+                info!("[CMD] :template {}", arg);
+            }
+            _ => {
+                status.set("error: usage :template save <name> or :template insert <name>");
+                warn!("[CMD] :template — bad argument: {}", arg);
+            }
+        },
+        "estyle" => match parse_vim_command(text) {
+            VimCommand::EdgeStroke(change) => {
+                let request = crate::render::edge_stroke::EdgeStrokeRequest(change);
+                requests.edge_stroke.write(request);
+                info!("[CMD] :estyle {}", arg);
+            }
+            _ => {
+                status.set("error: :estyle expects dashed, solid, bold or thin");
+                warn!("[CMD] :estyle — unknown style: {}", arg);
+            }
+        },
+        "export" => {
+            if arg.is_empty() {
+                status.set("error: :export requires a path");
+                warn!("[CMD] :export requires a path");
+                return;
+            }
+            let path = std::path::PathBuf::from(arg);
+            let result = match path.extension().and_then(|e| e.to_str()) {
+                Some("dot") => crate::io::export_dot::export_dot(&path, canvas),
+                Some("puml") | Some("plantuml") => {
+                    crate::io::export_plantuml::export_plantuml(&path, canvas)
+                }
+                _ => Err(format!("unsupported export format: {} (use .dot or .puml)", arg)),
+            };
+            match result {
+                Ok(()) => {
+                    status.set(format!("Exported {}", arg));
+                    info!("[CMD] :export → {}", path.display());
+                }
+                Err(e) => {
+                    status.set(format!("Error: {}", e));
+                    error!("[CMD] :export failed: {}", e);
+                }
+            }
+        }
+        "import" => {
+            if arg.is_empty() {
+                status.set("error: :import requires a path");
+                warn!("[CMD] :import requires a path");
+                return;
+            }
+            let path = std::path::Path::new(arg);
+            match path.extension().and_then(|e| e.to_str()) {
+                Some("mmd") | Some("mermaid") => {
+                    requests.import.write(crate::io::import_mermaid::ImportRequest {
+                        path: arg.to_string(),
+                    });
+                    info!("[CMD] :import {}", arg);
+                }
+                _ => {
+                    status.set(format!("Error: unsupported import format: {} (use .mmd)", arg));
+                    warn!("[CMD] :import unsupported format: {}", arg);
+                }
+            }
+        }
+        "layout" => match parse_vim_command(text) {
+            VimCommand::Layout(kind) => {
+                requests.layout.write(crate::render::layout::LayoutRequest(kind));
+                info!("[CMD] :layout {:?}", kind);
+            }
+            _ => {
+                status.set("error: :layout expects circle or grid");
+                warn!("[CMD] :layout — unknown kind: {}", arg);
+            }
+        },
+        "align" | "distribute" | "sort" | "sort!" => match parse_vim_command(text) {
+            VimCommand::Align(op) => {
+                requests.align.write(crate::render::align::AlignRequest {
+                    op,
+                    members: visual_members.to_vec(),
                 });
-                info!("[CMD] :crawl {} (no_flow={})", path, no_flow);
+                info!("[CMD] :{} {} ({} visual members)", cmd, arg, visual_members.len());
+            }
+            VimCommand::Unknown(usage) => {
+                status.set(format!("error: :{}", usage));
+                warn!("[CMD] :{} — bad argument: {}", cmd, arg);
+            }
+            _ => {}
+        },
+        "tabnew" | "tabn" | "tabnext" | "tabp" | "tabprev" | "tabprevious" | "tabc"
+        | "tabclose" => {
+            if let VimCommand::Tab(request) = parse_vim_command(text) {
+                requests.tab.write(request);
+                info!("[CMD] :{}", cmd);
+            }
+        }
+        "flow" => match parse_vim_command(text) {
+            VimCommand::Flow(show) => {
+                requests.flow.write(crate::crawler::flow::FlowRequest(show));
+                info!("[CMD] :flow {}", arg);
+            }
+            _ => {
+                status.set("error: :flow expects on or off");
+                warn!("[CMD] :flow — bad argument: {}", arg);
+            }
+        },
+        "callers" | "callees" => {
+            if let VimCommand::Callers(callers) = parse_vim_command(text) {
+                requests
+                    .callers
+                    .write(crate::crawler::callers::CallersRequest(callers));
+                info!("[CMD] :{}", cmd);
+            }
+        }
+        "grid" => match parse_vim_command(text) {
+            VimCommand::Grid(style) => {
+                config.background_grid = style;
+                status.set(match style {
+                    Some(style) => format!("Grid: {:?}", style).to_lowercase(),
+                    None => "Grid off".to_string(),
+                });
+                info!("[CMD] :grid {}", arg);
+            }
+            _ => {
+                status.set("error: :grid expects dots, lines or off");
+                warn!("[CMD] :grid — bad argument: {}", arg);
+            }
+        },
+        "minimap" => match parse_vim_command(text) {
+            VimCommand::Minimap(on) => {
+                config.minimap = on.unwrap_or(!config.minimap);
+                status.set(if config.minimap { "Minimap on" } else { "Minimap off" });
+                info!("[CMD] :minimap → {}", config.minimap);
+            }
+            _ => {
+                status.set("error: :minimap expects on or off");
+                warn!("[CMD] :minimap — bad argument: {}", arg);
+            }
+        },
+        "bundle" => match parse_vim_command(text) {
+            VimCommand::Bundle(on) => {
+                config.bundle_edges = on.unwrap_or(!config.bundle_edges);
+                status.set(if config.bundle_edges { "Edge bundling on" } else { "Edge bundling off" });
+                info!("[CMD] :bundle → {}", config.bundle_edges);
+            }
+            _ => {
+                status.set("error: :bundle expects on or off");
+                warn!("[CMD] :bundle — bad argument: {}", arg);
+            }
+        },
+        "theme" => match parse_vim_command(text) {
+            VimCommand::Theme(theme) => {
+                config.theme = theme;
+                status.set(format!("Theme: {}", theme.name()));
+                info!("[CMD] :theme {}", theme.name());
+            }
+            _ => {
+                status.set("error: :theme expects dark or light");
+                warn!("[CMD] :theme — unknown theme: {}", arg);
+            }
+        },
+        "shape" => match parse_vim_command(text) {
+            VimCommand::Shape(shape) => {
+                requests.shape.write(crate::render::shapes::ShapeRequest(shape));
+                info!("[CMD] :shape {}", shape.name());
+            }
+            _ => {
+                status.set("error: :shape expects rect, ellipse or diamond");
+                warn!("[CMD] :shape — unknown shape: {}", arg);
+            }
+        },
+        "color" => match parse_vim_command(text) {
+            VimCommand::Color(color) => {
+                requests.recolor.write(crate::render::recolor::RecolorRequest(color));
+                info!("[CMD] :color {}", arg);
+            }
+            _ => {
+                status.set(format!("error: invalid color: {}", arg));
+                warn!("[CMD] :color — bad argument: {}", arg);
+            }
+        },
+        "set" => match parse_vim_command(text) {
+            VimCommand::Set { option, value } => {
+                let name = option.strip_suffix('?').unwrap_or(option);
+                let Some(setting) = crate::core::settings::find_setting(name) else {
+                    status.set(format!("error: unknown option: {}", name));
+                    warn!("[CMD] Unknown option: :{}", text);
+                    return;
+                };
+                // `:set key?` and a bare `:set key` show the current value.
+                if option.ends_with('?') || value.is_empty() {
+                    status.set(format!("{}={}", name, setting.get(config)));
+                    return;
+                }
+                match setting.set(config, value) {
+                    Ok(()) => {
+                        status.set(format!("{}={}", name, setting.get(config)));
+                        info!("[CMD] :set {} {}", name, value);
+                    }
+                    Err(e) => {
+                        status.set(format!("error: {}: {}", name, e));
+                        warn!("[CMD] :set {} — {}", name, e);
+                    }
+                }
             }
+            _ => {
+                status.set(format!("error: unknown option: :{}", text));
+                warn!("[CMD] Unknown option: :{}", text);
+            }
+        },
+        "grep" | "nohl" | "nohlsearch" if cmd != "grep" || arg.is_empty() => {
+            grep.0 = None;
+            status.set("Filter cleared");
+            info!("[CMD] :{} → filter cleared", cmd);
         }
+        "grep" => match crate::render::grep::GrepPattern::parse(arg) {
+            Ok(_) => {
+                grep.0 = Some(arg.to_string());
+                status.set(format!("Filter: {}", arg));
+                info!("[CMD] :grep {}", arg);
+            }
+            Err(e) => {
+                status.set(format!("error: {}", e));
+                warn!("[CMD] :grep — {}", e);
+            }
+        },
         "q" | "quit" => {
             info!("[CMD] :q");
             std::process::exit(0);
@@ -700,24 +1240,19 @@ pub fn vim_cmdline_system(
     mut pending_load: ResMut<PendingLoad>,
     mut status: ResMut<crate::core::resources::StatusMessage>,
     mut finder: ResMut<crate::ui::fuzzy::FuzzyFinderState>,
-    node_query: Query<
-        (
-            Entity,
-            &Transform,
-            &crate::core::components::TextData,
-            &crate::core::components::NodeColor,
-        ),
-        With<CanvasNode>,
-    >,
-    edge_query: Query<(Entity, &Edge)>,
+    canvas: SaveSources,
     camera_query: Query<(&Transform, &Projection), With<MainCamera>>,
-    mut crawl_events: MessageWriter<crate::crawler::CrawlRequest>,
+    mut requests: CmdLineRequests,
+    mut config: ResMut<crate::core::config::GlyphConfig>,
+    mut grep: ResMut<crate::core::resources::GrepFilter>,
+    mut visual: ResMut<crate::core::resources::VisualSelection>,
 ) {
     let ctrl = keycodes.pressed(KeyCode::ControlLeft) || keycodes.pressed(KeyCode::ControlRight);
 
     // Cancel: Esc or Ctrl+[
     if keys.just_pressed(Key::Escape) || (ctrl && keycodes.just_pressed(KeyCode::BracketLeft)) {
         cmdline.text.clear();
+        visual.0.clear();
         next_state.set(crate::core::state::InputMode::VimNormal);
         info!("→ VimNormal (cmdline cancelled)");
         return;
@@ -727,6 +1262,8 @@ pub fn vim_cmdline_system(
     if keys.just_pressed(Key::Enter) {
         let text = cmdline.text.trim().to_string();
         cmdline.text.clear();
+        // A command typed from visual mode ends it; `:align` / `:distribute` act on the set.
+        let visual_members: Vec<Entity> = std::mem::take(&mut visual.0).into_iter().collect();
         next_state.set(crate::core::state::InputMode::VimNormal);
         info!("→ VimNormal (executed: :{})", text);
 
@@ -749,10 +1286,12 @@ pub fn vim_cmdline_system(
             &mut recent,
             &mut pending_load,
             &mut status,
-            &node_query,
-            &edge_query,
+            &canvas,
             &camera_query,
-            &mut crawl_events,
+            &mut requests,
+            &mut config,
+            &mut grep,
+            &visual_members,
         );
         return;
     }
@@ -775,9 +1314,11 @@ pub fn vim_cmdline_system(
 
 /// Floating legend panel: lists each source file with its halo color swatch.
 /// Only shown when crawled nodes (nodes with SourceLocation) are present.
+/// The header shows the last crawl's root, file count and age.
 pub fn ui_legend_system(
     mut contexts: EguiContexts,
     node_query: Query<&SourceLocation, With<CanvasNode>>,
+    crawl_info: Res<crate::crawler::CrawlInfo>,
 ) {
     // Collect unique absolute paths, sorted for stable ordering.
     let mut files: Vec<String> = node_query
@@ -798,6 +1339,10 @@ pub fn ui_legend_system(
         .collapsible(true)
         .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-8.0, 40.0))
         .show(ctx, |ui| {
+            if let Some(summary) = crawl_info.summary() {
+                ui.label(egui::RichText::new(summary).small().weak());
+                ui.separator();
+            }
             for file in &files {
                 let (r, g, b) = crate::render::cluster::palette_rgb(file);
                 let swatch = egui::Color32::from_rgb(
@@ -831,14 +1376,46 @@ pub fn status_message_tick_system(
     }
 }
 
+/// Saved edges of the active tab, counted in the bottom bar.
+type StatsEdgeQuery<'w, 's> =
+    Query<'w, 's, (), (With<Edge>, Without<FlowBypass>, Without<InactiveCanvas>)>;
+
+/// Live counts for the bottom bar: the active tab's nodes and saved edges,
+/// and the camera's orthographic scale.
+#[derive(SystemParam)]
+pub struct CanvasStats<'w, 's> {
+    nodes: Query<'w, 's, (), (With<CanvasNode>, Without<InactiveCanvas>)>,
+    edges: StatsEdgeQuery<'w, 's>,
+    projection: Query<'w, 's, &'static Projection, With<MainCamera>>,
+}
+
+impl CanvasStats<'_, '_> {
+    fn label(&self) -> String {
+        let scale = match self.projection.single() {
+            Ok(Projection::Orthographic(ortho)) => ortho.scale,
+            _ => 1.0,
+        };
+        stats_label(self.nodes.iter().len(), self.edges.iter().len(), scale)
+    }
+}
+
+/// `nodes: N  edges: M  zoom: X%`, 100% at scale 1.0 (zooming in lowers the scale).
+fn stats_label(nodes: usize, edges: usize, scale: f32) -> String {
+    let zoom = (100.0 / scale.max(f32::EPSILON)).round();
+    format!("nodes: {}  edges: {}  zoom: {}%", nodes, edges, zoom)
+}
+
 /// Bottom bar: mode indicator and vim command line.
 /// Shows `-- MODE --` normally; shows `:[text]|` in VimCommand.
-/// A status message (crawl result / error) is shown on the right when active.
+/// A status message (crawl result / error) is shown on the right when active,
+/// and a spinner with the file count while a crawl runs.
 pub fn ui_bottom_bar_system(
     mut contexts: EguiContexts,
     state: Res<State<crate::core::state::InputMode>>,
     cmdline: Res<VimCmdLine>,
     status: Res<crate::core::resources::StatusMessage>,
+    crawl_job: Option<Res<crate::crawler::CrawlJob>>,
+    stats: CanvasStats,
 ) {
     let Ok(ctx) = contexts.ctx_mut() else {
         return;
@@ -884,23 +1461,45 @@ pub fn ui_bottom_bar_system(
                                 .color(egui::Color32::from_rgb(160, 140, 220)),
                         );
                     }
+                    crate::core::state::InputMode::VimVisual => {
+                        ui.label(
+                            egui::RichText::new("-- VISUAL --")
+                                .strong()
+                                .color(egui::Color32::from_rgb(255, 115, 190)),
+                        );
+                    }
                 }
 
-                // Status / error message — right-aligned, fades over the last second.
-                if status.timer > 0.0 && !status.text.is_empty() {
-                    let alpha = (status.timer.min(1.0) * 255.0) as u8;
-                    let is_error = status.text.starts_with("crawl: ")
-                        || status.text.starts_with("Error")
-                        || status.text.starts_with("error");
-                    let color = if is_error {
-                        egui::Color32::from_rgba_premultiplied(240, 100, 80, alpha)
-                    } else {
-                        egui::Color32::from_rgba_premultiplied(100, 220, 130, alpha)
-                    };
-                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                // Canvas stats on the far right; the status / error message
+                // left of them, fading over the last second.
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(
+                        egui::RichText::new(stats.label())
+                            .monospace()
+                            .color(egui::Color32::GRAY),
+                    );
+                    if status.timer > 0.0 && !status.text.is_empty() {
+                        let alpha = (status.timer.min(1.0) * 255.0) as u8;
+                        let is_error = status.text.starts_with("crawl: ")
+                            || status.text.starts_with("Error")
+                            || status.text.starts_with("error");
+                        let color = if is_error {
+                            egui::Color32::from_rgba_premultiplied(240, 100, 80, alpha)
+                        } else {
+                            egui::Color32::from_rgba_premultiplied(100, 220, 130, alpha)
+                        };
+                        ui.add_space(16.0);
                         ui.label(egui::RichText::new(&status.text).color(color));
-                    });
-                }
+                    }
+                    if let Some(job) = &crawl_job {
+                        ui.add_space(16.0);
+                        ui.label(
+                            egui::RichText::new(job.progress_label())
+                                .color(egui::Color32::LIGHT_GRAY),
+                        );
+                        ui.spinner();
+                    }
+                });
             });
         });
 }
@@ -909,6 +1508,13 @@ pub fn ui_bottom_bar_system(
 mod tests {
     use super::*;
 
+    #[test]
+    fn stats_label_reports_zoom_relative_to_scale_one() {
+        assert_eq!(stats_label(12, 30, 1.0), "nodes: 12  edges: 30  zoom: 100%");
+        assert_eq!(stats_label(0, 0, 0.5), "nodes: 0  edges: 0  zoom: 200%");
+        assert_eq!(stats_label(1, 0, 4.0), "nodes: 1  edges: 0  zoom: 25%");
+    }
+
     #[test]
     fn parse_empty() {
         assert_eq!(parse_vim_command(""), VimCommand::Empty);
@@ -916,15 +1522,21 @@ mod tests {
 
     #[test]
     fn parse_write_no_path() {
-        assert_eq!(parse_vim_command("w"), VimCommand::Write { path: None });
-        assert_eq!(parse_vim_command("write"), VimCommand::Write { path: None });
+        let write = VimCommand::Write { path: None, force: false };
+        assert_eq!(parse_vim_command("w"), write);
+        assert_eq!(parse_vim_command("write"), write);
+        assert_eq!(parse_vim_command("w!"), VimCommand::Write { path: None, force: true });
     }
 
     #[test]
     fn parse_write_with_path() {
         assert_eq!(
             parse_vim_command("w foo.glyph"),
-            VimCommand::Write { path: Some("foo.glyph") }
+            VimCommand::Write { path: Some("foo.glyph"), force: false }
+        );
+        assert_eq!(
+            parse_vim_command("write! foo.glyph"),
+            VimCommand::Write { path: Some("foo.glyph"), force: true }
         );
     }
 
@@ -949,7 +1561,15 @@ mod tests {
     fn parse_crawl() {
         assert_eq!(
             parse_vim_command("crawl ./src"),
-            VimCommand::Crawl { path: "./src", no_flow: false }
+            VimCommand::Crawl {
+                paths: vec!["./src"],
+                no_flow: false,
+                max_depth: None,
+                exclude: vec![],
+                follow_links: false,
+                respect_gitignore: false,
+                mode: crate::crawler::CrawlMode::Calls,
+            }
         );
     }
 
@@ -957,7 +1577,15 @@ mod tests {
     fn parse_crawl_no_flow_suffix() {
         assert_eq!(
             parse_vim_command("crawl ./src --no-flow"),
-            VimCommand::Crawl { path: "./src", no_flow: true }
+            VimCommand::Crawl {
+                paths: vec!["./src"],
+                no_flow: true,
+                max_depth: None,
+                exclude: vec![],
+                follow_links: false,
+                respect_gitignore: false,
+                mode: crate::crawler::CrawlMode::Calls,
+            }
         );
     }
 
@@ -965,15 +1593,278 @@ mod tests {
     fn parse_crawl_no_flow_prefix() {
         assert_eq!(
             parse_vim_command("crawl --no-flow ./src"),
-            VimCommand::Crawl { path: "./src", no_flow: true }
+            VimCommand::Crawl {
+                paths: vec!["./src"],
+                no_flow: true,
+                max_depth: None,
+                exclude: vec![],
+                follow_links: false,
+                respect_gitignore: false,
+                mode: crate::crawler::CrawlMode::Calls,
+            }
         );
     }
 
+    #[test]
+    fn parse_crawl_depth() {
+        assert_eq!(
+            parse_vim_command("crawl ./src --depth 1"),
+            VimCommand::Crawl {
+                paths: vec!["./src"],
+                no_flow: false,
+                max_depth: Some(1),
+                exclude: vec![],
+                follow_links: false,
+                respect_gitignore: false,
+                mode: crate::crawler::CrawlMode::Calls,
+            }
+        );
+        assert!(matches!(parse_vim_command("crawl ./src --depth x"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_crawl_follow_links() {
+        assert!(matches!(
+            parse_vim_command("crawl ./ --follow-links"),
+            VimCommand::Crawl { follow_links: true, .. }
+        ));
+    }
+
     #[test]
     fn parse_crawl_missing_path() {
         assert!(matches!(parse_vim_command("crawl"), VimCommand::Unknown(_)));
     }
 
+    #[test]
+    fn parse_simplify() {
+        assert_eq!(parse_vim_command("simplify"), VimCommand::Simplify);
+    }
+
+    #[test]
+    fn parse_flow() {
+        assert_eq!(parse_vim_command("flow"), VimCommand::Flow(None));
+        assert_eq!(parse_vim_command("flow on"), VimCommand::Flow(Some(true)));
+        assert_eq!(parse_vim_command("flow off"), VimCommand::Flow(Some(false)));
+        assert!(matches!(parse_vim_command("flow yes"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_callers() {
+        assert_eq!(parse_vim_command("callers"), VimCommand::Callers(true));
+        assert_eq!(parse_vim_command("callees"), VimCommand::Callers(false));
+    }
+
+    #[test]
+    fn parse_color() {
+        assert_eq!(
+            parse_vim_command("color #ff0000"),
+            VimCommand::Color(Color::srgb(1.0, 0.0, 0.0))
+        );
+        assert_eq!(
+            parse_vim_command("color 00ff00"),
+            VimCommand::Color(Color::srgb(0.0, 1.0, 0.0))
+        );
+        assert!(matches!(parse_vim_command("color blue"), VimCommand::Color(_)));
+        assert!(matches!(parse_vim_command("color #zzzzzz"), VimCommand::Unknown(_)));
+        assert!(matches!(parse_vim_command("color"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_estyle() {
+        use crate::render::edge_stroke::StrokeChange;
+        assert_eq!(
+            parse_vim_command("estyle dashed"),
+            VimCommand::EdgeStroke(StrokeChange::Dashed)
+        );
+        assert_eq!(
+            parse_vim_command("estyle bold"),
+            VimCommand::EdgeStroke(StrokeChange::Bold)
+        );
+        assert!(matches!(parse_vim_command("estyle"), VimCommand::Unknown(_)));
+        assert!(matches!(parse_vim_command("estyle wavy"), VimCommand::Unknown(_)));
+        assert_eq!(parse_vim_command("straighten"), VimCommand::Straighten);
+    }
+
+    #[test]
+    fn parse_history() {
+        assert_eq!(parse_vim_command("history"), VimCommand::History { path: None });
+        assert_eq!(
+            parse_vim_command("history > out.log"),
+            VimCommand::History { path: Some("out.log") }
+        );
+        assert_eq!(
+            parse_vim_command("history >out.log"),
+            VimCommand::History { path: Some("out.log") }
+        );
+        assert!(matches!(parse_vim_command("history >"), VimCommand::Unknown(_)));
+        assert!(matches!(parse_vim_command("history out.log"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_template() {
+        assert_eq!(
+            parse_vim_command("template save trio"),
+            VimCommand::Template {
+                insert: false,
+                name: "trio"
+            }
+        );
+        assert_eq!(
+            parse_vim_command("template insert trio"),
+            VimCommand::Template {
+                insert: true,
+                name: "trio"
+            }
+        );
+        assert!(matches!(parse_vim_command("template save"), VimCommand::Unknown(_)));
+        assert!(matches!(parse_vim_command("template load trio"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_yank() {
+        assert_eq!(parse_vim_command("yank"), VimCommand::Yank);
+    }
+
+    #[test]
+    fn parse_path() {
+        assert_eq!(parse_vim_command("path"), VimCommand::Path);
+        assert_eq!(parse_vim_command("pin"), VimCommand::Pin);
+        assert_eq!(parse_vim_command("stats"), VimCommand::Stats);
+    }
+
+    #[test]
+    fn parse_connect() {
+        use crate::input::connect::ConnectTarget;
+        assert_eq!(
+            parse_vim_command("connect 'a 'b"),
+            VimCommand::Connect(ConnectTarget::Marks('a', 'b'))
+        );
+        assert_eq!(
+            parse_vim_command("connect load config"),
+            VimCommand::Connect(ConnectTarget::Label("load config".into()))
+        );
+        assert!(matches!(parse_vim_command("connect"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_tabs() {
+        use crate::core::tabs::TabRequest;
+        assert_eq!(parse_vim_command("tabnew"), VimCommand::Tab(TabRequest::New));
+        assert_eq!(parse_vim_command("tabn"), VimCommand::Tab(TabRequest::Next));
+        assert_eq!(parse_vim_command("tabprevious"), VimCommand::Tab(TabRequest::Prev));
+        assert_eq!(parse_vim_command("tabclose"), VimCommand::Tab(TabRequest::Close));
+    }
+
+    #[test]
+    fn parse_grid() {
+        use crate::core::config::GridStyle;
+        assert_eq!(parse_vim_command("grid dots"), VimCommand::Grid(Some(GridStyle::Dots)));
+        assert_eq!(parse_vim_command("grid off"), VimCommand::Grid(None));
+        assert!(matches!(parse_vim_command("grid"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_minimap() {
+        assert_eq!(parse_vim_command("minimap"), VimCommand::Minimap(None));
+        assert_eq!(parse_vim_command("minimap off"), VimCommand::Minimap(Some(false)));
+        assert!(matches!(parse_vim_command("minimap big"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_bundle() {
+        assert_eq!(parse_vim_command("bundle"), VimCommand::Bundle(None));
+        assert_eq!(parse_vim_command("bundle on"), VimCommand::Bundle(Some(true)));
+        assert_eq!(parse_vim_command("bundle off"), VimCommand::Bundle(Some(false)));
+        assert!(matches!(parse_vim_command("bundle maybe"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_theme() {
+        use crate::core::config::ThemeName;
+        assert_eq!(parse_vim_command("theme light"), VimCommand::Theme(ThemeName::Light));
+        assert_eq!(parse_vim_command("theme dark"), VimCommand::Theme(ThemeName::Dark));
+        assert!(matches!(parse_vim_command("theme"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_reverse() {
+        assert_eq!(parse_vim_command("reverse"), VimCommand::Reverse);
+    }
+
+    #[test]
+    fn parse_export() {
+        assert_eq!(
+            parse_vim_command("export graph.dot"),
+            VimCommand::Export { path: "graph.dot" }
+        );
+        assert!(matches!(parse_vim_command("export"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_import() {
+        assert_eq!(
+            parse_vim_command("import flow.mmd"),
+            VimCommand::Import { path: "flow.mmd" }
+        );
+        assert!(matches!(parse_vim_command("import"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_set_grid() {
+        assert_eq!(
+            parse_vim_command("set grid 20"),
+            VimCommand::Set { option: "grid", value: "20" }
+        );
+        assert_eq!(
+            parse_vim_command("set minimap?"),
+            VimCommand::Set { option: "minimap?", value: "" }
+        );
+    }
+
+    #[test]
+    fn parse_layout() {
+        use crate::render::layout::LayoutKind;
+        assert_eq!(parse_vim_command("layout circle"), VimCommand::Layout(LayoutKind::Circle));
+        assert_eq!(parse_vim_command("layout grid"), VimCommand::Layout(LayoutKind::Grid));
+        assert!(matches!(parse_vim_command("layout spiral"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_shape() {
+        use crate::core::components::NodeShape;
+        assert_eq!(parse_vim_command("shape diamond"), VimCommand::Shape(NodeShape::Diamond));
+        assert_eq!(parse_vim_command("shape ellipse"), VimCommand::Shape(NodeShape::Ellipse));
+        assert!(matches!(parse_vim_command("shape"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_align_and_distribute() {
+        use crate::render::align::{AlignEdge, AlignOp, Axis};
+        assert_eq!(
+            parse_vim_command("align vcenter"),
+            VimCommand::Align(AlignOp::Align(AlignEdge::VCenter))
+        );
+        assert_eq!(
+            parse_vim_command("distribute h"),
+            VimCommand::Align(AlignOp::Distribute(Axis::Horizontal))
+        );
+        assert!(matches!(parse_vim_command("align middle"), VimCommand::Unknown(_)));
+        assert!(matches!(parse_vim_command("distribute"), VimCommand::Unknown(_)));
+        assert_eq!(
+            parse_vim_command("sort!"),
+            VimCommand::Align(crate::render::align::AlignOp::Sort { reverse: true })
+        );
+        assert!(matches!(parse_vim_command("sort x"), VimCommand::Unknown(_)));
+    }
+
+    #[test]
+    fn parse_grep() {
+        assert_eq!(parse_vim_command("grep parse"), VimCommand::Grep { pattern: Some("parse") });
+        assert_eq!(parse_vim_command("grep /^on_/"), VimCommand::Grep { pattern: Some("/^on_/") });
+        assert_eq!(parse_vim_command("grep"), VimCommand::Grep { pattern: None });
+        assert_eq!(parse_vim_command("nohl"), VimCommand::Grep { pattern: None });
+    }
+
     #[test]
     fn parse_quit() {
         assert_eq!(parse_vim_command("q"), VimCommand::Quit);
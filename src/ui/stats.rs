@@ -0,0 +1,218 @@
+//! `:stats`: a window summarising the active canvas as a call graph — node
+//! and edge counts, roots, depth and the most-connected functions. The
+//! metrics are pure functions over an edge list; the window shows the
+//! numbers from when the command ran.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+use crate::core::components::{
+    CanvasNode, Edge, FlowBypass, InactiveCanvas, SourceLocation, TextData,
+};
+
+/// Message sent by `:stats`.
+#[derive(Message)]
+pub struct StatsRequest;
+
+/// Structure of a graph; `K` identifies a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphMetrics<K> {
+    pub nodes: usize,
+    pub edges: usize,
+    /// Nodes no other node points to (hierarchy level 0).
+    pub roots: usize,
+    /// Most hops from a root to any node reachable from one.
+    pub max_depth: usize,
+    /// Node with the highest in-degree, and that degree.
+    pub most_called: Option<(K, usize)>,
+    /// Node with the highest out-degree, and that degree.
+    pub most_callees: Option<(K, usize)>,
+}
+
+/// Metrics of the graph with `nodes` and directed `edges`. Edges whose ends
+/// are not in `nodes` are ignored; self-edges (recursion) are counted as edges
+/// but not toward degrees or roots. Ties go to the smallest key.
+pub fn graph_metrics<K: Copy + Eq + Hash + Ord>(
+    nodes: &[K],
+    edges: &[(K, K)],
+) -> GraphMetrics<K> {
+    let known: HashSet<K> = nodes.iter().copied().collect();
+    let edges: Vec<(K, K)> = edges
+        .iter()
+        .copied()
+        .filter(|(s, t)| known.contains(s) && known.contains(t))
+        .collect();
+
+    let mut adjacency: HashMap<K, Vec<K>> = HashMap::new();
+    let mut in_degree: HashMap<K, usize> = HashMap::new();
+    let mut out_degree: HashMap<K, usize> = HashMap::new();
+    for &(source, target) in edges.iter().filter(|(s, t)| s != t) {
+        adjacency.entry(source).or_default().push(target);
+        *in_degree.entry(target).or_default() += 1;
+        *out_degree.entry(source).or_default() += 1;
+    }
+
+    let roots: Vec<K> = nodes.iter().copied().filter(|n| !in_degree.contains_key(n)).collect();
+    GraphMetrics {
+        nodes: known.len(),
+        edges: edges.len(),
+        roots: roots.len(),
+        max_depth: max_depth(&adjacency, &roots),
+        most_called: busiest(&in_degree),
+        most_callees: busiest(&out_degree),
+    }
+}
+
+/// Breadth-first distance of the farthest node from the nearest of `roots`.
+fn max_depth<K: Copy + Eq + Hash>(adjacency: &HashMap<K, Vec<K>>, roots: &[K]) -> usize {
+    let mut depth: HashMap<K, usize> = roots.iter().map(|&r| (r, 0)).collect();
+    let mut queue: VecDeque<K> = roots.iter().copied().collect();
+    let mut deepest = 0;
+    while let Some(node) = queue.pop_front() {
+        let next = depth[&node] + 1;
+        for &target in adjacency.get(&node).into_iter().flatten() {
+            if !depth.contains_key(&target) {
+                depth.insert(target, next);
+                deepest = deepest.max(next);
+                queue.push_back(target);
+            }
+        }
+    }
+    deepest
+}
+
+/// Key with the largest degree; the smallest key wins a tie.
+fn busiest<K: Copy + Ord>(degrees: &HashMap<K, usize>) -> Option<(K, usize)> {
+    degrees
+        .iter()
+        .map(|(&k, &d)| (k, d))
+        .max_by(|(ka, a), (kb, b)| a.cmp(b).then(kb.cmp(ka)))
+}
+
+/// What the window shows: the metrics with node names resolved.
+#[derive(Clone, Debug)]
+pub struct CanvasStats {
+    pub metrics: GraphMetrics<Entity>,
+    /// Nodes with a `SourceLocation`, i.e. crawled functions.
+    pub functions: usize,
+    pub most_called: Option<(String, usize)>,
+    pub most_callees: Option<(String, usize)>,
+}
+
+/// Stats computed by the last `:stats`; the window is open while `Some`.
+#[derive(Resource, Default)]
+pub struct StatsState {
+    pub stats: Option<CanvasStats>,
+}
+
+type StatsNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static TextData, Has<SourceLocation>),
+    (With<CanvasNode>, Without<InactiveCanvas>),
+>;
+
+/// Handles `StatsRequest`: measure the active canvas and open the window.
+pub fn handle_stats_requests(
+    mut requests: MessageReader<StatsRequest>,
+    mut state: ResMut<StatsState>,
+    nodes: StatsNodeQuery,
+    edges: Query<&Edge, (Without<FlowBypass>, Without<InactiveCanvas>)>,
+) {
+    if requests.read().count() == 0 {
+        return;
+    }
+    let ids: Vec<Entity> = nodes.iter().map(|(entity, ..)| entity).collect();
+    let pairs: Vec<(Entity, Entity)> = edges.iter().map(|e| (e.source, e.target)).collect();
+    let metrics = graph_metrics(&ids, &pairs);
+    let name = |top: Option<(Entity, usize)>| {
+        top.and_then(|(entity, degree)| {
+            let (_, text, _) = nodes.get(entity).ok()?;
+            Some((text.content.clone(), degree))
+        })
+    };
+    state.stats = Some(CanvasStats {
+        functions: nodes.iter().filter(|(.., is_fn)| *is_fn).count(),
+        most_called: name(metrics.most_called),
+        most_callees: name(metrics.most_callees),
+        metrics,
+    });
+}
+
+/// The stats window. Esc or the close button hides it.
+pub fn stats_ui_system(mut contexts: EguiContexts, mut state: ResMut<StatsState>) {
+    let Some(stats) = &state.stats else {
+        return;
+    };
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    let m = &stats.metrics;
+    let top = |entry: &Option<(String, usize)>| match entry {
+        Some((name, degree)) => format!("{} ({})", name, degree),
+        None => "—".to_string(),
+    };
+    let rows = [
+        ("Nodes", m.nodes.to_string()),
+        ("Edges", m.edges.to_string()),
+        ("Functions", stats.functions.to_string()),
+        ("Roots", m.roots.to_string()),
+        ("Max depth", m.max_depth.to_string()),
+        ("Most called", top(&stats.most_called)),
+        ("Most callees", top(&stats.most_callees)),
+    ];
+    let mut open = true;
+    egui::Window::new("Stats")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+        .show(ctx, |ui| {
+            egui::Grid::new("stats_grid").num_columns(2).show(ui, |ui| {
+                for (label, value) in &rows {
+                    ui.label(egui::RichText::new(*label).color(egui::Color32::GRAY));
+                    ui.label(egui::RichText::new(value).monospace());
+                    ui.end_row();
+                }
+            });
+        });
+    if !open || ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+        state.stats = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_of_a_small_call_graph() {
+        // main → parse → lex, main → eval → lex, eval → eval; helper stands alone.
+        let nodes = ["eval", "helper", "lex", "main", "parse"];
+        let edges = [
+            ("main", "parse"),
+            ("main", "eval"),
+            ("parse", "lex"),
+            ("eval", "lex"),
+            ("eval", "eval"),
+            ("main", "missing"),
+        ];
+        let m = graph_metrics(&nodes, &edges);
+        assert_eq!((m.nodes, m.edges, m.roots, m.max_depth), (5, 5, 2, 2));
+        assert_eq!(m.most_called, Some(("lex", 2)));
+        assert_eq!(m.most_callees, Some(("main", 2)));
+    }
+
+    #[test]
+    fn metrics_of_a_cycle_and_an_empty_graph() {
+        let m = graph_metrics(&[1, 2, 3], &[(1, 2), (2, 3), (3, 2)]);
+        assert_eq!((m.roots, m.max_depth), (1, 2));
+        assert_eq!(m.most_called, Some((2, 2)));
+
+        let m = graph_metrics::<u32>(&[], &[]);
+        assert_eq!((m.nodes, m.edges, m.roots, m.max_depth), (0, 0, 0, 0));
+        assert_eq!(m.most_called, None);
+    }
+}
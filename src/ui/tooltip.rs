@@ -0,0 +1,135 @@
+//! Hover tooltip: the full text of the node under the mouse and, for crawled
+//! nodes, the absolute `file:line` it came from. Node boxes truncate long
+//! labels and the file badge shows only a relative path.
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::core::components::{
+    CanvasNode, CollapseHidden, InactiveCanvas, MainCamera, NodeSize, SourceLocation, TextData,
+};
+use crate::core::resources::SpatialIndex;
+
+/// Nodes whose center is further than this from the cursor are not hit-tested
+/// (world units). Wider than any node box.
+const HOVER_SEARCH_RADIUS: f32 = 400.0;
+/// Offset of the tooltip from the pointer (logical pixels).
+const TOOLTIP_OFFSET: egui::Vec2 = egui::vec2(14.0, 18.0);
+/// Tooltip text wraps at this width (logical pixels).
+const TOOLTIP_MAX_WIDTH: f32 = 360.0;
+
+type TooltipNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        &'static TextData,
+        Option<&'static NodeSize>,
+        Option<&'static SourceLocation>,
+    ),
+    (
+        With<CanvasNode>,
+        Without<InactiveCanvas>,
+        Without<CollapseHidden>,
+    ),
+>;
+
+/// The entity of `candidates` (center, size) whose box contains `cursor`;
+/// the one whose center is nearest when boxes overlap.
+fn node_under_cursor(
+    cursor: Vec2,
+    candidates: impl IntoIterator<Item = (Entity, Vec2, Vec2)>,
+) -> Option<Entity> {
+    candidates
+        .into_iter()
+        .filter(|(_, center, size)| {
+            let d = (cursor - *center).abs();
+            d.x <= size.x * 0.5 && d.y <= size.y * 0.5
+        })
+        .min_by(|(_, a, _), (_, b, _)| {
+            a.distance_squared(cursor)
+                .total_cmp(&b.distance_squared(cursor))
+        })
+        .map(|(entity, ..)| entity)
+}
+
+/// Show the tooltip of the node under the mouse. Nothing while the pointer
+/// is over an egui panel or the node has no text.
+pub fn ui_node_tooltip_system(
+    mut contexts: EguiContexts,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    spatial: Res<SpatialIndex>,
+    nodes: TooltipNodeQuery,
+) {
+    let Some(cursor) = window_q.single().ok().and_then(|w| w.cursor_position()) else {
+        return;
+    };
+    let Ok((camera, cam_transform)) = camera_q.single() else {
+        return;
+    };
+    let Ok(world) = camera.viewport_to_world_2d(cam_transform, cursor) else {
+        return;
+    };
+    let (min, max) = (world - HOVER_SEARCH_RADIUS, world + HOVER_SEARCH_RADIUS);
+    let candidates = spatial
+        .entities_in_bounds(min.x, max.x, min.y, max.y)
+        .into_iter()
+        .filter_map(|e| {
+            let (transform, _, size, _) = nodes.get(e).ok()?;
+            let size = size.copied().unwrap_or_default().current;
+            Some((e, transform.translation.truncate(), size))
+        });
+    let Some(hovered) = node_under_cursor(world, candidates) else {
+        return;
+    };
+    let Ok((_, text, _, source)) = nodes.get(hovered) else {
+        return;
+    };
+    if text.content.is_empty() && source.is_none() {
+        return;
+    }
+
+    let Ok(ctx) = contexts.ctx_mut() else { return };
+    if ctx.is_pointer_over_area() {
+        return;
+    }
+    let Some(pointer) = ctx.pointer_hover_pos() else {
+        return;
+    };
+    egui::Area::new(egui::Id::new("node_tooltip"))
+        .order(egui::Order::Tooltip)
+        .interactable(false)
+        .fixed_pos(pointer + TOOLTIP_OFFSET)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_max_width(TOOLTIP_MAX_WIDTH);
+                if !text.content.is_empty() {
+                    ui.label(&text.content);
+                }
+                if let Some(loc) = source {
+                    let path = format!("{}:{}", loc.file, loc.line);
+                    ui.label(egui::RichText::new(path).small().weak());
+                }
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hover_picks_the_box_under_the_cursor() {
+        let mut world = World::new();
+        let (a, b) = (world.spawn_empty().id(), world.spawn_empty().id());
+        let boxes = [
+            (a, Vec2::ZERO, Vec2::new(100.0, 40.0)),
+            (b, Vec2::new(60.0, 0.0), Vec2::new(100.0, 40.0)),
+        ];
+        assert_eq!(node_under_cursor(Vec2::new(-40.0, 10.0), boxes), Some(a));
+        assert_eq!(node_under_cursor(Vec2::new(40.0, 0.0), boxes), Some(b));
+        assert_eq!(node_under_cursor(Vec2::new(0.0, 30.0), boxes), None);
+    }
+}
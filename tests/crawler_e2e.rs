@@ -4,8 +4,10 @@
 //! edge connections, and node labels (visual structure).
 
 use bevy::prelude::*;
-use glyph::core::components::{Edge, TextData};
-use glyph::crawler::{handle_crawl_requests, CrawlRequest};
+use glyph::core::components::{Edge, FlowNodeId, TextData};
+use glyph::crawler::{
+    finish_crawl_system, handle_crawl_requests, CrawlJob, CrawlMode, CrawlRequest, WatchState,
+};
 use glyph::render::layout::ForceLayoutActive;
 use glyph::core::resources::SpatialIndex;
 use glyph::core::spatial::{spatial_index_cleanup_system, update_spatial_index_system};
@@ -17,17 +19,34 @@ fn crawler_e2e_app() -> App {
         .init_resource::<SpatialIndex>()
         .init_resource::<ForceLayoutActive>()
         .init_resource::<glyph::crawler::WatchState>()
+        .init_resource::<glyph::crawler::CrawlInfo>()
+        .init_resource::<glyph::crawler::flow::CrawledFlow>()
         .init_resource::<glyph::core::resources::StatusMessage>()
         .insert_resource(glyph::core::config::GlyphConfig::default())
+        .init_resource::<glyph::core::config::Theme>()
+        .init_resource::<glyph::io::file_io::PendingCanvasCheckpoint>()
         .add_message::<CrawlRequest>()
         .add_systems(
             PostUpdate,
             (update_spatial_index_system, spatial_index_cleanup_system),
         )
-        .add_systems(Update, handle_crawl_requests);
+        .add_systems(Update, (handle_crawl_requests, finish_crawl_system).chain());
     app
 }
 
+/// Run frames until the background crawl's graph is on the canvas.
+fn run_crawl(app: &mut App) {
+    app.update();
+    for _ in 0..500 {
+        if !app.world().contains_resource::<CrawlJob>() {
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        app.update();
+    }
+    panic!("crawl did not finish");
+}
+
 /// Hierarchy layout constants (must match crawler/mod.rs).
 const FLOW_ROW_HEIGHT: f32 = 380.0;
 
@@ -48,11 +67,20 @@ fn baz() { foo(); }
 
     let mut app = crawler_e2e_app();
     app.world_mut().write_message(CrawlRequest {
-        path: dir_path.to_str().unwrap().to_string(),
+        paths: vec![dir_path.to_str().unwrap().to_string()],
         no_flow: false,
+        max_depth: None,
+        exclude: vec![],
+        follow_links: false,
+        respect_gitignore: false,
+        mode: CrawlMode::Calls,
     });
 
-    app.update();
+    run_crawl(&mut app);
+
+    let info = app.world().resource::<glyph::crawler::CrawlInfo>();
+    assert_eq!(info.file_count, 1, "one source file crawled");
+    assert!(info.crawled_at.is_some());
 
     let world = app.world_mut();
     let nodes: Vec<_> = world
@@ -88,6 +116,50 @@ fn baz() { foo(); }
     );
 }
 
+#[test]
+fn e2e_crawl_merges_several_roots_into_one_graph() {
+    let base = tempfile::tempdir().unwrap();
+    let (front, back) = (base.path().join("frontend"), base.path().join("backend"));
+    fs::create_dir_all(&front).unwrap();
+    fs::create_dir_all(&back).unwrap();
+    fs::write(front.join("lib.rs"), "fn render() { fetch(); }\n").unwrap();
+    fs::write(back.join("lib.rs"), "fn fetch() {}\n").unwrap();
+
+    let mut app = crawler_e2e_app();
+    app.world_mut().write_message(CrawlRequest {
+        paths: vec![
+            front.to_str().unwrap().to_string(),
+            back.to_str().unwrap().to_string(),
+        ],
+        no_flow: false,
+        max_depth: None,
+        exclude: vec![],
+        follow_links: false,
+        respect_gitignore: false,
+        mode: CrawlMode::Calls,
+    });
+    run_crawl(&mut app);
+
+    let info = app.world().resource::<glyph::crawler::CrawlInfo>();
+    assert_eq!(info.roots, ["frontend", "backend"]);
+    assert_eq!(info.file_count, 2);
+    assert_eq!(app.world().resource::<WatchState>().watch_paths.len(), 2);
+
+    let world = app.world_mut();
+    let ids: std::collections::HashMap<String, Entity> = world
+        .query::<(Entity, &FlowNodeId)>()
+        .iter(world)
+        .map(|(e, id)| (id.0.clone(), e))
+        .collect();
+    let (render, fetch) = (ids["frontend/lib.rs::render"], ids["backend/lib.rs::fetch"]);
+    let edges: Vec<(Entity, Entity)> = world
+        .query::<&Edge>()
+        .iter(world)
+        .map(|e| (e.source, e.target))
+        .collect();
+    assert_eq!(edges, [(render, fetch)], "the call crosses the two roots");
+}
+
 #[test]
 fn e2e_crawl_spawns_edges_between_callers_and_callees() {
     let dir = tempfile::tempdir().unwrap();
@@ -104,11 +176,16 @@ fn caller() { callee(); }
 
     let mut app = crawler_e2e_app();
     app.world_mut().write_message(CrawlRequest {
-        path: dir_path.to_str().unwrap().to_string(),
+        paths: vec![dir_path.to_str().unwrap().to_string()],
         no_flow: false,
+        max_depth: None,
+        exclude: vec![],
+        follow_links: false,
+        respect_gitignore: false,
+        mode: CrawlMode::Calls,
     });
 
-    app.update();
+    run_crawl(&mut app);
 
     let world = app.world_mut();
     let name_to_entity: std::collections::HashMap<String, Entity> = world
@@ -159,11 +236,16 @@ fn e() {}
 
     let mut app = crawler_e2e_app();
     app.world_mut().write_message(CrawlRequest {
-        path: dir_path.to_str().unwrap().to_string(),
+        paths: vec![dir_path.to_str().unwrap().to_string()],
         no_flow: false,
+        max_depth: None,
+        exclude: vec![],
+        follow_links: false,
+        respect_gitignore: false,
+        mode: CrawlMode::Calls,
     });
 
-    app.update();
+    run_crawl(&mut app);
 
     let world = app.world_mut();
     let positions: Vec<(f32, f32)> = world
@@ -192,3 +274,203 @@ fn e() {}
         }
     }
 }
+
+#[test]
+fn e2e_crawl_depth_prunes_and_keeps_source_locations() {
+    let dir = tempfile::tempdir().unwrap();
+    let dir_path = dir.path();
+
+    fs::write(
+        dir_path.join("lib.rs"),
+        r#"
+fn leaf() {}
+fn mid() { leaf(); }
+fn top() { mid(); }
+"#,
+    )
+    .unwrap();
+
+    let mut app = crawler_e2e_app();
+    app.world_mut().write_message(CrawlRequest {
+        paths: vec![dir_path.to_str().unwrap().to_string()],
+        no_flow: false,
+        max_depth: Some(1),
+        exclude: vec![],
+        follow_links: false,
+        respect_gitignore: false,
+        mode: CrawlMode::Calls,
+    });
+
+    run_crawl(&mut app);
+
+    let world = app.world_mut();
+    let nodes: std::collections::HashMap<String, Option<u32>> = world
+        .query::<(&TextData, Option<&glyph::core::components::SourceLocation>)>()
+        .iter(world)
+        .filter(|(td, _)| !td.content.is_empty())
+        .map(|(td, src)| (td.content.clone(), src.map(|s| s.line)))
+        .collect();
+
+    assert!(nodes.contains_key("top"));
+    assert!(nodes.contains_key("mid"));
+    assert!(!nodes.contains_key("leaf"), "leaf is two hops from the root: {:?}", nodes);
+    assert_eq!(nodes["top"], Some(4), "gd needs the source line of surviving nodes");
+}
+
+#[test]
+fn e2e_flow_off_hides_decisions_and_bypasses_them() {
+    use glyph::core::components::{CollapseHidden, DecisionNode, FlowBypass};
+    use glyph::crawler::flow::{sync_flow_system, CrawledFlow};
+    use glyph::render::collapse::sync_collapsed_system;
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("lib.rs"),
+        r#"
+fn run() {}
+fn main(x: bool) { if x { run(); } }
+"#,
+    )
+    .unwrap();
+
+    let mut app = crawler_e2e_app();
+    app.add_systems(
+        Update,
+        (sync_flow_system, sync_collapsed_system)
+            .chain()
+            .after(finish_crawl_system),
+    );
+    app.world_mut().write_message(CrawlRequest {
+        paths: vec![dir.path().to_str().unwrap().to_string()],
+        no_flow: false,
+        max_depth: None,
+        exclude: vec![],
+        follow_links: false,
+        respect_gitignore: false,
+        mode: CrawlMode::Calls,
+    });
+    run_crawl(&mut app);
+
+    app.world_mut().resource_mut::<CrawledFlow>().hide_decisions = true;
+    app.update();
+    app.update();
+
+    let world = app.world_mut();
+    let decisions: Vec<bool> = world
+        .query_filtered::<Has<CollapseHidden>, With<DecisionNode>>()
+        .iter(world)
+        .collect();
+    assert!(!decisions.is_empty(), "the if becomes a decision node");
+    assert!(decisions.iter().all(|hidden| *hidden), "decision nodes are hidden");
+    let names: std::collections::HashMap<Entity, String> = world
+        .query::<(Entity, &TextData)>()
+        .iter(world)
+        .map(|(e, td)| (e, td.content.clone()))
+        .collect();
+    let bypass: Vec<(String, String)> = world
+        .query_filtered::<&Edge, With<FlowBypass>>()
+        .iter(world)
+        .map(|e| (names[&e.source].clone(), names[&e.target].clone()))
+        .collect();
+    assert_eq!(bypass, vec![("main".to_string(), "run".to_string())]);
+
+    app.world_mut().resource_mut::<CrawledFlow>().hide_decisions = false;
+    app.update();
+    app.update();
+    let world = app.world_mut();
+    assert_eq!(world.query_filtered::<(), With<FlowBypass>>().iter(world).count(), 0);
+    assert_eq!(world.query_filtered::<(), With<CollapseHidden>>().iter(world).count(), 0);
+}
+
+#[test]
+fn e2e_undo_after_crawl_restores_the_hand_drawn_canvas() {
+    use glyph::core::history::{Action, UndoHistory};
+    use glyph::io::file_io::{
+        process_pending_restore_system, push_canvas_checkpoint_system, PendingCanvasRestore,
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("lib.rs"), "fn crawled() {}\n").unwrap();
+
+    let mut app = crawler_e2e_app();
+    app.init_resource::<UndoHistory>()
+        .init_resource::<PendingCanvasRestore>()
+        .init_resource::<glyph::core::resources::SelectedEdge>()
+        .add_systems(
+            Update,
+            (push_canvas_checkpoint_system, process_pending_restore_system)
+                .chain()
+                .after(finish_crawl_system),
+        );
+    app.world_mut().spawn((
+        glyph::core::components::CanvasNode,
+        Transform::from_xyz(10.0, 20.0, 0.0),
+        TextData {
+            content: "sketch".to_string(),
+        },
+        glyph::core::components::NodeColor(Color::WHITE),
+    ));
+    app.world_mut().write_message(CrawlRequest {
+        paths: vec![dir.path().to_str().unwrap().to_string()],
+        no_flow: false,
+        max_depth: None,
+        exclude: vec![],
+        follow_links: false,
+        respect_gitignore: false,
+        mode: CrawlMode::Calls,
+    });
+    run_crawl(&mut app);
+
+    fn texts(app: &mut App) -> Vec<String> {
+        let world = app.world_mut();
+        world.query::<&TextData>().iter(world).map(|t| t.content.clone()).collect()
+    }
+    assert_eq!(texts(&mut app), ["crawled"]);
+
+    // `u` reverts the single checkpoint the crawl pushed.
+    let action = app.world_mut().resource_mut::<UndoHistory>().pop_undo();
+    let Some(Action::ReplaceCanvas { before, .. }) = action else {
+        panic!("expected one ReplaceCanvas entry, got {:?}", action.map(|a| a.to_string()));
+    };
+    app.world_mut().resource_mut::<PendingCanvasRestore>().0 = Some(before);
+    app.update();
+    assert_eq!(texts(&mut app), ["sketch"]);
+    assert!(app.world().resource::<UndoHistory>().undo_stack.is_empty());
+}
+
+#[test]
+fn e2e_escape_cancels_a_running_crawl() {
+    use glyph::crawler::cancel_crawl_system;
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("lib.rs"), "fn crawled() {}\n").unwrap();
+
+    let mut app = crawler_e2e_app();
+    app.init_resource::<ButtonInput<KeyCode>>()
+        .add_systems(
+            Update,
+            cancel_crawl_system
+                .after(handle_crawl_requests)
+                .before(finish_crawl_system),
+        );
+    app.world_mut().write_message(CrawlRequest {
+        paths: vec![dir.path().to_str().unwrap().to_string()],
+        no_flow: false,
+        max_depth: None,
+        exclude: vec![],
+        follow_links: false,
+        respect_gitignore: false,
+        mode: CrawlMode::Calls,
+    });
+    // Esc in the frame the job starts, before its result can land.
+    app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Escape);
+    app.update();
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    app.update();
+
+    assert!(!app.world().contains_resource::<CrawlJob>());
+    let world = app.world_mut();
+    assert_eq!(world.query::<&TextData>().iter(world).count(), 0, "no nodes after cancel");
+    let status = &app.world().resource::<glyph::core::resources::StatusMessage>().text;
+    assert_eq!(status, "Crawl cancelled");
+}
@@ -1,13 +1,43 @@
 //! E2E tests for save/load: save round-trip, load from file, verify.
 
 use bevy::prelude::*;
-use glyph::core::components::{CanvasNode, Edge, MainCamera, NodeColor, TextData};
+use glyph::core::components::{Edge, MainCamera, TextData};
 use glyph::core::helpers::spawn_node_with_color;
-use glyph::io::file_io::{process_pending_load_system, save_to_path, CurrentFile, PendingLoad, RecentFiles};
+use glyph::core::marks::{get_mark, set_mark, Marks};
+use glyph::io::file_io::{
+    process_pending_load_system, save_to_path, CurrentFile, PendingLoad, RecentFiles, SaveSources,
+};
 use glyph::core::resources::SpatialIndex;
 use glyph::core::spatial::{spatial_index_cleanup_system, update_spatial_index_system};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// Serializes tests that change the process working directory.
+static CWD_LOCK: Mutex<()> = Mutex::new(());
+
+/// Loading a file adds it to `workflows/.recent.json` under the working
+/// directory, so tests that load run from their tempdir instead of the repo.
+/// The old directory is restored on drop.
+struct TempCwd {
+    old: PathBuf,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TempCwd {
+    fn enter(dir: &Path) -> Self {
+        let lock = CWD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(dir).unwrap();
+        Self { old, _lock: lock }
+    }
+}
+
+impl Drop for TempCwd {
+    fn drop(&mut self) {
+        let _ = std::env::set_current_dir(&self.old);
+    }
+}
 
 #[derive(Resource, Default)]
 struct TestSavePath(pub Option<PathBuf>);
@@ -21,6 +51,8 @@ fn io_test_app() -> App {
         .init_resource::<RecentFiles>()
         .init_resource::<glyph::core::resources::StatusMessage>()
         .init_resource::<TestSavePath>()
+        .init_resource::<Marks>()
+        .init_resource::<glyph::io::file_io::PendingCanvasCheckpoint>()
         .add_systems(Startup, |mut commands: Commands| {
             let n1 = spawn_node_with_color(&mut commands, 100.0, 200.0, "hello", Color::srgb(0.5, 0.6, 0.7));
             let n2 = spawn_node_with_color(&mut commands, 300.0, 400.0, "world", Color::srgb(0.8, 0.9, 1.0));
@@ -32,11 +64,9 @@ fn io_test_app() -> App {
         })
         .add_systems(
             Update,
-            |mut path: ResMut<TestSavePath>,
-             node_query: Query<(Entity, &Transform, &TextData, &NodeColor), With<CanvasNode>>,
-             edge_query: Query<(Entity, &Edge)>| {
+            |mut path: ResMut<TestSavePath>, canvas: SaveSources| {
                 if let Some(p) = path.0.take() {
-                    let _ = save_to_path(&p, &node_query, &edge_query, None);
+                    let _ = save_to_path(&p, &canvas, None);
                 }
             },
         )
@@ -64,6 +94,7 @@ fn e2e_save_produces_valid_glyph() {
 #[test]
 fn e2e_load_restores_nodes_and_edges() {
     let dir = tempfile::tempdir().unwrap();
+    let _cwd = TempCwd::enter(dir.path());
     let path = dir.path().join("load_test.glyph");
 
     fs::write(
@@ -106,3 +137,82 @@ fn e2e_load_restores_nodes_and_edges() {
     assert!(!recent.0.is_empty());
     assert_eq!(recent.0[0], path);
 }
+
+#[test]
+fn e2e_marks_survive_save_and_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let _cwd = TempCwd::enter(dir.path());
+    let path = dir.path().join("marks.glyph");
+
+    let mut app = io_test_app();
+    app.world_mut().spawn((Camera2d, MainCamera));
+    app.add_systems(Update, process_pending_load_system);
+    app.update();
+    set_mark(&mut app.world_mut().resource_mut::<Marks>(), 'a', Vec2::new(120.0, -40.0));
+    *app.world_mut().resource_mut::<TestSavePath>() = TestSavePath(Some(path.clone()));
+    app.update();
+    assert!(fs::read_to_string(&path).unwrap().contains("\"marks\""));
+
+    app.world_mut().resource_mut::<Marks>().locations.clear();
+    *app.world_mut().resource_mut::<PendingLoad>() = PendingLoad(Some(path.clone()));
+    app.update();
+
+    let marks = app.world().resource::<Marks>();
+    assert_eq!(get_mark(marks, 'a'), Some(Vec2::new(120.0, -40.0)));
+    assert_eq!(marks.locations.len(), 1);
+}
+
+#[test]
+fn e2e_tabs_keep_canvases_apart() {
+    use glyph::core::components::{CanvasId, CollapseHidden};
+    use glyph::core::tabs::{handle_tab_requests, sync_canvas_tabs_system, Canvases, TabRequest};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("second.glyph");
+
+    let mut app = io_test_app();
+    app.init_resource::<Canvases>()
+        .init_resource::<glyph::core::history::UndoHistory>()
+        .init_resource::<glyph::core::resources::SelectedEdge>()
+        .add_message::<TabRequest>()
+        .add_systems(
+            Update,
+            (
+                handle_tab_requests,
+                sync_canvas_tabs_system,
+                glyph::render::collapse::sync_collapsed_system,
+            )
+                .chain(),
+        );
+    app.update();
+
+    app.world_mut().write_message(TabRequest::New);
+    app.update();
+    spawn_node_with_color(&mut app.world_mut().commands(), 0.0, 0.0, "third", Color::WHITE);
+    app.update();
+    *app.world_mut().resource_mut::<TestSavePath>() = TestSavePath(Some(path.clone()));
+    app.update();
+
+    // Only the new tab's node is saved; the first tab's nodes are hidden.
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("\"text\": \"third\""));
+    assert!(!contents.contains("hello"));
+    fn state(world: &mut World, text: &str) -> (usize, bool) {
+        let mut nodes = world.query::<(&TextData, &CanvasId, Has<CollapseHidden>)>();
+        nodes
+            .iter(world)
+            .find(|(t, ..)| t.content == text)
+            .map(|(_, id, hidden)| (id.0, hidden))
+            .unwrap()
+    }
+    let world = app.world_mut();
+    assert_eq!(state(world, "hello"), (0, true));
+    assert_eq!(state(world, "third"), (1, false));
+
+    world.write_message(TabRequest::Next);
+    app.update();
+    let world = app.world_mut();
+    assert_eq!(world.resource::<Canvases>().active, 0);
+    assert_eq!(state(world, "hello"), (0, false));
+    assert_eq!(state(world, "third"), (1, true));
+}